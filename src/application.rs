@@ -10,7 +10,7 @@ use gtk::{gio, glib};
 use std::cell::RefCell;
 
 #[cfg(any(target_os = "linux", windows))]
-const ACCELS_MAP: &[(&'static str, &[&'static str])] = &[
+const ACCELS_MAP: &[(&str, &[&str])] = &[
     ("app.quit", &["<ctrl>q"]),
     ("app.new-window", &["<ctrl><shift>n"]),
     ("win.new-project", &["<ctrl>n"]),
@@ -24,7 +24,7 @@ const ACCELS_MAP: &[(&'static str, &[&'static str])] = &[
 // I have no idea if gtk/gdk or some other wonky library on this stack automatically remaps ctrl to primary
 // in OSX context but just in case...
 #[cfg(target_os = "macos")]
-const ACCELS_MAP: &[(&'static str, &[&'static str])] = &[
+const ACCELS_MAP: &[(&str, &[&str])] = &[
     ("app.quit", &["<primary>q"]),
     ("app.new-window", &["<primary><shift>n"]),
     ("win.new-project", &["<primary>n"]),
@@ -24,3 +24,10 @@ pub const TAG_NAME_SUBSCRIPT: &str = "subscript";
 pub const TAG_NAME_CODE_BLOCK: &str = "code_block";
 pub const TAG_NAME_CODE_TEXT: &str = "code_text";
 pub const TAG_NAME_UNFOCUSED_TEXT: &str = "unfocused_text";
+pub const TAG_NAME_NOTE: &str = "note";
+pub const TAG_NAME_HIGHLIGHT: &str = "highlight";
+/// Prefix of the tags tinting the names of a character, followed by its id
+pub const TAG_NAME_CHARACTER_PREFIX: &str = "character:";
+
+/// Tags that are not derived from the Markdown source, and are saved along with it
+pub const PERSISTENT_TAG_NAMES: &[&str] = &[TAG_NAME_HIGHLIGHT];
@@ -103,3 +103,185 @@ where
 
     dialog.show();
 }
+
+/// Shows a file selection dialog for SVG images with a save intent
+/// and executes `on_choice` when a file is selected
+pub fn with_svg_save_dialog<F>(title: &str, suggested_name: &str, on_choice: F)
+where
+    F: Fn(String) + 'static,
+{
+    let win = window();
+
+    let dialog = gtk::FileChooserNative::builder()
+        .accept_label(i18n("_Export"))
+        .cancel_label(i18n("_Cancel"))
+        .modal(true)
+        .title(title)
+        .transient_for(&win)
+        .select_multiple(false)
+        .action(gtk::FileChooserAction::Save)
+        .build();
+    dialog.set_current_name(suggested_name);
+
+    let svg_file_filter = gtk::FileFilter::new();
+    svg_file_filter.set_name(Some(&i18n("SVG images")));
+    svg_file_filter.add_mime_type("image/svg+xml");
+    svg_file_filter.add_pattern("*.svg");
+
+    dialog.add_filter(&svg_file_filter);
+
+    dialog.connect_response(glib::clone!(@strong dialog => move |_, response| {
+        let file = dialog.file();
+        if response == gtk::ResponseType::Accept {
+            if let Some(file) = file.as_ref() {
+                on_choice(file.path().unwrap().to_str().unwrap().into());
+            }
+        }
+    }));
+
+    dialog.show();
+}
+
+/// Shows a file selection dialog for projects created with
+/// other writing tools and executes `on_done` when a file is selected
+pub fn with_file_import_dialog<F>(patterns: &[&str], on_done: F)
+where
+    F: Fn(String) + 'static,
+{
+    let win = window();
+    let dialog = gtk::FileChooserNative::builder()
+        .accept_label(i18n("_Import"))
+        .cancel_label(i18n("_Cancel"))
+        .modal(true)
+        .title(i18n("Import project"))
+        .transient_for(&win)
+        .select_multiple(false)
+        .action(gtk::FileChooserAction::Open)
+        .build();
+
+    let supported_file_filter = gtk::FileFilter::new();
+    supported_file_filter.set_name(Some(&i18n("Supported projects")));
+    for pattern in patterns {
+        supported_file_filter.add_pattern(pattern);
+    }
+
+    let any_file_filter = gtk::FileFilter::new();
+    any_file_filter.set_name(Some(&i18n("All files")));
+    any_file_filter.add_pattern("*");
+
+    dialog.add_filter(&supported_file_filter);
+    dialog.add_filter(&any_file_filter);
+
+    dialog.connect_response(glib::clone!(@strong dialog => move |_, response| {
+        let file = dialog.file();
+        if response == gtk::ResponseType::Accept {
+            if let Some(file) = file.as_ref() {
+                if file.query_exists(gio::Cancellable::NONE) {
+                    on_done(file.path().unwrap().to_str().unwrap().into());
+                }
+            }
+        }
+    }));
+
+    dialog.show();
+}
+
+/// Shows a file selection dialog for pictures and executes
+/// `on_done` with the path of the selected image
+pub fn with_image_open_dialog<F>(title: &str, on_done: F)
+where
+    F: Fn(String) + 'static,
+{
+    let win = window();
+    let dialog = gtk::FileChooserNative::builder()
+        .accept_label(i18n("_Open"))
+        .cancel_label(i18n("_Cancel"))
+        .modal(true)
+        .title(title)
+        .transient_for(&win)
+        .select_multiple(false)
+        .action(gtk::FileChooserAction::Open)
+        .build();
+
+    let image_file_filter = gtk::FileFilter::new();
+    image_file_filter.set_name(Some(&i18n("Images")));
+    image_file_filter.add_pixbuf_formats();
+
+    dialog.add_filter(&image_file_filter);
+
+    dialog.connect_response(glib::clone!(@strong dialog => move |_, response| {
+        let file = dialog.file();
+        if response == gtk::ResponseType::Accept {
+            if let Some(file) = file.as_ref() {
+                if file.query_exists(gio::Cancellable::NONE) {
+                    on_done(file.path().unwrap().to_str().unwrap().into());
+                }
+            }
+        }
+    }));
+
+    dialog.show();
+}
+
+/// Shows a file selection dialog for research material and executes
+/// `on_done` with the path of the selected file
+pub fn with_attachment_open_dialog<F>(on_done: F)
+where
+    F: Fn(String) + 'static,
+{
+    let win = window();
+    let dialog = gtk::FileChooserNative::builder()
+        .accept_label(i18n("_Attach"))
+        .cancel_label(i18n("_Cancel"))
+        .modal(true)
+        .title(i18n("Add Attachment"))
+        .transient_for(&win)
+        .select_multiple(false)
+        .action(gtk::FileChooserAction::Open)
+        .build();
+
+    let supported_file_filter = gtk::FileFilter::new();
+    supported_file_filter.set_name(Some(&i18n("Images, PDFs and web pages")));
+    supported_file_filter.add_pixbuf_formats();
+    supported_file_filter.add_mime_type("application/pdf");
+    supported_file_filter.add_mime_type("text/html");
+    supported_file_filter.add_mime_type("application/x-mimearchive");
+
+    let any_file_filter = gtk::FileFilter::new();
+    any_file_filter.set_name(Some(&i18n("All files")));
+    any_file_filter.add_pattern("*");
+
+    dialog.add_filter(&supported_file_filter);
+    dialog.add_filter(&any_file_filter);
+
+    dialog.connect_response(glib::clone!(@strong dialog => move |_, response| {
+        let file = dialog.file();
+        if response == gtk::ResponseType::Accept {
+            if let Some(file) = file.as_ref() {
+                if file.query_exists(gio::Cancellable::NONE) {
+                    on_done(file.path().unwrap().to_str().unwrap().into());
+                }
+            }
+        }
+    }));
+
+    dialog.show();
+}
+
+/// Writes `content` to a cached file named after `name` and opens
+/// it with the default application of the system
+pub fn open_with_system_viewer(name: &str, content: &[u8]) -> Result<(), glib::Error> {
+    let io_error = |error: std::io::Error| {
+        glib::Error::new(gio::IOErrorEnum::Failed, error.to_string().as_str())
+    };
+
+    let directory = glib::user_cache_dir()
+        .join("manuscript")
+        .join("attachments");
+    std::fs::create_dir_all(&directory).map_err(io_error)?;
+    let path = directory.join(name);
+    std::fs::write(&path, content).map_err(io_error)?;
+
+    let uri = gio::File::for_path(&path).uri();
+    gio::AppInfo::launch_default_for_uri(uri.as_str(), None::<&gio::AppLaunchContext>)
+}
@@ -0,0 +1,289 @@
+use super::{BufferChunk, ChunkType, Document, DocumentChunk};
+use crate::services::analyst::MentionMatcher;
+
+/// Where a character is mentioned across the chapters of a manuscript
+#[derive(Debug, Clone)]
+pub struct CharacterAppearance {
+    character_id: String,
+    name: String,
+    mentions: u64,
+    /// Indexes of the chapters mentioning the character, in reading order
+    chapters: Vec<usize>,
+    /// Mentions in each chapter, in reading order
+    chapter_mentions: Vec<u64>,
+}
+
+impl CharacterAppearance {
+    pub fn character_id(&self) -> &str {
+        self.character_id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// How many times the character is mentioned in the whole manuscript
+    pub fn mentions(&self) -> u64 {
+        self.mentions
+    }
+
+    /// Indexes in `AppearanceIndex::chapters` of the chapters mentioning the character
+    pub fn chapters(&self) -> &Vec<usize> {
+        &self.chapters
+    }
+
+    pub fn appears_in(&self, chapter: usize) -> bool {
+        self.chapters.binary_search(&chapter).is_ok()
+    }
+
+    /// How many times the character is mentioned in the chapter at index `chapter`
+    pub fn mentions_in(&self, chapter: usize) -> u64 {
+        self.chapter_mentions.get(chapter).copied().unwrap_or(0)
+    }
+
+    pub fn first_chapter(&self) -> Option<usize> {
+        self.chapters.first().copied()
+    }
+
+    pub fn last_chapter(&self) -> Option<usize> {
+        self.chapters.last().copied()
+    }
+}
+
+/// A chapter of the manuscript, along with the scenes nested in it
+#[derive(Debug, Clone)]
+pub struct IndexedChapter {
+    id: String,
+    title: String,
+    words_count: u64,
+}
+
+impl IndexedChapter {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn words_count(&self) -> u64 {
+        self.words_count
+    }
+}
+
+/// The mentions of every character across the chapters of a document,
+/// in reading order
+#[derive(Debug, Clone, Default)]
+pub struct AppearanceIndex {
+    chapters: Vec<IndexedChapter>,
+    appearances: Vec<CharacterAppearance>,
+}
+
+impl AppearanceIndex {
+    pub fn new(document: &Document) -> Self {
+        // Text of each chapter, scenes nested in it included
+        let outline = document.outline();
+        let mut chapters = vec![];
+        let mut texts: Vec<String> = vec![];
+        for (index, (chunk, depth)) in outline.iter().enumerate() {
+            if chunk.chunk_type() != ChunkType::Chapter {
+                continue;
+            }
+
+            let nested = outline[index + 1..]
+                .iter()
+                .take_while(|(_, nested_depth)| nested_depth > depth)
+                .map(|(nested, _)| *nested);
+            let buffers: Vec<&dyn BufferChunk> = std::iter::once(*chunk)
+                .chain(nested)
+                .filter_map(|c| c.as_buffer_chunk())
+                .collect();
+            texts.push(
+                buffers
+                    .iter()
+                    .map(|c| String::from_utf8_lossy(c.buffer()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+            chapters.push(IndexedChapter {
+                id: chunk.id().to_string(),
+                title: chunk.safe_title(),
+                words_count: buffers.iter().map(|c| c.words_count()).sum(),
+            });
+        }
+
+        let appearances = document
+            .character_sheets_sorted_by(None)
+            .into_iter()
+            .map(|sheet| {
+                let matcher = MentionMatcher::new(&sheet.mention_names());
+                let counts: Vec<u64> = texts.iter().map(|text| matcher.count(text)).collect();
+                CharacterAppearance {
+                    character_id: sheet.id().to_string(),
+                    name: sheet.safe_title(),
+                    mentions: counts.iter().sum(),
+                    chapters: counts
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, count)| **count > 0)
+                        .map(|(index, _)| index)
+                        .collect(),
+                    chapter_mentions: counts,
+                }
+            })
+            .collect();
+
+        Self {
+            chapters,
+            appearances,
+        }
+    }
+
+    /// Chapters of the manuscript, in reading order
+    pub fn chapters(&self) -> &Vec<IndexedChapter> {
+        &self.chapters
+    }
+
+    /// Appearances of every character, sorted by name
+    pub fn appearances(&self) -> &Vec<CharacterAppearance> {
+        &self.appearances
+    }
+
+    pub fn appearance(&self, character_id: &str) -> Option<&CharacterAppearance> {
+        self.appearances
+            .iter()
+            .find(|a| a.character_id() == character_id)
+    }
+
+    /// The longest stretch of chapters without mentions of a character,
+    /// counted from the first appearance to the end of the manuscript, as
+    /// the number of chapters and of words in them
+    pub fn longest_absence(&self, character_id: &str) -> (usize, u64) {
+        let Some(appearance) = self.appearance(character_id) else {
+            return (0, 0);
+        };
+        let Some(first) = appearance.first_chapter() else {
+            return (0, 0);
+        };
+
+        let mut longest = (0, 0);
+        let mut current = (0, 0);
+        for (index, chapter) in self.chapters.iter().enumerate().skip(first + 1) {
+            if appearance.appears_in(index) {
+                current = (0, 0);
+            } else {
+                current = (current.0 + 1, current.1 + chapter.words_count());
+                if current.1 > longest.1 || (current.1 == longest.1 && current.0 > longest.0) {
+                    longest = current;
+                }
+            }
+        }
+        longest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Chapter, CharacterSheet, MutableBufferChunk, Scene};
+    use bytes::Bytes;
+
+    /// Four chapters mentioning Ada, known by an alias too, and Will. The
+    /// third chapter holds a scene. Returns the ids of Ada, of Will and of
+    /// the chapters
+    fn appearances_document() -> (Document, String, String, Vec<String>) {
+        let mut doc = Document::default();
+        let mut ada = CharacterSheet::default();
+        ada.set_name(Some("Ada Lovelace".into()));
+        ada.set_aliases(vec!["Ada".into(), " ".into()]);
+        let mut will = CharacterSheet::default();
+        will.set_name(Some("Will".into()));
+        let (ada_id, will_id) = (ada.id().to_string(), will.id().to_string());
+        doc.add_chunk(ada);
+        doc.add_chunk(will);
+
+        let texts = [
+            "Ada Lovelace met Will. Ada's notes will survive.",
+            "Nobody here, just a few words.",
+            "Still nobody.",
+            "Will came back, with Ada.",
+        ];
+        let mut ids = vec![];
+        for text in texts.iter() {
+            let mut chapter = Chapter::default();
+            chapter.set_buffer(Bytes::from(*text));
+            ids.push(chapter.id().to_string());
+            doc.add_chunk(chapter);
+        }
+        for pair in ids.windows(2) {
+            doc.move_chunk_next_to(&pair[1], &pair[0], true).unwrap();
+        }
+        let mut scene = Scene::default();
+        scene.set_buffer(Bytes::from("Willful Ada."));
+        let scene_id = scene.id().to_string();
+        doc.add_chunk(scene);
+        doc.set_parent(&scene_id, Some(&ids[2])).unwrap();
+        (doc, ada_id, will_id, ids)
+    }
+
+    #[test]
+    fn chapters_in_reading_order() {
+        let (doc, _, _, ids) = appearances_document();
+        let index = AppearanceIndex::new(&doc);
+        let chapter_ids: Vec<&str> = index.chapters().iter().map(|c| c.id()).collect();
+        assert_eq!(
+            chapter_ids,
+            ids.iter().map(String::as_str).collect::<Vec<_>>()
+        );
+        // Scenes count toward the chapter they are nested in
+        assert_eq!(index.chapters()[2].words_count(), 4);
+    }
+
+    #[test]
+    fn mentions_by_name_and_alias() {
+        let (doc, ada_id, _, _) = appearances_document();
+        let index = AppearanceIndex::new(&doc);
+        let ada = index.appearance(&ada_id).unwrap();
+        assert_eq!(ada.mentions(), 4);
+        assert_eq!(ada.chapters(), &vec![0, 2, 3]);
+        assert_eq!(ada.mentions_in(0), 2);
+        assert_eq!(ada.mentions_in(1), 0);
+        assert!(ada.appears_in(2));
+    }
+
+    #[test]
+    fn mentions_are_whole_words() {
+        let (doc, _, will_id, _) = appearances_document();
+        let index = AppearanceIndex::new(&doc);
+        let will = index.appearance(&will_id).unwrap();
+        assert_eq!(will.mentions(), 2);
+        assert_eq!(
+            (will.first_chapter(), will.last_chapter()),
+            (Some(0), Some(3))
+        );
+    }
+
+    #[test]
+    fn longest_absence() {
+        let (doc, ada_id, will_id, _) = appearances_document();
+        let index = AppearanceIndex::new(&doc);
+        assert_eq!(index.longest_absence(&ada_id), (1, 6));
+        assert_eq!(index.longest_absence(&will_id), (2, 10));
+        assert_eq!(index.longest_absence("01MISSING"), (0, 0));
+    }
+
+    #[test]
+    fn characters_never_mentioned() {
+        let (mut doc, _, _, _) = appearances_document();
+        let mut nobody = CharacterSheet::default();
+        nobody.set_name(Some("Charles".into()));
+        let nobody_id = nobody.id().to_string();
+        doc.add_chunk(nobody);
+        let index = AppearanceIndex::new(&doc);
+        let appearance = index.appearance(&nobody_id).unwrap();
+        assert_eq!(appearance.mentions(), 0);
+        assert_eq!(appearance.first_chapter(), None);
+        assert_eq!(index.longest_absence(&nobody_id), (0, 0));
+    }
+}
@@ -1,13 +1,18 @@
 use super::{BufferChunk, ChunkType, DocumentChunk, MutableBufferChunk};
 use crate::{
+    libs::consts::PERSISTENT_TAG_NAMES,
     models::{
-        prelude::{Color, ManuscriptResult, TagMark},
+        format::v0,
+        prelude::{Color, ManuscriptError, ManuscriptResult, TagMark},
+        search_key,
         settings::DocumentSettings,
+        ChunkLabel, ChunkStatus, FieldDefinition, FieldValue, PublicationMetadata,
     },
     services::i18n::i18n,
 };
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use ulid::Ulid;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -16,6 +21,11 @@ pub struct DocumentManifest {
     author: String,
     settings: DocumentSettings,
     locked: bool,
+    character_fields: Vec<FieldDefinition>,
+    cover: Option<Bytes>,
+    publication: PublicationMetadata,
+    statuses: Vec<ChunkStatus>,
+    labels: Vec<ChunkLabel>,
 }
 
 impl Default for DocumentManifest {
@@ -27,6 +37,27 @@ impl Default for DocumentManifest {
                 .unwrap_or(String::from("")),
             settings: DocumentSettings::default(),
             locked: false,
+            character_fields: vec![],
+            cover: None,
+            publication: PublicationMetadata::default(),
+            statuses: ChunkStatus::defaults(),
+            labels: vec![],
+        }
+    }
+}
+
+impl From<v0::DocumentManifest> for DocumentManifest {
+    fn from(source: v0::DocumentManifest) -> Self {
+        Self {
+            title: source.title,
+            author: source.author,
+            settings: source.settings,
+            locked: source.locked,
+            character_fields: vec![],
+            cover: None,
+            publication: PublicationMetadata::default(),
+            statuses: ChunkStatus::defaults(),
+            labels: vec![],
         }
     }
 }
@@ -55,6 +86,114 @@ impl DocumentManifest {
     pub fn settings_mut(&mut self) -> &mut DocumentSettings {
         &mut self.settings
     }
+
+    pub fn publication(&self) -> &PublicationMetadata {
+        &self.publication
+    }
+
+    pub fn publication_mut(&mut self) -> &mut PublicationMetadata {
+        &mut self.publication
+    }
+
+    /// Custom fields available on every character sheet
+    pub fn character_fields(&self) -> &Vec<FieldDefinition> {
+        &self.character_fields
+    }
+
+    pub fn character_field(&self, id: &str) -> Option<&FieldDefinition> {
+        self.character_fields.iter().find(|f| f.id() == id)
+    }
+
+    /// Looks up a field by its search key, see [`FieldDefinition::search_key`]
+    pub fn character_field_by_key(&self, key: &str) -> Option<&FieldDefinition> {
+        self.character_fields.iter().find(|f| f.search_key() == key)
+    }
+
+    pub fn add_character_field(&mut self, field: FieldDefinition) {
+        self.character_fields.push(field);
+    }
+
+    pub fn remove_character_field(&mut self, id: &str) -> Option<FieldDefinition> {
+        let index = self.character_fields.iter().position(|f| f.id() == id)?;
+        Some(self.character_fields.remove(index))
+    }
+
+    /// Raw bytes of the cover image, as read from the original file
+    pub fn cover(&self) -> Option<&Bytes> {
+        self.cover.as_ref()
+    }
+
+    pub fn set_cover(&mut self, value: Option<Bytes>) {
+        self.cover = value;
+    }
+
+    /// Workflow statuses available to buffer chunks, in workflow order
+    pub fn statuses(&self) -> &Vec<ChunkStatus> {
+        &self.statuses
+    }
+
+    pub fn status(&self, id: &str) -> Option<&ChunkStatus> {
+        self.statuses.iter().find(|s| s.id() == id)
+    }
+
+    pub fn status_mut(&mut self, id: &str) -> Option<&mut ChunkStatus> {
+        self.statuses.iter_mut().find(|s| s.id() == id)
+    }
+
+    /// Looks up a status by its search key, see [`search_key`]
+    pub fn status_by_key(&self, key: &str) -> Option<&ChunkStatus> {
+        self.statuses.iter().find(|s| search_key(s.name()) == key)
+    }
+
+    pub fn add_status(&mut self, status: ChunkStatus) {
+        self.statuses.push(status);
+    }
+
+    pub fn remove_status(&mut self, id: &str) -> Option<ChunkStatus> {
+        let index = self.statuses.iter().position(|s| s.id() == id)?;
+        Some(self.statuses.remove(index))
+    }
+
+    /// Labels available to buffer chunks
+    pub fn labels(&self) -> &Vec<ChunkLabel> {
+        &self.labels
+    }
+
+    pub fn label(&self, id: &str) -> Option<&ChunkLabel> {
+        self.labels.iter().find(|l| l.id() == id)
+    }
+
+    pub fn label_mut(&mut self, id: &str) -> Option<&mut ChunkLabel> {
+        self.labels.iter_mut().find(|l| l.id() == id)
+    }
+
+    /// Looks up a label by its search key, see [`search_key`]
+    pub fn label_by_key(&self, key: &str) -> Option<&ChunkLabel> {
+        self.labels.iter().find(|l| search_key(l.name()) == key)
+    }
+
+    pub fn add_label(&mut self, label: ChunkLabel) {
+        self.labels.push(label);
+    }
+
+    pub fn remove_label(&mut self, id: &str) -> Option<ChunkLabel> {
+        let index = self.labels.iter().position(|l| l.id() == id)?;
+        Some(self.labels.remove(index))
+    }
+
+    /// Whether a chunk with the given status and labels belongs in a
+    /// compiled manuscript, according to the compile setting of each
+    pub fn compiles(&self, status: Option<&String>, labels: &[String]) -> bool {
+        let status_compiles = status
+            .and_then(|id| self.status(id))
+            .map(|s| s.compile())
+            .unwrap_or(true);
+        status_compiles
+            && labels
+                .iter()
+                .filter_map(|id| self.label(id))
+                .all(|l| l.compile())
+    }
 }
 
 impl DocumentChunk for DocumentManifest {
@@ -114,9 +253,16 @@ pub struct Chapter {
     accent: Option<Color>,
     locked: bool,
     title: Option<String>,
+    synopsis: Option<String>,
     buffer: Bytes,
     tags: Vec<TagMark>,
     notes: Vec<Note>,
+    parent_id: Option<String>,
+    status: Option<String>,
+    labels: Vec<String>,
+    words_target: Option<u64>,
+    modified: Option<i64>,
+    story_time: Option<String>,
 }
 
 impl DocumentChunk for Chapter {
@@ -181,6 +327,23 @@ impl DocumentChunk for Chapter {
         self.accent = value;
         Ok(())
     }
+
+    fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+
+    fn set_parent_id(&mut self, value: Option<String>) -> ManuscriptResult<()> {
+        self.parent_id = value;
+        Ok(())
+    }
+
+    fn as_buffer_chunk(&self) -> Option<&dyn BufferChunk> {
+        Some(self)
+    }
+
+    fn as_mutable_buffer_chunk(&mut self) -> Option<&mut dyn MutableBufferChunk> {
+        Some(self)
+    }
 }
 
 impl BufferChunk for Chapter {
@@ -191,85 +354,59 @@ impl BufferChunk for Chapter {
     fn tags_map(&self) -> &Vec<TagMark> {
         &self.tags
     }
-}
 
-impl MutableBufferChunk for Chapter {
-    fn set_buffer(&mut self, value: Bytes) {
-        self.buffer = value;
+    fn synopsis(&self) -> Option<&String> {
+        self.synopsis.as_ref()
     }
-}
 
-impl Default for Chapter {
-    fn default() -> Self {
-        Self {
-            id: Ulid::new().into(),
-            priority: 0,
-            include_in_compilation: true,
-            accent: None,
-            locked: false,
-            title: None,
-            buffer: Bytes::from(""),
-            tags: vec![],
-            notes: vec![],
-        }
+    fn status(&self) -> Option<&String> {
+        self.status.as_ref()
+    }
+
+    fn labels(&self) -> &Vec<String> {
+        &self.labels
+    }
+
+    fn words_target(&self) -> Option<u64> {
+        self.words_target
+    }
+
+    fn modified(&self) -> Option<i64> {
+        self.modified
     }
 }
 
-impl Chapter {
-    pub fn new() -> Self {
-        Self::default()
+impl MutableBufferChunk for Chapter {
+    fn set_buffer(&mut self, value: Bytes) {
+        self.buffer = value;
     }
 
-    pub fn title(&self) -> Option<&String> {
-        self.title.as_ref()
+    fn set_synopsis(&mut self, value: Option<String>) {
+        self.synopsis = value;
     }
 
-    pub fn set_title(&mut self, value: &str) {
-        self.title = Some(String::from(value))
+    fn set_status(&mut self, value: Option<String>) {
+        self.status = value;
     }
 
-    pub fn add_note(&mut self, from: NoteOffsetType, to: NoteOffsetType, content: String) {
-        self.notes.push(Note {
-            id: Ulid::new().into(),
-            buffer: Bytes::from(content),
-            offset: from,
-            len: to - from,
-        });
+    fn set_labels(&mut self, value: Vec<String>) {
+        self.labels = value;
     }
 
-    pub fn notes_at(&self, offset: NoteOffsetType) -> Vec<&Note> {
-        self.notes
-            .iter()
-            .filter(|n| n.offset_start() <= offset && n.offset_end() >= offset)
-            .collect::<Vec<&Note>>()
+    fn set_words_target(&mut self, value: Option<u64>) {
+        self.words_target = value;
     }
 
-    pub fn note_at_mut(&mut self, offset: NoteOffsetType) -> Vec<&mut Note> {
-        self.notes
-            .iter_mut()
-            .filter(|n| n.offset_start() <= offset && n.offset_end() >= offset)
-            .collect::<Vec<&mut Note>>()
+    fn set_modified(&mut self, value: Option<i64>) {
+        self.modified = value;
     }
-}
 
-/// A CharacterSheet is a chunk representing the description of a character
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct CharacterSheet {
-    id: String,
-    priority: u64,
-    include_in_compilation: bool,
-    accent: Option<Color>,
-    locked: bool,
-    name: Option<String>,
-    gender: Gender,
-    age: Option<u32>,
-    role: Option<String>,
-    physical_traits: Bytes,
-    psycological_traits: Bytes,
-    background: Bytes,
+    fn set_tags_map(&mut self, value: Vec<TagMark>) {
+        self.tags = value;
+    }
 }
 
-impl Default for CharacterSheet {
+impl Default for Chapter {
     fn default() -> Self {
         Self {
             id: Ulid::new().into(),
@@ -277,106 +414,264 @@ impl Default for CharacterSheet {
             include_in_compilation: true,
             accent: None,
             locked: false,
-            name: None,
-            gender: Gender::Unspecified,
-            age: None,
-            role: None,
-            physical_traits: Bytes::new(),
-            psycological_traits: Bytes::new(),
-            background: Bytes::new(),
+            title: None,
+            synopsis: None,
+            buffer: Bytes::from(""),
+            tags: vec![],
+            notes: vec![],
+            parent_id: None,
+            status: None,
+            labels: vec![],
+            words_target: None,
+            modified: None,
+            story_time: None,
         }
     }
 }
 
-impl CharacterSheet {
-    pub fn name(&self) -> Option<&String> {
-        self.name.as_ref()
+impl From<v0::Chapter> for Chapter {
+    fn from(source: v0::Chapter) -> Self {
+        Self {
+            id: source.id,
+            priority: source.priority,
+            include_in_compilation: source.include_in_compilation,
+            accent: source.accent,
+            locked: source.locked,
+            title: source.title,
+            synopsis: None,
+            buffer: source.buffer,
+            // Baseline files saved every tag of the editor, most of them derived
+            // from the Markdown source and applied again whenever it is loaded
+            tags: source
+                .tags
+                .into_iter()
+                .filter(|mark| PERSISTENT_TAG_NAMES.contains(&mark.name()))
+                .collect(),
+            notes: source.notes.into_iter().map(Note::from).collect(),
+            // Chapters used to be a flat list, they now sit at the root of the outline
+            parent_id: None,
+            status: None,
+            labels: vec![],
+            words_target: None,
+            modified: None,
+            story_time: None,
+        }
     }
+}
 
-    pub fn set_name(&mut self, value: Option<String>) {
-        self.name = value;
+impl Chapter {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn role(&self) -> Option<&String> {
-        self.role.as_ref()
+    /// Creates a copy of this chapter with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
     }
 
-    pub fn set_role(&mut self, value: Option<String>) {
-        self.role = value;
+    pub fn title(&self) -> Option<&String> {
+        self.title.as_ref()
     }
 
-    pub fn gender(&self) -> Gender {
-        self.gender
+    pub fn set_title(&mut self, value: &str) {
+        self.title = Some(String::from(value))
     }
 
-    pub fn set_gender(&mut self, value: Gender) {
-        self.gender = value;
+    /// When the chapter happens in the story, in whatever form fits its calendar
+    pub fn story_time(&self) -> Option<&String> {
+        self.story_time.as_ref()
     }
 
-    pub fn age(&self) -> Option<u32> {
-        self.age
+    pub fn set_story_time(&mut self, value: Option<String>) {
+        self.story_time = value;
     }
 
-    pub fn set_age(&mut self, value: Option<u32>) {
-        self.age = value;
+    pub fn add_note(&mut self, from: NoteOffsetType, to: NoteOffsetType, content: String) {
+        self.notes.push(Note::new(from, to - from, content));
     }
 
-    pub fn background(&self) -> &[u8] {
-        self.background.as_ref()
+    pub fn notes(&self) -> &Vec<Note> {
+        &self.notes
     }
 
-    pub fn set_background(&mut self, value: &[u8]) {
-        self.background = Bytes::from(Vec::from(value));
+    pub fn push_note(&mut self, note: Note) {
+        self.notes.push(note);
     }
 
-    pub fn set_background_bytes(&mut self, value: Bytes) {
-        self.background = value;
+    pub fn note_mut(&mut self, id: &str) -> Option<&mut Note> {
+        self.notes.iter_mut().find(|n| n.id == id)
     }
 
-    pub fn physical_traits(&self) -> &[u8] {
-        self.physical_traits.as_ref()
+    pub fn remove_note(&mut self, id: &str) -> Option<Note> {
+        let index = self.notes.iter().position(|n| n.id == id)?;
+        Some(self.notes.remove(index))
     }
 
-    pub fn set_physical_traits(&mut self, value: &[u8]) {
-        self.physical_traits = Bytes::from(Vec::from(value))
+    pub fn notes_at(&self, offset: NoteOffsetType) -> Vec<&Note> {
+        self.notes
+            .iter()
+            .filter(|n| n.offset_start() <= offset && n.offset_end() >= offset)
+            .collect::<Vec<&Note>>()
     }
 
-    pub fn set_physical_traits_bytes(&mut self, value: Bytes) {
-        self.physical_traits = value;
+    pub fn note_at_mut(&mut self, offset: NoteOffsetType) -> Vec<&mut Note> {
+        self.notes
+            .iter_mut()
+            .filter(|n| n.offset_start() <= offset && n.offset_end() >= offset)
+            .collect::<Vec<&mut Note>>()
     }
 
-    pub fn psycological_traits(&self) -> &[u8] {
-        self.physical_traits.as_ref()
+    /// Moves the start and end of every note with `f`, called with each
+    /// offset and whether it ends a note
+    pub fn remap_notes<F>(&mut self, f: F)
+    where
+        F: Fn(NoteOffsetType, bool) -> NoteOffsetType,
+    {
+        for note in self.notes.iter_mut() {
+            let start = f(note.offset_start(), false);
+            let end = f(note.offset_end(), true);
+            note.offset = start;
+            note.len = end.max(start) - start;
+        }
     }
 
-    pub fn set_psycological_traits(&mut self, value: &[u8]) {
-        self.psycological_traits = Bytes::from(Vec::from(value))
+    /// Moves the text after the character `offset` into a new chapter placed
+    /// under the same parent, along with the notes and tag marks anchored to
+    /// it. Notes and marks spanning `offset` are cut in two
+    pub fn split_off(&mut self, offset: NoteOffsetType) -> Chapter {
+        let text = String::from_utf8_lossy(self.buffer.as_ref()).to_string();
+        let offset = offset.clamp(0, text.chars().count() as NoteOffsetType);
+        let split_at = text
+            .char_indices()
+            .nth(offset as usize)
+            .map(|(index, _)| index)
+            .unwrap_or(text.len());
+
+        let mut other = Chapter {
+            parent_id: self.parent_id.clone(),
+            accent: self.accent,
+            include_in_compilation: self.include_in_compilation,
+            status: self.status.clone(),
+            labels: self.labels.clone(),
+            story_time: self.story_time.clone(),
+            buffer: Bytes::from(text[split_at..].to_string()),
+            ..Chapter::default()
+        };
+        self.buffer = Bytes::from(text[..split_at].to_string());
+
+        for note in std::mem::take(&mut self.notes) {
+            let (start, end) = (note.offset_start(), note.offset_end());
+            if start < offset {
+                if end > offset {
+                    other.notes.push(Note {
+                        id: Ulid::new().into(),
+                        offset: 0,
+                        len: end - offset,
+                        ..note.clone()
+                    });
+                }
+                self.notes.push(Note {
+                    len: end.min(offset) - start,
+                    ..note
+                });
+            } else {
+                other.notes.push(Note {
+                    offset: start - offset,
+                    ..note
+                });
+            }
+        }
+
+        for mark in std::mem::take(&mut self.tags) {
+            if mark.start() < offset {
+                if mark.end() > offset {
+                    other
+                        .tags
+                        .push(TagMark::new(0, mark.end() - offset, mark.name()));
+                }
+                self.tags.push(TagMark::new(
+                    mark.start(),
+                    mark.end().min(offset),
+                    mark.name(),
+                ));
+            } else {
+                other.tags.push(TagMark::new(
+                    mark.start() - offset,
+                    mark.end() - offset,
+                    mark.name(),
+                ));
+            }
+        }
+
+        other
     }
 
-    pub fn set_psycological_traits_bytes(&mut self, value: Bytes) {
-        self.psycological_traits = value;
+    /// Appends the text of `other` after a blank line, along with its notes
+    /// and tag marks
+    pub fn append(&mut self, other: &Chapter) {
+        let mut text = String::from_utf8_lossy(self.buffer.as_ref()).to_string();
+        if !text.is_empty() && !other.buffer.is_empty() && !text.ends_with("\n\n") {
+            text.push_str(if text.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        let shift = text.chars().count() as NoteOffsetType;
+        text.push_str(String::from_utf8_lossy(other.buffer.as_ref()).as_ref());
+        self.buffer = Bytes::from(text);
+
+        self.notes.extend(other.notes.iter().map(|note| Note {
+            offset: note.offset + shift,
+            ..note.clone()
+        }));
+        self.tags.extend(
+            other
+                .tags
+                .iter()
+                .map(|mark| TagMark::new(mark.start() + shift, mark.end() + shift, mark.name())),
+        );
     }
 }
 
-impl DocumentChunk for CharacterSheet {
+/// A Part groups chapters into a larger section of the manuscript. Its buffer
+/// holds any text that opens the part, like an epigraph or an introduction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Part {
+    id: String,
+    priority: u64,
+    include_in_compilation: bool,
+    accent: Option<Color>,
+    locked: bool,
+    title: Option<String>,
+    buffer: Bytes,
+    parent_id: Option<String>,
+    synopsis: Option<String>,
+    status: Option<String>,
+    labels: Vec<String>,
+    words_target: Option<u64>,
+    modified: Option<i64>,
+    tags: Vec<TagMark>,
+}
+
+impl DocumentChunk for Part {
     fn id(&self) -> &str {
         self.id.as_str()
     }
 
     fn title(&self) -> Option<String> {
-        self.name.as_ref().cloned()
+        self.title.as_ref().cloned()
     }
 
     fn default_title(&self) -> String {
-        i18n("Unnamed character")
+        i18n("Untitled part")
     }
 
     fn chunk_type(&self) -> ChunkType {
-        ChunkType::CharacterSheet
+        ChunkType::Part
     }
 
     fn category_name(&self) -> String {
-        i18n("Character sheets")
+        i18n("Parts")
     }
 
     fn priority(&self) -> Option<u64> {
@@ -420,58 +715,1265 @@ impl DocumentChunk for CharacterSheet {
         self.accent = value;
         Ok(())
     }
-}
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum Gender {
-    Male,
-    Female,
-    Other,
-    Unspecified,
-}
+    fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
 
-impl From<u64> for Gender {
-    fn from(idx: u64) -> Self {
-        match idx {
-            0 => Self::Male,
-            1 => Self::Female,
-            2 => Self::Other,
-            _ => Self::Unspecified,
-        }
+    fn set_parent_id(&mut self, value: Option<String>) -> ManuscriptResult<()> {
+        self.parent_id = value;
+        Ok(())
     }
-}
 
-impl From<u32> for Gender {
-    fn from(idx: u32) -> Self {
-        Self::from(idx as u64)
+    fn as_buffer_chunk(&self) -> Option<&dyn BufferChunk> {
+        Some(self)
     }
-}
 
-impl From<Gender> for u64 {
-    fn from(source: Gender) -> Self {
-        match source {
-            Gender::Male => 0,
-            Gender::Female => 1,
-            Gender::Other => 2,
-            Gender::Unspecified => 10,
-        }
+    fn as_mutable_buffer_chunk(&mut self) -> Option<&mut dyn MutableBufferChunk> {
+        Some(self)
     }
 }
 
-impl From<Gender> for u32 {
-    fn from(source: Gender) -> Self {
-        match source {
-            Gender::Male => 0,
-            Gender::Female => 1,
-            Gender::Other => 2,
-            Gender::Unspecified => 10,
-        }
+impl BufferChunk for Part {
+    fn buffer(&self) -> &Bytes {
+        &self.buffer
+    }
+
+    fn tags_map(&self) -> &Vec<TagMark> {
+        &self.tags
+    }
+
+    fn synopsis(&self) -> Option<&String> {
+        self.synopsis.as_ref()
+    }
+
+    fn status(&self) -> Option<&String> {
+        self.status.as_ref()
+    }
+
+    fn labels(&self) -> &Vec<String> {
+        &self.labels
+    }
+
+    fn words_target(&self) -> Option<u64> {
+        self.words_target
+    }
+
+    fn modified(&self) -> Option<i64> {
+        self.modified
+    }
+}
+
+impl MutableBufferChunk for Part {
+    fn set_buffer(&mut self, value: Bytes) {
+        self.buffer = value;
+    }
+
+    fn set_synopsis(&mut self, value: Option<String>) {
+        self.synopsis = value;
+    }
+
+    fn set_status(&mut self, value: Option<String>) {
+        self.status = value;
+    }
+
+    fn set_labels(&mut self, value: Vec<String>) {
+        self.labels = value;
+    }
+
+    fn set_words_target(&mut self, value: Option<u64>) {
+        self.words_target = value;
+    }
+
+    fn set_modified(&mut self, value: Option<i64>) {
+        self.modified = value;
+    }
+
+    fn set_tags_map(&mut self, value: Vec<TagMark>) {
+        self.tags = value;
+    }
+}
+
+impl Default for Part {
+    fn default() -> Self {
+        Self {
+            id: Ulid::new().into(),
+            priority: 0,
+            include_in_compilation: true,
+            accent: None,
+            locked: false,
+            title: None,
+            buffer: Bytes::from(""),
+            parent_id: None,
+            synopsis: None,
+            status: None,
+            labels: vec![],
+            words_target: None,
+            modified: None,
+            tags: vec![],
+        }
+    }
+}
+
+impl Part {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a copy of this part with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn set_title(&mut self, value: &str) {
+        self.title = Some(String::from(value))
+    }
+}
+
+/// A Scene is a chunk holding a single scene of a chapter, along with
+/// structured information about what happens in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scene {
+    id: String,
+    priority: u64,
+    include_in_compilation: bool,
+    accent: Option<Color>,
+    locked: bool,
+    title: Option<String>,
+    buffer: Bytes,
+    parent_id: Option<String>,
+    synopsis: Option<String>,
+    point_of_view: Option<String>,
+    location: Option<String>,
+    story_time: Option<String>,
+    goal: Option<String>,
+    conflict: Option<String>,
+    outcome: Option<String>,
+    status: Option<String>,
+    labels: Vec<String>,
+    words_target: Option<u64>,
+    modified: Option<i64>,
+    tags: Vec<TagMark>,
+}
+
+impl DocumentChunk for Scene {
+    fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn title(&self) -> Option<String> {
+        self.title.as_ref().cloned()
+    }
+
+    fn default_title(&self) -> String {
+        i18n("Untitled scene")
+    }
+
+    fn chunk_type(&self) -> ChunkType {
+        ChunkType::Scene
+    }
+
+    fn category_name(&self) -> String {
+        i18n("Scenes")
+    }
+
+    fn priority(&self) -> Option<u64> {
+        Some(self.priority)
+    }
+
+    fn set_priority(&mut self, value: Option<u64>) {
+        self.priority = value.unwrap_or(0);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn locked(&self) -> bool {
+        self.locked
+    }
+
+    fn set_locked(&mut self, value: bool) {
+        self.locked = value;
+    }
+
+    fn include_in_compilation(&self) -> bool {
+        self.include_in_compilation
+    }
+
+    fn set_include_in_compilation(&mut self, value: bool) -> ManuscriptResult<()> {
+        self.include_in_compilation = value;
+        Ok(())
+    }
+
+    fn accent(&self) -> Option<Color> {
+        self.accent
+    }
+
+    fn set_accent(&mut self, value: Option<Color>) -> ManuscriptResult<()> {
+        self.accent = value;
+        Ok(())
+    }
+
+    fn parent_id(&self) -> Option<&str> {
+        self.parent_id.as_deref()
+    }
+
+    fn set_parent_id(&mut self, value: Option<String>) -> ManuscriptResult<()> {
+        self.parent_id = value;
+        Ok(())
+    }
+
+    fn as_buffer_chunk(&self) -> Option<&dyn BufferChunk> {
+        Some(self)
+    }
+
+    fn as_mutable_buffer_chunk(&mut self) -> Option<&mut dyn MutableBufferChunk> {
+        Some(self)
+    }
+}
+
+impl BufferChunk for Scene {
+    fn buffer(&self) -> &Bytes {
+        &self.buffer
+    }
+
+    fn tags_map(&self) -> &Vec<TagMark> {
+        &self.tags
+    }
+
+    fn synopsis(&self) -> Option<&String> {
+        self.synopsis.as_ref()
+    }
+
+    fn status(&self) -> Option<&String> {
+        self.status.as_ref()
+    }
+
+    fn labels(&self) -> &Vec<String> {
+        &self.labels
+    }
+
+    fn words_target(&self) -> Option<u64> {
+        self.words_target
+    }
+
+    fn modified(&self) -> Option<i64> {
+        self.modified
+    }
+}
+
+impl MutableBufferChunk for Scene {
+    fn set_buffer(&mut self, value: Bytes) {
+        self.buffer = value;
+    }
+
+    fn set_synopsis(&mut self, value: Option<String>) {
+        self.synopsis = value;
+    }
+
+    fn set_status(&mut self, value: Option<String>) {
+        self.status = value;
+    }
+
+    fn set_labels(&mut self, value: Vec<String>) {
+        self.labels = value;
+    }
+
+    fn set_words_target(&mut self, value: Option<u64>) {
+        self.words_target = value;
+    }
+
+    fn set_modified(&mut self, value: Option<i64>) {
+        self.modified = value;
+    }
+
+    fn set_tags_map(&mut self, value: Vec<TagMark>) {
+        self.tags = value;
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self {
+            id: Ulid::new().into(),
+            priority: 0,
+            include_in_compilation: true,
+            accent: None,
+            locked: false,
+            title: None,
+            buffer: Bytes::from(""),
+            parent_id: None,
+            synopsis: None,
+            point_of_view: None,
+            location: None,
+            story_time: None,
+            goal: None,
+            conflict: None,
+            outcome: None,
+            status: None,
+            labels: vec![],
+            words_target: None,
+            modified: None,
+            tags: vec![],
+        }
+    }
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a copy of this scene with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn set_title(&mut self, value: &str) {
+        self.title = Some(String::from(value))
+    }
+
+    /// A one line summary of the scene
+    /// Id of the character sheet of the viewpoint character
+    pub fn point_of_view(&self) -> Option<&String> {
+        self.point_of_view.as_ref()
+    }
+
+    pub fn set_point_of_view(&mut self, value: Option<String>) {
+        self.point_of_view = value;
+    }
+
+    pub fn location(&self) -> Option<&String> {
+        self.location.as_ref()
+    }
+
+    pub fn set_location(&mut self, value: Option<String>) {
+        self.location = value;
+    }
+
+    /// When the scene happens in the story, in whatever form fits its calendar
+    pub fn story_time(&self) -> Option<&String> {
+        self.story_time.as_ref()
+    }
+
+    pub fn set_story_time(&mut self, value: Option<String>) {
+        self.story_time = value;
+    }
+
+    pub fn goal(&self) -> Option<&String> {
+        self.goal.as_ref()
+    }
+
+    pub fn set_goal(&mut self, value: Option<String>) {
+        self.goal = value;
+    }
+
+    pub fn conflict(&self) -> Option<&String> {
+        self.conflict.as_ref()
+    }
+
+    pub fn set_conflict(&mut self, value: Option<String>) {
+        self.conflict = value;
+    }
+
+    pub fn outcome(&self) -> Option<&String> {
+        self.outcome.as_ref()
+    }
+
+    pub fn set_outcome(&mut self, value: Option<String>) {
+        self.outcome = value;
+    }
+}
+
+/// The kind of a relationship between two characters
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RelationshipKind {
+    Sibling,
+    Rival,
+    Mentor,
+    Spouse,
+    Custom(String),
+}
+
+impl RelationshipKind {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Sibling => i18n("Sibling"),
+            Self::Rival => i18n("Rival"),
+            Self::Mentor => i18n("Mentor"),
+            Self::Spouse => i18n("Spouse"),
+            Self::Custom(label) => label.clone(),
+        }
+    }
+}
+
+/// A directed relationship from a character to the character sheet
+/// identified by `target`. The kind describes what the character is to the target
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Relationship {
+    id: String,
+    target: String,
+    kind: RelationshipKind,
+    note: Option<String>,
+}
+
+impl Relationship {
+    pub fn new(target: &str, kind: RelationshipKind, note: Option<String>) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            target: target.into(),
+            kind,
+            note,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn target(&self) -> &str {
+        self.target.as_str()
+    }
+
+    pub fn kind(&self) -> &RelationshipKind {
+        &self.kind
+    }
+
+    pub fn note(&self) -> Option<&String> {
+        self.note.as_ref()
+    }
+}
+
+/// A CharacterSheet is a chunk representing the description of a character
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CharacterSheet {
+    id: String,
+    priority: u64,
+    include_in_compilation: bool,
+    accent: Option<Color>,
+    locked: bool,
+    name: Option<String>,
+    gender: Gender,
+    age: Option<u32>,
+    role: Option<String>,
+    physical_traits: Bytes,
+    psycological_traits: Bytes,
+    background: Bytes,
+    custom_fields: HashMap<String, FieldValue>,
+    relationships: Vec<Relationship>,
+    portrait: Option<Bytes>,
+    aliases: Vec<String>,
+}
+
+impl Default for CharacterSheet {
+    fn default() -> Self {
+        Self {
+            id: Ulid::new().into(),
+            priority: 0,
+            include_in_compilation: true,
+            accent: None,
+            locked: false,
+            name: None,
+            gender: Gender::Unspecified,
+            age: None,
+            role: None,
+            physical_traits: Bytes::new(),
+            psycological_traits: Bytes::new(),
+            background: Bytes::new(),
+            custom_fields: HashMap::new(),
+            relationships: vec![],
+            portrait: None,
+            aliases: vec![],
+        }
+    }
+}
+
+impl From<v0::CharacterSheet> for CharacterSheet {
+    fn from(source: v0::CharacterSheet) -> Self {
+        Self {
+            id: source.id,
+            priority: source.priority,
+            include_in_compilation: source.include_in_compilation,
+            accent: source.accent,
+            locked: source.locked,
+            name: source.name,
+            gender: source.gender,
+            age: source.age,
+            role: source.role,
+            physical_traits: source.physical_traits,
+            psycological_traits: source.psycological_traits,
+            background: source.background,
+            custom_fields: HashMap::new(),
+            relationships: vec![],
+            portrait: None,
+            aliases: vec![],
+        }
+    }
+}
+
+impl CharacterSheet {
+    /// Creates a copy of this character sheet with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    pub fn set_name(&mut self, value: Option<String>) {
+        self.name = value;
+    }
+
+    /// Other names the character goes by in the text, like nicknames
+    pub fn aliases(&self) -> &Vec<String> {
+        &self.aliases
+    }
+
+    pub fn set_aliases(&mut self, value: Vec<String>) {
+        self.aliases = value;
+    }
+
+    /// The name and aliases the character can be mentioned by
+    pub fn mention_names(&self) -> Vec<String> {
+        self.name
+            .iter()
+            .chain(self.aliases.iter())
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn role(&self) -> Option<&String> {
+        self.role.as_ref()
+    }
+
+    pub fn set_role(&mut self, value: Option<String>) {
+        self.role = value;
+    }
+
+    pub fn gender(&self) -> Gender {
+        self.gender
+    }
+
+    pub fn set_gender(&mut self, value: Gender) {
+        self.gender = value;
+    }
+
+    pub fn age(&self) -> Option<u32> {
+        self.age
+    }
+
+    pub fn set_age(&mut self, value: Option<u32>) {
+        self.age = value;
+    }
+
+    pub fn background(&self) -> &[u8] {
+        self.background.as_ref()
+    }
+
+    pub fn set_background(&mut self, value: &[u8]) {
+        self.background = Bytes::from(Vec::from(value));
+    }
+
+    pub fn set_background_bytes(&mut self, value: Bytes) {
+        self.background = value;
+    }
+
+    pub fn physical_traits(&self) -> &[u8] {
+        self.physical_traits.as_ref()
+    }
+
+    pub fn set_physical_traits(&mut self, value: &[u8]) {
+        self.physical_traits = Bytes::from(Vec::from(value))
+    }
+
+    pub fn set_physical_traits_bytes(&mut self, value: Bytes) {
+        self.physical_traits = value;
+    }
+
+    pub fn psycological_traits(&self) -> &[u8] {
+        self.psycological_traits.as_ref()
+    }
+
+    pub fn set_psycological_traits(&mut self, value: &[u8]) {
+        self.psycological_traits = Bytes::from(Vec::from(value))
+    }
+
+    pub fn set_psycological_traits_bytes(&mut self, value: Bytes) {
+        self.psycological_traits = value;
+    }
+
+    /// Raw bytes of the portrait image, as read from the original file
+    pub fn portrait(&self) -> Option<&Bytes> {
+        self.portrait.as_ref()
+    }
+
+    pub fn set_portrait(&mut self, value: Option<Bytes>) {
+        self.portrait = value;
+    }
+
+    pub fn custom_fields(&self) -> &HashMap<String, FieldValue> {
+        &self.custom_fields
+    }
+
+    /// Value of the custom field identified by `field_id`, if any
+    pub fn custom_field(&self, field_id: &str) -> Option<&FieldValue> {
+        self.custom_fields.get(field_id)
+    }
+
+    /// Drops the link values and relationships pointing to chunks for which
+    /// `f` returns false
+    pub fn retain_links<F: Fn(&str) -> bool>(&mut self, f: F) {
+        self.remap_links(|link| Some(link.to_string()).filter(|l| f(l)));
+    }
+
+    /// Whether a link value or a relationship points to the chunk `id`
+    pub fn links_to(&self, id: &str) -> bool {
+        self.custom_fields
+            .values()
+            .any(|v| matches!(v, FieldValue::Link(link) if link == id))
+            || self.relationships.iter().any(|r| r.target == id)
+    }
+
+    /// Points link values and relationships to the chunk returned by `f`
+    /// for their current target, or drops them if it returns `None`
+    pub fn remap_links<F: Fn(&str) -> Option<String>>(&mut self, f: F) {
+        self.custom_fields.retain(|_, value| match value {
+            FieldValue::Link(link) => match f(link.as_str()) {
+                Some(target) => {
+                    *link = target;
+                    true
+                }
+                None => false,
+            },
+            _ => true,
+        });
+        self.relationships
+            .retain_mut(|relationship| match f(relationship.target.as_str()) {
+                Some(target) => {
+                    relationship.target = target;
+                    true
+                }
+                None => false,
+            });
+    }
+
+    pub fn relationships(&self) -> &Vec<Relationship> {
+        &self.relationships
+    }
+
+    pub fn add_relationship(&mut self, value: Relationship) {
+        self.relationships.push(value);
+    }
+
+    pub fn remove_relationship(&mut self, id: &str) -> Option<Relationship> {
+        let index = self.relationships.iter().position(|r| r.id() == id)?;
+        Some(self.relationships.remove(index))
+    }
+
+    pub fn set_relationship_note(&mut self, id: &str, note: Option<String>) {
+        if let Some(relationship) = self.relationships.iter_mut().find(|r| r.id() == id) {
+            relationship.note = note;
+        }
+    }
+
+    pub fn set_custom_field(&mut self, field_id: &str, value: Option<FieldValue>) {
+        match value {
+            Some(value) => {
+                self.custom_fields.insert(field_id.into(), value);
+            }
+            None => {
+                self.custom_fields.remove(field_id);
+            }
+        }
+    }
+}
+
+impl DocumentChunk for CharacterSheet {
+    fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn title(&self) -> Option<String> {
+        self.name.as_ref().cloned()
+    }
+
+    fn default_title(&self) -> String {
+        i18n("Unnamed character")
+    }
+
+    fn chunk_type(&self) -> ChunkType {
+        ChunkType::CharacterSheet
+    }
+
+    fn category_name(&self) -> String {
+        i18n("Character sheets")
+    }
+
+    fn priority(&self) -> Option<u64> {
+        Some(self.priority)
+    }
+
+    fn set_priority(&mut self, value: Option<u64>) {
+        self.priority = value.unwrap_or(0);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn locked(&self) -> bool {
+        self.locked
+    }
+
+    fn set_locked(&mut self, value: bool) {
+        self.locked = value;
+    }
+
+    fn include_in_compilation(&self) -> bool {
+        self.include_in_compilation
+    }
+
+    fn set_include_in_compilation(&mut self, value: bool) -> ManuscriptResult<()> {
+        self.include_in_compilation = value;
+        Ok(())
+    }
+
+    fn accent(&self) -> Option<Color> {
+        self.accent
+    }
+
+    fn set_accent(&mut self, value: Option<Color>) -> ManuscriptResult<()> {
+        self.accent = value;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Gender {
+    Male,
+    Female,
+    Other,
+    #[default]
+    Unspecified,
+}
+
+impl From<u64> for Gender {
+    fn from(idx: u64) -> Self {
+        match idx {
+            0 => Self::Male,
+            1 => Self::Female,
+            2 => Self::Other,
+            _ => Self::Unspecified,
+        }
+    }
+}
+
+impl From<u32> for Gender {
+    fn from(idx: u32) -> Self {
+        Self::from(idx as u64)
+    }
+}
+
+impl From<Gender> for u64 {
+    fn from(source: Gender) -> Self {
+        match source {
+            Gender::Male => 0,
+            Gender::Female => 1,
+            Gender::Other => 2,
+            Gender::Unspecified => 10,
+        }
+    }
+}
+
+impl From<Gender> for u32 {
+    fn from(source: Gender) -> Self {
+        match source {
+            Gender::Male => 0,
+            Gender::Female => 1,
+            Gender::Other => 2,
+            Gender::Unspecified => 10,
+        }
+    }
+}
+
+/// A LocationSheet describes a place of the story world, optionally
+/// contained in a wider location
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LocationSheet {
+    id: String,
+    priority: u64,
+    include_in_compilation: bool,
+    accent: Option<Color>,
+    locked: bool,
+    name: Option<String>,
+    kind: Option<String>,
+    description: Bytes,
+    parent_location: Option<String>,
+    map: Option<Bytes>,
+}
+
+impl Default for LocationSheet {
+    fn default() -> Self {
+        Self {
+            id: Ulid::new().into(),
+            priority: 0,
+            include_in_compilation: true,
+            accent: None,
+            locked: false,
+            name: None,
+            kind: None,
+            description: Bytes::new(),
+            parent_location: None,
+            map: None,
+        }
+    }
+}
+
+impl LocationSheet {
+    /// Creates a copy of this location sheet with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    pub fn set_name(&mut self, value: Option<String>) {
+        self.name = value;
+    }
+
+    /// The kind of place, like a city, a region or a building
+    pub fn kind(&self) -> Option<&String> {
+        self.kind.as_ref()
+    }
+
+    pub fn set_kind(&mut self, value: Option<String>) {
+        self.kind = value;
+    }
+
+    pub fn description(&self) -> &[u8] {
+        self.description.as_ref()
+    }
+
+    pub fn set_description_bytes(&mut self, value: Bytes) {
+        self.description = value;
+    }
+
+    /// Id of the location sheet containing this one
+    pub fn parent_location(&self) -> Option<&String> {
+        self.parent_location.as_ref()
+    }
+
+    pub fn set_parent_location(&mut self, value: Option<String>) {
+        self.parent_location = value;
+    }
+
+    /// Raw bytes of the map image, as read from the original file
+    pub fn map(&self) -> Option<&Bytes> {
+        self.map.as_ref()
+    }
+
+    pub fn set_map(&mut self, value: Option<Bytes>) {
+        self.map = value;
+    }
+}
+
+impl DocumentChunk for LocationSheet {
+    fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn title(&self) -> Option<String> {
+        self.name.as_ref().cloned()
+    }
+
+    fn default_title(&self) -> String {
+        i18n("Unnamed location")
+    }
+
+    fn chunk_type(&self) -> ChunkType {
+        ChunkType::LocationSheet
+    }
+
+    fn category_name(&self) -> String {
+        i18n("Locations")
+    }
+
+    fn priority(&self) -> Option<u64> {
+        Some(self.priority)
+    }
+
+    fn set_priority(&mut self, value: Option<u64>) {
+        self.priority = value.unwrap_or(0);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn locked(&self) -> bool {
+        self.locked
+    }
+
+    fn set_locked(&mut self, value: bool) {
+        self.locked = value;
+    }
+
+    fn include_in_compilation(&self) -> bool {
+        self.include_in_compilation
+    }
+
+    fn set_include_in_compilation(&mut self, value: bool) -> ManuscriptResult<()> {
+        self.include_in_compilation = value;
+        Ok(())
+    }
+
+    fn accent(&self) -> Option<Color> {
+        self.accent
+    }
+
+    fn set_accent(&mut self, value: Option<Color>) -> ManuscriptResult<()> {
+        self.accent = value;
+        Ok(())
+    }
+}
+
+/// A LoreEntry is a free form note about the story world, like a custom,
+/// a historical event or a magic system
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LoreEntry {
+    id: String,
+    priority: u64,
+    include_in_compilation: bool,
+    accent: Option<Color>,
+    locked: bool,
+    name: Option<String>,
+    category: Option<String>,
+    body: Bytes,
+    story_time: Option<String>,
+}
+
+impl Default for LoreEntry {
+    fn default() -> Self {
+        Self {
+            id: Ulid::new().into(),
+            priority: 0,
+            include_in_compilation: true,
+            accent: None,
+            locked: false,
+            name: None,
+            category: None,
+            body: Bytes::new(),
+            story_time: None,
+        }
+    }
+}
+
+impl LoreEntry {
+    /// Creates a copy of this lore entry with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    pub fn set_name(&mut self, value: Option<String>) {
+        self.name = value;
+    }
+
+    pub fn category(&self) -> Option<&String> {
+        self.category.as_ref()
+    }
+
+    pub fn set_category(&mut self, value: Option<String>) {
+        self.category = value;
+    }
+
+    pub fn body(&self) -> &[u8] {
+        self.body.as_ref()
+    }
+
+    pub fn set_body_bytes(&mut self, value: Bytes) {
+        self.body = value;
+    }
+
+    /// When the entry happened in the story, for entries recording an event
+    pub fn story_time(&self) -> Option<&String> {
+        self.story_time.as_ref()
+    }
+
+    pub fn set_story_time(&mut self, value: Option<String>) {
+        self.story_time = value;
+    }
+}
+
+impl DocumentChunk for LoreEntry {
+    fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn title(&self) -> Option<String> {
+        self.name.as_ref().cloned()
+    }
+
+    fn default_title(&self) -> String {
+        i18n("Untitled entry")
+    }
+
+    fn chunk_type(&self) -> ChunkType {
+        ChunkType::LoreEntry
+    }
+
+    fn category_name(&self) -> String {
+        i18n("Lore")
+    }
+
+    fn priority(&self) -> Option<u64> {
+        Some(self.priority)
+    }
+
+    fn set_priority(&mut self, value: Option<u64>) {
+        self.priority = value.unwrap_or(0);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn locked(&self) -> bool {
+        self.locked
+    }
+
+    fn set_locked(&mut self, value: bool) {
+        self.locked = value;
+    }
+
+    fn include_in_compilation(&self) -> bool {
+        self.include_in_compilation
+    }
+
+    fn set_include_in_compilation(&mut self, value: bool) -> ManuscriptResult<()> {
+        self.include_in_compilation = value;
+        Ok(())
+    }
+
+    fn accent(&self) -> Option<Color> {
+        self.accent
+    }
+
+    fn set_accent(&mut self, value: Option<Color>) -> ManuscriptResult<()> {
+        self.accent = value;
+        Ok(())
+    }
+}
+
+/// A file stored inside the project, like a picture, a PDF or
+/// a saved web page
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Attachment {
+    id: String,
+    name: String,
+    content_type: String,
+    content: Bytes,
+}
+
+impl Attachment {
+    pub fn new(name: &str, content_type: &str, content: Bytes) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            name: name.into(),
+            content_type: content_type.into(),
+            content,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    /// The original file name of the attachment
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn content_type(&self) -> &str {
+        self.content_type.as_str()
     }
+
+    pub fn content(&self) -> &Bytes {
+        &self.content
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+}
+
+/// A Research chunk gathers notes and reference material. It is
+/// never part of a compiled manuscript
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Research {
+    id: String,
+    priority: u64,
+    accent: Option<Color>,
+    locked: bool,
+    title: Option<String>,
+    notes: Bytes,
+    attachments: Vec<Attachment>,
 }
 
-impl Default for Gender {
+impl Default for Research {
     fn default() -> Self {
-        Self::Unspecified
+        Self {
+            id: Ulid::new().into(),
+            priority: 0,
+            accent: None,
+            locked: false,
+            title: None,
+            notes: Bytes::new(),
+            attachments: vec![],
+        }
+    }
+}
+
+impl Research {
+    /// Creates a copy of this research chunk with a new identifier
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            ..self.clone()
+        }
+    }
+
+    pub fn set_title(&mut self, value: Option<String>) {
+        self.title = value;
+    }
+
+    /// Markdown notes about this research
+    pub fn notes(&self) -> &[u8] {
+        self.notes.as_ref()
+    }
+
+    pub fn set_notes_bytes(&mut self, value: Bytes) {
+        self.notes = value;
+    }
+
+    pub fn attachments(&self) -> &Vec<Attachment> {
+        &self.attachments
+    }
+
+    pub fn add_attachment(&mut self, value: Attachment) {
+        self.attachments.push(value);
+    }
+
+    pub fn remove_attachment(&mut self, id: &str) -> Option<Attachment> {
+        let position = self.attachments.iter().position(|a| a.id() == id)?;
+        Some(self.attachments.remove(position))
+    }
+}
+
+impl DocumentChunk for Research {
+    fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    fn title(&self) -> Option<String> {
+        self.title.as_ref().cloned()
+    }
+
+    fn default_title(&self) -> String {
+        i18n("Untitled research")
+    }
+
+    fn chunk_type(&self) -> ChunkType {
+        ChunkType::Research
+    }
+
+    fn category_name(&self) -> String {
+        i18n("Research")
+    }
+
+    fn priority(&self) -> Option<u64> {
+        Some(self.priority)
+    }
+
+    fn set_priority(&mut self, value: Option<u64>) {
+        self.priority = value.unwrap_or(0);
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn locked(&self) -> bool {
+        self.locked
+    }
+
+    fn set_locked(&mut self, value: bool) {
+        self.locked = value;
+    }
+
+    fn include_in_compilation(&self) -> bool {
+        false
+    }
+
+    fn set_include_in_compilation(&mut self, value: bool) -> ManuscriptResult<()> {
+        if value {
+            Err(ManuscriptError::Reason("Research can not be compiled"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn accent(&self) -> Option<Color> {
+        self.accent
+    }
+
+    fn set_accent(&mut self, value: Option<Color>) -> ManuscriptResult<()> {
+        self.accent = value;
+        Ok(())
     }
 }
 
@@ -483,9 +1985,36 @@ pub struct Note {
     buffer: Bytes,
     offset: NoteOffsetType,
     len: NoteOffsetType,
+    resolved: bool,
+}
+
+impl From<v0::Note> for Note {
+    fn from(source: v0::Note) -> Self {
+        Self {
+            id: source.id,
+            buffer: source.buffer,
+            offset: source.offset,
+            len: source.len,
+            resolved: false,
+        }
+    }
 }
 
 impl Note {
+    pub fn new(offset: NoteOffsetType, len: NoteOffsetType, content: String) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            buffer: Bytes::from(content),
+            offset,
+            len,
+            resolved: false,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
     pub fn offset_start(&self) -> NoteOffsetType {
         self.offset
     }
@@ -505,4 +2034,229 @@ impl Note {
     pub fn set_buffer(&mut self, value: Bytes) {
         self.buffer = value;
     }
+
+    /// Moves the note over `len` characters starting at `offset`
+    pub fn set_range(&mut self, offset: NoteOffsetType, len: NoteOffsetType) {
+        self.offset = offset;
+        self.len = len;
+    }
+
+    /// Whether the note was dealt with, and is kept only for reference
+    pub fn resolved(&self) -> bool {
+        self.resolved
+    }
+
+    pub fn set_resolved(&mut self, value: bool) {
+        self.resolved = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn research_is_never_compiled() {
+        let mut research = Research::default();
+        assert!(!research.include_in_compilation());
+        assert!(research.set_include_in_compilation(true).is_err());
+        assert!(research.set_include_in_compilation(false).is_ok());
+    }
+
+    #[test]
+    fn research_attachments() {
+        let mut research = Research::default();
+        let image = Attachment::new("brig.png", "image/png", Bytes::from_static(b"PNG"));
+        let pdf = Attachment::new("log.pdf", "application/pdf", Bytes::from_static(b"%PDF"));
+        assert!(image.is_image());
+        assert!(!pdf.is_image());
+
+        let image_id = image.id().to_string();
+        research.add_attachment(image);
+        research.add_attachment(pdf);
+        assert_eq!(
+            research
+                .remove_attachment(&image_id)
+                .map(|a| a.name().to_string()),
+            Some("brig.png".into())
+        );
+        assert!(research.remove_attachment(&image_id).is_none());
+        assert_eq!(research.attachments().len(), 1);
+        assert_eq!(research.attachments()[0].content_type(), "application/pdf");
+    }
+
+    #[test]
+    fn duplicated_research_gets_a_new_id() {
+        let mut research = Research::default();
+        research.set_title(Some("Sailing ships".into()));
+        let copy = research.duplicate();
+        assert_ne!(copy.id(), research.id());
+        assert_eq!(copy.title(), research.title());
+    }
+
+    #[test]
+    fn duplicated_sheets_keep_their_portrait() {
+        let portrait = Bytes::from_static(b"\x89PNG portrait");
+        let mut sheet = CharacterSheet::default();
+        assert_eq!(sheet.portrait(), None);
+        sheet.set_portrait(Some(portrait.clone()));
+        let copy = sheet.duplicate();
+        assert_ne!(copy.id(), sheet.id());
+        assert_eq!(copy.portrait(), Some(&portrait));
+    }
+
+    #[test]
+    fn touch_marks_the_chunk_modified() {
+        let mut scene = Scene::default();
+        assert!(scene.modified().is_none());
+        let before = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        scene.touch();
+        assert!(scene.modified().is_some_and(|m| m >= before));
+    }
+
+    #[test]
+    fn duplicated_chapters_keep_their_target() {
+        let mut chapter = Chapter::default();
+        assert!(chapter.words_target().is_none());
+        chapter.set_words_target(Some(2500));
+        assert_eq!(chapter.duplicate().words_target(), Some(2500));
+    }
+
+    #[test]
+    fn resolve_and_move_notes() {
+        let mut note = Note::new(9, 4, "Too cliché?".into());
+        assert!(!note.resolved());
+        note.set_resolved(true);
+        assert!(note.resolved());
+        note.set_range(18, 6);
+        assert_eq!((note.offset_start(), note.offset_end()), (18, 24));
+        assert_eq!(note.buffer(), "Too cliché?".as_bytes());
+    }
+
+    #[test]
+    fn notes_at_include_their_bounds() {
+        let mut chapter = Chapter::default();
+        chapter.add_note(9, 13, "Too cliché?".into());
+        chapter.add_note(0, 2, "Spell it out".into());
+        assert_eq!(chapter.notes_at(9).len(), 1);
+        assert_eq!(chapter.notes_at(13).len(), 1);
+        assert!(chapter.notes_at(14).is_empty());
+        assert_eq!(chapter.notes_at(0)[0].buffer(), b"Spell it out");
+    }
+
+    #[test]
+    fn remove_notes() {
+        let mut chapter = Chapter::default();
+        let note = Note::new(9, 4, "Too cliché?".into());
+        let note_id = note.id().to_string();
+        chapter.push_note(note);
+        chapter.add_note(0, 2, "Spell it out".into());
+        assert!(chapter.note_mut(&note_id).is_some());
+        assert!(chapter.remove_note(&note_id).is_some());
+        assert!(chapter.remove_note(&note_id).is_none());
+        assert!(chapter.note_mut(&note_id).is_none());
+        assert_eq!(chapter.notes().len(), 1);
+    }
+
+    fn chapter_with(text: &str) -> Chapter {
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from(text.to_string()));
+        chapter
+    }
+
+    fn text(chapter: &Chapter) -> &str {
+        std::str::from_utf8(chapter.buffer()).unwrap()
+    }
+
+    fn note_spans(chapter: &Chapter) -> Vec<(NoteOffsetType, NoteOffsetType)> {
+        chapter
+            .notes()
+            .iter()
+            .map(|n| (n.offset_start(), n.offset_end()))
+            .collect()
+    }
+
+    #[test]
+    fn split_off_moves_the_text_after_offset() {
+        let mut chapter = chapter_with("Before the storm. After the storm.");
+        let other = chapter.split_off(18);
+        assert_eq!(text(&chapter), "Before the storm. ");
+        assert_eq!(text(&other), "After the storm.");
+        assert_ne!(other.id(), chapter.id());
+
+        // Offsets count characters, not bytes
+        let mut chapter = chapter_with("Café. Thé.");
+        assert_eq!(text(&chapter.split_off(6)), "Thé.");
+        assert_eq!(text(&chapter), "Café. ");
+
+        let mut chapter = chapter_with("Short");
+        assert!(chapter.split_off(100).buffer().is_empty());
+        assert_eq!(text(&chapter), "Short");
+    }
+
+    #[test]
+    fn split_off_cuts_notes_and_marks_in_two() {
+        let mut chapter = chapter_with("Before the storm. After the storm.");
+        chapter.add_note(0, 6, "Opening".into());
+        chapter.add_note(11, 24, "Spans the split".into());
+        chapter.add_note(28, 33, "Ending".into());
+        chapter.set_tags_map(vec![
+            TagMark::new(0, 6, "bold"),
+            TagMark::new(11, 24, "italic"),
+        ]);
+        let other = chapter.split_off(18);
+
+        assert_eq!(note_spans(&chapter), vec![(0, 6), (11, 18)]);
+        assert_eq!(note_spans(&other), vec![(0, 6), (10, 15)]);
+        assert_ne!(other.notes()[0].id(), chapter.notes()[1].id());
+        assert_eq!(
+            chapter.tags_map(),
+            &vec![TagMark::new(0, 6, "bold"), TagMark::new(11, 18, "italic")]
+        );
+        assert_eq!(other.tags_map(), &vec![TagMark::new(0, 6, "italic")]);
+    }
+
+    #[test]
+    fn split_off_keeps_place_and_workflow() {
+        let mut chapter = chapter_with("One. Two.");
+        chapter.set_parent_id(Some("01PART".into())).unwrap();
+        chapter.set_status(Some("01DRAFT".into()));
+        chapter.set_labels(vec!["01CUT".into()]);
+        chapter.set_title("First");
+        let other = chapter.split_off(5);
+        assert_eq!(other.parent_id(), Some("01PART"));
+        assert_eq!(other.status(), Some(&"01DRAFT".to_string()));
+        assert_eq!(other.labels(), &vec!["01CUT".to_string()]);
+        assert_eq!(other.title(), None);
+    }
+
+    #[test]
+    fn append_after_a_blank_line() {
+        let appended = |first: &str, second: &str| {
+            let mut chapter = chapter_with(first);
+            chapter.append(&chapter_with(second));
+            text(&chapter).to_string()
+        };
+        assert_eq!(appended("One.", "Two."), "One.\n\nTwo.");
+        assert_eq!(appended("One.\n", "Two."), "One.\n\nTwo.");
+        assert_eq!(appended("One.\n\n", "Two."), "One.\n\nTwo.");
+        assert_eq!(appended("", "Two."), "Two.");
+        assert_eq!(appended("One.", ""), "One.");
+    }
+
+    #[test]
+    fn append_shifts_notes_and_marks() {
+        let mut chapter = chapter_with("Été.");
+        chapter.add_note(0, 3, "Season".into());
+        let mut next = chapter_with("Next day.");
+        next.add_note(0, 4, "Morning".into());
+        next.set_tags_map(vec![TagMark::new(5, 8, "bold")]);
+        chapter.append(&next);
+
+        assert_eq!(note_spans(&chapter), vec![(0, 3), (6, 10)]);
+        assert_eq!(chapter.tags_map(), &vec![TagMark::new(11, 14, "bold")]);
+    }
 }
@@ -1,9 +1,18 @@
 use super::{
-    chunk::{Chapter, CharacterSheet, DocumentManifest},
+    chunk::{
+        Chapter, CharacterSheet, DocumentManifest, LocationSheet, LoreEntry, Part, Research, Scene,
+    },
+    fields::{FieldDefinition, FieldValue},
+    format::{self, v0},
+    links::title_key,
     prelude::*,
+    query::{search_key, ChunkQuery},
+    rename::{replace_ranges, shift_offset, NameOccurrence},
+    workflow::{ChunkLabel, ChunkStatus},
 };
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Default)]
 pub struct Document {
@@ -11,6 +20,32 @@ pub struct Document {
     chunks: HashMap<String, Box<dyn DocumentChunk>>,
 }
 
+/// Chunks as they were before a structural edit, like splitting a chapter,
+/// along with the chunks the edit created, so that it can be reverted in one go
+#[derive(Debug, Clone)]
+pub struct EditSnapshot {
+    chunks: Vec<Box<dyn DocumentChunk>>,
+    created: Vec<String>,
+    priorities: Vec<(String, Option<u64>)>,
+}
+
+impl EditSnapshot {
+    /// Ids of the chunks created by the edit
+    pub fn created(&self) -> &Vec<String> {
+        &self.created
+    }
+
+    /// Whether the edit created, changed or removed the chunk `id`
+    pub fn involves(&self, id: &str) -> bool {
+        self.created.iter().any(|c| c == id) || self.chunks.iter().any(|c| c.id() == id)
+    }
+
+    /// Ids of the chunks changed or removed by the edit
+    pub fn saved_ids(&self) -> Vec<String> {
+        self.chunks.iter().map(|c| c.id().to_string()).collect()
+    }
+}
+
 impl glib::StaticType for Document {
     fn static_type() -> glib::Type {
         glib::Type::OBJECT
@@ -31,8 +66,34 @@ impl Document {
             .insert(String::from(value.id()), Box::new(value));
     }
 
+    pub fn add_boxed_chunk(&mut self, value: Box<dyn DocumentChunk>) {
+        self.chunks.insert(String::from(value.id()), value);
+    }
+
+    /// Removes a chunk from the document. Its children are moved
+    /// under the parent of the removed chunk
     pub fn remove_chunk(&mut self, id: &String) -> Option<Box<dyn DocumentChunk>> {
-        self.chunks.remove(id)
+        let removed = self.chunks.remove(id)?;
+        let parent_id = removed.parent_id().map(String::from);
+        let parent_location = removed
+            .as_any()
+            .downcast_ref::<LocationSheet>()
+            .and_then(|l| l.parent_location())
+            .cloned();
+        for chunk in self.chunks.values_mut() {
+            if chunk.parent_id() == Some(id.as_str()) {
+                let _ = chunk.set_parent_id(parent_id.clone());
+            }
+            if let Some(location) = chunk.as_any_mut().downcast_mut::<LocationSheet>() {
+                if location.parent_location() == Some(id) {
+                    location.set_parent_location(parent_location.clone());
+                }
+            }
+            if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                sheet.retain_links(|link| link != id);
+            }
+        }
+        Some(removed)
     }
 
     pub fn get_chunk_ref(&self, id: &str) -> Option<&dyn DocumentChunk> {
@@ -65,6 +126,17 @@ impl Document {
             .collect::<Vec<&dyn DocumentChunk>>()
     }
 
+    /// The chunk a `[[Name]]` link points to, matching titles regardless of case.
+    /// When several chunks share a title the oldest one wins
+    pub fn chunk_by_title(&self, name: &str) -> Option<&dyn DocumentChunk> {
+        let name = title_key(name);
+        self.chunks
+            .values()
+            .filter(|c| c.title().is_some_and(|title| title_key(&title) == name))
+            .min_by(|a, b| a.id().cmp(b.id()))
+            .map(|c| c.as_ref())
+    }
+
     pub fn chunks_by_type_ref(&self, ty: ChunkType) -> Vec<&dyn DocumentChunk> {
         self.chunks
             .values()
@@ -80,6 +152,535 @@ impl Document {
             .map(|c| c.as_ref())
             .collect::<Vec<&dyn DocumentChunk>>()
     }
+
+    /// Chunks matching `query`, in outline order first and then by type
+    pub fn search(&self, query: &ChunkQuery) -> Vec<&dyn DocumentChunk> {
+        let mut found: Vec<&dyn DocumentChunk> = self
+            .outline()
+            .into_iter()
+            .map(|(chunk, _)| chunk)
+            .filter(|chunk| query.matches(self, *chunk))
+            .collect();
+
+        let mut others: Vec<&dyn DocumentChunk> = self
+            .chunks()
+            .into_iter()
+            .filter(|chunk| !chunk.chunk_type().is_outline() && query.matches(self, *chunk))
+            .collect();
+        others.sort_by_key(|c| (c.category_name(), c.priority().unwrap_or(0)));
+        found.append(&mut others);
+        found
+    }
+
+    /// Moves the text after the character `offset` of chapter `id` into a
+    /// new chapter placed right after it
+    pub fn split_chapter(&mut self, id: &str, offset: i32) -> ManuscriptResult<EditSnapshot> {
+        let priorities = self.sibling_priorities(id);
+        let chapter = self
+            .get_chunk_mut(id)
+            .and_then(|c| c.as_any_mut().downcast_mut::<Chapter>())
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        if chapter.locked() {
+            return Err(ManuscriptError::ChunkBusy);
+        }
+
+        let saved: Box<dyn DocumentChunk> = Box::new(chapter.clone());
+        let mut new_chapter = chapter.split_off(offset);
+        new_chapter.touch();
+        chapter.touch();
+        let new_id = new_chapter.id().to_string();
+        self.add_chunk(new_chapter);
+        self.move_chunk_next_to(&new_id, id, true)?;
+
+        Ok(EditSnapshot {
+            chunks: vec![saved],
+            created: vec![new_id],
+            priorities,
+        })
+    }
+
+    /// Appends the chapter following `id` among its siblings to it, nesting
+    /// the chunks of the former under the latter
+    pub fn merge_chapter_with_next(&mut self, id: &str) -> ManuscriptResult<EditSnapshot> {
+        let siblings = self.siblings_of(id);
+        let next = siblings
+            .iter()
+            .position(|c| c.id() == id)
+            .and_then(|position| siblings.get(position + 1))
+            .and_then(|c| c.as_any().downcast_ref::<Chapter>())
+            .cloned()
+            .ok_or(ManuscriptError::Reason("No chapter follows this one"))?;
+        let chapter = self
+            .chunks
+            .get(id)
+            .cloned()
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        if chapter.locked() || next.locked() {
+            return Err(ManuscriptError::ChunkBusy);
+        }
+
+        let priorities = self.sibling_priorities(id);
+        let children: Vec<String> = self
+            .children_of(Some(next.id()))
+            .iter()
+            .map(|c| c.id().to_string())
+            .collect();
+        let sheets: Vec<String> = self
+            .chunks
+            .values()
+            .filter(|c| {
+                c.as_any()
+                    .downcast_ref::<CharacterSheet>()
+                    .is_some_and(|sheet| sheet.links_to(next.id()))
+            })
+            .map(|c| c.id().to_string())
+            .collect();
+        let mut saved: Vec<Box<dyn DocumentChunk>> = vec![chapter, Box::new(next.clone())];
+        for other in children.iter().chain(sheets.iter()) {
+            if let Some(chunk) = self.chunks.get(other) {
+                saved.push(chunk.clone());
+            }
+        }
+
+        if let Some(chapter) = self
+            .get_chunk_mut(id)
+            .and_then(|c| c.as_any_mut().downcast_mut::<Chapter>())
+        {
+            chapter.append(&next);
+            chapter.touch();
+        }
+        for child in children.iter() {
+            self.set_parent(child, Some(id))?;
+        }
+        for sheet in sheets.iter() {
+            if let Some(sheet) = self
+                .get_chunk_mut(sheet)
+                .and_then(|c| c.as_any_mut().downcast_mut::<CharacterSheet>())
+            {
+                sheet.remap_links(|link| {
+                    Some(if link == next.id() { id } else { link }.to_string())
+                });
+            }
+        }
+        self.remove_chunk(&next.id().to_string());
+
+        Ok(EditSnapshot {
+            chunks: saved,
+            created: vec![],
+            priorities,
+        })
+    }
+
+    /// Replaces each of `occurrences` with `name`, moving the notes and tag
+    /// marks of the edited chunks along with the text around them
+    pub fn rename_occurrences(
+        &mut self,
+        occurrences: &[NameOccurrence],
+        name: &str,
+    ) -> ManuscriptResult<EditSnapshot> {
+        let mut ranges: BTreeMap<&str, Vec<(i32, i32)>> = BTreeMap::new();
+        for occurrence in occurrences.iter() {
+            ranges
+                .entry(occurrence.chunk_id())
+                .or_default()
+                .push((occurrence.start(), occurrence.end()));
+        }
+
+        // Check everything first, so that the edit is either applied as a whole or not at all
+        let mut saved: Vec<Box<dyn DocumentChunk>> = vec![];
+        for (id, chunk_ranges) in ranges.iter_mut() {
+            let chunk = self
+                .chunks
+                .get(*id)
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            if chunk.locked() {
+                return Err(ManuscriptError::ChunkBusy);
+            }
+            let text: Vec<char> = String::from_utf8_lossy(
+                chunk
+                    .as_buffer_chunk()
+                    .ok_or(ManuscriptError::ChunkUnavailable)?
+                    .buffer(),
+            )
+            .chars()
+            .collect();
+
+            chunk_ranges.sort();
+            chunk_ranges.dedup();
+            let matches_text = occurrences.iter().filter(|o| o.chunk_id() == *id).all(|o| {
+                text.get(o.start() as usize..o.end() as usize)
+                    .is_some_and(|found| found.iter().collect::<String>() == o.name())
+            });
+            let overlaps = chunk_ranges.windows(2).any(|pair| pair[0].1 > pair[1].0);
+            if !matches_text || overlaps {
+                return Err(ManuscriptError::Reason(
+                    "The text changed since the occurrences were found",
+                ));
+            }
+            saved.push(chunk.clone());
+        }
+
+        let len = name.chars().count() as i32;
+        for (id, chunk_ranges) in ranges.iter() {
+            let Some(chunk) = self.get_chunk_mut(id) else {
+                continue;
+            };
+            if let Some(chapter) = chunk.as_any_mut().downcast_mut::<Chapter>() {
+                chapter
+                    .remap_notes(|offset, is_end| shift_offset(offset, chunk_ranges, len, is_end));
+            }
+            if let Some(buffer_chunk) = chunk.as_mutable_buffer_chunk() {
+                let text = String::from_utf8_lossy(buffer_chunk.buffer()).to_string();
+                let tags = buffer_chunk
+                    .tags_map()
+                    .iter()
+                    .map(|mark| {
+                        TagMark::new(
+                            shift_offset(mark.start(), chunk_ranges, len, false),
+                            shift_offset(mark.end(), chunk_ranges, len, true),
+                            mark.name(),
+                        )
+                    })
+                    .collect();
+                buffer_chunk.set_buffer(Bytes::from(replace_ranges(&text, chunk_ranges, name)));
+                buffer_chunk.set_tags_map(tags);
+                buffer_chunk.touch();
+            }
+        }
+
+        Ok(EditSnapshot {
+            chunks: saved,
+            created: vec![],
+            priorities: vec![],
+        })
+    }
+
+    /// Reverts the edit `snapshot` was taken for
+    pub fn restore(&mut self, snapshot: EditSnapshot) {
+        for id in snapshot.created.iter() {
+            self.remove_chunk(id);
+        }
+        for chunk in snapshot.chunks {
+            self.add_boxed_chunk(chunk);
+        }
+        for (id, priority) in snapshot.priorities {
+            if let Some(chunk) = self.get_chunk_mut(&id) {
+                chunk.set_priority(priority);
+            }
+        }
+    }
+
+    fn sibling_priorities(&self, id: &str) -> Vec<(String, Option<u64>)> {
+        self.siblings_of(id)
+            .iter()
+            .map(|c| (c.id().to_string(), c.priority()))
+            .collect()
+    }
+
+    /// Removes a custom character field along with the values sheets hold for it
+    pub fn remove_character_field(&mut self, id: &str) -> Option<FieldDefinition> {
+        let removed = self.manifest.remove_character_field(id)?;
+        for chunk in self.chunks.values_mut() {
+            if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                sheet.set_custom_field(id, None);
+            }
+        }
+        Some(removed)
+    }
+
+    /// Removes a workflow status, unsetting it on the chunks marked with it
+    pub fn remove_status(&mut self, id: &str) -> Option<ChunkStatus> {
+        let removed = self.manifest.remove_status(id)?;
+        for chunk in self.chunks.values_mut() {
+            if let Some(chunk) = chunk.as_mutable_buffer_chunk() {
+                if chunk.status().map(String::as_str) == Some(id) {
+                    chunk.set_status(None);
+                }
+            }
+        }
+        Some(removed)
+    }
+
+    /// Removes a label, detaching it from the chunks marked with it
+    pub fn remove_label(&mut self, id: &str) -> Option<ChunkLabel> {
+        let removed = self.manifest.remove_label(id)?;
+        for chunk in self.chunks.values_mut() {
+            if let Some(chunk) = chunk.as_mutable_buffer_chunk() {
+                let labels = chunk
+                    .labels()
+                    .iter()
+                    .filter(|l| *l != id)
+                    .cloned()
+                    .collect();
+                chunk.set_labels(labels);
+            }
+        }
+        Some(removed)
+    }
+
+    /// Character sheets ordered by the value of the custom field `field_id`,
+    /// or by their position in the project if `None`
+    pub fn character_sheets_sorted_by(&self, field_id: Option<&str>) -> Vec<&CharacterSheet> {
+        let mut sheets: Vec<&CharacterSheet> = self
+            .chunks
+            .values()
+            .filter_map(|c| c.as_any().downcast_ref::<CharacterSheet>())
+            .collect();
+        sheets.sort_by(|a, b| {
+            let by_field = field_id
+                .map(|id| FieldValue::compare(a.custom_field(id), b.custom_field(id), self))
+                .unwrap_or(std::cmp::Ordering::Equal);
+            by_field.then(a.priority().cmp(&b.priority()))
+        });
+        sheets
+    }
+
+    /// Creates a standalone document holding a copy of the chunks identified
+    /// by `ids` and the same manifest as this one
+    pub fn subset(&self, ids: &[String]) -> Document {
+        let mut document = Document {
+            manifest: self.manifest.clone(),
+            chunks: HashMap::new(),
+        };
+        for id in ids {
+            if let Some(chunk) = self.chunks.get(id) {
+                document.chunks.insert(id.clone(), chunk.clone());
+            }
+        }
+        for chunk in document.chunks.values_mut() {
+            if chunk.parent_id().map(|p| !ids.iter().any(|id| id == p)) == Some(true) {
+                let _ = chunk.set_parent_id(None);
+            }
+            if let Some(location) = chunk.as_any_mut().downcast_mut::<LocationSheet>() {
+                if location.parent_location().map(|p| !ids.contains(p)) == Some(true) {
+                    location.set_parent_location(None);
+                }
+            }
+            if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                sheet.retain_links(|link| ids.iter().any(|id| id == link));
+            }
+            if let Some(scene) = chunk.as_any_mut().downcast_mut::<Scene>() {
+                if scene.point_of_view().map(|p| !ids.contains(p)) == Some(true) {
+                    scene.set_point_of_view(None);
+                }
+                if scene.location().map(|l| !ids.contains(l)) == Some(true) {
+                    scene.set_location(None);
+                }
+            }
+        }
+        document
+    }
+
+    /// Copies the chunks identified by `ids` from `source` into this document.
+    /// Copies get new identifiers and are placed after their existing siblings,
+    /// keeping their relative order. Copies nested in another copied chunk keep
+    /// their hierarchy. Returns the new identifiers
+    pub fn merge(&mut self, source: &Document, ids: &[String]) -> Vec<String> {
+        let mut selected: Vec<&dyn DocumentChunk> = ids
+            .iter()
+            .filter_map(|id| source.get_chunk_ref(id))
+            .collect();
+        selected.sort_by_key(|c| c.priority().unwrap_or(0));
+
+        let mut new_ids: HashMap<String, String> = HashMap::new();
+        let mut merged = vec![];
+        for chunk in selected {
+            let copy: Box<dyn DocumentChunk> = if let Some(chapter) =
+                chunk.as_any().downcast_ref::<Chapter>()
+            {
+                Box::new(chapter.duplicate())
+            } else if let Some(part) = chunk.as_any().downcast_ref::<Part>() {
+                Box::new(part.duplicate())
+            } else if let Some(scene) = chunk.as_any().downcast_ref::<Scene>() {
+                Box::new(scene.duplicate())
+            } else if let Some(character_sheet) = chunk.as_any().downcast_ref::<CharacterSheet>() {
+                Box::new(character_sheet.duplicate())
+            } else if let Some(location_sheet) = chunk.as_any().downcast_ref::<LocationSheet>() {
+                Box::new(location_sheet.duplicate())
+            } else if let Some(lore_entry) = chunk.as_any().downcast_ref::<LoreEntry>() {
+                Box::new(lore_entry.duplicate())
+            } else if let Some(research) = chunk.as_any().downcast_ref::<Research>() {
+                Box::new(research.duplicate())
+            } else {
+                continue;
+            };
+
+            new_ids.insert(chunk.id().to_string(), copy.id().to_string());
+            merged.push(copy.id().to_string());
+            self.chunks.insert(copy.id().to_string(), copy);
+        }
+
+        let existing_ids: Vec<String> = self.chunks.keys().cloned().collect();
+        for id in merged.iter() {
+            if let Some(copy) = self.chunks.get_mut(id) {
+                let parent_id = copy.parent_id().and_then(|p| new_ids.get(p)).cloned();
+                let _ = copy.set_parent_id(parent_id);
+
+                // Viewpoints and locations follow copied sheets, or are dropped
+                // if the sheet does not exist in this document
+                if let Some(scene) = copy.as_any_mut().downcast_mut::<Scene>() {
+                    let remap = |id: &String| {
+                        new_ids
+                            .get(id)
+                            .cloned()
+                            .or(Some(id.clone()).filter(|id| existing_ids.contains(id)))
+                    };
+                    let point_of_view = scene.point_of_view().and_then(remap);
+                    let location = scene.location().and_then(remap);
+                    scene.set_point_of_view(point_of_view);
+                    scene.set_location(location);
+                }
+
+                if let Some(location) = copy.as_any_mut().downcast_mut::<LocationSheet>() {
+                    let parent_location = location
+                        .parent_location()
+                        .and_then(|p| new_ids.get(p))
+                        .cloned();
+                    location.set_parent_location(parent_location);
+                }
+            }
+        }
+
+        // Each group of siblings receiving copies is renumbered with the
+        // copies last, in the order they had in the source
+        let mut placed: Vec<String> = vec![];
+        for id in merged.iter() {
+            if placed.contains(id) {
+                continue;
+            }
+            let (copies, mut siblings): (Vec<String>, Vec<String>) = self
+                .siblings_of(id)
+                .iter()
+                .map(|c| c.id().to_string())
+                .partition(|c| merged.contains(c));
+            let copies: Vec<String> = merged
+                .iter()
+                .filter(|m| copies.contains(m))
+                .cloned()
+                .collect();
+            siblings.extend(copies.iter().cloned());
+            self.renumber(siblings.as_slice());
+            placed.extend(copies);
+        }
+
+        // Custom field values are moved to the fields of this project with
+        // the same name. Fields the copies use are created when missing
+        let used_fields: Vec<&str> = ids
+            .iter()
+            .filter_map(|id| source.get_chunk_ref(id))
+            .filter_map(|c| c.as_any().downcast_ref::<CharacterSheet>())
+            .flat_map(|sheet| sheet.custom_fields().keys())
+            .map(String::as_str)
+            .collect();
+        let mut field_ids: HashMap<String, String> = HashMap::new();
+        for field in source
+            .manifest
+            .character_fields()
+            .iter()
+            .filter(|f| used_fields.contains(&f.id()))
+        {
+            let id = match self.manifest.character_field_by_key(&field.search_key()) {
+                Some(existing) => existing.id().to_string(),
+                None => {
+                    self.manifest.add_character_field(field.clone());
+                    field.id().to_string()
+                }
+            };
+            field_ids.insert(field.id().to_string(), id);
+        }
+        // Links and relationships follow copied chunks, like viewpoints do
+        for id in merged.iter() {
+            if let Some(sheet) = self
+                .chunks
+                .get_mut(id)
+                .and_then(|c| c.as_any_mut().downcast_mut::<CharacterSheet>())
+            {
+                sheet.remap_links(|link| {
+                    new_ids
+                        .get(link)
+                        .cloned()
+                        .or(Some(link.to_string()).filter(|l| existing_ids.contains(l)))
+                });
+
+                let values: Vec<(String, FieldValue)> = sheet
+                    .custom_fields()
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                for (field_id, value) in values {
+                    sheet.set_custom_field(field_id.as_str(), None);
+                    if let Some(target) = field_ids.get(&field_id) {
+                        sheet.set_custom_field(target.as_str(), Some(value));
+                    }
+                }
+            }
+        }
+
+        // Statuses and labels are matched by name too
+        let buffer_chunks = || {
+            ids.iter()
+                .filter_map(|id| source.get_chunk_ref(id))
+                .filter_map(|c| c.as_buffer_chunk())
+        };
+        let used_statuses: Vec<&str> = buffer_chunks()
+            .filter_map(|c| c.status())
+            .map(String::as_str)
+            .collect();
+        let used_labels: Vec<&str> = buffer_chunks()
+            .flat_map(|c| c.labels())
+            .map(String::as_str)
+            .collect();
+        let mut workflow_ids: HashMap<String, String> = HashMap::new();
+        for status in source
+            .manifest
+            .statuses()
+            .iter()
+            .filter(|s| used_statuses.contains(&s.id()))
+        {
+            let id = match self.manifest.status_by_key(&search_key(status.name())) {
+                Some(existing) => existing.id().to_string(),
+                None => {
+                    self.manifest.add_status(status.clone());
+                    status.id().to_string()
+                }
+            };
+            workflow_ids.insert(status.id().to_string(), id);
+        }
+        for label in source
+            .manifest
+            .labels()
+            .iter()
+            .filter(|l| used_labels.contains(&l.id()))
+        {
+            let id = match self.manifest.label_by_key(&search_key(label.name())) {
+                Some(existing) => existing.id().to_string(),
+                None => {
+                    self.manifest.add_label(label.clone());
+                    label.id().to_string()
+                }
+            };
+            workflow_ids.insert(label.id().to_string(), id);
+        }
+        for id in merged.iter() {
+            if let Some(chunk) = self
+                .chunks
+                .get_mut(id)
+                .and_then(|c| c.as_mutable_buffer_chunk())
+            {
+                let status = chunk.status().and_then(|s| workflow_ids.get(s)).cloned();
+                let labels = chunk
+                    .labels()
+                    .iter()
+                    .filter_map(|l| workflow_ids.get(l))
+                    .cloned()
+                    .collect();
+                chunk.set_status(status);
+                chunk.set_labels(labels);
+            }
+        }
+
+        merged
+    }
 }
 
 impl Document {
@@ -90,10 +691,7 @@ impl Document {
     }
 
     pub fn serialize(&self) -> Result<Vec<u8>, ManuscriptError> {
-        match bincode::serialize(&SerializableDocument::new(self)) {
-            Ok(ser) => Ok(ser),
-            Err(_) => Err(ManuscriptError::DocumentSerialize),
-        }
+        format::encode(&SerializableDocument::new(self))
     }
 }
 
@@ -101,12 +699,7 @@ impl<'d> TryFrom<&'d [u8]> for Document {
     type Error = ManuscriptError;
 
     fn try_from(value: &'d [u8]) -> Result<Self, Self::Error> {
-        let maybe_deserialized_document: Result<SerializableDocument, Box<bincode::ErrorKind>> =
-            bincode::deserialize(value);
-        match maybe_deserialized_document {
-            Ok(deserialized) => Ok(Document::from(deserialized)),
-            Err(_) => Err(ManuscriptError::DocumentDeserialize),
-        }
+        format::decode(value).map(Document::from)
     }
 }
 
@@ -115,6 +708,11 @@ pub struct SerializableDocument {
     manifest: DocumentManifest,
     chapters: Vec<Chapter>,
     character_sheets: Vec<CharacterSheet>,
+    parts: Vec<Part>,
+    scenes: Vec<Scene>,
+    location_sheets: Vec<LocationSheet>,
+    lore_entries: Vec<LoreEntry>,
+    research: Vec<Research>,
 }
 
 impl SerializableDocument {
@@ -123,6 +721,11 @@ impl SerializableDocument {
         let manifest = source.manifest().clone();
         let mut chapters = vec![];
         let mut character_sheets = vec![];
+        let mut parts = vec![];
+        let mut scenes = vec![];
+        let mut location_sheets = vec![];
+        let mut lore_entries = vec![];
+        let mut research = vec![];
 
         let chunks = source.chunks();
         for c in chunks {
@@ -131,6 +734,16 @@ impl SerializableDocument {
                 chapters.push(downcasted.clone());
             } else if let Some(downcasted) = chunk.downcast_ref::<CharacterSheet>() {
                 character_sheets.push(downcasted.clone());
+            } else if let Some(downcasted) = chunk.downcast_ref::<Part>() {
+                parts.push(downcasted.clone());
+            } else if let Some(downcasted) = chunk.downcast_ref::<Scene>() {
+                scenes.push(downcasted.clone());
+            } else if let Some(downcasted) = chunk.downcast_ref::<LocationSheet>() {
+                location_sheets.push(downcasted.clone());
+            } else if let Some(downcasted) = chunk.downcast_ref::<LoreEntry>() {
+                lore_entries.push(downcasted.clone());
+            } else if let Some(downcasted) = chunk.downcast_ref::<Research>() {
+                research.push(downcasted.clone());
             } else {
                 unreachable!()
             }
@@ -140,6 +753,11 @@ impl SerializableDocument {
             manifest,
             chapters,
             character_sheets,
+            parts,
+            scenes,
+            location_sheets,
+            lore_entries,
+            research,
         }
     }
 
@@ -162,10 +780,49 @@ impl From<SerializableDocument> for Document {
             document.add_chunk(character_sheet);
         }
 
+        for part in source.parts {
+            document.add_chunk(part);
+        }
+
+        for scene in source.scenes {
+            document.add_chunk(scene);
+        }
+
+        for location_sheet in source.location_sheets {
+            document.add_chunk(location_sheet);
+        }
+
+        for lore_entry in source.lore_entries {
+            document.add_chunk(lore_entry);
+        }
+
+        for research in source.research {
+            document.add_chunk(research);
+        }
+
         document
     }
 }
 
+impl From<v0::SerializableDocument> for SerializableDocument {
+    fn from(source: v0::SerializableDocument) -> Self {
+        Self {
+            manifest: source.manifest.into(),
+            chapters: source.chapters.into_iter().map(Chapter::from).collect(),
+            character_sheets: source
+                .character_sheets
+                .into_iter()
+                .map(CharacterSheet::from)
+                .collect(),
+            parts: vec![],
+            scenes: vec![],
+            location_sheets: vec![],
+            lore_entries: vec![],
+            research: vec![],
+        }
+    }
+}
+
 impl From<&Document> for SerializableDocument {
     fn from(source: &Document) -> Self {
         Self::new(source)
@@ -181,6 +838,7 @@ impl From<Vec<u8>> for Document {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{Color, FieldKind};
     use bytes::Bytes;
 
     fn make_test_document_1() -> Document {
@@ -220,7 +878,7 @@ mod tests {
         let serialized = doc.serialize();
         assert!(serialized.is_ok());
 
-        let deserialized = Document::try_from(serialized.unwrap());
+        let deserialized = Document::try_from(serialized.unwrap().as_slice());
         assert!(deserialized.is_ok());
         let deserialized = deserialized.unwrap();
         assert_eq!(deserialized.chunks().len(), 5);
@@ -240,4 +898,418 @@ mod tests {
         let actual_bytes = chap.unwrap().buffer().to_vec();
         assert_eq!(expected_bytes, actual_bytes);
     }
+    #[test]
+    fn subset() {
+        let mut doc = make_test_document_1();
+        doc.set_title(Some("Anthology".into()));
+        let ids: Vec<String> = doc
+            .chunks_by_type_ref(ChunkType::Chapter)
+            .iter()
+            .take(2)
+            .map(|c| c.id().to_string())
+            .collect();
+
+        let subset = doc.subset(ids.as_slice());
+        assert_eq!(subset.chunks().len(), 2);
+        assert_eq!(subset.title(), doc.title());
+        for id in ids.iter() {
+            assert!(subset.get_chunk_ref(id).is_some());
+        }
+        assert!(subset.serialize().is_ok());
+    }
+
+    #[test]
+    fn subset_drops_references_to_missing_sheets() {
+        let mut doc = Document::default();
+        let (ada, brand) = (CharacterSheet::default(), CharacterSheet::default());
+        let (harbour, tower) = (LocationSheet::default(), LocationSheet::default());
+        let mut kept = Scene::default();
+        kept.set_point_of_view(Some(ada.id().into()));
+        kept.set_location(Some(harbour.id().into()));
+        let mut dropped = Scene::default();
+        dropped.set_point_of_view(Some(brand.id().into()));
+        dropped.set_location(Some(tower.id().into()));
+        let ids: Vec<String> = [ada.id(), harbour.id(), kept.id(), dropped.id()]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        for chunk in [ada, brand] {
+            doc.add_chunk(chunk);
+        }
+        for chunk in [harbour, tower] {
+            doc.add_chunk(chunk);
+        }
+        doc.add_chunk(kept);
+        doc.add_chunk(dropped);
+
+        let subset = doc.subset(ids.as_slice());
+        let scene = |id: &str| {
+            subset
+                .get_chunk_ref(id)
+                .and_then(|c| c.as_any().downcast_ref::<Scene>())
+                .unwrap()
+        };
+        assert_eq!(scene(&ids[2]).point_of_view(), Some(&ids[0]));
+        assert_eq!(scene(&ids[2]).location(), Some(&ids[1]));
+        assert_eq!(scene(&ids[3]).point_of_view(), None);
+        assert_eq!(scene(&ids[3]).location(), None);
+    }
+
+    #[test]
+    fn merge() {
+        let mut doc = make_test_document_1();
+        let mut last_chapter = Chapter::default();
+        last_chapter.set_priority(Some(7));
+        doc.add_chunk(last_chapter);
+
+        let mut other = Document::default();
+        let mut first = Chapter::default();
+        first.set_title("First");
+        first.set_priority(Some(0));
+        let mut second = Chapter::default();
+        second.set_title("Second");
+        second.set_priority(Some(1));
+        let sheet = CharacterSheet::default();
+        let ids = vec![
+            second.id().to_string(),
+            first.id().to_string(),
+            sheet.id().to_string(),
+        ];
+        other.add_chunk(first);
+        other.add_chunk(second);
+        other.add_chunk(sheet);
+
+        let merged = doc.merge(&other, ids.as_slice());
+        assert_eq!(merged.len(), 3);
+        assert_eq!(doc.chunks().len(), 8);
+
+        // Copies get fresh ids
+        for id in ids.iter() {
+            assert!(doc.get_chunk_ref(id).is_none());
+        }
+
+        // Copies follow the existing siblings, keeping the source order
+        let first = doc.get_chunk_ref(merged[0].as_str()).unwrap();
+        let second = doc.get_chunk_ref(merged[1].as_str()).unwrap();
+        assert_eq!(first.title().unwrap(), "First");
+        assert_eq!(first.priority(), Some(4));
+        assert_eq!(second.title().unwrap(), "Second");
+        assert_eq!(second.priority(), Some(5));
+        let roots: Vec<&str> = doc.children_of(None).iter().map(|c| c.id()).collect();
+        assert_eq!(roots[4..], [merged[0].as_str(), merged[1].as_str()]);
+
+        let sheet = doc.get_chunk_ref(merged[2].as_str()).unwrap();
+        assert_eq!(sheet.chunk_type(), ChunkType::CharacterSheet);
+        assert_eq!(sheet.priority(), Some(1));
+    }
+
+    #[test]
+    fn merge_remaps_scene_sheets() {
+        let mut doc = Document::default();
+        let existing = LocationSheet::default();
+        let existing_id = existing.id().to_string();
+        doc.add_chunk(existing);
+
+        let mut other = Document::default();
+        let (ada, harbour) = (CharacterSheet::default(), LocationSheet::default());
+        let mut copied = Scene::default();
+        copied.set_point_of_view(Some(ada.id().into()));
+        copied.set_location(Some(harbour.id().into()));
+        let mut shared = Scene::default();
+        shared.set_point_of_view(Some("01MISSING".into()));
+        shared.set_location(Some(existing_id.clone()));
+        let ids: Vec<String> = [ada.id(), harbour.id(), copied.id(), shared.id()]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        other.add_chunk(ada);
+        other.add_chunk(harbour);
+        other.add_chunk(copied);
+        other.add_chunk(shared);
+
+        let merged = doc.merge(&other, ids.as_slice());
+        let scene = |id: &str| {
+            doc.get_chunk_ref(id)
+                .and_then(|c| c.as_any().downcast_ref::<Scene>())
+                .unwrap()
+        };
+        let (ada, harbour, copied, shared) = (&merged[0], &merged[1], &merged[2], &merged[3]);
+        assert_eq!(scene(copied).point_of_view(), Some(ada));
+        assert_eq!(scene(copied).location(), Some(harbour));
+        assert_eq!(scene(shared).point_of_view(), None);
+        assert_eq!(scene(shared).location(), Some(&existing_id));
+    }
+
+    #[test]
+    fn merge_renumbers_each_parent() {
+        let mut doc = Document::default();
+        let mut existing_part = Part::default();
+        existing_part.set_priority(Some(0));
+        let mut nested = Chapter::default();
+        nested.set_priority(Some(5));
+        nested
+            .set_parent_id(Some(existing_part.id().into()))
+            .unwrap();
+        doc.add_chunk(existing_part);
+        doc.add_chunk(nested);
+
+        let mut other = Document::default();
+        let mut part = Part::default();
+        part.set_priority(Some(0));
+        let mut first = Chapter::default();
+        first.set_title("First");
+        first.set_priority(Some(0));
+        first.set_parent_id(Some(part.id().into())).unwrap();
+        let mut second = Chapter::default();
+        second.set_title("Second");
+        second.set_priority(Some(1));
+        second.set_parent_id(Some(part.id().into())).unwrap();
+        let mut loose = Chapter::default();
+        loose.set_title("Loose");
+        loose.set_priority(Some(1));
+        let ids: Vec<String> = [part.id(), first.id(), second.id(), loose.id()]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        other.add_chunk(part);
+        other.add_chunk(first);
+        other.add_chunk(second);
+        other.add_chunk(loose);
+
+        let merged = doc.merge(&other, ids.as_slice());
+        let title_of = |id: &str| doc.get_chunk_ref(id).unwrap().title();
+
+        // Copied chapters restart from 0 under the copied part, rather than
+        // following every other chapter in the project
+        let part_copy = doc.get_chunk_ref(merged[0].as_str()).unwrap();
+        let children: Vec<(Option<String>, Option<u64>)> = doc
+            .children_of(Some(part_copy.id()))
+            .iter()
+            .map(|c| (c.title(), c.priority()))
+            .collect();
+        assert_eq!(
+            children,
+            vec![
+                (Some("First".into()), Some(0)),
+                (Some("Second".into()), Some(1))
+            ]
+        );
+
+        let roots: Vec<&str> = doc.children_of(None).iter().map(|c| c.id()).collect();
+        assert_eq!(roots.len(), 3);
+        assert_eq!(roots[1], part_copy.id());
+        assert_eq!(title_of(roots[2]).as_deref(), Some("Loose"));
+        let roots: Vec<Option<u64>> = doc.children_of(None).iter().map(|c| c.priority()).collect();
+        assert_eq!(roots, vec![Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn merge_copies_used_fields_and_workflow() {
+        let mut doc = Document::default();
+        let mut other = Document::default();
+        let eyes = FieldDefinition::new("Eye color", FieldKind::Text);
+        let height = FieldDefinition::new("Height", FieldKind::Number);
+        let research = ChunkStatus::new("Needs research");
+        let abandoned = ChunkStatus::new("Abandoned");
+        let flashback = ChunkLabel::new("Flashback", Color::new(0.2, 0.4, 0.6, 1.0));
+        let subplot = ChunkLabel::new("Subplot", Color::new(0.6, 0.4, 0.2, 1.0));
+        let manifest = other.manifest_mut();
+        manifest.add_character_field(eyes.clone());
+        manifest.add_character_field(height.clone());
+        manifest.add_status(research.clone());
+        manifest.add_status(abandoned.clone());
+        manifest.add_label(flashback.clone());
+        manifest.add_label(subplot.clone());
+
+        let mut sheet = CharacterSheet::default();
+        sheet.set_custom_field(eyes.id(), Some(FieldValue::Text("Grey".into())));
+        let mut chapter = Chapter::default();
+        chapter.set_status(Some(research.id().into()));
+        chapter.set_labels(vec![flashback.id().into()]);
+        let ids = vec![sheet.id().to_string(), chapter.id().to_string()];
+        other.add_chunk(sheet);
+        other.add_chunk(chapter);
+
+        let fields_before = doc.manifest().character_fields().len();
+        let statuses_before = doc.manifest().statuses().len();
+        let labels_before = doc.manifest().labels().len();
+        doc.merge(&other, ids.as_slice());
+
+        let manifest = doc.manifest();
+        assert_eq!(manifest.character_fields().len(), fields_before + 1);
+        assert!(manifest
+            .character_field_by_key(&search_key("Eye color"))
+            .is_some());
+        assert!(manifest
+            .character_field_by_key(&search_key("Height"))
+            .is_none());
+        assert_eq!(manifest.statuses().len(), statuses_before + 1);
+        assert!(manifest
+            .status_by_key(&search_key("Needs research"))
+            .is_some());
+        assert!(manifest.status_by_key(&search_key("Abandoned")).is_none());
+        assert_eq!(manifest.labels().len(), labels_before + 1);
+        assert!(manifest.label_by_key(&search_key("Flashback")).is_some());
+        assert!(manifest.label_by_key(&search_key("Subplot")).is_none());
+    }
+
+    fn add<C: DocumentChunk + 'static>(doc: &mut Document, chunk: C) -> String {
+        let id = chunk.id().to_string();
+        doc.add_chunk(chunk);
+        id
+    }
+
+    fn chunk_text(doc: &Document, id: &str) -> String {
+        String::from_utf8(
+            doc.get_chunk_ref(id)
+                .and_then(|c| c.as_buffer_chunk())
+                .unwrap()
+                .buffer()
+                .to_vec(),
+        )
+        .unwrap()
+    }
+
+    fn sibling_ids(doc: &Document, id: &str) -> Vec<String> {
+        doc.siblings_of(id)
+            .iter()
+            .map(|c| c.id().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn split_chapter_places_the_new_one_next() {
+        let mut doc = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Before the storm. After the storm."));
+        chapter.set_priority(Some(0));
+        let mut following = Chapter::default();
+        following.set_priority(Some(1));
+        let chapter_id = add(&mut doc, chapter);
+        let following_id = add(&mut doc, following);
+
+        let snapshot = doc.split_chapter(&chapter_id, 18).unwrap();
+        let new_id = snapshot.created()[0].clone();
+        assert_eq!(chunk_text(&doc, &chapter_id), "Before the storm. ");
+        assert_eq!(chunk_text(&doc, &new_id), "After the storm.");
+        assert_eq!(
+            sibling_ids(&doc, &chapter_id),
+            vec![chapter_id.clone(), new_id.clone(), following_id]
+        );
+        assert!(doc
+            .get_chunk_ref(&new_id)
+            .unwrap()
+            .as_buffer_chunk()
+            .unwrap()
+            .modified()
+            .is_some());
+    }
+
+    #[test]
+    fn undo_split_chapter() {
+        let mut doc = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Before the storm. After the storm."));
+        chapter.set_priority(Some(0));
+        let mut following = Chapter::default();
+        following.set_priority(Some(1));
+        let chapter_id = add(&mut doc, chapter);
+        let following_id = add(&mut doc, following);
+
+        let snapshot = doc.split_chapter(&chapter_id, 18).unwrap();
+        let new_id = snapshot.created()[0].clone();
+        doc.restore(snapshot);
+        assert_eq!(
+            chunk_text(&doc, &chapter_id),
+            "Before the storm. After the storm."
+        );
+        assert!(doc.get_chunk_ref(&new_id).is_none());
+        assert_eq!(
+            sibling_ids(&doc, &chapter_id),
+            vec![chapter_id, following_id]
+        );
+    }
+
+    #[test]
+    fn merge_chapter_with_next() {
+        let mut doc = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Before the storm. After the storm."));
+        chapter.set_priority(Some(0));
+        let mut following = Chapter::default();
+        following.set_buffer(Bytes::from("Next day."));
+        following.add_note(0, 4, "Morning".into());
+        following.set_priority(Some(1));
+        let chapter_id = add(&mut doc, chapter);
+        let following_id = add(&mut doc, following);
+        let scene_id = add(&mut doc, Scene::default());
+        doc.set_parent(&scene_id, Some(&following_id)).unwrap();
+
+        doc.merge_chapter_with_next(&chapter_id).unwrap();
+        assert_eq!(
+            chunk_text(&doc, &chapter_id),
+            "Before the storm. After the storm.\n\nNext day."
+        );
+        assert!(doc.get_chunk_ref(&following_id).is_none());
+        let chapter = doc
+            .get_chunk_ref(&chapter_id)
+            .and_then(|c| c.as_any().downcast_ref::<Chapter>())
+            .unwrap();
+        assert_eq!(chapter.notes_at(37)[0].buffer(), b"Morning");
+        // The chunks of the merged chapter now sit under the one it joined
+        assert_eq!(
+            doc.get_chunk_ref(&scene_id).unwrap().parent_id(),
+            Some(chapter_id.as_str())
+        );
+        // There is nothing left to merge with
+        assert!(doc.merge_chapter_with_next(&chapter_id).is_err());
+    }
+
+    #[test]
+    fn undo_merge_chapter_with_next() {
+        let mut doc = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Before the storm."));
+        chapter.set_priority(Some(0));
+        let mut following = Chapter::default();
+        following.set_buffer(Bytes::from("Next day."));
+        following.set_priority(Some(1));
+        let chapter_id = add(&mut doc, chapter);
+        let following_id = add(&mut doc, following);
+        let scene_id = add(&mut doc, Scene::default());
+        doc.set_parent(&scene_id, Some(&following_id)).unwrap();
+
+        let snapshot = doc.merge_chapter_with_next(&chapter_id).unwrap();
+        doc.restore(snapshot);
+        assert_eq!(chunk_text(&doc, &chapter_id), "Before the storm.");
+        assert_eq!(chunk_text(&doc, &following_id), "Next day.");
+        assert_eq!(
+            doc.get_chunk_ref(&scene_id).unwrap().parent_id(),
+            Some(following_id.as_str())
+        );
+        assert_eq!(
+            sibling_ids(&doc, &chapter_id),
+            vec![chapter_id, following_id]
+        );
+    }
+
+    #[test]
+    fn locked_chapters_stay_whole() {
+        let mut doc = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Before the storm. After the storm."));
+        let mut following = Chapter::default();
+        following.set_locked(true);
+        let chapter_id = add(&mut doc, chapter);
+        let following_id = add(&mut doc, following);
+        doc.move_chunk_next_to(&following_id, &chapter_id, true)
+            .unwrap();
+
+        assert!(doc.merge_chapter_with_next(&chapter_id).is_err());
+        assert!(doc.get_chunk_ref(&following_id).is_some());
+
+        doc.get_chunk_mut(&chapter_id).unwrap().set_locked(true);
+        assert!(doc.split_chapter(&chapter_id, 18).is_err());
+        assert_eq!(sibling_ids(&doc, &chapter_id).len(), 2);
+    }
 }
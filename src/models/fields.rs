@@ -0,0 +1,297 @@
+use super::{search_key, Document};
+use crate::services::i18n::i18n;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use ulid::Ulid;
+
+/// The kind of values a custom field holds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum FieldKind {
+    Text,
+    Number,
+    Date,
+    Choice(Vec<String>),
+    Link,
+}
+
+impl FieldKind {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Text => i18n("Text"),
+            Self::Number => i18n("Number"),
+            Self::Date => i18n("Date"),
+            Self::Choice(_) => i18n("Choice"),
+            Self::Link => i18n("Link"),
+        }
+    }
+}
+
+/// A project level field that every character sheet can hold a value for
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FieldDefinition {
+    id: String,
+    name: String,
+    kind: FieldKind,
+}
+
+impl FieldDefinition {
+    pub fn new(name: &str, kind: FieldKind) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            name: name.into(),
+            kind,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn kind(&self) -> &FieldKind {
+        &self.kind
+    }
+
+    /// The name used to refer to this field in searches, lowercased and
+    /// without whitespaces
+    pub fn search_key(&self) -> String {
+        search_key(self.name.as_str())
+    }
+
+    /// Parses the text typed for this field, if it is valid for its kind
+    pub fn parse_value(&self, text: &str) -> Option<FieldValue> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        match &self.kind {
+            FieldKind::Text => Some(FieldValue::Text(text.into())),
+            FieldKind::Number => text.parse::<f64>().ok().map(FieldValue::Number),
+            FieldKind::Date => {
+                parse_date(text).map(|(year, month, day)| FieldValue::Date(year, month, day))
+            }
+            FieldKind::Choice(choices) => choices
+                .iter()
+                .find(|c| c.as_str() == text)
+                .map(|c| FieldValue::Choice(c.clone())),
+            FieldKind::Link => Some(FieldValue::Link(text.into())),
+        }
+    }
+}
+
+/// Parses a YYYY-MM-DD date into its year, month and day
+pub fn parse_date(text: &str) -> Option<(i32, u32, u32)> {
+    let mut parts = text.trim().splitn(3, '-');
+    let year = parts.next()?.parse::<i32>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+    if (1..=12).contains(&month) && (1..=31).contains(&day) {
+        Some((year, month, day))
+    } else {
+        None
+    }
+}
+
+/// The value of a custom field on a character sheet
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Text(String),
+    Number(f64),
+    Date(i32, u32, u32),
+    Choice(String),
+    Link(String),
+}
+
+impl FieldValue {
+    /// Text representation of this value, resolving links to the title
+    /// of the chunk they point to
+    pub fn display(&self, document: &Document) -> String {
+        match self {
+            Self::Text(value) | Self::Choice(value) => value.clone(),
+            Self::Number(value) => value.to_string(),
+            Self::Date(year, month, day) => format!("{year:04}-{month:02}-{day:02}"),
+            Self::Link(id) => document
+                .get_chunk_ref(id)
+                .map(|c| c.safe_title())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Orders two optional values, placing sheets without a value last
+    pub fn compare(
+        a: Option<&FieldValue>,
+        b: Option<&FieldValue>,
+        document: &Document,
+    ) -> Ordering {
+        match (a, b) {
+            (Some(Self::Number(a)), Some(Self::Number(b))) => a.total_cmp(b),
+            (Some(Self::Date(y1, m1, d1)), Some(Self::Date(y2, m2, d2))) => {
+                (y1, m1, d1).cmp(&(y2, m2, d2))
+            }
+            (Some(a), Some(b)) => a
+                .display(document)
+                .to_lowercase()
+                .cmp(&b.display(document).to_lowercase()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CharacterSheet, ChunkQuery, DocumentChunk};
+
+    #[test]
+    fn parse_values_by_kind() {
+        let number = FieldDefinition::new("Height", FieldKind::Number);
+        assert_eq!(number.parse_value(" 1.8 "), Some(FieldValue::Number(1.8)));
+        assert_eq!(number.parse_value("tall"), None);
+        assert_eq!(number.parse_value("  "), None);
+
+        let house = FieldDefinition::new(
+            "House",
+            FieldKind::Choice(vec!["Stark".into(), "Lannister".into()]),
+        );
+        assert_eq!(
+            house.parse_value("Stark"),
+            Some(FieldValue::Choice("Stark".into()))
+        );
+        assert_eq!(house.parse_value("Targaryen"), None);
+
+        let text = FieldDefinition::new("Nickname", FieldKind::Text);
+        assert_eq!(
+            text.parse_value(" Kingslayer "),
+            Some(FieldValue::Text("Kingslayer".into()))
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("289-01-01"), Some((289, 1, 1)));
+        assert_eq!(parse_date("-12-03-04"), None);
+        assert_eq!(parse_date("283-13-01"), None);
+        assert_eq!(parse_date("283-12-32"), None);
+        assert_eq!(parse_date("283-12"), None);
+
+        let birth = FieldDefinition::new("Date of birth", FieldKind::Date);
+        assert_eq!(
+            birth.parse_value("289-01-01"),
+            Some(FieldValue::Date(289, 1, 1))
+        );
+        assert_eq!(
+            FieldValue::Date(289, 1, 1).display(&Document::default()),
+            "0289-01-01"
+        );
+    }
+
+    #[test]
+    fn search_keys() {
+        let field = FieldDefinition::new("Date of  Birth", FieldKind::Date);
+        assert_eq!(field.search_key(), "dateofbirth");
+    }
+
+    #[test]
+    fn links_display_their_target() {
+        let mut doc = Document::default();
+        let mut sheet = CharacterSheet::default();
+        sheet.set_name(Some("Arya".into()));
+        let id = sheet.id().to_string();
+        doc.add_chunk(sheet);
+        assert_eq!(FieldValue::Link(id).display(&doc), "Arya");
+        assert_eq!(FieldValue::Link("01MISSING".into()).display(&doc), "");
+    }
+
+    #[test]
+    fn compare_values() {
+        let doc = Document::default();
+        let compare = |a: &FieldValue, b: &FieldValue| FieldValue::compare(Some(a), Some(b), &doc);
+        assert_eq!(
+            compare(&FieldValue::Number(9.0), &FieldValue::Number(10.0)),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare(&FieldValue::Date(300, 1, 1), &FieldValue::Date(289, 12, 31)),
+            Ordering::Greater
+        );
+        // Text is compared ignoring case, not by its bytes
+        assert_eq!(
+            compare(
+                &FieldValue::Text("arya".into()),
+                &FieldValue::Text("Bran".into())
+            ),
+            Ordering::Less
+        );
+        // Missing values come last
+        let value = FieldValue::Number(1.0);
+        assert_eq!(
+            FieldValue::compare(Some(&value), None, &doc),
+            Ordering::Less
+        );
+        assert_eq!(
+            FieldValue::compare(None, Some(&value), &doc),
+            Ordering::Greater
+        );
+        assert_eq!(FieldValue::compare(None, None, &doc), Ordering::Equal);
+    }
+
+    #[test]
+    fn sheets_sorted_by_a_field() {
+        let mut doc = Document::default();
+        let birth = FieldDefinition::new("Date of birth", FieldKind::Date);
+        let mut sheets = vec![];
+        for (priority, born) in [Some("289-01-01"), Some("266-01-01"), None]
+            .into_iter()
+            .enumerate()
+        {
+            let mut sheet = CharacterSheet::default();
+            sheet.set_priority(Some(priority as u64));
+            sheet.set_custom_field(birth.id(), born.and_then(|b| birth.parse_value(b)));
+            sheets.push(sheet.id().to_string());
+            doc.add_chunk(sheet);
+        }
+        let birth_id = birth.id().to_string();
+        doc.manifest_mut().add_character_field(birth);
+
+        let sorted = |field: Option<&str>| -> Vec<String> {
+            doc.character_sheets_sorted_by(field)
+                .iter()
+                .map(|c| c.id().to_string())
+                .collect()
+        };
+        // Sheets without a value come last
+        assert_eq!(
+            sorted(Some(&birth_id)),
+            vec![sheets[1].clone(), sheets[0].clone(), sheets[2].clone()]
+        );
+        assert_eq!(sorted(None), sheets);
+    }
+
+    #[test]
+    fn removing_a_field_clears_its_values() {
+        let mut doc = Document::default();
+        let house = FieldDefinition::new("House", FieldKind::Choice(vec!["Lannister".into()]));
+        let mut sheet = CharacterSheet::default();
+        sheet.set_custom_field(house.id(), house.parse_value("Lannister"));
+        let (house_id, sheet_id) = (house.id().to_string(), sheet.id().to_string());
+        doc.manifest_mut().add_character_field(house);
+        doc.add_chunk(sheet);
+        assert_eq!(doc.search(&ChunkQuery::parse("lannister")).len(), 1);
+
+        assert!(doc.remove_character_field(&house_id).is_some());
+        assert!(doc.manifest().character_field(&house_id).is_none());
+        assert!(doc.search(&ChunkQuery::parse("lannister")).is_empty());
+        let sheet = doc
+            .get_chunk_ref(&sheet_id)
+            .and_then(|c| c.as_any().downcast_ref::<CharacterSheet>())
+            .unwrap();
+        assert!(sheet.custom_field(&house_id).is_none());
+        assert!(doc.remove_character_field(&house_id).is_none());
+    }
+}
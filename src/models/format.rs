@@ -0,0 +1,462 @@
+use super::{
+    document::SerializableDocument,
+    prelude::{ManuscriptError, ManuscriptResult},
+    DocumentManifest,
+};
+use serde::{Deserialize, Serialize};
+
+/// Starts every project file saved with a format version. Files saved before
+/// carry no header, and start with the manifest right away
+const FORMAT_MAGIC: [u8; 8] = *b"MSCRIPT\0";
+
+/// Version of the layout of `SerializableDocument` in project files. Bump it
+/// whenever a serialized struct changes, freezing the previous layout in a
+/// module like `v0` to migrate from
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<D> {
+    magic: [u8; 8],
+    version: u32,
+    document: D,
+}
+
+/// Writes `document` to the bytes of a project file
+pub fn encode(document: &SerializableDocument) -> ManuscriptResult<Vec<u8>> {
+    bincode::serialize(&Envelope {
+        magic: FORMAT_MAGIC,
+        version: FORMAT_VERSION,
+        document,
+    })
+    .map_err(|_| ManuscriptError::DocumentSerialize)
+}
+
+/// Reads the bytes of a project file, migrating older layouts
+pub fn decode(bytes: &[u8]) -> ManuscriptResult<SerializableDocument> {
+    match version_of(bytes)? {
+        0 => bincode::deserialize::<v0::SerializableDocument>(bytes)
+            .map(SerializableDocument::from)
+            .map_err(|_| ManuscriptError::DocumentDeserialize),
+        _ => bincode::deserialize::<Envelope<SerializableDocument>>(bytes)
+            .map(|envelope| envelope.document)
+            .map_err(|_| ManuscriptError::DocumentDeserialize),
+    }
+}
+
+/// Reads only the manifest of a project file, which comes before its chunks
+pub fn decode_manifest(bytes: &[u8]) -> ManuscriptResult<DocumentManifest> {
+    match version_of(bytes)? {
+        0 => bincode::deserialize::<v0::DocumentManifest>(bytes)
+            .map(DocumentManifest::from)
+            .map_err(|_| ManuscriptError::DocumentDeserialize),
+        _ => bincode::deserialize::<Envelope<DocumentManifest>>(bytes)
+            .map(|envelope| envelope.document)
+            .map_err(|_| ManuscriptError::DocumentDeserialize),
+    }
+}
+
+/// Format version of a project file, 0 standing for files with no header
+fn version_of(bytes: &[u8]) -> ManuscriptResult<u32> {
+    if !bytes.starts_with(&FORMAT_MAGIC) {
+        return Ok(0);
+    }
+    match bincode::deserialize::<Envelope<()>>(bytes) {
+        Ok(envelope) if envelope.version <= FORMAT_VERSION => Ok(envelope.version),
+        Ok(_) => Err(ManuscriptError::Reason(
+            "This project was saved by a newer version of Manuscript",
+        )),
+        Err(_) => Err(ManuscriptError::DocumentDeserialize),
+    }
+}
+
+/// The layout of project files saved before the format was versioned. These
+/// structs are frozen: they must keep matching the files already out there.
+/// Types whose layout never changed since are reused as they are
+pub(crate) mod v0 {
+    use crate::models::{Color, DocumentSettings, Gender, TagMark};
+    use bytes::Bytes;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    pub(crate) struct SerializableDocument {
+        pub(crate) manifest: DocumentManifest,
+        pub(crate) chapters: Vec<Chapter>,
+        pub(crate) character_sheets: Vec<CharacterSheet>,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct DocumentManifest {
+        pub(crate) title: Option<String>,
+        pub(crate) author: String,
+        pub(crate) settings: DocumentSettings,
+        pub(crate) locked: bool,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct Chapter {
+        pub(crate) id: String,
+        pub(crate) priority: u64,
+        pub(crate) include_in_compilation: bool,
+        pub(crate) accent: Option<Color>,
+        pub(crate) locked: bool,
+        pub(crate) title: Option<String>,
+        pub(crate) buffer: Bytes,
+        pub(crate) tags: Vec<TagMark>,
+        pub(crate) notes: Vec<Note>,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct CharacterSheet {
+        pub(crate) id: String,
+        pub(crate) priority: u64,
+        pub(crate) include_in_compilation: bool,
+        pub(crate) accent: Option<Color>,
+        pub(crate) locked: bool,
+        pub(crate) name: Option<String>,
+        pub(crate) gender: Gender,
+        pub(crate) age: Option<u32>,
+        pub(crate) role: Option<String>,
+        pub(crate) physical_traits: Bytes,
+        pub(crate) psycological_traits: Bytes,
+        pub(crate) background: Bytes,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct Note {
+        pub(crate) id: String,
+        pub(crate) buffer: Bytes,
+        pub(crate) offset: i32,
+        pub(crate) len: i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        Attachment, BufferChunk, Chapter, CharacterSheet, ChunkLabel, ChunkStatus, ChunkType,
+        Color, Document, DocumentChunk, DocumentSettings, FieldDefinition, FieldKind, FieldValue,
+        Gender, LocationSheet, LoreEntry, MutableBufferChunk, Note, Part, PublicationMetadata,
+        Relationship, RelationshipKind, Research, TagMark,
+    };
+    use bytes::Bytes;
+
+    /// A project as saved before the format was versioned, its structs written
+    /// as tuples of the fields they had back then
+    fn baseline_project() -> Vec<u8> {
+        let manifest = (
+            Some("Persuasion"),
+            "Jane Austen",
+            DocumentSettings::default(),
+            false,
+        );
+        let note = ("01NOTE", Bytes::from("Too long?"), 4, 6);
+        let chapter = (
+            "01CHAPTER",
+            3u64,
+            true,
+            Some(Color::new(0.2, 0.4, 0.6, 1.0)),
+            false,
+            Some("Kellynch Hall"),
+            Bytes::from("Sir Walter Elliot, of Kellynch Hall"),
+            vec![
+                TagMark::new(0, 10, "bold"),
+                TagMark::new(15, 20, "highlight"),
+            ],
+            vec![note],
+        );
+        let sheet = (
+            "01SHEET",
+            0u64,
+            true,
+            None::<Color>,
+            true,
+            Some("Anne Elliot"),
+            Gender::Female,
+            Some(27u32),
+            Some("Heroine"),
+            Bytes::new(),
+            Bytes::from("Gentle"),
+            Bytes::new(),
+        );
+        bincode::serialize(&(manifest, vec![chapter], vec![sheet])).unwrap()
+    }
+
+    fn chapter(document: &Document) -> &Chapter {
+        document
+            .get_chunk_ref("01CHAPTER")
+            .and_then(|c| c.as_any().downcast_ref::<Chapter>())
+            .unwrap()
+    }
+
+    fn sheet(document: &Document) -> &CharacterSheet {
+        document
+            .get_chunk_ref("01SHEET")
+            .and_then(|c| c.as_any().downcast_ref::<CharacterSheet>())
+            .unwrap()
+    }
+
+    #[test]
+    fn load_baseline_project() {
+        let document = Document::try_from(baseline_project().as_slice()).unwrap();
+        assert_eq!(document.title().map(String::as_str), Some("Persuasion"));
+        assert_eq!(document.manifest().author(), "Jane Austen");
+        assert_eq!(document.chunks().len(), 2);
+
+        let chapter = chapter(&document);
+        assert_eq!(chapter.title().unwrap(), "Kellynch Hall");
+        assert_eq!(chapter.priority(), Some(3));
+        assert!(chapter.accent().is_some());
+        assert_eq!(
+            chapter.buffer().as_ref(),
+            b"Sir Walter Elliot, of Kellynch Hall"
+        );
+        // Tags derived from the Markdown source are applied again by the editor
+        assert_eq!(chapter.tags_map(), &vec![TagMark::new(15, 20, "highlight")]);
+        assert_eq!(chapter.synopsis(), None);
+        assert_eq!(chapter.words_target(), None);
+        assert_eq!(chapter.modified(), None);
+        assert_eq!(chapter.story_time(), None);
+        let note = &chapter.notes()[0];
+        assert_eq!(
+            (note.id(), note.buffer()),
+            ("01NOTE", b"Too long?".as_ref())
+        );
+        assert_eq!((note.offset_start(), note.offset_end()), (4, 10));
+        assert!(!note.resolved());
+
+        let sheet = sheet(&document);
+        assert_eq!(sheet.name().unwrap(), "Anne Elliot");
+        assert_eq!(sheet.age(), Some(27));
+        assert!(sheet.locked());
+        assert_eq!(sheet.psycological_traits(), b"Gentle");
+        assert!(sheet.custom_fields().is_empty());
+        assert!(sheet.relationships().is_empty());
+        assert_eq!(sheet.portrait(), None);
+        assert!(sheet.aliases().is_empty());
+        assert_eq!(document.manifest().cover(), None);
+        assert!(document.manifest().character_fields().is_empty());
+        assert_eq!(
+            document.manifest().publication(),
+            &PublicationMetadata::default()
+        );
+    }
+
+    #[test]
+    fn baseline_workflow() {
+        let document = Document::try_from(baseline_project().as_slice()).unwrap();
+        let names = |statuses: &[ChunkStatus]| -> Vec<String> {
+            statuses.iter().map(|s| s.name().to_string()).collect()
+        };
+        assert_eq!(
+            names(document.manifest().statuses()),
+            names(&ChunkStatus::defaults())
+        );
+        assert!(document.manifest().statuses().iter().all(|s| s.compile()));
+        assert!(document.manifest().labels().is_empty());
+        assert_eq!(chapter(&document).status(), None);
+        assert!(chapter(&document).labels().is_empty());
+    }
+
+    #[test]
+    fn baseline_chapters_sit_at_the_root() {
+        let document = Document::try_from(baseline_project().as_slice()).unwrap();
+        assert_eq!(chapter(&document).parent_id(), None);
+        let roots: Vec<&str> = document.children_of(None).iter().map(|c| c.id()).collect();
+        assert_eq!(roots, vec!["01CHAPTER"]);
+        assert!(document.chunks_by_type_ref(ChunkType::Part).is_empty());
+        assert!(document.chunks_by_type_ref(ChunkType::Scene).is_empty());
+    }
+
+    #[test]
+    fn baseline_sheets_and_entries() {
+        let document = Document::try_from(baseline_project().as_slice()).unwrap();
+        assert_eq!(
+            document.chunks_by_type_ref(ChunkType::CharacterSheet).len(),
+            1
+        );
+        assert!(document
+            .chunks_by_type_ref(ChunkType::LocationSheet)
+            .is_empty());
+        assert!(document.chunks_by_type_ref(ChunkType::LoreEntry).is_empty());
+        assert!(document.chunks_by_type_ref(ChunkType::Research).is_empty());
+    }
+
+    #[test]
+    fn save_with_version() {
+        let document = Document::try_from(baseline_project().as_slice()).unwrap();
+        let bytes = document.serialize().unwrap();
+        assert!(bytes.starts_with(&FORMAT_MAGIC));
+        assert_eq!(version_of(&bytes).unwrap(), FORMAT_VERSION);
+
+        let reloaded = Document::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(chapter(&reloaded).buffer(), chapter(&document).buffer());
+    }
+
+    /// Everything a project holds, saved and read back
+    #[test]
+    fn round_trip() {
+        let mut document = Document::default();
+        let draft = document.manifest().statuses()[1].id().to_string();
+        let mut cut = ChunkLabel::new("Cut", Color::new(0.9, 0.1, 0.1, 1.0));
+        cut.set_compile(false);
+        let mut part = Part::default();
+        part.set_buffer(Bytes::from("Remember this."));
+        part.set_tags_map(vec![TagMark::new(0, 13, "highlight")]);
+        let mut chapter = Chapter::default();
+        chapter.set_status(Some(draft.clone()));
+        chapter.set_labels(vec![cut.id().into()]);
+        chapter.set_words_target(Some(2500));
+        chapter.set_buffer(Bytes::from("It was a dark and stormy night."));
+        chapter.set_tags_map(vec![TagMark::new(9, 13, "highlight")]);
+        let mut note = Note::new(18, 6, "Too cliché?".into());
+        note.set_resolved(true);
+        let note_id = note.id().to_string();
+        chapter.push_note(note);
+        chapter.add_note(0, 2, "Spell it out".into());
+        chapter.touch();
+        let modified = chapter.modified();
+        let (part_id, chapter_id) = (part.id().to_string(), chapter.id().to_string());
+        document.manifest_mut().add_label(cut);
+        document.add_chunk(part);
+        document.add_chunk(chapter);
+        document.set_parent(&chapter_id, Some(&part_id)).unwrap();
+        let (city, district) = (LocationSheet::default(), LocationSheet::default());
+        let (city_id, district_id) = (city.id().to_string(), district.id().to_string());
+        document.add_chunk(city);
+        document.add_chunk(district);
+        document
+            .set_parent_location(&district_id, Some(&city_id))
+            .unwrap();
+        document.add_chunk(LoreEntry::default());
+        let mut research = Research::default();
+        research.set_notes_bytes(Bytes::from("# Rigging"));
+        research.add_attachment(Attachment::new(
+            "log.pdf",
+            "application/pdf",
+            Bytes::from_static(b"%PDF"),
+        ));
+        let research_id = research.id().to_string();
+        document.add_chunk(research);
+        let birth = FieldDefinition::new("Date of birth", FieldKind::Date);
+        let mentor = CharacterSheet::default();
+        let mut sheet = CharacterSheet::default();
+        sheet.set_portrait(Some(Bytes::from_static(b"\x89PNG portrait")));
+        sheet.set_custom_field(birth.id(), birth.parse_value("289-01-01"));
+        sheet.add_relationship(Relationship::new(
+            mentor.id(),
+            RelationshipKind::Mentor,
+            Some("Trained her".into()),
+        ));
+        let (birth_id, sheet_id) = (birth.id().to_string(), sheet.id().to_string());
+        let mentor_id = mentor.id().to_string();
+        document.manifest_mut().add_character_field(birth);
+        document.add_chunk(mentor);
+        document.add_chunk(sheet);
+        document
+            .manifest_mut()
+            .set_cover(Some(Bytes::from_static(b"\x89PNG cover")));
+        let publication = document.manifest_mut().publication_mut();
+        publication.set_subtitle(Some("A Song of Ice and Fire".into()));
+        publication.set_series_number(Some(1));
+        publication.set_isbns(vec!["978-0-306-40615-7".into()]);
+        publication.set_publication_date(Some((1996, 8, 1)));
+        publication.set_keywords(vec!["fantasy".into(), "epic".into()]);
+
+        let saved = Document::try_from(document.serialize().unwrap().as_slice()).unwrap();
+        assert_eq!(saved.ancestors_of(&chapter_id), vec![part_id.clone()]);
+        let chapter = saved
+            .get_chunk_ref(&chapter_id)
+            .and_then(|c| c.as_buffer_chunk())
+            .unwrap();
+        assert_eq!(chapter.status(), Some(&draft));
+        assert_eq!(chapter.words_target(), Some(2500));
+        assert_eq!(chapter.tags_map(), &vec![TagMark::new(9, 13, "highlight")]);
+        assert_eq!(
+            saved
+                .get_chunk_ref(&part_id)
+                .unwrap()
+                .as_buffer_chunk()
+                .unwrap()
+                .tags_map(),
+            &vec![TagMark::new(0, 13, "highlight")]
+        );
+        assert_eq!(chapter.modified(), modified);
+        assert_eq!(
+            chapter.labels(),
+            &vec![saved.manifest().labels()[0].id().to_string()]
+        );
+        assert!(!saved.manifest().labels()[0].compile());
+        let notes = saved
+            .get_chunk_ref(&chapter_id)
+            .and_then(|c| c.as_any().downcast_ref::<Chapter>())
+            .unwrap()
+            .notes();
+        assert_eq!(notes[0].id(), note_id);
+        assert_eq!((notes[0].offset_start(), notes[0].offset_end()), (18, 24));
+        assert!(notes[0].resolved());
+        assert!(!notes[1].resolved());
+        assert_eq!(saved.location_ancestors_of(&district_id), vec![city_id]);
+        assert_eq!(saved.chunks_by_type_ref(ChunkType::LoreEntry).len(), 1);
+        let research = saved
+            .get_chunk_ref(&research_id)
+            .and_then(|c| c.as_any().downcast_ref::<Research>())
+            .unwrap();
+        assert_eq!(research.notes(), b"# Rigging");
+        assert_eq!(research.attachments()[0].name(), "log.pdf");
+        assert_eq!(research.attachments()[0].content().as_ref(), b"%PDF");
+        assert!(saved.manifest().character_field(&birth_id).is_some());
+        let sheet = saved
+            .get_chunk_ref(&sheet_id)
+            .and_then(|c| c.as_any().downcast_ref::<CharacterSheet>())
+            .unwrap();
+        assert_eq!(
+            sheet.custom_field(&birth_id),
+            Some(&FieldValue::Date(289, 1, 1))
+        );
+        assert_eq!(
+            sheet.portrait(),
+            Some(&Bytes::from_static(b"\x89PNG portrait"))
+        );
+        assert_eq!(
+            saved.manifest().cover(),
+            Some(&Bytes::from_static(b"\x89PNG cover"))
+        );
+        assert_eq!(
+            saved.manifest().publication(),
+            document.manifest().publication()
+        );
+        let relationship = &sheet.relationships()[0];
+        assert_eq!(relationship.target(), mentor_id);
+        assert_eq!(relationship.kind(), &RelationshipKind::Mentor);
+    }
+
+    #[test]
+    fn manifest_only() {
+        let manifest = decode_manifest(baseline_project().as_slice()).unwrap();
+        assert_eq!(manifest.author(), "Jane Austen");
+
+        let mut document = Document::try_from(baseline_project().as_slice()).unwrap();
+        document
+            .manifest_mut()
+            .set_cover(Some(Bytes::from("cover")));
+        let manifest = decode_manifest(document.serialize().unwrap().as_slice()).unwrap();
+        assert_eq!(
+            manifest.manifest_title().map(String::as_str),
+            Some("Persuasion")
+        );
+        assert_eq!(manifest.cover(), Some(&Bytes::from("cover")));
+
+        assert!(decode_manifest(&FORMAT_MAGIC).is_err());
+    }
+
+    #[test]
+    fn refuse_newer_versions() {
+        let bytes = bincode::serialize(&(FORMAT_MAGIC, FORMAT_VERSION + 1)).unwrap();
+        assert!(matches!(
+            Document::try_from(bytes.as_slice()),
+            Err(ManuscriptError::Reason(_))
+        ));
+        assert!(Document::try_from(&FORMAT_MAGIC[..4]).is_err());
+    }
+}
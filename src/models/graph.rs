@@ -0,0 +1,338 @@
+use super::{CharacterSheet, Document, DocumentChunk};
+
+/// Radius of the circles representing characters
+pub const NODE_RADIUS: f64 = 36.0;
+
+/// Space left around the drawing when exporting it
+const MARGIN: f64 = 64.0;
+
+/// Distance between the two edges linking the same characters in opposite directions
+const EDGE_SPREAD: f64 = 6.0;
+
+/// A character placed on the relationship graph
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub id: String,
+    pub name: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A relationship between the nodes at index `from` and `to`
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub label: String,
+    pub note: Option<String>,
+}
+
+/// The characters of a document and the relationships between them,
+/// laid out on a circle centered on the origin
+#[derive(Debug, Clone, Default)]
+pub struct RelationshipGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+impl RelationshipGraph {
+    pub fn new(document: &Document) -> Self {
+        let sheets = document.character_sheets_sorted_by(None);
+        let radius = (sheets.len() as f64 * NODE_RADIUS * 3.0) / std::f64::consts::TAU;
+        let radius = radius.max(NODE_RADIUS * 3.0);
+        let nodes: Vec<GraphNode> = sheets
+            .iter()
+            .enumerate()
+            .map(|(index, sheet)| {
+                let angle = std::f64::consts::TAU * index as f64 / sheets.len() as f64
+                    - std::f64::consts::FRAC_PI_2;
+                GraphNode {
+                    id: sheet.id().to_string(),
+                    name: sheet.safe_title(),
+                    x: (radius * angle.cos()).round(),
+                    y: (radius * angle.sin()).round(),
+                }
+            })
+            .collect();
+
+        let index_of = |id: &str| nodes.iter().position(|n| n.id == id);
+        let edges = sheets
+            .iter()
+            .enumerate()
+            .flat_map(|(from, sheet): (usize, &&CharacterSheet)| {
+                sheet
+                    .relationships()
+                    .iter()
+                    .filter_map(|r| {
+                        index_of(r.target()).map(|to| GraphEdge {
+                            from,
+                            to,
+                            label: r.kind().label(),
+                            note: r.note().cloned(),
+                        })
+                    })
+                    .collect::<Vec<GraphEdge>>()
+            })
+            .collect();
+
+        Self { nodes, edges }
+    }
+
+    pub fn nodes(&self) -> &Vec<GraphNode> {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &Vec<GraphEdge> {
+        &self.edges
+    }
+
+    /// The node drawn at the given point, if any
+    pub fn node_at(&self, x: f64, y: f64) -> Option<&GraphNode> {
+        self.nodes
+            .iter()
+            .find(|n| (n.x - x).hypot(n.y - y) <= NODE_RADIUS)
+    }
+
+    /// Start and end points of the line representing `edge`, going from
+    /// border to border of its nodes. Edges linking the same nodes in
+    /// opposite directions are drawn side by side
+    pub fn edge_line(&self, edge: &GraphEdge) -> ((f64, f64), (f64, f64)) {
+        let (from, to) = (&self.nodes[edge.from], &self.nodes[edge.to]);
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let length = dx.hypot(dy).max(f64::EPSILON);
+        let (ux, uy) = (dx / length, dy / length);
+
+        let reciprocal = self
+            .edges
+            .iter()
+            .any(|e| e.from == edge.to && e.to == edge.from);
+        let spread = if reciprocal { EDGE_SPREAD } else { 0.0 };
+        let (ox, oy) = (-uy * spread, ux * spread);
+
+        (
+            (
+                from.x + ux * NODE_RADIUS + ox,
+                from.y + uy * NODE_RADIUS + oy,
+            ),
+            (to.x - ux * NODE_RADIUS + ox, to.y - uy * NODE_RADIUS + oy),
+        )
+    }
+
+    /// Smallest rectangle containing every node, as (x, y, width, height)
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        if self.nodes.is_empty() {
+            return (0.0, 0.0, 0.0, 0.0);
+        }
+
+        let min_x = self.nodes.iter().map(|n| n.x).fold(f64::MAX, f64::min) - NODE_RADIUS;
+        let min_y = self.nodes.iter().map(|n| n.y).fold(f64::MAX, f64::min) - NODE_RADIUS;
+        let max_x = self.nodes.iter().map(|n| n.x).fold(f64::MIN, f64::max) + NODE_RADIUS;
+        let max_y = self.nodes.iter().map(|n| n.y).fold(f64::MIN, f64::max) + NODE_RADIUS;
+        (min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// Renders the graph as a standalone SVG image
+    pub fn to_svg(&self) -> String {
+        let (x, y, width, height) = self.bounds();
+        let (x, y, width, height) = (
+            x - MARGIN,
+            y - MARGIN,
+            width + MARGIN * 2.0,
+            height + MARGIN * 2.0,
+        );
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{x} {y} {width} {height}\" \
+             width=\"{width}\" height=\"{height}\" font-family=\"sans-serif\" font-size=\"12\">\n"
+        );
+        svg.push_str(
+            "  <defs>\n    <marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+             markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">\n      \
+             <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#3d3846\"/>\n    </marker>\n  </defs>\n",
+        );
+        svg.push_str(&format!(
+            "  <rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{height}\" fill=\"#ffffff\"/>\n"
+        ));
+
+        for edge in self.edges.iter() {
+            let ((x1, y1), (x2, y2)) = self.edge_line(edge);
+            svg.push_str("  <g>\n");
+            if let Some(note) = edge.note.as_ref() {
+                svg.push_str(&format!("    <title>{}</title>\n", escape(note)));
+            }
+            svg.push_str(&format!(
+                "    <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" \
+                 stroke=\"#3d3846\" stroke-width=\"1.5\" marker-end=\"url(#arrow)\"/>\n"
+            ));
+            svg.push_str(&format!(
+                "    <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"#5e5c64\">{}</text>\n",
+                (x1 + x2) / 2.0,
+                (y1 + y2) / 2.0 - 4.0,
+                escape(edge.label.as_str())
+            ));
+            svg.push_str("  </g>\n");
+        }
+
+        for node in self.nodes.iter() {
+            svg.push_str(&format!(
+                "  <circle cx=\"{}\" cy=\"{}\" r=\"{NODE_RADIUS}\" fill=\"#dce8f7\" \
+                 stroke=\"#3584e4\" stroke-width=\"2\"/>\n",
+                node.x, node.y
+            ));
+            svg.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"middle\" \
+                 fill=\"#241f31\">{}</text>\n",
+                node.x,
+                node.y,
+                escape(node.name.as_str())
+            ));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Relationship, RelationshipKind};
+
+    /// Obi-Wan mentors Anakin, who is married to Padmé and calls himself
+    /// the padawan of Obi-Wan
+    fn cast() -> (Document, [String; 3]) {
+        let mut doc = Document::default();
+        let mut obi_wan = CharacterSheet::default();
+        obi_wan.set_name(Some("Obi-Wan".into()));
+        let mut anakin = CharacterSheet::default();
+        anakin.set_name(Some("Anakin <Vader>".into()));
+        anakin.set_priority(Some(1));
+        let mut padme = CharacterSheet::default();
+        padme.set_name(Some("Padmé".into()));
+        padme.set_priority(Some(2));
+        let ids = [
+            obi_wan.id().to_string(),
+            anakin.id().to_string(),
+            padme.id().to_string(),
+        ];
+        obi_wan.add_relationship(Relationship::new(
+            &ids[1],
+            RelationshipKind::Mentor,
+            Some("Trained him".into()),
+        ));
+        anakin.add_relationship(Relationship::new(&ids[2], RelationshipKind::Spouse, None));
+        anakin.add_relationship(Relationship::new(
+            &ids[0],
+            RelationshipKind::Custom("Padawan".into()),
+            None,
+        ));
+        doc.add_chunk(obi_wan);
+        doc.add_chunk(anakin);
+        doc.add_chunk(padme);
+        (doc, ids)
+    }
+
+    #[test]
+    fn nodes_and_edges() {
+        let (doc, ids) = cast();
+        let graph = RelationshipGraph::new(&doc);
+        let nodes: Vec<&str> = graph.nodes().iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(nodes, ids.iter().map(String::as_str).collect::<Vec<&str>>());
+        let edges: Vec<(usize, usize, &str)> = graph
+            .edges()
+            .iter()
+            .map(|e| (e.from, e.to, e.label.as_str()))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![(0, 1, "Mentor"), (1, 2, "Spouse"), (1, 0, "Padawan")]
+        );
+    }
+
+    #[test]
+    fn relationships_to_missing_sheets_are_skipped() {
+        let (doc, [obi_wan_id, anakin_id, _]) = cast();
+        let subset = doc.subset(&[obi_wan_id, anakin_id]);
+        assert_eq!(RelationshipGraph::new(&subset).edges().len(), 2);
+    }
+
+    #[test]
+    fn reciprocal_edges_do_not_overlap() {
+        let (doc, _) = cast();
+        let graph = RelationshipGraph::new(&doc);
+        let (mentor, spouse, padawan) = (&graph.edges()[0], &graph.edges()[1], &graph.edges()[2]);
+        assert_eq!((mentor.from, mentor.to), (padawan.to, padawan.from));
+        assert_ne!(graph.edge_line(mentor).1, graph.edge_line(padawan).0);
+
+        // Lines go from border to border of the nodes they link
+        let (start, _) = graph.edge_line(spouse);
+        let from = &graph.nodes()[spouse.from];
+        let distance = (start.0 - from.x).hypot(start.1 - from.y);
+        assert!((distance - NODE_RADIUS).abs() < 1e-6);
+    }
+
+    #[test]
+    fn node_hit_testing() {
+        let (doc, [obi_wan_id, ..]) = cast();
+        let graph = RelationshipGraph::new(&doc);
+        let node = &graph.nodes()[0];
+        assert_eq!(
+            graph
+                .node_at(node.x + 1.0, node.y - 1.0)
+                .map(|n| n.id.as_str()),
+            Some(obi_wan_id.as_str())
+        );
+        assert!(graph.node_at(0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn bounds_enclose_every_node() {
+        assert_eq!(RelationshipGraph::default().bounds(), (0.0, 0.0, 0.0, 0.0));
+        let (doc, _) = cast();
+        let graph = RelationshipGraph::new(&doc);
+        let (x, y, width, height) = graph.bounds();
+        for node in graph.nodes() {
+            assert!(node.x - NODE_RADIUS >= x && node.x + NODE_RADIUS <= x + width);
+            assert!(node.y - NODE_RADIUS >= y && node.y + NODE_RADIUS <= y + height);
+        }
+    }
+
+    #[test]
+    fn svg_export() {
+        let (doc, _) = cast();
+        let svg = RelationshipGraph::new(&doc).to_svg();
+        assert!(svg.contains("Anakin &lt;Vader&gt;"));
+        assert!(svg.contains("<title>Trained him</title>"));
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert_eq!(svg.matches("<circle").count(), 3);
+    }
+
+    #[test]
+    fn removing_a_character_drops_relationships_to_it() {
+        let (mut doc, [obi_wan_id, anakin_id, padme_id]) = cast();
+        let targets = |doc: &Document| -> Vec<String> {
+            doc.get_chunk_ref(&anakin_id)
+                .and_then(|c| c.as_any().downcast_ref::<CharacterSheet>())
+                .unwrap()
+                .relationships()
+                .iter()
+                .map(|r| r.target().to_string())
+                .collect()
+        };
+
+        // Subsets keep only the relationships between the chosen characters
+        let subset = doc.subset(&[obi_wan_id.clone(), anakin_id.clone()]);
+        assert_eq!(targets(&subset), vec![obi_wan_id.clone()]);
+
+        doc.remove_chunk(&obi_wan_id);
+        assert_eq!(targets(&doc), vec![padme_id]);
+        assert_eq!(RelationshipGraph::new(&doc).edges().len(), 1);
+    }
+}
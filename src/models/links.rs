@@ -0,0 +1,314 @@
+use super::{Document, DocumentChunk};
+use bytes::Bytes;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Matches `[[Name]]` links, capturing the name
+pub const WIKI_LINK_PATTERN: &str = r"\[\[(?P<name>[^\[\]\n]+)\]\]";
+
+static WIKI_LINK_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(WIKI_LINK_PATTERN).unwrap());
+
+/// A `[[Name]]` link to the chunk titled `Name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    start: i32,
+    end: i32,
+    name: String,
+}
+
+impl WikiLink {
+    /// Character offset of the opening brackets
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    /// Character offset right after the closing brackets
+    pub fn end(&self) -> i32 {
+        self.end
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+/// Finds the `[[Name]]` links in `text`
+pub fn wiki_links(text: &str) -> Vec<WikiLink> {
+    let char_offset = |byte_offset: usize| text[..byte_offset].chars().count() as i32;
+    WIKI_LINK_REGEX
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let link = captures.get(0)?;
+            let name = captures["name"].trim();
+            (!name.is_empty()).then(|| WikiLink {
+                start: char_offset(link.start()),
+                end: char_offset(link.end()),
+                name: name.into(),
+            })
+        })
+        .collect()
+}
+
+/// Normalizes a chunk title or a link name, so that they match regardless
+/// of case and surrounding spaces
+pub(super) fn title_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// A chunk as it was when its links were last indexed
+#[derive(Debug, Clone)]
+struct IndexedChunk {
+    title: Option<String>,
+    text: Bytes,
+    /// Keys of the titles its text links to
+    links: Vec<String>,
+}
+
+/// The `[[Name]]` links between the chunks of a document, parsed once so
+/// that the backlinks of every open chunk can be listed without parsing the
+/// whole manuscript again. Links are resolved to chunks when listed, so that
+/// only the chunk whose text changed has to be indexed again
+#[derive(Debug, Clone, Default)]
+pub struct LinkIndex {
+    chunks: HashMap<String, IndexedChunk>,
+}
+
+impl LinkIndex {
+    pub fn new(document: &Document) -> Self {
+        let mut index = Self::default();
+        for chunk in document.chunks() {
+            index.update(document, chunk.id());
+        }
+        index
+    }
+
+    /// Indexes the chunk `id` of `document` again, dropping it if it was
+    /// removed. Returns whether its title or its text changed since the last
+    /// time, and with them the backlinks of some chunks
+    pub fn update(&mut self, document: &Document, id: &str) -> bool {
+        let Some(chunk) = document.get_chunk_ref(id) else {
+            return self.chunks.remove(id).is_some();
+        };
+        let title = chunk.title();
+        let text = chunk
+            .as_buffer_chunk()
+            .map(|c| c.buffer().clone())
+            .unwrap_or_default();
+
+        if let Some(indexed) = self.chunks.get_mut(id) {
+            if indexed.text == text {
+                let renamed = indexed.title != title;
+                indexed.title = title;
+                return renamed;
+            }
+        }
+
+        let mut links: Vec<String> = wiki_links(String::from_utf8_lossy(&text).as_ref())
+            .iter()
+            .map(|link| title_key(link.name()))
+            .collect();
+        links.sort();
+        links.dedup();
+        self.chunks
+            .insert(id.to_string(), IndexedChunk { title, text, links });
+        true
+    }
+
+    /// Takes the new title of the chunk `id`, leaving its text to `update`.
+    /// Returns whether the title changed
+    pub fn rename(&mut self, document: &Document, id: &str) -> bool {
+        let title = document.get_chunk_ref(id).and_then(|c| c.title());
+        match self.chunks.get_mut(id) {
+            Some(indexed) if indexed.title != title => {
+                indexed.title = title;
+                true
+            }
+            Some(_) => false,
+            None => self.update(document, id),
+        }
+    }
+
+    /// Chunks of `document` whose text has `[[Name]]` links to the chunk `id`,
+    /// sorted by title
+    pub fn backlinks<'a>(&self, document: &'a Document, id: &str) -> Vec<&'a dyn DocumentChunk> {
+        let Some(title) = self.chunks.get(id).and_then(|c| c.title.as_ref()) else {
+            return vec![];
+        };
+        // When several chunks share a title the oldest one wins, like in
+        // `Document::chunk_by_title`
+        let key = title_key(title);
+        let shadowed = self.chunks.iter().any(|(other, indexed)| {
+            other.as_str() < id
+                && indexed
+                    .title
+                    .as_ref()
+                    .is_some_and(|title| title_key(title) == key)
+        });
+        if shadowed {
+            return vec![];
+        }
+
+        let mut chunks: Vec<&dyn DocumentChunk> = self
+            .chunks
+            .iter()
+            .filter(|(source, indexed)| source.as_str() != id && indexed.links.contains(&key))
+            .filter_map(|(source, _)| document.get_chunk_ref(source))
+            .collect();
+        chunks.sort_by_key(|c| c.safe_title().to_lowercase());
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Chapter, CharacterSheet, LocationSheet, MutableBufferChunk, Scene};
+    use bytes::Bytes;
+
+    #[test]
+    fn find_links() {
+        let links = wiki_links("Où est [[ Ada Lovelace ]]? At [[the Mill]], or [[]].");
+        let names: Vec<&str> = links.iter().map(|l| l.name()).collect();
+        assert_eq!(names, vec!["Ada Lovelace", "the Mill"]);
+        assert_eq!((links[0].start(), links[0].end()), (7, 25));
+        assert_eq!((links[1].start(), links[1].end()), (30, 42));
+    }
+
+    #[test]
+    fn links_do_not_span_lines_or_nest() {
+        assert!(wiki_links("[[Ada\nLovelace]]").is_empty());
+        assert!(wiki_links("[[  ]]").is_empty());
+        let links = wiki_links("[[[Ada]]]");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].name(), "Ada");
+    }
+
+    /// A character, a location and a chapter linking to both, and a scene
+    /// linking back to the chapter and to a missing chunk. Returns the ids
+    /// of the character, of the location, of the chapter and of the scene
+    fn linked_document() -> (Document, [String; 4]) {
+        let mut doc = Document::default();
+        let mut sheet = CharacterSheet::default();
+        sheet.set_name(Some("Ada Lovelace".into()));
+        let mut location = LocationSheet::default();
+        location.set_name(Some("The Mill".into()));
+        let mut chapter = Chapter::default();
+        chapter.set_title("Arrival");
+        chapter.set_buffer(Bytes::from(
+            "[[ada lovelace]] reaches [[The Mill]]. [[Ada Lovelace]] waits.",
+        ));
+        let mut scene = Scene::default();
+        scene.set_title("Nightfall");
+        scene.set_buffer(Bytes::from(
+            "Back to [[Arrival]] and [[Nobody]]. [[Nightfall]]",
+        ));
+        let ids = [
+            sheet.id().to_string(),
+            location.id().to_string(),
+            chapter.id().to_string(),
+            scene.id().to_string(),
+        ];
+        doc.add_chunk(sheet);
+        doc.add_chunk(location);
+        doc.add_chunk(chapter);
+        doc.add_chunk(scene);
+        (doc, ids)
+    }
+
+    fn backlinks(doc: &Document, index: &LinkIndex, id: &str) -> Vec<String> {
+        index
+            .backlinks(doc, id)
+            .iter()
+            .map(|c| c.id().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn chunks_by_title() {
+        let (doc, [_, location_id, _, _]) = linked_document();
+        assert_eq!(doc.chunk_by_title(" the mill ").unwrap().id(), location_id);
+        assert!(doc.chunk_by_title("Nobody").is_none());
+    }
+
+    #[test]
+    fn backlinks_of_each_chunk() {
+        let (doc, [sheet_id, location_id, chapter_id, scene_id]) = linked_document();
+        let index = LinkIndex::new(&doc);
+        // A chunk linking twice is listed once
+        assert_eq!(backlinks(&doc, &index, &sheet_id), vec![chapter_id.clone()]);
+        assert_eq!(
+            backlinks(&doc, &index, &location_id),
+            vec![chapter_id.clone()]
+        );
+        assert_eq!(backlinks(&doc, &index, &chapter_id), vec![scene_id.clone()]);
+        // Links to oneself are left out
+        assert!(backlinks(&doc, &index, &scene_id).is_empty());
+    }
+
+    #[test]
+    fn backlinks_sorted_by_title() {
+        let (mut doc, [sheet_id, _, chapter_id, _]) = linked_document();
+        let mut epilogue = Chapter::default();
+        epilogue.set_title("Epilogue");
+        epilogue.set_buffer(Bytes::from("[[Ada Lovelace]] leaves."));
+        let epilogue_id = epilogue.id().to_string();
+        doc.add_chunk(epilogue);
+        let index = LinkIndex::new(&doc);
+        assert_eq!(
+            backlinks(&doc, &index, &sheet_id),
+            vec![chapter_id, epilogue_id]
+        );
+    }
+
+    #[test]
+    fn shared_titles_link_to_the_oldest_chunk() {
+        let (mut doc, [sheet_id, _, chapter_id, _]) = linked_document();
+        let mut twin = CharacterSheet::default();
+        twin.set_name(Some("Ada Lovelace".into()));
+        let twin_id = twin.id().to_string();
+        doc.add_chunk(twin);
+        let index = LinkIndex::new(&doc);
+        let target = doc.chunk_by_title("Ada Lovelace").unwrap().id().to_string();
+        let other = if target == sheet_id {
+            twin_id
+        } else {
+            sheet_id
+        };
+        assert_eq!(target.as_str().min(other.as_str()), target);
+        assert_eq!(backlinks(&doc, &index, &target), vec![chapter_id]);
+        assert!(backlinks(&doc, &index, &other).is_empty());
+    }
+
+    #[test]
+    fn update_a_chunk() {
+        let (mut doc, [sheet_id, location_id, chapter_id, _]) = linked_document();
+        let mut index = LinkIndex::new(&doc);
+        assert!(!index.update(&doc, &chapter_id));
+
+        let chapter = doc
+            .get_chunk_mut(&chapter_id)
+            .and_then(|c| c.as_any_mut().downcast_mut::<Chapter>())
+            .unwrap();
+        chapter.set_buffer(Bytes::from("[[The Mill]] stands empty."));
+        assert!(index.update(&doc, &chapter_id));
+        assert!(backlinks(&doc, &index, &sheet_id).is_empty());
+        assert_eq!(
+            backlinks(&doc, &index, &location_id),
+            vec![chapter_id.clone()]
+        );
+
+        let chapter = doc
+            .get_chunk_mut(&chapter_id)
+            .and_then(|c| c.as_any_mut().downcast_mut::<Chapter>())
+            .unwrap();
+        chapter.set_title("Departure");
+        assert!(index.rename(&doc, &chapter_id));
+        assert!(!index.update(&doc, &chapter_id));
+        assert!(backlinks(&doc, &index, &chapter_id).is_empty());
+
+        doc.remove_chunk(&location_id);
+        assert!(index.update(&doc, &location_id));
+        assert!(!index.update(&doc, &location_id));
+    }
+}
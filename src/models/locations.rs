@@ -0,0 +1,98 @@
+use super::{ChunkType, Document, LocationSheet, ManuscriptError, ManuscriptResult};
+
+impl Document {
+    /// Ids of the locations containing the location `id`, nearest first
+    pub fn location_ancestors_of(&self, id: &str) -> Vec<String> {
+        let mut ancestors: Vec<String> = vec![];
+        let mut current = self
+            .get_chunk_ref(id)
+            .and_then(|c| c.as_any().downcast_ref::<LocationSheet>());
+        while let Some(parent_id) = current.and_then(|l| l.parent_location()) {
+            if parent_id == id || ancestors.contains(parent_id) {
+                break;
+            }
+            ancestors.push(parent_id.clone());
+            current = self
+                .get_chunk_ref(parent_id)
+                .and_then(|c| c.as_any().downcast_ref::<LocationSheet>());
+        }
+        ancestors
+    }
+
+    /// Places the location identified by `id` inside the location `parent`,
+    /// or makes it a standalone location when `parent` is `None`
+    pub fn set_parent_location(&mut self, id: &str, parent: Option<&str>) -> ManuscriptResult<()> {
+        if let Some(parent) = parent {
+            let is_location = self
+                .get_chunk_ref(parent)
+                .ok_or(ManuscriptError::ChunkUnavailable)?
+                .chunk_type()
+                == ChunkType::LocationSheet;
+            let creates_cycle =
+                parent == id || self.location_ancestors_of(parent).iter().any(|a| a == id);
+            if !is_location || creates_cycle {
+                return Err(ManuscriptError::InvalidParent);
+            }
+        }
+
+        let location = self
+            .get_chunk_mut(id)
+            .and_then(|c| c.as_any_mut().downcast_mut::<LocationSheet>())
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        location.set_parent_location(parent.map(String::from));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DocumentChunk, LoreEntry};
+
+    fn add_location(doc: &mut Document, parent: Option<&str>) -> String {
+        let location = LocationSheet::default();
+        let id = location.id().to_string();
+        doc.add_chunk(location);
+        doc.set_parent_location(&id, parent).unwrap();
+        id
+    }
+
+    #[test]
+    fn location_ancestors() {
+        let mut doc = Document::default();
+        let kingdom = add_location(&mut doc, None);
+        let city = add_location(&mut doc, Some(&kingdom));
+        let citadel = add_location(&mut doc, Some(&city));
+        assert_eq!(
+            doc.location_ancestors_of(&citadel),
+            vec![city, kingdom.clone()]
+        );
+        assert!(doc.location_ancestors_of(&kingdom).is_empty());
+    }
+
+    #[test]
+    fn invalid_parent_locations() {
+        let mut doc = Document::default();
+        let kingdom = add_location(&mut doc, None);
+        let city = add_location(&mut doc, Some(&kingdom));
+        let lore = LoreEntry::default();
+        let lore_id = lore.id().to_string();
+        doc.add_chunk(lore);
+
+        // Locations can not go under themselves or their descendants, nor under lore
+        assert!(doc.set_parent_location(&kingdom, Some(&city)).is_err());
+        assert!(doc.set_parent_location(&city, Some(&city)).is_err());
+        assert!(doc.set_parent_location(&city, Some(&lore_id)).is_err());
+        assert_eq!(doc.location_ancestors_of(&city), vec![kingdom]);
+    }
+
+    #[test]
+    fn removing_a_location_lifts_nested_ones() {
+        let mut doc = Document::default();
+        let kingdom = add_location(&mut doc, None);
+        let city = add_location(&mut doc, Some(&kingdom));
+        let citadel = add_location(&mut doc, Some(&city));
+        doc.remove_chunk(&city);
+        assert_eq!(doc.location_ancestors_of(&citadel), vec![kingdom]);
+    }
+}
@@ -1,9 +1,31 @@
+mod appearances;
 mod chunk;
 mod document;
+mod fields;
+mod format;
+mod graph;
+mod links;
+mod locations;
+mod outline;
 mod prelude;
+mod publication;
+mod query;
+mod rename;
 mod settings;
+mod timeline;
+mod workflow;
 
+pub use appearances::*;
 pub use chunk::*;
 pub use document::*;
+pub use fields::*;
+pub use format::decode_manifest;
+pub use graph::*;
+pub use links::*;
 pub use prelude::*;
+pub use publication::*;
+pub use query::*;
+pub use rename::*;
 pub use settings::*;
+pub use timeline::*;
+pub use workflow::*;
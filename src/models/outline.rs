@@ -0,0 +1,455 @@
+use super::{Document, DocumentChunk, ManuscriptError, ManuscriptResult};
+
+impl Document {
+    /// Chunks nested directly under `parent`, sorted by priority. With no
+    /// `parent`, the top level outline chunks are returned instead
+    pub fn children_of(&self, parent: Option<&str>) -> Vec<&dyn DocumentChunk> {
+        let mut children = self
+            .chunks()
+            .into_iter()
+            .filter(|c| c.chunk_type().is_outline())
+            .filter(|c| match parent {
+                Some(parent) => c.parent_id() == Some(parent),
+                None => c
+                    .parent_id()
+                    .map(|id| self.get_chunk_ref(id).is_none())
+                    .unwrap_or(true),
+            })
+            .collect::<Vec<&dyn DocumentChunk>>();
+        children.sort_by_key(|c| (c.priority().unwrap_or(0), c.id().to_string()));
+        children
+    }
+
+    /// Ids of the chunks containing `id`, from the closest one up to the top level
+    pub fn ancestors_of(&self, id: &str) -> Vec<String> {
+        let mut ancestors: Vec<String> = vec![];
+        let mut current = self.get_chunk_ref(id).and_then(|c| c.parent_id());
+        while let Some(parent) = current.and_then(|id| self.get_chunk_ref(id)) {
+            if parent.id() == id || ancestors.iter().any(|a| a == parent.id()) {
+                break;
+            }
+            ancestors.push(parent.id().to_string());
+            current = parent.parent_id();
+        }
+        ancestors
+    }
+
+    /// The manuscript outline walked depth first, paired with the depth
+    /// of each chunk in the hierarchy
+    pub fn outline(&self) -> Vec<(&dyn DocumentChunk, u32)> {
+        let mut outline = vec![];
+        let mut pending: Vec<(&dyn DocumentChunk, u32)> = self
+            .children_of(None)
+            .into_iter()
+            .rev()
+            .map(|c| (c, 0))
+            .collect();
+
+        while let Some((chunk, depth)) = pending.pop() {
+            outline.push((chunk, depth));
+            pending.extend(
+                self.children_of(Some(chunk.id()))
+                    .into_iter()
+                    .rev()
+                    .map(|c| (c, depth + 1)),
+            );
+        }
+
+        outline
+    }
+
+    /// Outline chunks in the order they appear in a compiled manuscript.
+    /// Chunks are left out when excluded from compilation or marked with a
+    /// status or label that is, along with everything nested in them
+    pub fn compile_order(&self) -> Vec<&dyn DocumentChunk> {
+        let mut excluded_depth: Option<u32> = None;
+        self.outline()
+            .into_iter()
+            .filter(|(chunk, depth)| {
+                if let Some(excluded) = excluded_depth {
+                    if *depth > excluded {
+                        return false;
+                    }
+                    excluded_depth = None;
+                }
+                let selected = chunk
+                    .as_buffer_chunk()
+                    .is_none_or(|c| self.manifest().compiles(c.status(), c.labels()));
+                if !chunk.include_in_compilation() || !selected {
+                    excluded_depth = Some(*depth);
+                    return false;
+                }
+                true
+            })
+            .map(|(chunk, _)| chunk)
+            .collect()
+    }
+
+    /// Counts the words of a chunk and of everything nested in it
+    pub fn words_count_of(&self, id: &str) -> u64 {
+        let own = self
+            .get_chunk_ref(id)
+            .and_then(|c| c.as_buffer_chunk())
+            .map(|c| c.words_count())
+            .unwrap_or(0);
+        let nested: u64 = self
+            .children_of(Some(id))
+            .iter()
+            .map(|c| self.words_count_of(c.id()))
+            .sum();
+        own + nested
+    }
+
+    /// Nests the chunk identified by `id` under `parent`, or moves it to the
+    /// top level if `parent` is `None`. The chunk is placed after its new siblings
+    pub fn set_parent(&mut self, id: &str, parent: Option<&str>) -> ManuscriptResult<()> {
+        let chunk = self
+            .get_chunk_ref(id)
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        if let Some(parent) = parent {
+            let parent_chunk = self
+                .get_chunk_ref(parent)
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            let creates_cycle = parent == id || self.ancestors_of(parent).iter().any(|a| a == id);
+            if creates_cycle
+                || !chunk
+                    .chunk_type()
+                    .can_be_child_of(&parent_chunk.chunk_type())
+            {
+                return Err(ManuscriptError::InvalidParent);
+            }
+        } else if !chunk.chunk_type().is_outline() {
+            return Err(ManuscriptError::InvalidParent);
+        }
+
+        let priority = self
+            .children_of(parent)
+            .iter()
+            .filter(|c| c.id() != id)
+            .filter_map(|c| c.priority())
+            .max()
+            .map(|p| p + 1)
+            .unwrap_or(0);
+
+        let chunk = self
+            .get_chunk_mut(id)
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        chunk.set_parent_id(parent.map(String::from))?;
+        chunk.set_priority(Some(priority));
+        Ok(())
+    }
+
+    /// Chunks listed along with `id` in the project layout, in order: the
+    /// outline chunks sharing its parent, or the other chunks of its type
+    pub fn siblings_of(&self, id: &str) -> Vec<&dyn DocumentChunk> {
+        let Some(chunk) = self.get_chunk_ref(id) else {
+            return vec![];
+        };
+        if chunk.chunk_type().is_outline() {
+            let parent = chunk
+                .parent_id()
+                .filter(|p| self.get_chunk_ref(p).is_some());
+            return self.children_of(parent);
+        }
+
+        let mut siblings = self.chunks_by_type_ref(chunk.chunk_type());
+        siblings.sort_by_key(|c| (c.priority().unwrap_or(0), c.id().to_string()));
+        siblings
+    }
+
+    /// Places the chunk identified by `id` right before `target` in the
+    /// outline, or right after it if `after` is set, nesting it under the
+    /// parent of `target` if needed. The priorities of the new siblings are
+    /// renumbered to keep their order
+    pub fn move_chunk_next_to(
+        &mut self,
+        id: &str,
+        target: &str,
+        after: bool,
+    ) -> ManuscriptResult<()> {
+        if id == target {
+            return Ok(());
+        }
+
+        let target_chunk = self
+            .get_chunk_ref(target)
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        let chunk = self
+            .get_chunk_ref(id)
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        if chunk.chunk_type().is_outline() {
+            if !target_chunk.chunk_type().is_outline() {
+                return Err(ManuscriptError::InvalidParent);
+            }
+            let parent = target_chunk
+                .parent_id()
+                .filter(|p| self.get_chunk_ref(p).is_some())
+                .map(String::from);
+            let current_parent = chunk
+                .parent_id()
+                .filter(|p| self.get_chunk_ref(p).is_some())
+                .map(String::from);
+            if parent != current_parent {
+                self.set_parent(id, parent.as_deref())?;
+            }
+        } else if chunk.chunk_type() != target_chunk.chunk_type() {
+            return Err(ManuscriptError::Reason("Chunks of different types"));
+        }
+
+        let mut siblings: Vec<String> = self
+            .siblings_of(target)
+            .iter()
+            .map(|c| c.id().to_string())
+            .filter(|c| c != id)
+            .collect();
+        let position = siblings
+            .iter()
+            .position(|c| c == target)
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        siblings.insert(position + after as usize, id.to_string());
+        self.renumber(siblings.as_slice());
+        Ok(())
+    }
+
+    /// Moves the chunk identified by `id` up or down among its siblings by
+    /// `offset` places, stopping at either end
+    pub fn move_chunk_by(&mut self, id: &str, offset: i32) -> ManuscriptResult<()> {
+        let mut siblings: Vec<String> = self
+            .siblings_of(id)
+            .iter()
+            .map(|c| c.id().to_string())
+            .collect();
+        let position = siblings
+            .iter()
+            .position(|c| c == id)
+            .ok_or(ManuscriptError::ChunkUnavailable)?;
+        let new_position = (position as i64 + offset as i64).clamp(0, siblings.len() as i64 - 1);
+        let moved = siblings.remove(position);
+        siblings.insert(new_position as usize, moved);
+        self.renumber(siblings.as_slice());
+        Ok(())
+    }
+
+    /// Sets the priorities of the chunks in `ids` to follow their order
+    pub(super) fn renumber(&mut self, ids: &[String]) {
+        for (priority, id) in ids.iter().enumerate() {
+            if let Some(chunk) = self.get_chunk_mut(id) {
+                chunk.set_priority(Some(priority as u64));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Chapter, CharacterSheet, MutableBufferChunk, Part, Scene};
+    use bytes::Bytes;
+
+    fn add<C: DocumentChunk + 'static>(doc: &mut Document, chunk: C) -> String {
+        let id = chunk.id().to_string();
+        doc.add_chunk(chunk);
+        id
+    }
+
+    fn ids(chunks: Vec<&dyn DocumentChunk>) -> Vec<String> {
+        chunks.iter().map(|c| c.id().to_string()).collect()
+    }
+
+    #[test]
+    fn invalid_parents() {
+        let mut doc = Document::default();
+        let part = add(&mut doc, Part::default());
+        let chapter = add(&mut doc, Chapter::default());
+        let nested = add(&mut doc, Chapter::default());
+        let sheet = add(&mut doc, CharacterSheet::default());
+        doc.set_parent(&nested, Some(&chapter)).unwrap();
+
+        // Parts can not go under chapters, nor a chunk under itself or its descendants
+        assert!(doc.set_parent(&part, Some(&chapter)).is_err());
+        assert!(doc.set_parent(&chapter, Some(&nested)).is_err());
+        assert!(doc.set_parent(&chapter, Some(&chapter)).is_err());
+        // Chunks outside of the outline have no place in it
+        assert!(doc.set_parent(&sheet, Some(&part)).is_err());
+        assert!(doc.set_parent(&sheet, None).is_err());
+    }
+
+    #[test]
+    fn children_and_ancestors() {
+        let mut doc = Document::default();
+        let part = add(&mut doc, Part::default());
+        let first = add(&mut doc, Chapter::default());
+        let second = add(&mut doc, Chapter::default());
+        let scene = add(&mut doc, Scene::default());
+        add(&mut doc, CharacterSheet::default());
+        doc.set_parent(&first, Some(&part)).unwrap();
+        doc.set_parent(&second, Some(&part)).unwrap();
+        doc.set_parent(&scene, Some(&second)).unwrap();
+
+        assert_eq!(
+            ids(doc.children_of(Some(&part))),
+            vec![first.clone(), second.clone()]
+        );
+        assert_eq!(ids(doc.children_of(None)), vec![part.clone()]);
+        assert_eq!(doc.ancestors_of(&first), vec![part.clone()]);
+        assert_eq!(doc.ancestors_of(&scene), vec![second, part]);
+    }
+
+    #[test]
+    fn outline_depths() {
+        let mut doc = Document::default();
+        let part = add(&mut doc, Part::default());
+        let chapter = add(&mut doc, Chapter::default());
+        let scene = add(&mut doc, Scene::default());
+        let loose = add(&mut doc, Chapter::default());
+        doc.set_parent(&chapter, Some(&part)).unwrap();
+        doc.set_parent(&scene, Some(&chapter)).unwrap();
+        doc.set_parent(&loose, None).unwrap();
+
+        let outline: Vec<(String, u32)> = doc
+            .outline()
+            .iter()
+            .map(|(c, depth)| (c.id().to_string(), *depth))
+            .collect();
+        assert_eq!(
+            outline,
+            vec![(part, 0), (chapter, 1), (scene, 2), (loose, 0)]
+        );
+    }
+
+    #[test]
+    fn compile_order_skips_excluded_branches() {
+        let mut doc = Document::default();
+        let mut excluded = Chapter::default();
+        excluded.set_include_in_compilation(false).unwrap();
+        let part = add(&mut doc, Part::default());
+        let kept = add(&mut doc, Chapter::default());
+        let excluded = add(&mut doc, excluded);
+        let nested = add(&mut doc, Scene::default());
+        let following = add(&mut doc, Chapter::default());
+        doc.set_parent(&kept, Some(&part)).unwrap();
+        doc.set_parent(&excluded, Some(&part)).unwrap();
+        doc.set_parent(&nested, Some(&excluded)).unwrap();
+        doc.set_parent(&following, Some(&part)).unwrap();
+
+        assert_eq!(ids(doc.compile_order()), vec![part, kept, following]);
+    }
+
+    #[test]
+    fn words_count_rolls_up() {
+        let mut doc = Document::default();
+        let mut part = Part::default();
+        part.set_buffer(Bytes::from("Book one"));
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("One two three"));
+        let mut scene = Scene::default();
+        scene.set_buffer(Bytes::from("Four"));
+        let part = add(&mut doc, part);
+        let chapter = add(&mut doc, chapter);
+        let scene = add(&mut doc, scene);
+        doc.set_parent(&chapter, Some(&part)).unwrap();
+        doc.set_parent(&scene, Some(&chapter)).unwrap();
+
+        assert_eq!(doc.words_count_of(&scene), 1);
+        assert_eq!(doc.words_count_of(&chapter), 4);
+        assert_eq!(doc.words_count_of(&part), 6);
+    }
+
+    #[test]
+    fn removing_a_parent_lifts_its_children() {
+        let mut doc = Document::default();
+        let part = add(&mut doc, Part::default());
+        let chapter = add(&mut doc, Chapter::default());
+        let scene = add(&mut doc, Scene::default());
+        doc.set_parent(&chapter, Some(&part)).unwrap();
+        doc.set_parent(&scene, Some(&chapter)).unwrap();
+
+        doc.remove_chunk(&chapter);
+        assert_eq!(ids(doc.children_of(Some(&part))), vec![scene]);
+    }
+
+    #[test]
+    fn move_next_to_a_sibling() {
+        let mut doc = Document::default();
+        let first = add(&mut doc, Chapter::default());
+        let second = add(&mut doc, Chapter::default());
+        doc.set_parent(&second, None).unwrap();
+
+        doc.move_chunk_next_to(&second, &first, false).unwrap();
+        assert_eq!(
+            ids(doc.children_of(None)),
+            vec![second.clone(), first.clone()]
+        );
+        doc.move_chunk_next_to(&second, &first, true).unwrap();
+        assert_eq!(ids(doc.children_of(None)), vec![first, second]);
+    }
+
+    #[test]
+    fn move_next_to_a_chunk_of_another_parent() {
+        let mut doc = Document::default();
+        let first = add(&mut doc, Chapter::default());
+        let second = add(&mut doc, Chapter::default());
+        let scene = add(&mut doc, Scene::default());
+        doc.set_parent(&second, None).unwrap();
+        doc.set_parent(&scene, Some(&first)).unwrap();
+
+        doc.move_chunk_next_to(&scene, &first, true).unwrap();
+        assert_eq!(
+            ids(doc.children_of(None)),
+            vec![first.clone(), scene, second]
+        );
+        assert!(doc.children_of(Some(&first)).is_empty());
+    }
+
+    #[test]
+    fn move_inside_itself() {
+        let mut doc = Document::default();
+        let chapter = add(&mut doc, Chapter::default());
+        let scene = add(&mut doc, Scene::default());
+        doc.set_parent(&scene, Some(&chapter)).unwrap();
+
+        assert!(doc.move_chunk_next_to(&chapter, &scene, false).is_err());
+        assert_eq!(doc.get_chunk_ref(&chapter).unwrap().parent_id(), None);
+    }
+
+    #[test]
+    fn move_by_stops_at_either_end() {
+        let mut doc = Document::default();
+        let first = add(&mut doc, Chapter::default());
+        let second = add(&mut doc, Chapter::default());
+        let scene = add(&mut doc, Scene::default());
+        doc.set_parent(&second, None).unwrap();
+        doc.set_parent(&scene, None).unwrap();
+
+        doc.move_chunk_by(&scene, -10).unwrap();
+        assert_eq!(
+            ids(doc.children_of(None)),
+            vec![scene.clone(), first.clone(), second.clone()]
+        );
+        doc.move_chunk_by(&first, 10).unwrap();
+        assert_eq!(ids(doc.children_of(None)), vec![scene, second, first]);
+    }
+
+    #[test]
+    fn move_among_chunks_of_a_type() {
+        let mut doc = Document::default();
+        let chapter = add(&mut doc, Chapter::default());
+        let sheets: Vec<String> = (0..3)
+            .map(|_| add(&mut doc, CharacterSheet::default()))
+            .collect();
+        doc.move_chunk_next_to(&sheets[1], &sheets[0], true)
+            .unwrap();
+        doc.move_chunk_next_to(&sheets[2], &sheets[1], true)
+            .unwrap();
+        doc.move_chunk_by(&sheets[2], -2).unwrap();
+        doc.move_chunk_next_to(&sheets[0], &sheets[1], true)
+            .unwrap();
+        assert_eq!(
+            ids(doc.siblings_of(&sheets[1])),
+            vec![sheets[2].clone(), sheets[1].clone(), sheets[0].clone()]
+        );
+
+        // Chunks outside of the outline can not be moved into it
+        assert!(doc.move_chunk_next_to(&sheets[0], &chapter, false).is_err());
+    }
+}
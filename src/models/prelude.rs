@@ -1,4 +1,4 @@
-use super::{Chapter, CharacterSheet};
+use super::{Chapter, CharacterSheet, LocationSheet, LoreEntry, Part, Research, Scene};
 use bytes::Bytes;
 use glib::{StaticType, Type};
 use gtk::gdk::RGBA;
@@ -48,6 +48,17 @@ impl Color {
             default_contrast_dark()
         }
     }
+
+    /// The color as `#rrggbb`, the notation Pango markup understands
+    pub fn hex(&self) -> String {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            channel(self.0.red()),
+            channel(self.0.green()),
+            channel(self.0.blue())
+        )
+    }
 }
 
 impl Default for Color {
@@ -227,16 +238,48 @@ impl<'de> Deserialize<'de> for Color {
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Clone, Serialize, Deserialize)]
 pub enum ChunkType {
     Manifest,
+    Part,
     Chapter,
+    Scene,
     CharacterSheet,
+    LocationSheet,
+    LoreEntry,
+    Research,
+}
+
+impl ChunkType {
+    /// Whether chunks of this type make up the manuscript outline
+    pub fn is_outline(&self) -> bool {
+        matches!(self, Self::Part | Self::Chapter | Self::Scene)
+    }
+
+    /// Whether chunks of this type can be part of a compiled manuscript
+    pub fn is_compilable(&self) -> bool {
+        !matches!(self, Self::Research)
+    }
+
+    /// Whether a chunk of this type can be nested under a chunk of type `parent`
+    pub fn can_be_child_of(&self, parent: &ChunkType) -> bool {
+        match self {
+            Self::Part => parent == &Self::Part,
+            Self::Chapter => matches!(parent, Self::Part | Self::Chapter),
+            Self::Scene => matches!(parent, Self::Part | Self::Chapter),
+            _ => false,
+        }
+    }
 }
 
 impl std::fmt::Display for ChunkType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let desc = match self {
             ChunkType::Manifest => "Manifest",
+            ChunkType::Part => "Part",
             ChunkType::Chapter => "Chapter",
+            ChunkType::Scene => "Scene",
             ChunkType::CharacterSheet => "Character Sheet",
+            ChunkType::LocationSheet => "Location Sheet",
+            ChunkType::LoreEntry => "Lore Entry",
+            ChunkType::Research => "Research",
         };
         write!(f, "{desc}")
     }
@@ -254,6 +297,8 @@ pub enum ManuscriptError {
     ChunkParse,
     ChunkBusy,
     ChunkUnavailable,
+    InvalidParent,
+    Import(String),
     Reason(&'static str),
 }
 
@@ -295,6 +340,23 @@ pub trait DocumentChunk {
     fn set_include_in_compilation(&mut self, _value: bool) -> ManuscriptResult<()> {
         Err(ManuscriptError::Reason("Not implemented"))
     }
+
+    /// Id of the chunk this one is nested under, if any
+    fn parent_id(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_parent_id(&mut self, _value: Option<String>) -> ManuscriptResult<()> {
+        Err(ManuscriptError::Reason("Not implemented"))
+    }
+
+    fn as_buffer_chunk(&self) -> Option<&dyn BufferChunk> {
+        None
+    }
+
+    fn as_mutable_buffer_chunk(&mut self) -> Option<&mut dyn MutableBufferChunk> {
+        None
+    }
 }
 
 impl StaticType for dyn DocumentChunk {
@@ -369,6 +431,22 @@ where
     fn set_locked(&mut self, value: bool) {
         self.deref_mut().set_locked(value);
     }
+
+    fn parent_id(&self) -> Option<&str> {
+        self.deref().parent_id()
+    }
+
+    fn set_parent_id(&mut self, value: Option<String>) -> ManuscriptResult<()> {
+        self.deref_mut().set_parent_id(value)
+    }
+
+    fn as_buffer_chunk(&self) -> Option<&dyn BufferChunk> {
+        self.deref().as_buffer_chunk()
+    }
+
+    fn as_mutable_buffer_chunk(&mut self) -> Option<&mut dyn MutableBufferChunk> {
+        self.deref_mut().as_mutable_buffer_chunk()
+    }
 }
 
 impl Clone for Box<dyn DocumentChunk> {
@@ -376,27 +454,65 @@ impl Clone for Box<dyn DocumentChunk> {
         let any = self.as_any();
         if let Some(chapter) = any.downcast_ref::<Chapter>() {
             Box::new(chapter.clone())
+        } else if let Some(part) = any.downcast_ref::<Part>() {
+            Box::new(part.clone())
+        } else if let Some(scene) = any.downcast_ref::<Scene>() {
+            Box::new(scene.clone())
         } else if let Some(character_sheet) = any.downcast_ref::<CharacterSheet>() {
             Box::new(character_sheet.clone())
+        } else if let Some(location_sheet) = any.downcast_ref::<LocationSheet>() {
+            Box::new(location_sheet.clone())
+        } else if let Some(lore_entry) = any.downcast_ref::<LoreEntry>() {
+            Box::new(lore_entry.clone())
+        } else if let Some(research) = any.downcast_ref::<Research>() {
+            Box::new(research.clone())
         } else {
             unreachable!()
         }
     }
 }
 
-pub trait SerializableChunk<'de>: DocumentChunk + Serialize + Deserialize<'de> {}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TagMark(i32, i32, String);
 
+impl TagMark {
+    pub fn new(start: i32, end: i32, name: &str) -> Self {
+        Self(start, end, name.into())
+    }
+
+    pub fn start(&self) -> i32 {
+        self.0
+    }
+
+    pub fn end(&self) -> i32 {
+        self.1
+    }
+
+    pub fn name(&self) -> &str {
+        self.2.as_str()
+    }
+}
+
 pub trait BufferChunk: DocumentChunk {
     fn buffer(&self) -> &Bytes;
 
-    fn tags_map(&self) -> &Vec<TagMark> {
-        static EMPTY_MARKS: once_cell::sync::Lazy<Vec<TagMark>> =
-            once_cell::sync::Lazy::new(|| vec![]);
-        EMPTY_MARKS.as_ref()
-    }
+    /// Short summary of the content
+    fn synopsis(&self) -> Option<&String>;
+
+    /// Id of the workflow status of the content, see `ChunkStatus`
+    fn status(&self) -> Option<&String>;
+
+    /// Ids of the labels attached to the content, see `ChunkLabel`
+    fn labels(&self) -> &Vec<String>;
+
+    /// How many words the content is meant to reach
+    fn words_target(&self) -> Option<u64>;
+
+    /// Last time the content was edited, in seconds since the Unix epoch
+    fn modified(&self) -> Option<i64>;
+
+    /// Marks of the tags applied to the buffer, like highlights
+    fn tags_map(&self) -> &Vec<TagMark>;
 
     /// Counts the words in the buffer
     fn words_count(&self) -> u64 {
@@ -411,6 +527,21 @@ pub trait BufferChunk: DocumentChunk {
 
 pub trait MutableBufferChunk: BufferChunk {
     fn set_buffer(&mut self, value: Bytes);
+    fn set_synopsis(&mut self, value: Option<String>);
+    fn set_status(&mut self, value: Option<String>);
+    fn set_labels(&mut self, value: Vec<String>);
+    fn set_words_target(&mut self, value: Option<u64>);
+    fn set_modified(&mut self, value: Option<i64>);
+    fn set_tags_map(&mut self, value: Vec<TagMark>);
+
+    /// Marks the content as edited right now
+    fn touch(&mut self) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .ok();
+        self.set_modified(now);
+    }
 }
 
 pub trait BufferAnalytics {
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+
+/// Bibliographic details of a project, used when publishing it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PublicationMetadata {
+    subtitle: Option<String>,
+    series: Option<String>,
+    series_number: Option<u32>,
+    language: Option<String>,
+    publisher: Option<String>,
+    isbns: Vec<String>,
+    publication_date: Option<(i32, u32, u32)>,
+    keywords: Vec<String>,
+    description: String,
+    rights: Option<String>,
+}
+
+impl PublicationMetadata {
+    pub fn subtitle(&self) -> Option<&String> {
+        self.subtitle.as_ref()
+    }
+
+    pub fn set_subtitle(&mut self, value: Option<String>) {
+        self.subtitle = value;
+    }
+
+    pub fn series(&self) -> Option<&String> {
+        self.series.as_ref()
+    }
+
+    pub fn set_series(&mut self, value: Option<String>) {
+        self.series = value;
+    }
+
+    /// Position of the book in its series
+    pub fn series_number(&self) -> Option<u32> {
+        self.series_number
+    }
+
+    pub fn set_series_number(&mut self, value: Option<u32>) {
+        self.series_number = value;
+    }
+
+    /// Language of the text, as a BCP 47 tag like `en` or `pt-BR`
+    pub fn language(&self) -> Option<&String> {
+        self.language.as_ref()
+    }
+
+    pub fn set_language(&mut self, value: Option<String>) {
+        self.language = value;
+    }
+
+    pub fn publisher(&self) -> Option<&String> {
+        self.publisher.as_ref()
+    }
+
+    pub fn set_publisher(&mut self, value: Option<String>) {
+        self.publisher = value;
+    }
+
+    /// ISBNs of the book, one for each edition or format
+    pub fn isbns(&self) -> &Vec<String> {
+        &self.isbns
+    }
+
+    pub fn set_isbns(&mut self, value: Vec<String>) {
+        self.isbns = value;
+    }
+
+    /// Publication date as year, month and day
+    pub fn publication_date(&self) -> Option<(i32, u32, u32)> {
+        self.publication_date
+    }
+
+    pub fn set_publication_date(&mut self, value: Option<(i32, u32, u32)>) {
+        self.publication_date = value;
+    }
+
+    /// Genres and keywords describing the book
+    pub fn keywords(&self) -> &Vec<String> {
+        &self.keywords
+    }
+
+    pub fn set_keywords(&mut self, value: Vec<String>) {
+        self.keywords = value;
+    }
+
+    /// Long description of the book, like the one printed on its back cover
+    pub fn description(&self) -> &str {
+        self.description.as_str()
+    }
+
+    pub fn set_description(&mut self, value: String) {
+        self.description = value;
+    }
+
+    pub fn rights(&self) -> Option<&String> {
+        self.rights.as_ref()
+    }
+
+    pub fn set_rights(&mut self, value: Option<String>) {
+        self.rights = value;
+    }
+}
+
+/// Splits a comma separated list, dropping empty items
+pub fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Checks the length and check digit of an ISBN-10 or ISBN-13, ignoring
+/// hyphens and spaces
+pub fn is_valid_isbn(text: &str) -> bool {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .collect();
+
+    match chars.len() {
+        10 => {
+            let mut sum = 0;
+            for (index, c) in chars.iter().enumerate() {
+                let value = match c {
+                    'X' | 'x' if index == 9 => 10,
+                    _ => match c.to_digit(10) {
+                        Some(value) => value,
+                        None => return false,
+                    },
+                };
+                sum += value * (10 - index as u32);
+            }
+            sum % 11 == 0
+        }
+        13 => {
+            let mut sum = 0;
+            for (index, c) in chars.iter().enumerate() {
+                match c.to_digit(10) {
+                    Some(value) => sum += value * if index % 2 == 0 { 1 } else { 3 },
+                    None => return false,
+                }
+            }
+            sum % 10 == 0
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isbn_10() {
+        assert!(is_valid_isbn("0 306 40615 2"));
+        assert!(is_valid_isbn("080442957X"));
+        assert!(is_valid_isbn("080442957x"));
+        assert!(!is_valid_isbn("0306406153"));
+        assert!(!is_valid_isbn("08044295X7"));
+    }
+
+    #[test]
+    fn isbn_13() {
+        assert!(is_valid_isbn("978-0-306-40615-7"));
+        assert!(!is_valid_isbn("978-0-306-40615-8"));
+        assert!(!is_valid_isbn("978-0-306-40615-X"));
+    }
+
+    #[test]
+    fn isbn_of_other_lengths() {
+        assert!(!is_valid_isbn(""));
+        assert!(!is_valid_isbn("not an isbn"));
+        assert!(!is_valid_isbn("978-0-306-40615"));
+    }
+
+    #[test]
+    fn split_lists() {
+        assert_eq!(split_list(" fantasy, ,epic "), vec!["fantasy", "epic"]);
+        assert_eq!(split_list("fantasy"), vec!["fantasy"]);
+        assert!(split_list(" , ").is_empty());
+    }
+}
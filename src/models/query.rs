@@ -0,0 +1,308 @@
+use super::{timeline::story_time_of, CharacterSheet, Document, DocumentChunk, Scene};
+
+/// A search over the chunks of a document. Terms prefixed by `pov:` or
+/// `location:` only match scenes with that point of view or location,
+/// `time:` only matches chunks with that story time, `status:` and `label:`
+/// only match chunks with that workflow status or label, while terms
+/// prefixed by the name of a custom character field only match sheets with
+/// that value. `sort:` followed by a field name orders character sheets by
+/// that field. Any other term matches titles, synopses, scene details and
+/// custom field values
+#[derive(Debug, Default, Clone)]
+pub struct ChunkQuery {
+    terms: Vec<String>,
+    point_of_view: Vec<String>,
+    location: Vec<String>,
+    story_time: Vec<String>,
+    status: Vec<String>,
+    label: Vec<String>,
+    fields: Vec<(String, String)>,
+    sort_by: Option<String>,
+}
+
+impl ChunkQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        for term in query.split_whitespace() {
+            let term = term.to_lowercase();
+            match term.split_once(':') {
+                Some(("pov", value)) if !value.is_empty() => {
+                    parsed.point_of_view.push(value.into())
+                }
+                Some(("location", value)) if !value.is_empty() => {
+                    parsed.location.push(value.into())
+                }
+                Some(("time", value)) if !value.is_empty() => parsed.story_time.push(value.into()),
+                Some(("status", value)) if !value.is_empty() => parsed.status.push(value.into()),
+                Some(("label", value)) if !value.is_empty() => parsed.label.push(value.into()),
+                Some(("sort", value)) if !value.is_empty() => parsed.sort_by = Some(value.into()),
+                Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+                    parsed.fields.push((key.into(), value.into()))
+                }
+                _ => parsed.terms.push(term),
+            }
+        }
+        parsed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+            && self.point_of_view.is_empty()
+            && self.location.is_empty()
+            && self.story_time.is_empty()
+            && self.status.is_empty()
+            && self.label.is_empty()
+            && self.fields.is_empty()
+    }
+
+    /// Key of the custom field to sort character sheets by, if any
+    pub fn sort_by(&self) -> Option<&String> {
+        self.sort_by.as_ref()
+    }
+
+    pub fn matches(&self, document: &Document, chunk: &dyn DocumentChunk) -> bool {
+        let scene = chunk.as_any().downcast_ref::<Scene>();
+        let sheet = chunk.as_any().downcast_ref::<CharacterSheet>();
+        let manifest = document.manifest();
+        let point_of_view = scene.and_then(|s| point_of_view_name(document, s));
+        let location = scene.and_then(|s| s.location()).cloned();
+        let story_time = story_time_of(chunk).cloned();
+
+        let field_matches = |values: &[String], field: &Option<String>| {
+            values.iter().all(|value| {
+                field
+                    .as_ref()
+                    .map(|f| f.to_lowercase().contains(value.as_str()))
+                    .unwrap_or(false)
+            })
+        };
+
+        if !field_matches(&self.point_of_view, &point_of_view)
+            || !field_matches(&self.location, &location)
+            || !field_matches(&self.story_time, &story_time)
+        {
+            return false;
+        }
+
+        let buffer_chunk = chunk.as_buffer_chunk();
+        let status = buffer_chunk
+            .and_then(|c| c.status())
+            .and_then(|id| manifest.status(id))
+            .map(|s| search_key(s.name()));
+        if !field_matches(&self.status, &status) {
+            return false;
+        }
+        let labels: Vec<String> = buffer_chunk
+            .map(|c| c.labels().as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|id| manifest.label(id))
+            .map(|l| search_key(l.name()))
+            .collect();
+        if !self
+            .label
+            .iter()
+            .all(|value| labels.iter().any(|l| l.contains(value.as_str())))
+        {
+            return false;
+        }
+
+        // Pairs not naming a custom field are searched as plain text
+        let mut terms: Vec<String> = self.terms.clone();
+        for (key, value) in self.fields.iter() {
+            match manifest.character_field_by_key(key) {
+                Some(field) => {
+                    let field_value = sheet
+                        .and_then(|s| s.custom_field(field.id()))
+                        .map(|v| v.display(document));
+                    if !field_matches(std::slice::from_ref(value), &field_value) {
+                        return false;
+                    }
+                }
+                None => terms.push(format!("{key}:{value}")),
+            }
+        }
+
+        let mut haystack = vec![
+            Some(chunk.safe_title()),
+            buffer_chunk.and_then(|c| c.synopsis()).cloned(),
+            point_of_view,
+            location,
+            story_time,
+        ];
+        if let Some(scene) = scene {
+            haystack.extend(
+                [scene.goal(), scene.conflict(), scene.outcome()]
+                    .into_iter()
+                    .map(|f| f.cloned()),
+            );
+        }
+        if let Some(sheet) = sheet {
+            haystack.extend(
+                sheet
+                    .custom_fields()
+                    .values()
+                    .map(|v| Some(v.display(document))),
+            );
+        }
+        let haystack = haystack
+            .into_iter()
+            .flatten()
+            .collect::<Vec<String>>()
+            .join("\n")
+            .to_lowercase();
+
+        terms.iter().all(|term| haystack.contains(term.as_str()))
+    }
+}
+
+/// Lowercases `name` and drops its whitespaces, so that names made of
+/// several words can be typed as a single search term
+pub fn search_key(name: &str) -> String {
+    name.split_whitespace().collect::<String>().to_lowercase()
+}
+
+/// Name of the viewpoint character of `scene`, if it links to an existing sheet
+pub fn point_of_view_name(document: &Document, scene: &Scene) -> Option<String> {
+    scene
+        .point_of_view()
+        .and_then(|id| document.get_chunk_ref(id))
+        .filter(|c| c.as_any().downcast_ref::<CharacterSheet>().is_some())
+        .map(|c| c.safe_title())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        Chapter, ChunkLabel, Color, FieldDefinition, FieldKind, MutableBufferChunk,
+    };
+
+    /// A chapter holding two scenes in Moscow, only the first one seen
+    /// through the eyes of Anna and dated. Returns the ids of the scenes
+    fn scenes_document() -> (Document, String, String) {
+        let mut doc = Document::default();
+        let mut anna = CharacterSheet::default();
+        anna.set_name(Some("Anna Karenina".into()));
+        let mut chapter = Chapter::default();
+        chapter.set_title("The Ball");
+        let mut ball = Scene::default();
+        ball.set_title("Mazurka");
+        ball.set_point_of_view(Some(anna.id().to_string()));
+        ball.set_location(Some("Moscow".into()));
+        ball.set_story_time(Some("Winter 1874".into()));
+        ball.set_conflict(Some("Kitty expects a proposal".into()));
+        let mut station = Scene::default();
+        station.set_title("Arrival");
+        station.set_location(Some("Moscow station".into()));
+        let (chapter_id, ball_id, station_id) = (
+            chapter.id().to_string(),
+            ball.id().to_string(),
+            station.id().to_string(),
+        );
+        doc.add_chunk(anna);
+        doc.add_chunk(chapter);
+        doc.add_chunk(ball);
+        doc.add_chunk(station);
+        doc.set_parent(&ball_id, Some(&chapter_id)).unwrap();
+        doc.set_parent(&station_id, Some(&chapter_id)).unwrap();
+        (doc, ball_id, station_id)
+    }
+
+    fn search(doc: &Document, query: &str) -> Vec<String> {
+        doc.search(&ChunkQuery::parse(query))
+            .iter()
+            .map(|c| c.id().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn point_of_view() {
+        let (doc, ball_id, _) = scenes_document();
+        assert_eq!(search(&doc, "pov:anna"), vec![ball_id]);
+        assert!(search(&doc, "pov:vronsky").is_empty());
+    }
+
+    #[test]
+    fn location_and_story_time() {
+        let (doc, ball_id, station_id) = scenes_document();
+        assert_eq!(
+            search(&doc, "location:moscow"),
+            vec![ball_id.clone(), station_id]
+        );
+        assert_eq!(search(&doc, "Location:Moscow time:1874"), vec![ball_id]);
+    }
+
+    #[test]
+    fn plain_terms_match_scene_details() {
+        let (doc, ball_id, station_id) = scenes_document();
+        assert_eq!(search(&doc, "proposal"), vec![ball_id]);
+        assert_eq!(search(&doc, "arrival"), vec![station_id]);
+        // The sheet matches by its name, the scene by its viewpoint
+        assert_eq!(search(&doc, "karenina").len(), 2);
+    }
+
+    #[test]
+    fn custom_field_terms() {
+        let mut doc = Document::default();
+        let house = FieldDefinition::new(
+            "House",
+            FieldKind::Choice(vec!["Stark".into(), "Lannister".into()]),
+        );
+        let mut arya = CharacterSheet::default();
+        arya.set_name(Some("Arya".into()));
+        arya.set_custom_field(house.id(), house.parse_value("Stark"));
+        let mut jaime = CharacterSheet::default();
+        jaime.set_name(Some("Jaime".into()));
+        jaime.set_custom_field(house.id(), house.parse_value("Lannister"));
+        let arya_id = arya.id().to_string();
+        let jaime_id = jaime.id().to_string();
+        doc.manifest_mut().add_character_field(house);
+        doc.add_chunk(arya);
+        doc.add_chunk(jaime);
+
+        assert_eq!(search(&doc, "house:stark"), vec![arya_id]);
+        // Field values are matched by plain terms too
+        assert_eq!(search(&doc, "lannister"), vec![jaime_id]);
+        // Pairs not naming a field are searched as plain text
+        assert!(search(&doc, "weapon:needle").is_empty());
+    }
+
+    #[test]
+    fn sort_term() {
+        let query = ChunkQuery::parse("house:stark sort:DateOfBirth");
+        assert_eq!(query.sort_by().map(String::as_str), Some("dateofbirth"));
+        assert_eq!(query.fields, vec![("house".into(), "stark".into())]);
+        // Sorting alone does not filter anything
+        assert!(ChunkQuery::parse("sort:age").is_empty());
+    }
+
+    #[test]
+    fn status_and_label_terms() {
+        let mut doc = Document::default();
+        let draft = doc.manifest().statuses()[1].id().to_string();
+        let label = ChunkLabel::new("Cut", Color::new(0.9, 0.1, 0.1, 1.0));
+        let cut = label.id().to_string();
+        doc.manifest_mut().add_label(label);
+        let mut chapter = Chapter::default();
+        chapter.set_status(Some(draft));
+        let mut scene = Scene::default();
+        scene.set_labels(vec![cut]);
+        let (chapter_id, scene_id) = (chapter.id().to_string(), scene.id().to_string());
+        doc.add_chunk(chapter);
+        doc.add_chunk(scene);
+
+        assert_eq!(search(&doc, "status:first"), vec![chapter_id]);
+        assert_eq!(search(&doc, "label:cut"), vec![scene_id]);
+        assert!(search(&doc, "status:final").is_empty());
+        assert!(search(&doc, "label:missing").is_empty());
+    }
+
+    #[test]
+    fn prefixes_without_value_are_plain_terms() {
+        assert!(ChunkQuery::parse("  ").is_empty());
+        let query = ChunkQuery::parse("pov: :moscow");
+        assert!(query.point_of_view.is_empty());
+        assert_eq!(query.terms, vec!["pov:", ":moscow"]);
+    }
+}
@@ -0,0 +1,349 @@
+use super::Document;
+use crate::services::analyst::MentionMatcher;
+
+/// How many characters around an occurrence are shown to tell it apart
+const CONTEXT_LENGTH: usize = 40;
+
+/// A whole-word occurrence of a name in the text of a manuscript chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameOccurrence {
+    chunk_id: String,
+    chunk_title: String,
+    start: i32,
+    end: i32,
+    name: String,
+    before: String,
+    after: String,
+}
+
+impl NameOccurrence {
+    pub fn chunk_id(&self) -> &str {
+        self.chunk_id.as_str()
+    }
+
+    pub fn chunk_title(&self) -> &str {
+        self.chunk_title.as_str()
+    }
+
+    /// Character offset of the first character of the name
+    pub fn start(&self) -> i32 {
+        self.start
+    }
+
+    /// Character offset right after the name
+    pub fn end(&self) -> i32 {
+        self.end
+    }
+
+    /// The name as it appears in the text
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Text preceding the name on the same line
+    pub fn before(&self) -> &str {
+        self.before.as_str()
+    }
+
+    /// Text following the name on the same line
+    pub fn after(&self) -> &str {
+        self.after.as_str()
+    }
+}
+
+/// Finds the whole-word occurrences of `names` in the text of the manuscript,
+/// in reading order. Locked chunks are left out, as they cannot be edited
+pub fn name_occurrences(document: &Document, names: &[String]) -> Vec<NameOccurrence> {
+    let matcher = MentionMatcher::new(names);
+    let mut occurrences = vec![];
+
+    for (chunk, _) in document.outline() {
+        if chunk.locked() {
+            continue;
+        }
+        let Some(buffer_chunk) = chunk.as_buffer_chunk() else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(buffer_chunk.buffer());
+        let chars: Vec<char> = text.chars().collect();
+
+        for (start, end) in matcher.ranges(text.as_ref()) {
+            let (start_index, end_index) = (start as usize, end as usize);
+            let before: String = chars[start_index.saturating_sub(CONTEXT_LENGTH)..start_index]
+                .iter()
+                .collect();
+            let after: String = chars[end_index..(end_index + CONTEXT_LENGTH).min(chars.len())]
+                .iter()
+                .collect();
+
+            occurrences.push(NameOccurrence {
+                chunk_id: chunk.id().to_string(),
+                chunk_title: chunk.safe_title(),
+                start,
+                end,
+                name: chars[start_index..end_index].iter().collect(),
+                before: before.rsplit('\n').next().unwrap_or_default().to_string(),
+                after: after.split('\n').next().unwrap_or_default().to_string(),
+            });
+        }
+    }
+
+    occurrences
+}
+
+/// Sorts character `ranges`, merging the ones that overlap
+fn merge_ranges(ranges: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort();
+    let mut merged: Vec<(i32, i32)> = Vec::with_capacity(sorted.len());
+    for (start, end) in sorted {
+        match merged.last_mut() {
+            Some(last) if last.1 > start => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Replaces the character `ranges` of `text` with `replacement`. Ranges
+/// that overlap are replaced once, as a whole
+pub(crate) fn replace_ranges(text: &str, ranges: &[(i32, i32)], replacement: &str) -> String {
+    let ranges = merge_ranges(ranges);
+    let mut result = String::with_capacity(text.len());
+    let mut ranges = ranges.iter().peekable();
+    for (offset, character) in text.chars().enumerate() {
+        let offset = offset as i32;
+        while ranges.peek().is_some_and(|(_, end)| *end <= offset) {
+            ranges.next();
+        }
+        match ranges.peek() {
+            Some((start, _)) if *start == offset => result.push_str(replacement),
+            Some((start, _)) if *start < offset => (),
+            _ => result.push(character),
+        }
+    }
+    result
+}
+
+/// Where the character `offset` moves once `ranges` are replaced with text
+/// `len` characters long, as `replace_ranges` does. Offsets inside a replaced
+/// range move to its start, or to its end for the end of a span
+pub(crate) fn shift_offset(offset: i32, ranges: &[(i32, i32)], len: i32, is_end: bool) -> i32 {
+    let mut delta = 0;
+    for (start, end) in merge_ranges(ranges).iter() {
+        if *end <= offset {
+            delta += len - (end - start);
+        } else if *start < offset {
+            return start + delta + if is_end { len } else { 0 };
+        } else {
+            break;
+        }
+    }
+    offset + delta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BufferChunk, Chapter, DocumentChunk, MutableBufferChunk, Scene, TagMark};
+    use bytes::Bytes;
+
+    #[test]
+    fn replace_each_range() {
+        let text = "Ada smiled at Ada.";
+        assert_eq!(
+            replace_ranges(text, &[(0, 3), (14, 17)], "Eve"),
+            "Eve smiled at Eve."
+        );
+        assert_eq!(
+            replace_ranges(text, &[(14, 17), (0, 3)], "Jo"),
+            "Jo smiled at Jo."
+        );
+        assert_eq!(replace_ranges(text, &[], "Eve"), text);
+        assert_eq!(replace_ranges("Ada", &[(0, 3)], ""), "");
+    }
+
+    #[test]
+    fn replace_adjacent_ranges() {
+        assert_eq!(
+            replace_ranges("AdaAda!", &[(0, 3), (3, 6)], "Eve"),
+            "EveEve!"
+        );
+    }
+
+    #[test]
+    fn replace_overlapping_ranges_once() {
+        assert_eq!(
+            replace_ranges("Ada Lovelace!", &[(0, 3), (0, 12)], "Eve"),
+            "Eve!"
+        );
+        assert_eq!(replace_ranges("abcdefgh", &[(0, 4), (2, 6)], "X"), "Xgh");
+    }
+
+    #[test]
+    fn replace_multibyte_text() {
+        assert_eq!(
+            replace_ranges("Élise et Zoé.", &[(0, 5), (9, 12)], "Ève"),
+            "Ève et Ève."
+        );
+        assert_eq!(
+            replace_ranges("Ada et Zoé.", &[(7, 10)], "Noémie"),
+            "Ada et Noémie."
+        );
+    }
+
+    #[test]
+    fn shift_offsets_around_ranges() {
+        // "Ada smiled at Ada." becoming "Ada Byron smiled at Ada Byron."
+        let ranges = [(0, 3), (14, 17)];
+        assert_eq!(shift_offset(0, &ranges, 9, false), 0);
+        assert_eq!(shift_offset(3, &ranges, 9, true), 9);
+        assert_eq!(shift_offset(4, &ranges, 9, false), 10);
+        assert_eq!(shift_offset(14, &ranges, 9, false), 20);
+        assert_eq!(shift_offset(18, &ranges, 9, true), 30);
+    }
+
+    #[test]
+    fn shift_offsets_inside_a_replaced_range() {
+        let ranges = [(4, 10)];
+        // A span ending inside the replaced text ends after the replacement
+        assert_eq!(shift_offset(6, &ranges, 3, true), 7);
+        // A span starting inside it starts at the replacement
+        assert_eq!(shift_offset(6, &ranges, 3, false), 4);
+        assert_eq!(shift_offset(12, &ranges, 3, false), 9);
+    }
+
+    #[test]
+    fn shift_offsets_of_overlapping_ranges() {
+        // "abcdefgh" becoming "Xgh"
+        let ranges = [(0, 4), (2, 6)];
+        assert_eq!(shift_offset(7, &ranges, 1, false), 2);
+        assert_eq!(shift_offset(8, &ranges, 1, true), 3);
+        assert_eq!(shift_offset(5, &ranges, 1, true), 1);
+    }
+
+    #[test]
+    fn shift_offsets_count_characters() {
+        // "Ada a dit" becoming "Élodie a dit", a name of 6 characters and 7 bytes
+        let len = "Élodie".chars().count() as i32;
+        assert_eq!(shift_offset(9, &[(0, 3)], len, true), 12);
+        // "Zoé a dit" becoming "Ada a dit"
+        assert_eq!(shift_offset(9, &[(0, 3)], 3, true), 9);
+    }
+
+    /// A chapter mentioning Ada and the Countess, with a note and a
+    /// highlight, and a locked chapter mentioning Ada. Returns the id of the
+    /// first chapter
+    fn rename_document() -> (Document, String) {
+        let mut doc = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Ada smiled at the Countess.\nAda Byron waved."));
+        chapter.add_note(4, 10, "Smiling".into());
+        chapter.set_tags_map(vec![TagMark::new(14, 26, "highlight")]);
+        let chapter_id = chapter.id().to_string();
+        doc.add_chunk(chapter);
+        let mut locked = Chapter::default();
+        locked.set_buffer(Bytes::from("Ada is not here."));
+        locked.set_locked(true);
+        doc.add_chunk(locked);
+        (doc, chapter_id)
+    }
+
+    /// Occurrences of "Ada" and "Countess", leaving "Ada Byron" alone
+    fn occurrences(doc: &Document) -> Vec<NameOccurrence> {
+        let names: Vec<String> = vec!["Ada".into(), "Ada Byron".into(), "Countess".into()];
+        name_occurrences(doc, &names)
+            .into_iter()
+            .filter(|o| o.name() != "Ada Byron")
+            .collect()
+    }
+
+    fn chapter(doc: &Document, id: &str) -> Chapter {
+        doc.get_chunk_ref(id)
+            .and_then(|c| c.as_any().downcast_ref::<Chapter>())
+            .cloned()
+            .unwrap()
+    }
+
+    #[test]
+    fn find_occurrences() {
+        let (doc, chapter_id) = rename_document();
+        let occurrences = occurrences(&doc);
+        let found: Vec<(&str, i32, &str, &str)> = occurrences
+            .iter()
+            .map(|o| (o.name(), o.start(), o.before(), o.after()))
+            .collect();
+        // Locked chunks are left out
+        assert_eq!(
+            found,
+            vec![
+                ("Ada", 0, "", " smiled at the Countess."),
+                ("Countess", 18, "Ada smiled at the ", "."),
+            ]
+        );
+        assert!(occurrences.iter().all(|o| o.chunk_id() == chapter_id));
+    }
+
+    #[test]
+    fn rename_occurrences() {
+        let (mut doc, chapter_id) = rename_document();
+        doc.rename_occurrences(&occurrences(&doc), "Ada Byron")
+            .unwrap();
+        let renamed = chapter(&doc, &chapter_id);
+        assert_eq!(
+            renamed.buffer().as_ref(),
+            b"Ada Byron smiled at the Ada Byron.\nAda Byron waved."
+        );
+        // Notes and tag marks stay on the text they were anchored to
+        let note = &renamed.notes()[0];
+        assert_eq!((note.offset_start(), note.offset_end()), (10, 16));
+        assert_eq!(renamed.tags_map(), &vec![TagMark::new(20, 33, "highlight")]);
+    }
+
+    #[test]
+    fn rename_shifts_scene_marks() {
+        let mut doc = Document::default();
+        let mut scene = Scene::default();
+        scene.set_buffer(Bytes::from("Ada smiled."));
+        scene.set_tags_map(vec![TagMark::new(4, 10, "highlight")]);
+        let scene_id = scene.id().to_string();
+        doc.add_chunk(scene);
+
+        let occurrences = name_occurrences(&doc, &["Ada".into()]);
+        doc.rename_occurrences(&occurrences, "Ada Byron").unwrap();
+        let scene = doc
+            .get_chunk_ref(&scene_id)
+            .and_then(|c| c.as_buffer_chunk())
+            .unwrap();
+        assert_eq!(scene.tags_map(), &vec![TagMark::new(10, 16, "highlight")]);
+    }
+
+    #[test]
+    fn stale_occurrences_are_refused() {
+        let (mut doc, chapter_id) = rename_document();
+        let occurrences = occurrences(&doc);
+        doc.rename_occurrences(&occurrences, "Ada Byron").unwrap();
+        let renamed = chapter(&doc, &chapter_id);
+        // Occurrences found before the text changed are refused as a whole
+        assert!(doc.rename_occurrences(&occurrences, "Ada").is_err());
+        assert_eq!(chapter(&doc, &chapter_id).buffer(), renamed.buffer());
+    }
+
+    #[test]
+    fn undo_rename_occurrences() {
+        let (mut doc, chapter_id) = rename_document();
+        let snapshot = doc
+            .rename_occurrences(&occurrences(&doc), "Ada Byron")
+            .unwrap();
+        doc.restore(snapshot);
+        let restored = chapter(&doc, &chapter_id);
+        assert_eq!(
+            restored.buffer().as_ref(),
+            b"Ada smiled at the Countess.\nAda Byron waved."
+        );
+        assert_eq!(
+            restored.tags_map(),
+            &vec![TagMark::new(14, 26, "highlight")]
+        );
+    }
+}
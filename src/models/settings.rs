@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(glib::ValueDelegate, Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(glib::ValueDelegate, Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[value_delegate(from = u8)]
 #[repr(u8)]
 pub enum TextMetricSize {
+    #[default]
     Narrow,
     Medium,
     Wide,
@@ -63,12 +64,6 @@ impl From<f64> for TextMetricSize {
     }
 }
 
-impl Default for TextMetricSize {
-    fn default() -> Self {
-        Self::Narrow
-    }
-}
-
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentSettings {
     line_height: TextMetricSize,
@@ -0,0 +1,501 @@
+use super::{
+    query::point_of_view_name, Chapter, ChunkType, Document, DocumentChunk, LoreEntry, Scene,
+};
+use crate::services::i18n::i18n;
+
+/// When `chunk` happens in the story, for chapters, scenes and lore entries
+pub fn story_time_of(chunk: &dyn DocumentChunk) -> Option<&String> {
+    let any = chunk.as_any();
+    let story_time = if let Some(scene) = any.downcast_ref::<Scene>() {
+        scene.story_time()
+    } else if let Some(chapter) = any.downcast_ref::<Chapter>() {
+        chapter.story_time()
+    } else {
+        any.downcast_ref::<LoreEntry>().and_then(|e| e.story_time())
+    };
+    story_time.filter(|t| !t.trim().is_empty())
+}
+
+/// Sort key of an in-story time, made of the numbers it contains in order. Times
+/// like "1815-12-10 08:30", "Day 3, 08:30" or "Year 12, day 4" sort as expected as
+/// long as they name larger units first. A leading minus makes the first number
+/// negative, for years before an era
+pub fn story_time_key(text: &str) -> Option<Vec<i64>> {
+    let text = text.trim();
+    let mut numbers = vec![];
+    let mut current: Option<i64> = None;
+    for character in text.chars() {
+        if let Some(digit) = character.to_digit(10) {
+            current = Some(
+                current
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit as i64),
+            );
+        } else if let Some(number) = current.take() {
+            numbers.push(number);
+        }
+    }
+    numbers.extend(current);
+
+    if text.starts_with('-') {
+        if let Some(first) = numbers.first_mut() {
+            *first = -*first;
+        }
+    }
+    (!numbers.is_empty()).then_some(numbers)
+}
+
+/// How the items of a timeline are split into lanes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimelineLanes {
+    /// One lane per viewpoint character of the scenes
+    #[default]
+    PointOfView,
+    /// One lane per label, labels standing for plot threads
+    PlotThread,
+}
+
+/// A chapter, scene or lore entry placed on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineItem {
+    chunk_id: String,
+    title: String,
+    story_time: String,
+    key: Vec<i64>,
+    reading_position: Option<usize>,
+    column: usize,
+    flashback: bool,
+}
+
+impl TimelineItem {
+    pub fn chunk_id(&self) -> &str {
+        self.chunk_id.as_str()
+    }
+
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    pub fn story_time(&self) -> &str {
+        self.story_time.as_str()
+    }
+
+    /// Where the item is read in the manuscript, counting dated chapters and
+    /// scenes from 0. Lore entries are not part of the reading order
+    pub fn reading_position(&self) -> Option<usize> {
+        self.reading_position
+    }
+
+    /// Index in `Timeline::columns` of the time the item happens at
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Whether the item happens before the one read right before it
+    pub fn is_flashback(&self) -> bool {
+        self.flashback
+    }
+
+    /// Whether the item is a standalone event rather than part of the manuscript
+    pub fn is_event(&self) -> bool {
+        self.reading_position.is_none()
+    }
+}
+
+/// A row of the timeline, following one character or plot thread
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineLane {
+    title: String,
+    color: Option<String>,
+    items: Vec<usize>,
+}
+
+impl TimelineLane {
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    /// Color of the character or label followed by the lane, as CSS
+    pub fn color(&self) -> Option<&str> {
+        self.color.as_deref()
+    }
+
+    /// Indexes in `Timeline::items` of the items in this lane, in chronological order
+    pub fn items(&self) -> &Vec<usize> {
+        &self.items
+    }
+}
+
+/// The dated chapters, scenes and lore entries of a document in chronological
+/// order, split into lanes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timeline {
+    columns: Vec<String>,
+    items: Vec<TimelineItem>,
+    lanes: Vec<TimelineLane>,
+    undated: usize,
+}
+
+impl Timeline {
+    pub fn new(document: &Document, lanes: TimelineLanes) -> Self {
+        let mut timeline = Self::default();
+        let mut pending: Vec<(TimelineItem, Vec<String>)> = vec![];
+
+        let item = |chunk: &dyn DocumentChunk, reading_position: Option<usize>| {
+            let story_time = story_time_of(chunk)?;
+            Some(TimelineItem {
+                chunk_id: chunk.id().to_string(),
+                title: chunk.safe_title(),
+                story_time: story_time.trim().to_string(),
+                key: story_time_key(story_time)?,
+                reading_position,
+                column: 0,
+                flashback: false,
+            })
+        };
+
+        let mut previous_key: Option<Vec<i64>> = None;
+        for (chunk, _) in document
+            .outline()
+            .into_iter()
+            .filter(|(c, _)| matches!(c.chunk_type(), ChunkType::Chapter | ChunkType::Scene))
+        {
+            let Some(mut item) = item(chunk, Some(pending.len())) else {
+                timeline.undated += 1;
+                continue;
+            };
+            item.flashback = previous_key.as_ref().is_some_and(|key| item.key < *key);
+            previous_key = Some(item.key.clone());
+            pending.push((item, Self::lane_ids(document, chunk, lanes)));
+        }
+
+        for chunk in document.chunks_by_type_ref(ChunkType::LoreEntry) {
+            if let Some(item) = item(chunk, None) {
+                pending.push((item, vec![]));
+            }
+        }
+
+        pending.sort_by(|(a, _), (b, _)| {
+            a.key
+                .cmp(&b.key)
+                .then(
+                    a.reading_position
+                        .is_some()
+                        .cmp(&b.reading_position.is_some()),
+                )
+                .then(a.reading_position.cmp(&b.reading_position))
+                .then(a.title.cmp(&b.title))
+        });
+
+        // Items happening at the same time share a column
+        let mut lane_ids: Vec<Vec<String>> = vec![];
+        for (mut item, ids) in pending.into_iter() {
+            let same_time = timeline
+                .items
+                .last()
+                .is_some_and(|last| last.key == item.key);
+            if !same_time {
+                timeline.columns.push(item.story_time.clone());
+            }
+            item.column = timeline.columns.len() - 1;
+            timeline.items.push(item);
+            lane_ids.push(ids);
+        }
+
+        timeline.fill_lanes(document, lanes, &lane_ids);
+        timeline
+    }
+
+    /// The in-story time of each column, from the earliest
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    /// The items of the timeline, in chronological order
+    pub fn items(&self) -> &Vec<TimelineItem> {
+        &self.items
+    }
+
+    pub fn lanes(&self) -> &Vec<TimelineLane> {
+        &self.lanes
+    }
+
+    /// How many chapters and scenes have no story time the timeline could place
+    pub fn undated(&self) -> usize {
+        self.undated
+    }
+
+    /// Ids of the characters or labels whose lanes `chunk` belongs to
+    fn lane_ids(
+        document: &Document,
+        chunk: &dyn DocumentChunk,
+        lanes: TimelineLanes,
+    ) -> Vec<String> {
+        match lanes {
+            TimelineLanes::PointOfView => chunk
+                .as_any()
+                .downcast_ref::<Scene>()
+                .filter(|scene| point_of_view_name(document, scene).is_some())
+                .and_then(|scene| scene.point_of_view())
+                .cloned()
+                .into_iter()
+                .collect(),
+            TimelineLanes::PlotThread => chunk
+                .as_buffer_chunk()
+                .map(|c| c.labels().clone())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|id| document.manifest().label(id).is_some())
+                .collect(),
+        }
+    }
+
+    fn fill_lanes(&mut self, document: &Document, lanes: TimelineLanes, lane_ids: &[Vec<String>]) {
+        let items_of = |filter: &dyn Fn(usize) -> bool| -> Vec<usize> {
+            (0..self.items.len())
+                .filter(|index| filter(*index))
+                .collect()
+        };
+
+        let events = items_of(&|index| self.items[index].is_event());
+        let mut named: Vec<TimelineLane> = match lanes {
+            TimelineLanes::PointOfView => {
+                let mut characters = document.chunks_by_type_ref(ChunkType::CharacterSheet);
+                characters.sort_by_key(|c| c.safe_title());
+                characters
+                    .into_iter()
+                    .map(|character| TimelineLane {
+                        title: character.safe_title(),
+                        color: character.accent().map(|c| c.to_string()),
+                        items: items_of(&|index| {
+                            lane_ids[index].iter().any(|id| id == character.id())
+                        }),
+                    })
+                    .collect()
+            }
+            TimelineLanes::PlotThread => document
+                .manifest()
+                .labels()
+                .iter()
+                .map(|label| TimelineLane {
+                    title: label.name().to_string(),
+                    color: Some(label.color().to_string()),
+                    items: items_of(&|index| lane_ids[index].iter().any(|id| id == label.id())),
+                })
+                .collect(),
+        };
+        let others = items_of(&|index| !self.items[index].is_event() && lane_ids[index].is_empty());
+
+        named.retain(|lane| !lane.items.is_empty());
+        if !events.is_empty() {
+            named.insert(
+                0,
+                TimelineLane {
+                    title: i18n("Events"),
+                    color: None,
+                    items: events,
+                },
+            );
+        }
+        if !others.is_empty() {
+            named.push(TimelineLane {
+                title: match lanes {
+                    TimelineLanes::PointOfView => i18n("No point of view"),
+                    TimelineLanes::PlotThread => i18n("No plot thread"),
+                },
+                color: None,
+                items: others,
+            });
+        }
+        self.lanes = named;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CharacterSheet, ChunkLabel, Color};
+
+    #[test]
+    fn story_time_keys_of_numbers_in_order() {
+        assert_eq!(story_time_key("Day 3, 08:30"), Some(vec![3, 8, 30]));
+        assert_eq!(
+            story_time_key("1815-12-10 08:30"),
+            Some(vec![1815, 12, 10, 8, 30])
+        );
+        assert!(story_time_key("Day 2") < story_time_key("Day 10"));
+    }
+
+    #[test]
+    fn story_time_keys_of_negative_years() {
+        assert_eq!(story_time_key("-44, March 15"), Some(vec![-44, 15]));
+        assert_eq!(story_time_key("  -44"), Some(vec![-44]));
+        // Only a leading minus makes a year negative
+        assert_eq!(story_time_key("Year -44"), Some(vec![44]));
+        assert!(story_time_key("-44") < story_time_key("12"));
+    }
+
+    #[test]
+    fn story_time_keys_without_digits() {
+        assert_eq!(story_time_key("Long ago"), None);
+        assert_eq!(story_time_key("-"), None);
+        assert_eq!(story_time_key("   "), None);
+    }
+
+    #[test]
+    fn story_time_keys_saturate() {
+        assert_eq!(
+            story_time_key("Year 99999999999999999999, day 2"),
+            Some(vec![i64::MAX, 2])
+        );
+        assert_eq!(
+            story_time_key("-99999999999999999999"),
+            Some(vec![-i64::MAX])
+        );
+    }
+
+    /// A chapter holding three scenes, the second one remembering an earlier
+    /// day and the third one undated, a dated epilogue and a lore entry
+    /// happening long before. Only the first scene has a viewpoint character.
+    /// Returns the ids of the chapter, of the scenes, of the epilogue and of
+    /// the lore entry
+    fn timeline_document() -> (Document, [String; 6]) {
+        let mut doc = Document::default();
+        let mut anna = CharacterSheet::default();
+        anna.set_name(Some("Anna Karenina".into()));
+        let mut moscow = Chapter::default();
+        moscow.set_priority(Some(1));
+        let mut ball = Scene::default();
+        ball.set_priority(Some(1));
+        ball.set_point_of_view(Some(anna.id().to_string()));
+        ball.set_story_time(Some("Winter 1874, day 3".into()));
+        let mut memory = Scene::default();
+        memory.set_priority(Some(2));
+        memory.set_story_time(Some("Winter 1874, day 1".into()));
+        let mut station = Scene::default();
+        station.set_priority(Some(3));
+        let mut epilogue = Chapter::default();
+        epilogue.set_priority(Some(2));
+        epilogue.set_story_time(Some("1876".into()));
+        let mut war = LoreEntry::default();
+        war.set_name(Some("Crimean War".into()));
+        war.set_story_time(Some("1853".into()));
+        let ids = [
+            moscow.id().to_string(),
+            ball.id().to_string(),
+            memory.id().to_string(),
+            station.id().to_string(),
+            epilogue.id().to_string(),
+            war.id().to_string(),
+        ];
+        doc.add_chunk(anna);
+        doc.add_chunk(moscow);
+        doc.add_chunk(ball);
+        doc.add_chunk(memory);
+        doc.add_chunk(station);
+        doc.add_chunk(epilogue);
+        doc.add_chunk(war);
+        for id in ids[1..4].iter() {
+            doc.set_parent(id, Some(&ids[0])).unwrap();
+        }
+        (doc, ids)
+    }
+
+    fn lanes(timeline: &Timeline) -> Vec<(&str, &Vec<usize>)> {
+        timeline
+            .lanes()
+            .iter()
+            .map(|l| (l.title(), l.items()))
+            .collect()
+    }
+
+    #[test]
+    fn columns_in_story_order() {
+        let (doc, _) = timeline_document();
+        let timeline = Timeline::new(&doc, TimelineLanes::PointOfView);
+        assert_eq!(
+            timeline.columns(),
+            &vec![
+                "1853".to_string(),
+                "Winter 1874, day 1".to_string(),
+                "Winter 1874, day 3".to_string(),
+                "1876".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn items_and_flashbacks() {
+        let (doc, [_, ball_id, memory_id, _, epilogue_id, war_id]) = timeline_document();
+        let timeline = Timeline::new(&doc, TimelineLanes::PointOfView);
+        let items: Vec<(&str, Option<usize>, bool)> = timeline
+            .items()
+            .iter()
+            .map(|i| (i.chunk_id(), i.reading_position(), i.is_flashback()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (war_id.as_str(), None, false),
+                (memory_id.as_str(), Some(1), true),
+                (ball_id.as_str(), Some(0), false),
+                (epilogue_id.as_str(), Some(2), false),
+            ]
+        );
+        assert!(timeline.items()[0].is_event());
+        // The chapter holding the scenes and the station scene have no story time
+        assert_eq!(timeline.undated(), 2);
+    }
+
+    #[test]
+    fn items_at_the_same_time_share_a_column() {
+        let (mut doc, [_, _, _, station_id, _, _]) = timeline_document();
+        doc.get_chunk_mut(&station_id)
+            .and_then(|c| c.as_any_mut().downcast_mut::<Scene>())
+            .unwrap()
+            .set_story_time(Some("winter 1874 day 3".into()));
+        let timeline = Timeline::new(&doc, TimelineLanes::PointOfView);
+        assert_eq!(timeline.columns().len(), 4);
+        let columns: Vec<usize> = timeline.items().iter().map(|i| i.column()).collect();
+        assert_eq!(columns, vec![0, 1, 2, 2, 3]);
+        assert_eq!(timeline.undated(), 1);
+    }
+
+    #[test]
+    fn point_of_view_lanes() {
+        let (doc, _) = timeline_document();
+        let timeline = Timeline::new(&doc, TimelineLanes::PointOfView);
+        assert_eq!(
+            lanes(&timeline),
+            vec![
+                ("Events", &vec![0]),
+                ("Anna Karenina", &vec![2]),
+                ("No point of view", &vec![1, 3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn plot_thread_lanes() {
+        let (mut doc, [_, ball_id, _, _, epilogue_id, _]) = timeline_document();
+        let label = ChunkLabel::new("Vronsky", Color::new(0.2, 0.4, 0.8, 1.0));
+        let label_id = label.id().to_string();
+        doc.manifest_mut().add_label(label);
+        for id in [&ball_id, &epilogue_id] {
+            doc.get_chunk_mut(id)
+                .and_then(|c| c.as_mutable_buffer_chunk())
+                .unwrap()
+                .set_labels(vec![label_id.clone(), "01MISSING".into()]);
+        }
+        let timeline = Timeline::new(&doc, TimelineLanes::PlotThread);
+        assert_eq!(
+            lanes(&timeline),
+            vec![
+                ("Events", &vec![0]),
+                ("Vronsky", &vec![2, 3]),
+                ("No plot thread", &vec![1]),
+            ]
+        );
+    }
+}
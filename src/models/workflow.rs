@@ -0,0 +1,188 @@
+use super::Color;
+use crate::services::i18n::i18n;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+/// A step of the writing workflow, like "First draft", that buffer chunks
+/// can be marked with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChunkStatus {
+    id: String,
+    name: String,
+    compile: bool,
+}
+
+impl ChunkStatus {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            name: name.into(),
+            compile: true,
+        }
+    }
+
+    /// The statuses new projects start with
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(i18n("To do").as_str()),
+            Self::new(i18n("First draft").as_str()),
+            Self::new(i18n("Revised").as_str()),
+            Self::new(i18n("Final").as_str()),
+        ]
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Whether chunks with this status are part of a compiled manuscript
+    pub fn compile(&self) -> bool {
+        self.compile
+    }
+
+    pub fn set_compile(&mut self, value: bool) {
+        self.compile = value;
+    }
+}
+
+/// A colored tag that buffer chunks can be marked with, any number at a time
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChunkLabel {
+    id: String,
+    name: String,
+    color: Color,
+    compile: bool,
+}
+
+impl ChunkLabel {
+    pub fn new(name: &str, color: Color) -> Self {
+        Self {
+            id: Ulid::new().into(),
+            name: name.into(),
+            color,
+            compile: true,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Whether chunks with this label are part of a compiled manuscript
+    pub fn compile(&self) -> bool {
+        self.compile
+    }
+
+    pub fn set_compile(&mut self, value: bool) {
+        self.compile = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Chapter, Document, DocumentChunk, MutableBufferChunk, Scene};
+
+    #[test]
+    fn default_statuses() {
+        let statuses = ChunkStatus::defaults();
+        assert_eq!(statuses.len(), 4);
+        assert!(statuses.iter().all(|s| s.compile()));
+        assert!(statuses[1..].iter().all(|s| s.id() != statuses[0].id()));
+    }
+
+    #[test]
+    fn new_labels_are_compiled() {
+        let mut label = ChunkLabel::new("Cut", Color::new(0.9, 0.1, 0.1, 1.0));
+        assert_eq!(label.name(), "Cut");
+        assert!(label.compile());
+        label.set_compile(false);
+        assert!(!label.compile());
+        assert_ne!(label.id(), ChunkLabel::new("Cut", label.color()).id());
+    }
+
+    #[test]
+    fn compile_order_skips_excluded_labels() {
+        let mut doc = Document::default();
+        let mut cut = ChunkLabel::new("Cut", Color::new(0.9, 0.1, 0.1, 1.0));
+        cut.set_compile(false);
+        let chapter = Chapter::default();
+        let kept = Scene::default();
+        let mut dropped = Scene::default();
+        dropped.set_labels(vec![cut.id().into()]);
+        let (chapter_id, kept_id, dropped_id) = (
+            chapter.id().to_string(),
+            kept.id().to_string(),
+            dropped.id().to_string(),
+        );
+        doc.manifest_mut().add_label(cut);
+        doc.add_chunk(chapter);
+        doc.add_chunk(kept);
+        doc.add_chunk(dropped);
+        doc.set_parent(&kept_id, Some(&chapter_id)).unwrap();
+        doc.set_parent(&dropped_id, Some(&chapter_id)).unwrap();
+
+        let compiled: Vec<&str> = doc.compile_order().iter().map(|c| c.id()).collect();
+        assert_eq!(compiled, vec![chapter_id.as_str(), kept_id.as_str()]);
+    }
+
+    #[test]
+    fn excluded_status_skips_nested_chunks() {
+        let mut doc = Document::default();
+        let draft = doc.manifest().statuses()[1].id().to_string();
+        let mut chapter = Chapter::default();
+        chapter.set_status(Some(draft.clone()));
+        let scene = Scene::default();
+        let (chapter_id, scene_id) = (chapter.id().to_string(), scene.id().to_string());
+        doc.add_chunk(chapter);
+        doc.add_chunk(scene);
+        doc.set_parent(&scene_id, Some(&chapter_id)).unwrap();
+        assert_eq!(doc.compile_order().len(), 2);
+
+        doc.manifest_mut()
+            .status_mut(&draft)
+            .unwrap()
+            .set_compile(false);
+        assert!(doc.compile_order().is_empty());
+    }
+
+    #[test]
+    fn removing_statuses_and_labels() {
+        let mut doc = Document::default();
+        let draft = doc.manifest().statuses()[1].id().to_string();
+        let mut cut = ChunkLabel::new("Cut", Color::new(0.9, 0.1, 0.1, 1.0));
+        cut.set_compile(false);
+        let cut_id = cut.id().to_string();
+        let mut chapter = Chapter::default();
+        chapter.set_status(Some(draft.clone()));
+        chapter.set_labels(vec![cut_id.clone()]);
+        let chapter_id = chapter.id().to_string();
+        doc.manifest_mut().add_label(cut);
+        doc.add_chunk(chapter);
+        assert!(doc.compile_order().is_empty());
+
+        assert!(doc.remove_label(&cut_id).is_some());
+        assert!(doc.remove_status(&draft).is_some());
+        assert!(doc.remove_label(&cut_id).is_none());
+        assert_eq!(doc.manifest().statuses().len(), 3);
+        let chapter = doc
+            .get_chunk_ref(&chapter_id)
+            .and_then(|c| c.as_buffer_chunk())
+            .unwrap();
+        assert_eq!(chapter.status(), None);
+        assert!(chapter.labels().is_empty());
+        assert_eq!(doc.compile_order().len(), 1);
+    }
+}
@@ -1,5 +1,8 @@
 use super::prelude::*;
-use crate::libs::{consts::*, text_metrics::*};
+use crate::{
+    libs::{consts::*, text_metrics::*},
+    models::WIKI_LINK_PATTERN,
+};
 use gtk::prelude::{TextBufferExt, TextBufferExtManual, TextViewExt};
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -38,6 +41,53 @@ impl MarkupHandler for gtk::TextView {
     }
 }
 
+/// Finds the mentions of a character in some text, by any of its names
+/// as whole words. Names are case sensitive, so that an alias like "Will"
+/// does not match the verb
+#[derive(Debug, Clone)]
+pub struct MentionMatcher {
+    regex: Option<Regex>,
+}
+
+impl MentionMatcher {
+    pub fn new(names: &[String]) -> Self {
+        let mut names: Vec<&String> = names.iter().filter(|n| !n.trim().is_empty()).collect();
+        // Longer names first, so that "Ada Lovelace" is one mention and not two
+        names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+        let alternatives: Vec<String> = names.iter().map(|n| regex::escape(n.trim())).collect();
+        let regex = (!alternatives.is_empty())
+            .then(|| Regex::new(format!(r"\b(?:{})\b", alternatives.join("|")).as_str()).ok())
+            .flatten();
+
+        Self { regex }
+    }
+
+    /// Counts the mentions in `text`
+    pub fn count(&self, text: &str) -> u64 {
+        self.regex
+            .as_ref()
+            .map_or(0, |regex| regex.find_iter(text).count() as u64)
+    }
+
+    /// Character offsets of the start and end of each mention in `text`
+    pub fn ranges(&self, text: &str) -> Vec<(i32, i32)> {
+        let Some(regex) = self.regex.as_ref() else {
+            return vec![];
+        };
+
+        let mut ranges = vec![];
+        let (mut byte_offset, mut char_offset) = (0, 0);
+        for matched in regex.find_iter(text) {
+            char_offset += text[byte_offset..matched.start()].chars().count() as i32;
+            let len = matched.as_str().chars().count() as i32;
+            ranges.push((char_offset, char_offset + len));
+            char_offset += len;
+            byte_offset = matched.end();
+        }
+        ranges
+    }
+}
+
 pub struct TextAnalyzer {
     markup_regex: RegexRuleCollection,
 }
@@ -100,7 +150,7 @@ impl<'a> RegexMatch<'a> {
         self.regex
     }
 
-    pub fn matched(&self) -> &regex::Match {
+    pub fn matched(&self) -> &regex::Match<'_> {
         &self.re_match
     }
 
@@ -300,6 +350,19 @@ impl RegexRuleCollection {
             },
         );
 
+        Self::create_regex(
+            &mut regexes,
+            "WIKI_LINK",
+            WIKI_LINK_PATTERN,
+            |matched: &RegexMatch, _view: &gtk::TextView| {
+                TagApplyRules::new(vec![TagLookup::ByName(
+                    TAG_NAME_LINK_COLOR_TEXT,
+                    matched.start(),
+                    matched.end(),
+                )])
+            },
+        );
+
         Self::create_regex(
             &mut regexes,
             "HEADER_UNDER",
@@ -524,3 +587,41 @@ impl RegexRule {
         (self.map_fn)(matched, view)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(names: &[&str]) -> MentionMatcher {
+        MentionMatcher::new(&names.iter().map(|n| n.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn character_mention_ranges() {
+        let matcher = matcher(&["Ada", "Ada Lovelace", "Élise"]);
+        assert_eq!(
+            matcher.ranges("Élise met Ada Lovelace, then Ada."),
+            vec![(0, 5), (10, 22), (29, 32)]
+        );
+    }
+
+    #[test]
+    fn mentions_are_whole_words_and_case_sensitive() {
+        let matcher = matcher(&["Will"]);
+        assert_eq!(matcher.count("Will will come, Willful or not. Will."), 2);
+        assert_eq!(matcher.ranges("Where is Will?"), vec![(9, 13)]);
+    }
+
+    #[test]
+    fn blank_names_match_nothing() {
+        let matcher = matcher(&["", "  "]);
+        assert_eq!(matcher.count("Anything at all"), 0);
+        assert!(matcher.ranges("Anything at all").is_empty());
+    }
+
+    #[test]
+    fn names_are_matched_literally() {
+        let matcher = matcher(&[" A.J "]);
+        assert_eq!(matcher.ranges("AxJ and A.J here"), vec![(8, 11)]);
+    }
+}
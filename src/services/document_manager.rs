@@ -1,6 +1,9 @@
-use crate::models::{
-    Chapter, CharacterSheet, Document, DocumentChunk, DocumentManifest, DocumentSettings,
-    ManuscriptError, ManuscriptResult, MutableBufferChunk,
+use crate::{
+    models::{
+        Chapter, CharacterSheet, Document, DocumentChunk, DocumentManifest, DocumentSettings,
+        EditSnapshot, ManuscriptError, ManuscriptResult, NameOccurrence,
+    },
+    services::i18n::i18n,
 };
 use adw::subclass::prelude::*;
 use bytes::Bytes;
@@ -54,6 +57,15 @@ pub enum DocumentAction {
     UpdateChunk(String),
     UpdateChunkWith(String, Box<ChunkUpdateFunc>),
     UpdateManifestWith(Box<ManifestUpdateFunc>),
+    RemoveCharacterField(String),
+    RemoveStatus(String),
+    RemoveLabel(String),
+    MoveChunkNextTo(String, String, bool),
+    ReorderChunk(String, i32),
+    SplitChapter(String, i32),
+    MergeChapterWithNext(String),
+    RenameOccurrences(Vec<NameOccurrence>, String),
+    UndoEdit,
 }
 
 impl std::fmt::Display for DocumentAction {
@@ -83,6 +95,31 @@ impl std::fmt::Display for DocumentAction {
             Self::UpdateManifestWith(_func) => {
                 write!(f, "DocumentAction::UpdateManifestWith(function)")
             }
+            Self::RemoveCharacterField(id) => {
+                write!(f, "DocumentAction::RemoveCharacterField(#{id})")
+            }
+            Self::RemoveStatus(id) => write!(f, "DocumentAction::RemoveStatus(#{id})"),
+            Self::RemoveLabel(id) => write!(f, "DocumentAction::RemoveLabel(#{id})"),
+            Self::MoveChunkNextTo(id, target, after) => write!(
+                f,
+                "DocumentAction::MoveChunkNextTo(#{id} - {} #{target})",
+                if *after { "after" } else { "before" }
+            ),
+            Self::ReorderChunk(id, offset) => {
+                write!(f, "DocumentAction::ReorderChunk(#{id} by {offset})")
+            }
+            Self::SplitChapter(id, offset) => {
+                write!(f, "DocumentAction::SplitChapter(#{id} at {offset})")
+            }
+            Self::MergeChapterWithNext(id) => {
+                write!(f, "DocumentAction::MergeChapterWithNext(#{id})")
+            }
+            Self::RenameOccurrences(occurrences, name) => write!(
+                f,
+                "DocumentAction::RenameOccurrences({} occurrences to {name})",
+                occurrences.len()
+            ),
+            Self::UndoEdit => write!(f, "DocumentAction::UndoEdit"),
         }
     }
 }
@@ -101,6 +138,7 @@ mod imp {
         pub(super) backend_file: RefCell<Option<gio::File>>,
         pub(super) rx: RefCell<Option<Receiver<DocumentAction>>>,
         pub(super) tx: Sender<DocumentAction>,
+        pub(super) undo_stack: RefCell<Vec<EditSnapshot>>,
     }
 
     impl Default for ManuscriptDocumentManager {
@@ -113,6 +151,7 @@ mod imp {
                 backend_file: RefCell::new(None),
                 rx: RefCell::new(Some(rx)),
                 tx,
+                undo_stack: RefCell::default(),
             }
         }
     }
@@ -138,9 +177,15 @@ mod imp {
                     Signal::builder("chunk-added")
                         .param_types([String::static_type()])
                         .build(),
+                    Signal::builder("chunk-imported")
+                        .param_types([String::static_type()])
+                        .build(),
                     Signal::builder("chunk-removed")
                         .param_types([String::static_type()])
                         .build(),
+                    Signal::builder("chunk-moved")
+                        .param_types([String::static_type()])
+                        .build(),
                     Signal::builder("chunk-selected")
                         .param_types([String::static_type()])
                         .build(),
@@ -148,6 +193,16 @@ mod imp {
                         .param_types([String::static_type()])
                         .build(),
                     Signal::builder("manifest-updated").build(),
+                    // The content of a chunk was replaced by something other
+                    // than its editor, like splitting a chapter
+                    Signal::builder("chunk-reloaded")
+                        .param_types([String::static_type()])
+                        .build(),
+                    // An edit that can be undone with `DocumentAction::UndoEdit`
+                    // was applied, described by the given message
+                    Signal::builder("edit-applied")
+                        .param_types([String::static_type()])
+                        .build(),
                     Signal::builder("chunk-stats-updated")
                         .param_types([
                             String::static_type(),
@@ -232,12 +287,13 @@ impl DocumentManager {
                 if let Ok(mut lock) = self.imp().document.write() {
                     if let Some(document) = lock.as_mut() {
                         if let Some(chunk) = document.get_chunk_mut(id.as_str()) {
-                            let as_any = chunk.as_any_mut();
-                            if let Some(mbc) = as_any.downcast_mut::<Chapter>() {
+                            if let Some(mbc) = chunk.as_mutable_buffer_chunk() {
                                 mbc.set_buffer(bytes);
+                                mbc.touch();
                                 self.set_sync(false);
+                                self.forget_edits_of(id.as_str());
                             } else {
-                                glib::g_warning!(G_LOG_DOMAIN, "An UpdateChunkBuffer was requested on {:?}, but it doesnt implement MutableBufferChunk", as_any);
+                                glib::g_warning!(G_LOG_DOMAIN, "An UpdateChunkBuffer was requested on {}#{}, but it doesnt implement MutableBufferChunk", chunk.chunk_type(), chunk.id());
                             }
                         } else {
                             glib::g_warning!(G_LOG_DOMAIN, "An UpdateChunkBuffer was requested on chunk with id {id}, but it was not found");
@@ -265,6 +321,7 @@ impl DocumentManager {
                         if let Some(chunk) = document.get_chunk_mut(id.as_str()) {
                             func(chunk);
                             self.set_sync(false);
+                            self.forget_edits_of(id.as_str());
                             drop(lock);
                             self.emit_by_name::<()>("chunk-updated", &[&id]);
                         }
@@ -281,6 +338,197 @@ impl DocumentManager {
                     }
                 }
             }
+            DocumentAction::RemoveCharacterField(id) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        if document.remove_character_field(id.as_str()).is_some() {
+                            self.set_sync(false);
+                            self.forget_edits();
+                            drop(lock);
+                            self.emit_by_name::<()>("manifest-updated", &[]);
+                        }
+                    }
+                }
+            }
+            DocumentAction::RemoveStatus(id) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        if document.remove_status(id.as_str()).is_some() {
+                            self.set_sync(false);
+                            self.forget_edits();
+                            drop(lock);
+                            self.emit_by_name::<()>("manifest-updated", &[]);
+                        }
+                    }
+                }
+            }
+            DocumentAction::RemoveLabel(id) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        if document.remove_label(id.as_str()).is_some() {
+                            self.set_sync(false);
+                            self.forget_edits();
+                            drop(lock);
+                            self.emit_by_name::<()>("manifest-updated", &[]);
+                        }
+                    }
+                }
+            }
+            DocumentAction::MoveChunkNextTo(id, target, after) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        match document.move_chunk_next_to(id.as_str(), target.as_str(), after) {
+                            Ok(()) => {
+                                self.set_sync(false);
+                                self.forget_edits();
+                                drop(lock);
+                                self.emit_by_name::<()>("chunk-moved", &[&id]);
+                            }
+                            Err(error) => glib::g_warning!(
+                                G_LOG_DOMAIN,
+                                "Could not move chunk {id} next to {target} - {:?}",
+                                error
+                            ),
+                        }
+                    }
+                }
+            }
+            DocumentAction::ReorderChunk(id, offset) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        match document.move_chunk_by(id.as_str(), offset) {
+                            Ok(()) => {
+                                self.set_sync(false);
+                                self.forget_edits();
+                                drop(lock);
+                                self.emit_by_name::<()>("chunk-moved", &[&id]);
+                            }
+                            Err(error) => glib::g_warning!(
+                                G_LOG_DOMAIN,
+                                "Could not move chunk {id} by {offset} - {:?}",
+                                error
+                            ),
+                        }
+                    }
+                }
+            }
+            DocumentAction::SplitChapter(id, offset) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        match document.split_chapter(id.as_str(), offset) {
+                            Ok(snapshot) => {
+                                let created = snapshot.created().clone();
+                                self.imp().undo_stack.borrow_mut().push(snapshot);
+                                self.set_sync(false);
+                                drop(lock);
+                                self.emit_by_name::<()>("chunk-reloaded", &[&id]);
+                                for created_id in created.iter() {
+                                    self.emit_by_name::<()>("chunk-added", &[created_id]);
+                                }
+                                self.emit_by_name::<()>("edit-applied", &[&i18n("Chapter split")]);
+                            }
+                            Err(error) => glib::g_warning!(
+                                G_LOG_DOMAIN,
+                                "Could not split chapter {id} at {offset} - {:?}",
+                                error
+                            ),
+                        }
+                    }
+                }
+            }
+            DocumentAction::MergeChapterWithNext(id) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        match document.merge_chapter_with_next(id.as_str()) {
+                            Ok(snapshot) => {
+                                let removed: Vec<String> = snapshot
+                                    .saved_ids()
+                                    .into_iter()
+                                    .filter(|saved| document.get_chunk_ref(saved).is_none())
+                                    .collect();
+                                self.imp().undo_stack.borrow_mut().push(snapshot);
+                                self.set_sync(false);
+                                drop(lock);
+                                for removed_id in removed.iter() {
+                                    self.emit_by_name::<()>("chunk-removed", &[removed_id]);
+                                }
+                                self.emit_by_name::<()>("chunk-reloaded", &[&id]);
+                                self.emit_by_name::<()>(
+                                    "edit-applied",
+                                    &[&i18n("Chapters merged")],
+                                );
+                            }
+                            Err(error) => glib::g_warning!(
+                                G_LOG_DOMAIN,
+                                "Could not merge chapter {id} with the next one - {:?}",
+                                error
+                            ),
+                        }
+                    }
+                }
+            }
+            DocumentAction::RenameOccurrences(occurrences, name) => {
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        match document.rename_occurrences(&occurrences, name.as_str()) {
+                            Ok(snapshot) => {
+                                let renamed = snapshot.saved_ids();
+                                for id in renamed.iter() {
+                                    self.forget_edits_of(id);
+                                }
+                                self.imp().undo_stack.borrow_mut().push(snapshot);
+                                self.set_sync(false);
+                                drop(lock);
+                                for id in renamed.iter() {
+                                    self.emit_by_name::<()>("chunk-reloaded", &[id]);
+                                }
+                                self.emit_by_name::<()>(
+                                    "edit-applied",
+                                    &[&i18n("Renamed {count} occurrences").replace(
+                                        "{count}",
+                                        occurrences.len().to_string().as_str(),
+                                    )],
+                                );
+                            }
+                            Err(error) => glib::g_warning!(
+                                G_LOG_DOMAIN,
+                                "Could not rename {} occurrences to {name} - {:?}",
+                                occurrences.len(),
+                                error
+                            ),
+                        }
+                    }
+                }
+            }
+            DocumentAction::UndoEdit => {
+                let Some(snapshot) = self.imp().undo_stack.borrow_mut().pop() else {
+                    return;
+                };
+                if let Ok(mut lock) = self.imp().document.write() {
+                    if let Some(document) = lock.as_mut() {
+                        let created = snapshot.created().clone();
+                        let (replaced, restored): (Vec<String>, Vec<String>) = snapshot
+                            .saved_ids()
+                            .into_iter()
+                            .partition(|saved| document.get_chunk_ref(saved).is_some());
+                        document.restore(snapshot);
+                        self.set_sync(false);
+                        drop(lock);
+                        for id in created.iter() {
+                            self.emit_by_name::<()>("chunk-removed", &[id]);
+                        }
+                        for id in restored.iter() {
+                            self.emit_by_name::<()>("chunk-imported", &[id]);
+                        }
+                        for id in replaced.iter() {
+                            self.emit_by_name::<()>("chunk-reloaded", &[id]);
+                        }
+                        if let Some(id) = replaced.first() {
+                            self.emit_by_name::<()>("chunk-moved", &[id]);
+                        }
+                    }
+                }
+            }
         }
     }
 
@@ -366,6 +614,7 @@ impl DocumentManager {
             if lock.is_some() {
                 *lock = None;
                 drop(lock);
+                self.imp().undo_stack.borrow_mut().clear();
                 *self.backend_file_mut() = None;
                 self.set_sync(true);
                 self.emit_by_name::<()>("document-unloaded", &[]);
@@ -388,6 +637,34 @@ impl DocumentManager {
         }
     }
 
+    /// Copies the chunks identified by `ids` from `source` into the current
+    /// document. Unlike `add_chunk`, this emits `chunk-imported` for each copy
+    pub fn merge_document(&self, source: &Document, ids: &[String]) -> ManuscriptResult<usize> {
+        let merged = self.with_document_mut(|document| Ok(document.merge(source, ids)))?;
+        self.forget_edits();
+        for id in merged.iter() {
+            self.emit_by_name::<()>("chunk-imported", &[id]);
+        }
+        Ok(merged.len())
+    }
+
+    /// Nests the chunk identified by `id` under `parent`, or moves it
+    /// to the top level of the outline when `parent` is `None`
+    pub fn set_chunk_parent(&self, id: &str, parent: Option<&str>) -> ManuscriptResult<()> {
+        self.with_document_mut(|document| document.set_parent(id, parent))?;
+        self.forget_edits();
+        self.emit_by_name::<()>("chunk-moved", &[&id.to_string()]);
+        Ok(())
+    }
+
+    /// Places the location identified by `id` inside the location `parent`
+    pub fn set_parent_location(&self, id: &str, parent: Option<&str>) -> ManuscriptResult<()> {
+        self.with_document_mut(|document| document.set_parent_location(id, parent))?;
+        self.forget_edits_of(id);
+        self.emit_by_name::<()>("chunk-updated", &[&id.to_string()]);
+        Ok(())
+    }
+
     pub fn remove_chunk(&self, id: &String) -> Option<Box<dyn DocumentChunk>> {
         if let Ok(removed) = self.with_document_mut(move |document| {
             if let Some(removed) = document.remove_chunk(id) {
@@ -397,6 +674,7 @@ impl DocumentManager {
             }
         }) {
             self.set_sync(false);
+            self.forget_edits_of(id);
             self.emit_by_name::<()>("chunk-removed", &[id]);
             Some(removed)
         } else {
@@ -404,11 +682,26 @@ impl DocumentManager {
         }
     }
 
-    pub fn document_ref(&self) -> LockResult<std::sync::RwLockReadGuard<Option<Document>>> {
+    /// Drops the edits that involve the chunk `id`, as undoing them would
+    /// now discard its newer changes
+    fn forget_edits_of(&self, id: &str) {
+        self.imp()
+            .undo_stack
+            .borrow_mut()
+            .retain(|snapshot| !snapshot.involves(id));
+    }
+
+    /// Drops every edit, for changes spreading over chunks no edit saved,
+    /// like priorities and parents
+    fn forget_edits(&self) {
+        self.imp().undo_stack.borrow_mut().clear();
+    }
+
+    pub fn document_ref(&self) -> LockResult<std::sync::RwLockReadGuard<'_, Option<Document>>> {
         self.imp().document.read()
     }
 
-    pub fn document_mut(&self) -> LockResult<std::sync::RwLockWriteGuard<Option<Document>>> {
+    pub fn document_mut(&self) -> LockResult<std::sync::RwLockWriteGuard<'_, Option<Document>>> {
         self.imp().document.write()
     }
 
@@ -446,6 +739,19 @@ impl DocumentManager {
         }
     }
 
+    /// Loads a document built by an importer. The document has no backend yet,
+    /// so it is flagged as unsynced until the user saves it somewhere
+    pub fn import_document(&self, document: Document) -> ManuscriptResult<()> {
+        if self.unload_document().is_ok() {
+            self.set_document(document)?;
+            self.unset_backend_path();
+            self.set_sync(false);
+            Ok(())
+        } else {
+            Err(ManuscriptError::Reason("Could not unload document"))
+        }
+    }
+
     pub fn sync(&self) -> ManuscriptResult<usize> {
         if let Some(backend_file) = self.backend_file().as_ref() {
             self.with_document(move |document| {
@@ -468,11 +774,11 @@ impl DocumentManager {
         }
     }
 
-    pub fn backend_file(&self) -> std::cell::Ref<Option<gio::File>> {
+    pub fn backend_file(&self) -> std::cell::Ref<'_, Option<gio::File>> {
         self.imp().backend_file.borrow()
     }
 
-    fn backend_file_mut(&self) -> std::cell::RefMut<Option<gio::File>> {
+    fn backend_file_mut(&self) -> std::cell::RefMut<'_, Option<gio::File>> {
         self.imp().backend_file.borrow_mut()
     }
 
@@ -504,3 +810,52 @@ impl DocumentManager {
         self.imp().sync.store(value, Ordering::Release);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BufferChunk, MutableBufferChunk};
+
+    #[test]
+    fn edits_after_a_split_are_not_undone() {
+        let manager = DocumentManager::new();
+        let mut document = Document::default();
+        let mut chapter = Chapter::default();
+        chapter.set_buffer(Bytes::from("Before the storm. After the storm."));
+        let id = chapter.id().to_string();
+        document.add_chunk(chapter);
+        manager.import_document(document).unwrap();
+
+        manager.process_action(DocumentAction::SplitChapter(id.clone(), 18));
+        manager.process_action(DocumentAction::UpdateChunkWith(
+            id.clone(),
+            Box::new(|chunk| {
+                if let Some(chapter) = chunk.as_any_mut().downcast_mut::<Chapter>() {
+                    chapter.set_title("Storm");
+                    chapter.add_note(0, 6, "Too short?".into());
+                }
+            }),
+        ));
+        manager.process_action(DocumentAction::UndoEdit);
+
+        let (title, text, notes, chapters) = manager
+            .with_document(|document| {
+                let chapter = document
+                    .get_chunk_ref(&id)
+                    .and_then(|c| c.as_any().downcast_ref::<Chapter>())
+                    .unwrap();
+                Ok((
+                    chapter.title().cloned(),
+                    chapter.buffer().clone(),
+                    chapter.notes().len(),
+                    document.children_of(None).len(),
+                ))
+            })
+            .unwrap();
+        assert_eq!(title.as_deref(), Some("Storm"));
+        assert_eq!(text, Bytes::from("Before the storm. "));
+        assert_eq!(notes, 1);
+        assert_eq!(chapters, 2);
+        assert!(manager.imp().undo_stack.borrow().is_empty());
+    }
+}
@@ -0,0 +1,627 @@
+use super::{
+    extension_of,
+    xml::{self, XmlElement, XmlNode},
+    DocumentImporter,
+};
+use crate::models::*;
+use std::{collections::HashMap, fs::File, io::Read, path::Path};
+
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// Imports EPUB 2 and 3 books, one chapter for each content document in the spine
+pub struct EpubImporter;
+
+impl DocumentImporter for EpubImporter {
+    fn accepts(&self, path: &Path) -> bool {
+        extension_of(path).as_deref() == Some("epub")
+    }
+
+    fn import(&self, path: &Path) -> ManuscriptResult<Document> {
+        let open_error = || ManuscriptError::Open(path.display().to_string());
+        let file = File::open(path).or(Err(open_error()))?;
+        let mut archive = zip::ZipArchive::new(file).or(Err(ManuscriptError::Import(
+            "Not a valid EPUB archive".into(),
+        )))?;
+
+        let mut read_entry = |name: &str| -> ManuscriptResult<String> {
+            let mut entry = archive
+                .by_name(name)
+                .or(Err(ManuscriptError::Import(format!(
+                    "Missing {name} in EPUB"
+                ))))?;
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .or(Err(ManuscriptError::Import(format!(
+                    "Unreadable {name} in EPUB"
+                ))))?;
+            Ok(content)
+        };
+
+        let container = xml::parse(read_entry(CONTAINER_PATH)?.as_str())?;
+        let package_path = container
+            .find("rootfile")
+            .and_then(|rootfile| rootfile.attribute("full-path"))
+            .map(String::from)
+            .ok_or(ManuscriptError::Import(
+                "Missing package document in EPUB".into(),
+            ))?;
+        let package = xml::parse(read_entry(package_path.as_str())?.as_str())?;
+
+        let mut document = Document::default();
+        if let Some(metadata) = package.child("metadata") {
+            document.set_title(metadata.child_text("title"));
+            if let Some(creator) = metadata.child_text("creator") {
+                document.manifest_mut().set_author(creator);
+            }
+            read_publication_metadata(metadata, document.manifest_mut().publication_mut());
+        }
+
+        let manifest: HashMap<&str, (&str, &str)> = package
+            .child("manifest")
+            .into_iter()
+            .flat_map(|m| m.children_named("item"))
+            .filter_map(|item| {
+                Some((
+                    item.attribute("id")?,
+                    (item.attribute("href")?, item.attribute("media-type")?),
+                ))
+            })
+            .collect();
+
+        let mut content_documents: Vec<(XmlElement, bool)> = vec![];
+        let spine = package
+            .child("spine")
+            .into_iter()
+            .flat_map(|s| s.children_named("itemref"));
+        for itemref in spine {
+            let linear = itemref.attribute("linear") != Some("no");
+            let href = itemref
+                .attribute("idref")
+                .and_then(|id| manifest.get(id))
+                .filter(|(_, media_type)| media_type.contains("html"))
+                .map(|(href, _)| resolve_href(package_path.as_str(), href));
+
+            if let Some(href) = href {
+                if let Ok(source) = read_entry(href.as_str()) {
+                    content_documents.push((xml::parse(source.as_str())?, linear));
+                }
+            }
+        }
+
+        // EPUB 3 flags the cover in the manifest, EPUB 2 points at it from the metadata
+        let cover_href = package
+            .child("manifest")
+            .into_iter()
+            .flat_map(|m| m.children_named("item"))
+            .find(|item| {
+                item.attribute("properties")
+                    .is_some_and(|p| p.split_whitespace().any(|p| p == "cover-image"))
+            })
+            .and_then(|item| item.attribute("href"))
+            .or_else(|| {
+                package
+                    .child("metadata")
+                    .into_iter()
+                    .flat_map(|m| m.children_named("meta"))
+                    .find(|meta| meta.attribute("name") == Some("cover"))
+                    .and_then(|meta| manifest.get(meta.attribute("content")?))
+                    .filter(|(_, media_type)| media_type.starts_with("image/"))
+                    .map(|(href, _)| *href)
+            });
+        if let Some(href) = cover_href {
+            let href = resolve_href(package_path.as_str(), href);
+            if let Ok(mut entry) = archive.by_name(href.as_str()) {
+                let mut content = vec![];
+                if entry.read_to_end(&mut content).is_ok() {
+                    document.manifest_mut().set_cover(Some(content.into()));
+                }
+            }
+        }
+
+        // Footnotes may live in a different document than the one referencing them
+        let mut footnotes = HashMap::new();
+        for (content_document, _) in content_documents.iter() {
+            collect_footnotes(content_document, &mut footnotes);
+        }
+
+        let mut priority = 0;
+        for (content_document, linear) in content_documents.iter() {
+            let body = match content_document.find("body") {
+                Some(body) => body,
+                None => continue,
+            };
+
+            let mut converter = XhtmlConverter::new(&footnotes);
+            let markdown = converter.convert(body);
+            if markdown.is_empty() {
+                continue;
+            }
+
+            let mut chapter = Chapter::new();
+            let title = converter.first_heading.clone().or(content_document
+                .child("head")
+                .and_then(|head| head.child_text("title")));
+            if let Some(title) = title {
+                chapter.set_title(title.as_str());
+            }
+            chapter.set_buffer(markdown.into());
+            chapter.set_priority(Some(priority));
+            let _ = chapter.set_include_in_compilation(*linear);
+            priority += 1;
+
+            document.add_chunk(chapter);
+        }
+
+        Ok(document)
+    }
+}
+
+/// Reads the Dublin Core elements of the package metadata, along with the
+/// EPUB 3 and Calibre ways of describing a series
+fn read_publication_metadata(metadata: &XmlElement, publication: &mut PublicationMetadata) {
+    let texts = |name: &str| -> Vec<String> {
+        metadata
+            .children_named(name)
+            .map(|e| e.text().trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+    // EPUB 3 refines elements through meta tags pointing at their id
+    let refinement = |element: &XmlElement, property: &str| -> Option<String> {
+        let id = format!("#{}", element.attribute("id")?);
+        metadata
+            .children_named("meta")
+            .find(|m| {
+                m.attribute("refines") == Some(id.as_str())
+                    && m.attribute("property") == Some(property)
+            })
+            .map(|m| m.text().trim().to_string())
+    };
+    let calibre = |name: &str| -> Option<String> {
+        metadata
+            .children_named("meta")
+            .find(|m| m.attribute("name") == Some(name))
+            .and_then(|m| m.attribute("content"))
+            .map(String::from)
+    };
+
+    publication.set_subtitle(
+        metadata
+            .children_named("title")
+            .find(|t| refinement(t, "title-type").as_deref() == Some("subtitle"))
+            .map(|t| t.text().trim().to_string()),
+    );
+    publication.set_language(metadata.child_text("language"));
+    publication.set_publisher(metadata.child_text("publisher"));
+    publication.set_rights(metadata.child_text("rights"));
+    publication.set_keywords(texts("subject"));
+    publication.set_description(metadata.child_text("description").unwrap_or_default());
+    publication.set_publication_date(
+        metadata
+            .child_text("date")
+            .and_then(|date| parse_date(date.get(..10).unwrap_or(date.as_str()))),
+    );
+    publication.set_isbns(
+        texts("identifier")
+            .iter()
+            .map(|id| id.trim_start_matches("urn:isbn:").to_string())
+            .filter(|id| is_valid_isbn(id))
+            .collect(),
+    );
+
+    let collection = metadata.children_named("meta").find(|m| {
+        m.attribute("property") == Some("belongs-to-collection")
+            && refinement(m, "collection-type").as_deref() != Some("set")
+    });
+    match collection {
+        Some(collection) => {
+            publication.set_series(Some(collection.text().trim().to_string()));
+            publication.set_series_number(
+                refinement(collection, "group-position").and_then(|n| series_number(&n)),
+            );
+        }
+        None => {
+            publication.set_series(calibre("calibre:series"));
+            publication
+                .set_series_number(calibre("calibre:series_index").and_then(|n| series_number(&n)));
+        }
+    }
+}
+
+/// Series positions may be fractional, like 1.5 for a novella set between
+/// two books
+fn series_number(text: &str) -> Option<u32> {
+    text.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| *n >= 1.0)
+        .map(|n| n as u32)
+}
+
+/// Resolves `href` relative to the archive entry `base`
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or_default();
+    let mut parts: Vec<&str> = base.split('/').collect();
+    parts.pop();
+    for part in href.split('/') {
+        match part {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => (),
+            _ => parts.push(part),
+        }
+    }
+    percent_decode(parts.join("/").as_str())
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).unwrap_or_default();
+        if bytes[i] == b'%' && hex.len() == 2 && hex.iter().all(u8::is_ascii_hexdigit) {
+            // Both digits are ASCII, so they always make a valid hex byte
+            let hex = std::str::from_utf8(hex).unwrap_or_default();
+            decoded.push(u8::from_str_radix(hex, 16).unwrap_or_default());
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(decoded.as_slice()).to_string()
+}
+
+fn is_footnote(element: &XmlElement) -> bool {
+    let semantics = [element.attribute("type"), element.attribute("role")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    semantics.contains("footnote")
+        || semantics.contains("endnote")
+        || semantics.contains("rearnote")
+}
+
+fn is_backlink(element: &XmlElement) -> bool {
+    element.is("a")
+        && [element.attribute("type"), element.attribute("role")]
+            .into_iter()
+            .flatten()
+            .any(|s| s.contains("backlink"))
+}
+
+fn collect_footnotes(element: &XmlElement, footnotes: &mut HashMap<String, String>) {
+    for child in element.children() {
+        if is_footnote(child) && !child.is("a") {
+            if let Some(id) = child.attribute("id") {
+                let empty = HashMap::new();
+                let mut converter = XhtmlConverter::new(&empty);
+                footnotes.insert(
+                    id.to_string(),
+                    converter.convert(child).replace("\n\n", " "),
+                );
+            }
+        } else {
+            collect_footnotes(child, footnotes);
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut last_was_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !last_was_space {
+                collapsed.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            collapsed.push(c);
+            last_was_space = false;
+        }
+    }
+    collapsed
+}
+
+/// Wraps `inner` in emphasis `marker`s, leaving surrounding whitespace outside
+fn emphasize(inner: String, marker: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner;
+    }
+    let leading = &inner[..inner.len() - inner.trim_start().len()];
+    let trailing = &inner[inner.trim_end().len()..];
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+/// Converts an XHTML content document into markdown
+struct XhtmlConverter<'a> {
+    footnotes: &'a HashMap<String, String>,
+    referenced: Vec<String>,
+    first_heading: Option<String>,
+}
+
+impl<'a> XhtmlConverter<'a> {
+    fn new(footnotes: &'a HashMap<String, String>) -> Self {
+        Self {
+            footnotes,
+            referenced: vec![],
+            first_heading: None,
+        }
+    }
+
+    fn convert(&mut self, element: &XmlElement) -> String {
+        let mut blocks = self.blocks(element);
+        for (index, id) in self.referenced.iter().enumerate() {
+            if let Some(footnote) = self.footnotes.get(id) {
+                blocks.push(format!("[^{}]: {}", index + 1, footnote));
+            }
+        }
+        blocks.join("\n\n")
+    }
+
+    fn blocks(&mut self, element: &XmlElement) -> Vec<String> {
+        let mut blocks = vec![];
+        let mut inline = String::new();
+
+        let flush = |inline: &mut String, blocks: &mut Vec<String>| {
+            let paragraph = inline.trim().to_string();
+            if !paragraph.is_empty() {
+                blocks.push(paragraph);
+            }
+            inline.clear();
+        };
+
+        for node in element.nodes() {
+            let child = match node {
+                XmlNode::Text(text) => {
+                    inline.push_str(collapse_whitespace(text).as_str());
+                    continue;
+                }
+                XmlNode::Element(child) => child,
+            };
+
+            match child.name().to_lowercase().as_str() {
+                "script" | "style" | "head" => (),
+                _ if is_footnote(child) && !child.is("a") => (),
+                "p" => {
+                    flush(&mut inline, &mut blocks);
+                    inline.push_str(self.inline(child).as_str());
+                    flush(&mut inline, &mut blocks);
+                }
+                name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                    flush(&mut inline, &mut blocks);
+                    let level = name[1..].parse::<usize>().unwrap_or(1);
+                    let heading = self.inline(child).trim().to_string();
+                    if !heading.is_empty() {
+                        if self.first_heading.is_none() {
+                            self.first_heading = Some(collapse_whitespace(child.text().trim()));
+                        }
+                        blocks.push(format!("{} {heading}", "#".repeat(level)));
+                    }
+                }
+                "hr" => {
+                    flush(&mut inline, &mut blocks);
+                    blocks.push("* * *".into());
+                }
+                "blockquote" => {
+                    flush(&mut inline, &mut blocks);
+                    for block in self.blocks(child) {
+                        let quoted = block
+                            .lines()
+                            .map(|line| format!("> {line}"))
+                            .collect::<Vec<String>>()
+                            .join("\n");
+                        blocks.push(quoted);
+                    }
+                }
+                list @ ("ul" | "ol") => {
+                    flush(&mut inline, &mut blocks);
+                    let items = child
+                        .children_named("li")
+                        .enumerate()
+                        .map(|(index, item)| {
+                            let bullet = if list == "ol" {
+                                format!("{}.", index + 1)
+                            } else {
+                                "-".into()
+                            };
+                            format!("{bullet} {}", self.blocks(item).join(" "))
+                        })
+                        .collect::<Vec<String>>();
+                    if !items.is_empty() {
+                        blocks.push(items.join("\n"));
+                    }
+                }
+                "div" | "section" | "article" | "main" | "header" | "footer" | "aside"
+                | "figure" | "nav" | "table" | "tbody" | "tr" | "td" | "th" | "li" | "dl"
+                | "dt" | "dd" | "body" => {
+                    flush(&mut inline, &mut blocks);
+                    blocks.append(&mut self.blocks(child));
+                }
+                _ => inline.push_str(self.inline_element(child).as_str()),
+            }
+        }
+
+        flush(&mut inline, &mut blocks);
+        blocks
+    }
+
+    fn inline(&mut self, element: &XmlElement) -> String {
+        let mut text = String::new();
+        for node in element.nodes() {
+            match node {
+                XmlNode::Text(value) => text.push_str(collapse_whitespace(value).as_str()),
+                XmlNode::Element(child) => text.push_str(self.inline_element(child).as_str()),
+            }
+        }
+        text
+    }
+
+    fn inline_element(&mut self, element: &XmlElement) -> String {
+        if is_backlink(element) {
+            return String::new();
+        }
+
+        match element.name().to_lowercase().as_str() {
+            "em" | "i" | "cite" => emphasize(self.inline(element), "*"),
+            "strong" | "b" => emphasize(self.inline(element), "**"),
+            "s" | "del" | "strike" => emphasize(self.inline(element), "~~"),
+            "br" => "\n".into(),
+            "script" | "style" => String::new(),
+            "a" => {
+                let target = element
+                    .attribute("href")
+                    .and_then(|href| href.split_once('#'))
+                    .map(|(_, fragment)| fragment.to_string())
+                    .filter(|fragment| self.footnotes.contains_key(fragment));
+
+                match target {
+                    Some(id) => {
+                        let index = match self.referenced.iter().position(|r| r == &id) {
+                            Some(index) => index,
+                            None => {
+                                self.referenced.push(id);
+                                self.referenced.len() - 1
+                            }
+                        };
+                        format!("[^{}]", index + 1)
+                    }
+                    None => self.inline(element),
+                }
+            }
+            _ => self.inline(element),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(source: &str, footnotes: &HashMap<String, String>) -> (String, Option<String>) {
+        let body = xml::parse(source).unwrap();
+        let mut converter = XhtmlConverter::new(footnotes);
+        let markdown = converter.convert(&body);
+        (markdown, converter.first_heading)
+    }
+
+    #[test]
+    fn paragraphs_and_headings() {
+        let (markdown, heading) = convert(
+            "<body><h1>Chapter <em>One</em></h1>\n  <p>First\n   line</p><p>  </p>\
+             <hr/><div><p>Second</p>Loose text</div></body>",
+            &HashMap::new(),
+        );
+        assert_eq!(
+            markdown,
+            "# Chapter *One*\n\nFirst line\n\n* * *\n\nSecond\n\nLoose text"
+        );
+        assert_eq!(heading.as_deref(), Some("Chapter One"));
+    }
+
+    #[test]
+    fn emphasis() {
+        let (markdown, _) = convert(
+            "<body><p>Some <em>soft </em>and <b>bold</b>, <del>gone</del><i> </i>text</p></body>",
+            &HashMap::new(),
+        );
+        assert_eq!(markdown, "Some *soft* and **bold**, ~~gone~~ text");
+    }
+
+    #[test]
+    fn lists_and_quotes() {
+        let (markdown, _) = convert(
+            "<body><ol><li>One</li><li>Two</li></ol><blockquote><p>Quoted</p></blockquote></body>",
+            &HashMap::new(),
+        );
+        assert_eq!(markdown, "1. One\n2. Two\n\n> Quoted");
+    }
+
+    #[test]
+    fn footnotes() {
+        let source = "<html><body>\
+            <p>A claim<a epub:type=\"noteref\" href=\"notes.xhtml#n2\">2</a> and another\
+            <a epub:type=\"noteref\" href=\"#n1\">1</a>, then<a href=\"#n2\">2</a> again.</p>\
+            <aside epub:type=\"footnote\" id=\"n1\">\
+            <p><a epub:type=\"backlink\" href=\"#r1\">1</a> First <em>source</em></p></aside>\
+            <aside epub:type=\"footnote\" id=\"n2\"><p>Second</p><p>source</p></aside>\
+            </body></html>";
+        let document = xml::parse(source).unwrap();
+        let mut footnotes = HashMap::new();
+        collect_footnotes(&document, &mut footnotes);
+        assert_eq!(
+            footnotes.get("n1").map(String::as_str),
+            Some("First *source*")
+        );
+        assert_eq!(
+            footnotes.get("n2").map(String::as_str),
+            Some("Second source")
+        );
+
+        let body = document.find("body").unwrap();
+        let mut converter = XhtmlConverter::new(&footnotes);
+        assert_eq!(
+            converter.convert(body),
+            "A claim[^1] and another[^2], then[^1] again.\n\n\
+             [^1]: Second source\n\n[^2]: First *source*"
+        );
+    }
+
+    #[test]
+    fn unknown_links_keep_their_text() {
+        let (markdown, _) = convert(
+            "<body><p>See <a href=\"ch2.xhtml#missing\">chapter two</a></p></body>",
+            &HashMap::new(),
+        );
+        assert_eq!(markdown, "See chapter two");
+    }
+
+    #[test]
+    fn relative_hrefs() {
+        assert_eq!(
+            resolve_href("OEBPS/content.opf", "Text/ch1.xhtml"),
+            "OEBPS/Text/ch1.xhtml"
+        );
+        assert_eq!(resolve_href("content.opf", "ch1.xhtml#start"), "ch1.xhtml");
+        assert_eq!(
+            resolve_href("OEBPS/content.opf", "./Text/../ch2.xhtml"),
+            "OEBPS/ch2.xhtml"
+        );
+    }
+
+    #[test]
+    fn parent_hrefs() {
+        assert_eq!(
+            resolve_href("OEBPS/Text/ch1.xhtml", "../Images/cover%20art.jpg"),
+            "OEBPS/Images/cover art.jpg"
+        );
+        assert_eq!(
+            resolve_href("OEBPS/content.opf", "../../cover.jpg"),
+            "cover.jpg"
+        );
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(percent_decode("a%20b"), "a b");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("%e2%80%94"), "—");
+    }
+
+    #[test]
+    fn malformed_percent_sequences() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%2"), "%2");
+        assert_eq!(percent_decode("%zz%41"), "%zzA");
+        assert_eq!(percent_decode("%+5"), "%+5");
+        assert_eq!(percent_decode("%%41"), "%A");
+        assert_eq!(percent_decode("%é"), "%é");
+        assert_eq!(percent_decode("%FF"), "\u{fffd}");
+    }
+}
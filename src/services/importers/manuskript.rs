@@ -0,0 +1,458 @@
+use super::{extension_of, paragraphs, ChapterBuilder, DocumentImporter, SceneMetadata};
+use crate::{models::*, services::i18n::i18n};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs::{self, File},
+    io::Read,
+    path::Path,
+};
+
+const OUTLINE_FOLDER: &str = "outline/";
+const CHARACTERS_FOLDER: &str = "characters/";
+
+/// Imports Manuskript projects, either saved as a single zip archive
+/// or as a `.msk` marker file next to a project folder
+pub struct ManuskriptImporter;
+
+impl DocumentImporter for ManuskriptImporter {
+    fn accepts(&self, path: &Path) -> bool {
+        extension_of(path).as_deref() == Some("msk")
+    }
+
+    fn import(&self, path: &Path) -> ManuscriptResult<Document> {
+        let project = ManuskriptProject::open(path)?;
+        let mut document = Document::default();
+
+        if let Some(infos) = project.headers("infos.txt") {
+            document.set_title(header(&infos, "Title"));
+            if let Some(author) = header(&infos, "Author") {
+                document.manifest_mut().set_author(author);
+            }
+
+            let publication = document.manifest_mut().publication_mut();
+            publication.set_subtitle(header(&infos, "Subtitle"));
+            publication.set_series(header(&infos, "Serie"));
+            publication.set_series_number(
+                header(&infos, "Volume").and_then(|v| v.trim().parse::<u32>().ok()),
+            );
+            publication.set_keywords(
+                header(&infos, "Genre")
+                    .map(|genre| split_list(genre.as_str()))
+                    .unwrap_or_default(),
+            );
+            publication.set_rights(header(&infos, "License"));
+        }
+
+        let characters = project.import_characters(&mut document);
+        let statuses = project.statuses();
+
+        for (priority, entry) in project.entries(OUTLINE_FOLDER).iter().enumerate() {
+            let (headers, content) = project.item(entry);
+            let mut builder = ChapterBuilder::new(header(&headers, "title"));
+
+            if entry.ends_with('/') {
+                project.collect_scenes(entry, &mut builder, &characters, &statuses);
+                builder.chapter_mut().set_synopsis(summary_of(&headers));
+            } else {
+                builder.set_text(
+                    content.as_str(),
+                    scene_metadata(&headers, &characters, &statuses),
+                );
+            }
+
+            let include = header(&headers, "compile")
+                .map(|c| c != "0")
+                .unwrap_or(true);
+            let chapter = builder.chapter_mut();
+            chapter.set_priority(Some(priority as u64));
+            let _ = chapter.set_include_in_compilation(include);
+
+            builder.add_to(&mut document);
+        }
+
+        Ok(document)
+    }
+}
+
+type Headers = Vec<(String, String)>;
+
+fn header(headers: &Headers, key: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.clone())
+        .filter(|v| !v.is_empty())
+}
+
+/// Parses the `key: value` headers Manuskript writes at the top of its files.
+/// Multiline values continue on indented lines. When `stop_at_blank` is set,
+/// the first blank line ends the headers and everything after it is returned
+/// as the file body
+fn parse_headers(text: &str, stop_at_blank: bool) -> (Headers, String) {
+    let mut headers: Headers = vec![];
+    let mut lines = text.lines();
+
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            if stop_at_blank {
+                let body = lines.collect::<Vec<&str>>().join("\n");
+                return (headers, body.trim().to_string());
+            }
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    (headers, String::new())
+}
+
+fn summary_of(headers: &Headers) -> Option<String> {
+    header(headers, "summaryFull").or(header(headers, "summarySentence"))
+}
+
+fn scene_metadata(
+    headers: &Headers,
+    characters: &HashMap<String, String>,
+    statuses: &HashMap<String, String>,
+) -> SceneMetadata {
+    let notes = header(headers, "notes");
+    let description = [summary_of(headers), notes]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    SceneMetadata {
+        title: header(headers, "title"),
+        point_of_view: header(headers, "POV").and_then(|id| characters.get(&id).cloned()),
+        status: header(headers, "status").and_then(|id| statuses.get(&id).cloned()),
+        description: Some(description).filter(|d| !d.is_empty()),
+        unused: header(headers, "compile").is_some_and(|c| c == "0"),
+    }
+}
+
+/// Orders Manuskript file names, which are prefixed by their position
+fn position_of(name: &str) -> (u64, String) {
+    let position = name
+        .split('-')
+        .next()
+        .and_then(|p| p.parse::<u64>().ok())
+        .unwrap_or(u64::MAX);
+    (position, name.to_string())
+}
+
+struct ManuskriptProject {
+    files: HashMap<String, String>,
+}
+
+impl ManuskriptProject {
+    fn open(path: &Path) -> ManuscriptResult<Self> {
+        let open_error = || ManuscriptError::Open(path.display().to_string());
+        let mut files = HashMap::new();
+
+        let file = File::open(path).or(Err(open_error()))?;
+        if let Ok(mut archive) = zip::ZipArchive::new(file) {
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index).or(Err(open_error()))?;
+                if entry.is_file() {
+                    let mut content = String::new();
+                    if entry.read_to_string(&mut content).is_ok() {
+                        files.insert(entry.name().replace('\\', "/"), content);
+                    }
+                }
+            }
+        } else {
+            // Uncompressed projects keep their content in a folder named after the marker file
+            let folder = path.with_extension("");
+            if !folder.is_dir() {
+                return Err(ManuscriptError::Import(
+                    "Manuskript project folder not found".into(),
+                ));
+            }
+            Self::read_folder(&folder, &folder, &mut files);
+        }
+
+        Ok(Self { files })
+    }
+
+    fn read_folder(root: &Path, folder: &Path, files: &mut HashMap<String, String>) {
+        for entry in fs::read_dir(folder).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::read_folder(root, &path, files);
+            } else if let (Ok(relative), Ok(content)) =
+                (path.strip_prefix(root), fs::read_to_string(&path))
+            {
+                let key = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<String>>()
+                    .join("/");
+                files.insert(key, content);
+            }
+        }
+    }
+
+    fn headers(&self, name: &str) -> Option<Headers> {
+        self.files
+            .get(name)
+            .map(|content| parse_headers(content, false).0)
+    }
+
+    /// Lists the direct children of `folder`, in their outline order. Folders
+    /// are returned with a trailing slash
+    fn entries(&self, folder: &str) -> Vec<String> {
+        let entries: BTreeSet<(u64, String)> = self
+            .files
+            .keys()
+            .filter_map(|key| key.strip_prefix(folder))
+            .filter_map(|rest| match rest.split_once('/') {
+                Some((name, _)) => Some(format!("{name}/")),
+                None if rest == "folder.txt" => None,
+                None => Some(rest.to_string()),
+            })
+            .map(|name| position_of(name.as_str()))
+            .collect();
+
+        entries
+            .into_iter()
+            .map(|(_, name)| format!("{folder}{name}"))
+            .collect()
+    }
+
+    /// Gets headers and content of an outline item. Folders keep
+    /// their metadata in a `folder.txt` file
+    fn item(&self, entry: &str) -> (Headers, String) {
+        let key = if entry.ends_with('/') {
+            format!("{entry}folder.txt")
+        } else {
+            entry.to_string()
+        };
+
+        self.files
+            .get(&key)
+            .map(|content| parse_headers(content, true))
+            .unwrap_or_default()
+    }
+
+    fn collect_scenes(
+        &self,
+        folder: &str,
+        builder: &mut ChapterBuilder,
+        characters: &HashMap<String, String>,
+        statuses: &HashMap<String, String>,
+    ) {
+        for entry in self.entries(folder) {
+            if entry.ends_with('/') {
+                self.collect_scenes(&entry, builder, characters, statuses);
+            } else {
+                let (headers, content) = self.item(&entry);
+                builder.push_scene(
+                    content.as_str(),
+                    scene_metadata(&headers, characters, statuses),
+                );
+            }
+        }
+    }
+
+    fn statuses(&self) -> HashMap<String, String> {
+        let mut statuses = HashMap::new();
+        if let Some(content) = self.files.get("status.txt") {
+            let lines = content.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+            for (index, line) in lines.enumerate() {
+                match line.split_once(':') {
+                    Some((id, name)) if id.trim().parse::<u64>().is_ok() => {
+                        statuses.insert(id.trim().to_string(), name.trim().to_string())
+                    }
+                    _ => statuses.insert(index.to_string(), line.to_string()),
+                };
+            }
+        }
+        statuses
+    }
+
+    /// Creates a character sheet for each character and returns
+    /// a map between Manuskript character ids and the ids of their sheets
+    fn import_characters(&self, document: &mut Document) -> HashMap<String, String> {
+        let mut sheets = HashMap::new();
+
+        for (priority, entry) in self.entries(CHARACTERS_FOLDER).iter().enumerate() {
+            let headers = match self.headers(entry) {
+                Some(headers) => headers,
+                None => continue,
+            };
+            let mut sheet = CharacterSheet::default();
+            if let Some(id) = header(&headers, "ID") {
+                sheets.insert(id, sheet.id().to_string());
+            }
+            sheet.set_name(header(&headers, "Name"));
+            sheet.set_role(header(&headers, "Importance").map(|i| match i.as_str() {
+                "2" => i18n("Main character"),
+                "1" => i18n("Secondary character"),
+                _ => i18n("Minor character"),
+            }));
+
+            let motives = [
+                (i18n("Motivation"), header(&headers, "Motivation")),
+                (i18n("Goal"), header(&headers, "Goal")),
+                (i18n("Conflict"), header(&headers, "Conflict")),
+                (i18n("Epiphany"), header(&headers, "Epiphany")),
+            ]
+            .into_iter()
+            .filter_map(|(label, value)| value.map(|v| format!("{label}: {v}")))
+            .collect::<Vec<String>>()
+            .join("\n\n");
+            sheet.set_psycological_traits(motives.as_bytes());
+
+            let background = [
+                header(&headers, "Full Summary")
+                    .or(header(&headers, "Paragraph Summary"))
+                    .or(header(&headers, "Phrase Summary")),
+                header(&headers, "Notes"),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|text| paragraphs(text.as_str()))
+            .collect::<Vec<String>>()
+            .join("\n\n");
+            sheet.set_background(background.as_bytes());
+            sheet.set_priority(Some(priority as u64));
+
+            document.add_chunk(sheet);
+        }
+
+        sheets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(files: &[(&str, &str)]) -> ManuskriptProject {
+        ManuskriptProject {
+            files: files
+                .iter()
+                .map(|(name, content)| (name.to_string(), content.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn headers_with_continuation_lines() {
+        let text = "title:       Arrival\nsummaryFull: The ship docks.\n    Ada waits.\nPOV: 1\n";
+        let (headers, body) = parse_headers(text, false);
+        assert_eq!(header(&headers, "Title").as_deref(), Some("Arrival"));
+        assert_eq!(
+            header(&headers, "summaryFull").as_deref(),
+            Some("The ship docks.\nAda waits.")
+        );
+        assert_eq!(header(&headers, "pov").as_deref(), Some("1"));
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn headers_stop_at_blank() {
+        let text = "title: Arrival\n\nThe ship docks.\n\nname: not a header\n";
+        let (headers, body) = parse_headers(text, true);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(body, "The ship docks.\n\nname: not a header");
+
+        let (headers, _) = parse_headers(text, false);
+        assert_eq!(header(&headers, "name").as_deref(), Some("not a header"));
+    }
+
+    #[test]
+    fn empty_headers_are_missing() {
+        let (headers, _) = parse_headers("title:\nnotes: \n", false);
+        assert_eq!(header(&headers, "title"), None);
+        assert_eq!(header(&headers, "notes"), None);
+    }
+
+    #[test]
+    fn entries_in_outline_order() {
+        let project = project(&[
+            ("outline/10-Epilogue.md", ""),
+            ("outline/2-Arrival/folder.txt", ""),
+            ("outline/2-Arrival/0-Harbour.md", ""),
+            ("outline/folder.txt", ""),
+            ("outline/1-Prologue.md", ""),
+            ("outline/Untitled.md", ""),
+            ("characters/0-Ada.txt", ""),
+        ]);
+        assert_eq!(
+            project.entries(OUTLINE_FOLDER),
+            vec![
+                "outline/1-Prologue.md",
+                "outline/2-Arrival/",
+                "outline/10-Epilogue.md",
+                "outline/Untitled.md",
+            ]
+        );
+        assert_eq!(
+            project.entries("outline/2-Arrival/"),
+            vec!["outline/2-Arrival/0-Harbour.md"]
+        );
+        assert_eq!(position_of("10-Epilogue.md").0, 10);
+        assert_eq!(position_of("Untitled.md").0, u64::MAX);
+    }
+
+    #[test]
+    fn folder_items_read_folder_txt() {
+        let project = project(&[
+            ("outline/1-Arrival/folder.txt", "title: Arrival\n"),
+            ("outline/2-Harbour.md", "title: Harbour\n\nThe ship docks."),
+        ]);
+        let (headers, _) = project.item("outline/1-Arrival/");
+        assert_eq!(header(&headers, "title").as_deref(), Some("Arrival"));
+        let (headers, content) = project.item("outline/2-Harbour.md");
+        assert_eq!(header(&headers, "title").as_deref(), Some("Harbour"));
+        assert_eq!(content, "The ship docks.");
+    }
+
+    #[test]
+    fn statuses_by_id_and_index() {
+        let with_ids = project(&[("status.txt", "0: TODO\n1: First draft\n\n2: Final\n")]);
+        let statuses = with_ids.statuses();
+        assert_eq!(statuses.get("1").map(String::as_str), Some("First draft"));
+        assert_eq!(statuses.get("2").map(String::as_str), Some("Final"));
+
+        let by_index = project(&[("status.txt", "TODO\n\nFirst draft\nFinal\n")]);
+        let statuses = by_index.statuses();
+        assert_eq!(statuses.get("0").map(String::as_str), Some("TODO"));
+        assert_eq!(statuses.get("2").map(String::as_str), Some("Final"));
+
+        assert!(project(&[]).statuses().is_empty());
+    }
+
+    #[test]
+    fn scene_point_of_view_and_status() {
+        let characters = HashMap::from([("3".to_string(), "01ADA".to_string())]);
+        let statuses = HashMap::from([("1".to_string(), "First draft".to_string())]);
+        let (headers, _) = parse_headers(
+            "title: Harbour\nPOV: 3\nstatus: 1\nsummarySentence: Docking\nnotes: Check tides\n",
+            false,
+        );
+        let scene = scene_metadata(&headers, &characters, &statuses);
+        assert_eq!(scene.title.as_deref(), Some("Harbour"));
+        assert_eq!(scene.point_of_view.as_deref(), Some("01ADA"));
+        assert_eq!(scene.status.as_deref(), Some("First draft"));
+        assert_eq!(scene.description.as_deref(), Some("Docking\n\nCheck tides"));
+        assert!(!scene.unused);
+
+        let (headers, _) = parse_headers("POV: 9\nstatus: 4\ncompile: 0\n", false);
+        let scene = scene_metadata(&headers, &characters, &statuses);
+        assert_eq!(scene.point_of_view, None);
+        assert_eq!(scene.status, None);
+        assert_eq!(scene.description, None);
+        assert!(scene.unused);
+    }
+}
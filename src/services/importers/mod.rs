@@ -0,0 +1,291 @@
+mod epub;
+mod manuskript;
+mod rtf;
+mod scrivener;
+mod xml;
+mod ywriter;
+
+use crate::{
+    models::{
+        search_key, Chapter, ChunkStatus, Document, DocumentChunk, ManuscriptError,
+        ManuscriptResult, MutableBufferChunk, Scene,
+    },
+    services::i18n::i18n,
+};
+use std::path::Path;
+
+/// Implemented by every format that can be turned into a manuscript document
+pub trait DocumentImporter {
+    /// Whether this importer knows how to read `path`
+    fn accepts(&self, path: &Path) -> bool;
+
+    /// Reads `path` and builds a brand new document out of it
+    fn import(&self, path: &Path) -> ManuscriptResult<Document>;
+}
+
+fn importers() -> Vec<Box<dyn DocumentImporter>> {
+    vec![
+        Box::new(scrivener::ScrivenerImporter),
+        Box::new(ywriter::YWriterImporter),
+        Box::new(manuskript::ManuskriptImporter),
+        Box::new(epub::EpubImporter),
+    ]
+}
+
+/// Lowercased extension of `path`, if any
+pub(crate) fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+}
+
+/// Imports the project at `path` using the first importer that accepts it
+pub fn import_document<P: AsRef<Path>>(path: P) -> ManuscriptResult<Document> {
+    let path = path.as_ref();
+    importers()
+        .into_iter()
+        .find(|importer| importer.accepts(path))
+        .ok_or(ManuscriptError::Import(format!(
+            "Unsupported project format: {}",
+            path.display()
+        )))?
+        .import(path)
+}
+
+/// File name patterns of the supported formats, used to filter file choosers.
+/// Scrivener projects are `.scriv` folders, which file choosers can not pick:
+/// users open the folder and pick the `.scrivx` binder file inside it instead
+pub fn supported_patterns() -> Vec<&'static str> {
+    vec!["*.scrivx", "*.yw7", "*.msk", "*.epub"]
+}
+
+/// Scene level information read from another tool. The point of view is the
+/// id of an imported character sheet and the status is the name of a workflow
+/// status
+#[derive(Debug, Default)]
+pub(crate) struct SceneMetadata {
+    pub title: Option<String>,
+    pub point_of_view: Option<String>,
+    pub status: Option<String>,
+    pub description: Option<String>,
+    pub unused: bool,
+}
+
+/// Id of the workflow status of `document` named `name`. Statuses the project
+/// does not have yet are added to it
+fn status_id(document: &mut Document, name: &str) -> String {
+    let manifest = document.manifest_mut();
+    if let Some(status) = manifest.status_by_key(search_key(name).as_str()) {
+        return status.id().to_string();
+    }
+
+    let status = ChunkStatus::new(name);
+    let id = status.id().to_string();
+    manifest.add_status(status);
+    id
+}
+
+/// Assembles a chapter out of the scenes it is made of, which are nested
+/// under it as scene chunks
+pub(crate) struct ChapterBuilder {
+    chapter: Chapter,
+    text: Option<(String, SceneMetadata)>,
+    scenes: Vec<(String, SceneMetadata)>,
+}
+
+impl ChapterBuilder {
+    pub fn new(title: Option<String>) -> Self {
+        let mut chapter = Chapter::new();
+        if let Some(title) = title {
+            chapter.set_title(title.as_str());
+        }
+        Self {
+            chapter,
+            text: None,
+            scenes: vec![],
+        }
+    }
+
+    pub fn chapter_mut(&mut self) -> &mut Chapter {
+        &mut self.chapter
+    }
+
+    /// Uses a single scene as the text of the chapter itself
+    pub fn set_text(&mut self, content: &str, metadata: SceneMetadata) {
+        self.text = Some((content.trim().to_string(), metadata));
+    }
+
+    pub fn push_scene(&mut self, content: &str, metadata: SceneMetadata) {
+        self.scenes.push((content.trim().to_string(), metadata));
+    }
+
+    /// Adds the chapter and its scenes to `document`
+    pub fn add_to(self, document: &mut Document) {
+        let mut chapter = self.chapter;
+        if let Some((content, metadata)) = self.text {
+            let length = content.chars().count() as i32;
+            chapter.set_buffer(content.into());
+            chapter.set_synopsis(metadata.description);
+            chapter.set_status(metadata.status.map(|name| status_id(document, &name)));
+
+            // Chapters have no point of view, it is kept as a note over their text
+            let point_of_view = metadata
+                .point_of_view
+                .and_then(|id| document.get_chunk_ref(&id))
+                .and_then(|sheet| sheet.title());
+            if let Some(name) = point_of_view {
+                chapter.add_note(0, length, format!("{}: {name}", i18n("Point of view")));
+            }
+        }
+
+        let parent = chapter.id().to_string();
+        document.add_chunk(chapter);
+
+        for (priority, (content, metadata)) in self.scenes.into_iter().enumerate() {
+            let mut scene = Scene::new();
+            if let Some(title) = metadata.title.as_ref() {
+                scene.set_title(title.as_str());
+            }
+            scene.set_buffer(content.into());
+            scene.set_synopsis(metadata.description);
+            scene.set_point_of_view(metadata.point_of_view);
+            scene.set_status(metadata.status.map(|name| status_id(document, &name)));
+            let _ = scene.set_include_in_compilation(!metadata.unused);
+            let _ = scene.set_parent_id(Some(parent.clone()));
+            scene.set_priority(Some(priority as u64));
+            document.add_chunk(scene);
+        }
+    }
+}
+
+/// Turns plain text, where each line is a paragraph, into markdown paragraphs
+pub(crate) fn paragraphs(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BufferChunk, CharacterSheet};
+
+    fn document_with_sheet(name: &str) -> (Document, String) {
+        let mut document = Document::default();
+        let mut sheet = CharacterSheet::default();
+        sheet.set_name(Some(name.into()));
+        let id = sheet.id().to_string();
+        document.add_chunk(sheet);
+        (document, id)
+    }
+
+    #[test]
+    fn scenes_keep_point_of_view_and_status() {
+        let (mut document, ada) = document_with_sheet("Ada");
+        let mut builder = ChapterBuilder::new(Some("Arrival".into()));
+        builder.push_scene(
+            " The ship docks. ",
+            SceneMetadata {
+                title: Some("Harbour".into()),
+                point_of_view: Some(ada.clone()),
+                status: Some("First  Draft".into()),
+                description: Some("Docking".into()),
+                unused: false,
+            },
+        );
+        builder.push_scene(
+            "Ada waits.",
+            SceneMetadata {
+                status: Some("Outline".into()),
+                unused: true,
+                ..Default::default()
+            },
+        );
+        builder.add_to(&mut document);
+
+        let chapter = document.children_of(None)[0];
+        assert_eq!(chapter.title().as_deref(), Some("Arrival"));
+        let scenes = document
+            .children_of(Some(chapter.id()))
+            .into_iter()
+            .filter_map(|c| c.as_any().downcast_ref::<Scene>())
+            .collect::<Vec<&Scene>>();
+        assert_eq!(scenes.len(), 2);
+
+        let manifest = document.manifest();
+        let status_name = |scene: &Scene| {
+            scene
+                .status()
+                .and_then(|id| manifest.status(id))
+                .map(|status| status.name().to_string())
+        };
+        assert_eq!(scenes[0].title().as_deref(), Some("Harbour"));
+        assert_eq!(scenes[0].buffer().as_ref(), b"The ship docks.");
+        assert_eq!(scenes[0].synopsis().map(String::as_str), Some("Docking"));
+        assert_eq!(scenes[0].point_of_view(), Some(&ada));
+        assert_eq!(status_name(scenes[0]).as_deref(), Some("First draft"));
+        assert!(scenes[0].include_in_compilation());
+
+        assert_eq!(scenes[1].point_of_view(), None);
+        assert_eq!(status_name(scenes[1]).as_deref(), Some("Outline"));
+        assert!(!scenes[1].include_in_compilation());
+        assert_eq!(manifest.statuses().len(), 5);
+    }
+
+    #[test]
+    fn chapter_text_keeps_point_of_view_in_a_note() {
+        let (mut document, ada) = document_with_sheet("Ada");
+        let mut builder = ChapterBuilder::new(Some("Arrival".into()));
+        builder.set_text(
+            "The ship docks.\n",
+            SceneMetadata {
+                point_of_view: Some(ada),
+                status: Some("Final".into()),
+                description: Some("Docking".into()),
+                ..Default::default()
+            },
+        );
+        builder.add_to(&mut document);
+
+        let chunk = document.children_of(None)[0];
+        assert!(document.children_of(Some(chunk.id())).is_empty());
+        let chapter = chunk.as_any().downcast_ref::<Chapter>().unwrap();
+        assert_eq!(chapter.buffer().as_ref(), b"The ship docks.");
+        assert_eq!(chapter.synopsis().map(String::as_str), Some("Docking"));
+        assert_eq!(
+            chapter
+                .status()
+                .and_then(|id| document.manifest().status(id))
+                .map(|status| status.name()),
+            Some("Final")
+        );
+
+        let note = &chapter.notes()[0];
+        assert_eq!(note.buffer(), b"Point of view: Ada".as_ref());
+        assert_eq!((note.offset_start(), note.offset_end()), (0, 15));
+        assert_eq!(document.manifest().statuses().len(), 4);
+    }
+
+    #[test]
+    fn chapters_without_text_have_no_note() {
+        let (mut document, ada) = document_with_sheet("Ada");
+        let mut builder = ChapterBuilder::new(None);
+        builder.push_scene(
+            "The ship docks.",
+            SceneMetadata {
+                point_of_view: Some(ada),
+                ..Default::default()
+            },
+        );
+        builder.add_to(&mut document);
+
+        let chapter = document.children_of(None)[0]
+            .as_any()
+            .downcast_ref::<Chapter>()
+            .unwrap();
+        assert!(chapter.notes().is_empty());
+        assert!(chapter.buffer().is_empty());
+    }
+}
@@ -0,0 +1,393 @@
+//! Converts an RTF document into Markdown flavoured plain text, which is what
+//! chapters store in their buffers. Only the subset of RTF produced by common
+//! writing tools is handled: paragraphs, line breaks, bold, italic, strikethrough,
+//! unicode escapes, code page escapes and footnotes. Everything else is dropped.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CharacterFormat {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct GroupState {
+    format: CharacterFormat,
+    skip: bool,
+    footnote: bool,
+    unicode_skip: usize,
+}
+
+#[derive(Default)]
+struct Output {
+    body: String,
+    footnotes: Vec<String>,
+    current_footnote: Option<String>,
+    open_format: CharacterFormat,
+}
+
+impl Output {
+    fn target(&mut self) -> &mut String {
+        match self.current_footnote.as_mut() {
+            Some(footnote) => footnote,
+            None => &mut self.body,
+        }
+    }
+
+    fn markers(format: CharacterFormat) -> String {
+        let mut markers = String::new();
+        if format.strike {
+            markers.push_str("~~");
+        }
+        if format.bold {
+            markers.push_str("**");
+        }
+        if format.italic {
+            markers.push('*');
+        }
+        markers
+    }
+
+    /// Closes the currently open emphasis markers, moving them before
+    /// any trailing whitespace so that the markdown stays valid
+    fn close_format(&mut self) {
+        let format = self.open_format;
+        if format == CharacterFormat::default() {
+            return;
+        }
+        self.open_format = CharacterFormat::default();
+
+        let opening = Self::markers(format);
+        let target = self.target();
+        if target.ends_with(opening.as_str()) {
+            // Nothing was written between the markers
+            target.truncate(target.len() - opening.len());
+            return;
+        }
+
+        let trimmed_len = target.trim_end().len();
+        let trailing = target.split_off(trimmed_len);
+        let closing: String = opening.chars().rev().collect();
+        target.push_str(closing.as_str());
+        target.push_str(trailing.as_str());
+    }
+
+    fn push_text(&mut self, text: &str, format: CharacterFormat) {
+        if text.is_empty() {
+            return;
+        }
+
+        if format != self.open_format {
+            self.close_format();
+            if format != CharacterFormat::default() {
+                let target = self.target();
+                // Leading whitespace stays outside of the emphasis
+                let trimmed = text.trim_start();
+                target.push_str(&text[..text.len() - trimmed.len()]);
+                target.push_str(Self::markers(format).as_str());
+                self.open_format = format;
+                self.target().push_str(trimmed);
+                return;
+            }
+        }
+
+        self.target().push_str(text);
+    }
+
+    fn push_break(&mut self, value: &str) {
+        self.close_format();
+        self.target().push_str(value);
+    }
+
+    fn begin_footnote(&mut self) {
+        self.close_format();
+        self.footnotes.push(String::new());
+        let index = self.footnotes.len();
+        self.body.push_str(format!("[^{index}]").as_str());
+        self.current_footnote = Some(String::new());
+    }
+
+    fn end_footnote(&mut self) {
+        self.close_format();
+        if let Some(footnote) = self.current_footnote.take() {
+            if let Some(last) = self.footnotes.last_mut() {
+                *last = footnote.split_whitespace().collect::<Vec<&str>>().join(" ");
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.close_format();
+
+        let mut paragraphs: Vec<String> = vec![];
+        for paragraph in self.body.split("\n\n") {
+            let lines: Vec<&str> = paragraph.lines().map(|l| l.trim_end()).collect();
+            let paragraph = lines.join("\n").trim().to_string();
+            if !paragraph.is_empty() {
+                paragraphs.push(paragraph);
+            }
+        }
+
+        let mut markdown = paragraphs.join("\n\n");
+        for (index, footnote) in self.footnotes.iter().enumerate() {
+            markdown.push_str(format!("\n\n[^{}]: {}", index + 1, footnote).as_str());
+        }
+        markdown
+    }
+}
+
+/// Destinations whose content is never part of the text
+const IGNORED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "info",
+    "pict",
+    "header",
+    "footer",
+    "headerl",
+    "headerr",
+    "footerl",
+    "footerr",
+    "listtable",
+    "listoverridetable",
+    "revtbl",
+    "rsidtbl",
+    "generator",
+    "xmlnstbl",
+    "themedata",
+    "colorschememapping",
+    "latentstyles",
+    "datastore",
+    "object",
+    "field",
+    "fldinst",
+    "bkmkstart",
+    "bkmkend",
+];
+
+fn cp1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9f => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+pub fn to_markdown(source: &str) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut output = Output::default();
+    let mut stack: Vec<GroupState> = vec![];
+    let mut state = GroupState {
+        unicode_skip: 1,
+        ..Default::default()
+    };
+    let mut text = String::new();
+    let mut pending_skip: usize = 0;
+    let mut i = 0;
+
+    macro_rules! flush {
+        () => {
+            if !text.is_empty() {
+                if !state.skip {
+                    output.push_text(text.as_str(), state.format);
+                }
+                text.clear();
+            }
+        };
+    }
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '{' => {
+                flush!();
+                stack.push(state);
+                i += 1;
+            }
+            '}' => {
+                flush!();
+                if state.footnote && !stack.last().map(|s| s.footnote).unwrap_or(false) {
+                    output.end_footnote();
+                }
+                state = stack.pop().unwrap_or_default();
+                i += 1;
+            }
+            '\\' => {
+                i += 1;
+                if i >= chars.len() {
+                    break;
+                }
+                let next = chars[i];
+                if next.is_ascii_alphabetic() {
+                    let start = i;
+                    while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    let num_start = i;
+                    if i < chars.len() && (chars[i] == '-' || chars[i].is_ascii_digit()) {
+                        i += 1;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                    let param: Option<i32> =
+                        chars[num_start..i].iter().collect::<String>().parse().ok();
+                    // A single space delimiting the control word is part of it
+                    if i < chars.len() && chars[i] == ' ' {
+                        i += 1;
+                    }
+
+                    if pending_skip > 0 && word != "u" {
+                        pending_skip = 0;
+                    }
+
+                    match word.as_str() {
+                        "par" | "sect" | "page" => {
+                            flush!();
+                            if !state.skip {
+                                output.push_break("\n\n");
+                            }
+                        }
+                        "line" => {
+                            flush!();
+                            if !state.skip {
+                                output.push_break("\n");
+                            }
+                        }
+                        "tab" => text.push('\t'),
+                        "emdash" => text.push('—'),
+                        "endash" => text.push('–'),
+                        "lquote" => text.push('‘'),
+                        "rquote" => text.push('’'),
+                        "ldblquote" => text.push('“'),
+                        "rdblquote" => text.push('”'),
+                        "bullet" => text.push('•'),
+                        "b" | "i" | "strike" | "plain" => {
+                            flush!();
+                            let enabled = param.unwrap_or(1) != 0;
+                            match word.as_str() {
+                                "b" => state.format.bold = enabled,
+                                "i" => state.format.italic = enabled,
+                                "strike" => state.format.strike = enabled,
+                                _ => state.format = CharacterFormat::default(),
+                            }
+                        }
+                        "uc" => state.unicode_skip = param.unwrap_or(1).max(0) as usize,
+                        "u" => {
+                            if let Some(code) = param {
+                                let code = if code < 0 { code + 65536 } else { code } as u32;
+                                if let Some(ch) = char::from_u32(code) {
+                                    text.push(ch);
+                                }
+                                pending_skip = state.unicode_skip;
+                            }
+                        }
+                        "footnote" => {
+                            flush!();
+                            if !state.skip {
+                                output.begin_footnote();
+                                state.footnote = true;
+                            }
+                        }
+                        "chftn" => (),
+                        _ if IGNORED_DESTINATIONS.contains(&word.as_str()) => {
+                            flush!();
+                            state.skip = true;
+                        }
+                        _ => (),
+                    }
+                } else {
+                    i += 1;
+                    match next {
+                        '\\' | '{' | '}' => text.push(next),
+                        '~' => text.push('\u{a0}'),
+                        '_' => text.push('-'),
+                        '-' => (),
+                        '*' => {
+                            // Unknown destinations are ignored as mandated by the spec
+                            flush!();
+                            state.skip = true;
+                        }
+                        '\'' => {
+                            let hex: String = chars.iter().skip(i).take(2).collect();
+                            i += hex.len();
+                            if let Ok(byte) = u8::from_str_radix(hex.as_str(), 16) {
+                                if pending_skip > 0 {
+                                    pending_skip -= 1;
+                                } else {
+                                    text.push(cp1252(byte));
+                                }
+                            }
+                        }
+                        '\n' | '\r' => {
+                            flush!();
+                            if !state.skip {
+                                output.push_break("\n\n");
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            '\n' | '\r' => i += 1,
+            _ => {
+                if pending_skip > 0 {
+                    pending_skip -= 1;
+                } else {
+                    text.push(c);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    flush!();
+    if output.current_footnote.is_some() {
+        output.end_footnote();
+    }
+    output.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_markdown;
+
+    #[test]
+    fn plain_paragraphs() {
+        let rtf = r"{\rtf1\ansi{\fonttbl\f0\fswiss Helvetica;}\f0\pard First paragraph\par Second one\par}";
+        assert_eq!(to_markdown(rtf), "First paragraph\n\nSecond one");
+    }
+
+    #[test]
+    fn emphasis() {
+        let rtf = r"{\rtf1 Some \b bold \b0 and {\i italic} text\par}";
+        assert_eq!(to_markdown(rtf), "Some **bold** and *italic* text");
+    }
+
+    #[test]
+    fn escapes() {
+        let rtf = r"{\rtf1\uc1 Caf\'e9 \u8220?quoted\u8221? \emdash  done\par}";
+        assert_eq!(to_markdown(rtf), "Café “quoted” — done");
+    }
+
+    #[test]
+    fn footnotes() {
+        let rtf = r"{\rtf1 A claim{\footnote\pard {\chftn} Source of the claim}.\par}";
+        assert_eq!(
+            to_markdown(rtf),
+            "A claim[^1].\n\n[^1]: Source of the claim"
+        );
+    }
+
+    #[test]
+    fn ignored_destinations() {
+        let rtf = r"{\rtf1{\colortbl;\red0\green0\blue0;}{\*\expandedcolortbl;;}Visible\par}";
+        assert_eq!(to_markdown(rtf), "Visible");
+    }
+}
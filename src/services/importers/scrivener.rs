@@ -0,0 +1,387 @@
+use super::{extension_of, rtf, xml, DocumentImporter};
+use crate::models::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const G_LOG_DOMAIN: &str = "ManuscriptScrivenerImporter";
+
+/// Imports Scrivener 2 and 3 projects. Both the `.scriv` bundle
+/// and the `.scrivx` binder file inside it are accepted, although
+/// file choosers only let users pick the latter
+pub struct ScrivenerImporter;
+
+impl DocumentImporter for ScrivenerImporter {
+    fn accepts(&self, path: &Path) -> bool {
+        matches!(
+            extension_of(path).as_deref(),
+            Some("scrivx") | Some("scriv")
+        )
+    }
+
+    fn import(&self, path: &Path) -> ManuscriptResult<Document> {
+        let binder_path = binder_file(path)?;
+        let project = ScrivenerProject {
+            base: binder_path
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default(),
+        };
+
+        let source = fs::read_to_string(&binder_path).or(Err(ManuscriptError::Open(
+            binder_path.display().to_string(),
+        )))?;
+        let title = binder_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(String::from);
+
+        project.import_binder(source.as_str(), title)
+    }
+}
+
+/// Locates the `.scrivx` binder file for `path`
+fn binder_file(path: &Path) -> ManuscriptResult<PathBuf> {
+    if path.is_dir() {
+        fs::read_dir(path)
+            .or(Err(ManuscriptError::Open(path.display().to_string())))?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|entry| extension_of(entry).as_deref() == Some("scrivx"))
+            .ok_or(ManuscriptError::Import(
+                "No binder file found in Scrivener project".into(),
+            ))
+    } else {
+        Ok(path.to_path_buf())
+    }
+}
+
+fn is_characters_folder(item: &xml::XmlElement) -> bool {
+    item.child_text("Title")
+        .map(|title| title.eq_ignore_ascii_case("characters"))
+        .unwrap_or(false)
+}
+
+fn item_children(item: &xml::XmlElement) -> impl Iterator<Item = &xml::XmlElement> {
+    item.child("Children")
+        .into_iter()
+        .flat_map(|children| children.children_named("BinderItem"))
+}
+
+/// Reads a text file trimming it, treating empty files as missing
+fn read_text(path: PathBuf) -> Option<String> {
+    fs::read(path)
+        .ok()
+        .map(|bytes| String::from_utf8_lossy(bytes.as_slice()).trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn read_rtf(path: PathBuf) -> Option<String> {
+    read_text(path)
+        .map(|source| rtf::to_markdown(source.as_str()))
+        .filter(|text| !text.is_empty())
+}
+
+/// Appends the document notes of an item to its synopsis
+fn with_notes(synopsis: Option<String>, notes: Option<String>) -> Option<String> {
+    match (synopsis, notes) {
+        (Some(synopsis), Some(notes)) => Some(format!("{synopsis}\n\n{notes}")),
+        (synopsis, notes) => synopsis.or(notes),
+    }
+}
+
+struct ScrivenerProject {
+    base: PathBuf,
+}
+
+impl ScrivenerProject {
+    /// Builds a document out of the binder file `source`
+    fn import_binder(&self, source: &str, title: Option<String>) -> ManuscriptResult<Document> {
+        let root = xml::parse(source)?;
+        let binder = root.child("Binder").ok_or(ManuscriptError::Import(
+            "Missing binder in Scrivener project".into(),
+        ))?;
+
+        let mut document = Document::default();
+        document.set_title(title);
+
+        for item in binder.children_named("BinderItem") {
+            match item.attribute("Type") {
+                Some("DraftFolder") => {
+                    self.import_draft(item, &mut document, None);
+                }
+                Some("Folder") if is_characters_folder(item) => {
+                    self.import_characters(item, &mut document);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(document)
+    }
+
+    /// Scrivener 3 keeps each item in its own folder named after the item UUID,
+    /// while Scrivener 2 uses flat files named after a numeric ID
+    fn item_file(&self, item: &xml::XmlElement, name: &str, legacy_suffix: &str) -> PathBuf {
+        if let Some(uuid) = item.attribute("UUID") {
+            self.base.join("Files").join("Data").join(uuid).join(name)
+        } else {
+            let id = item.attribute("ID").unwrap_or_default();
+            self.base
+                .join("Files")
+                .join("Docs")
+                .join(format!("{id}{legacy_suffix}"))
+        }
+    }
+
+    fn content(&self, item: &xml::XmlElement) -> Option<String> {
+        read_rtf(self.item_file(item, "content.rtf", ".rtf"))
+    }
+
+    fn synopsis(&self, item: &xml::XmlElement) -> Option<String> {
+        read_text(self.item_file(item, "synopsis.txt", "_synopsis.txt"))
+    }
+
+    fn notes(&self, item: &xml::XmlElement) -> Option<String> {
+        read_rtf(self.item_file(item, "notes.rtf", "_notes.rtf"))
+    }
+
+    /// Walks the draft folder depth first, keeping the binder hierarchy. Folders
+    /// holding other folders become parts, plain texts inside a chapter become
+    /// scenes, everything else becomes a chapter nested under the chunk created
+    /// for its containing item. Document notes become a note over the whole text
+    /// of a chapter, and are kept with the synopsis otherwise
+    fn import_draft(
+        &self,
+        folder: &xml::XmlElement,
+        document: &mut Document,
+        parent: Option<(String, ChunkType)>,
+    ) {
+        for (priority, item) in item_children(folder).enumerate() {
+            let title = item.child_text("Title");
+            let content = self.content(item);
+            let synopsis = self.synopsis(item);
+            let notes = self.notes(item);
+            let include = item
+                .child("MetaData")
+                .and_then(|m| m.child_text("IncludeInCompile"))
+                .map(|value| value.eq_ignore_ascii_case("yes"))
+                .unwrap_or(true);
+
+            let has_subfolders =
+                item_children(item).any(|child| child.attribute("Type") == Some("Folder"));
+            let can_be_part = parent
+                .as_ref()
+                .map(|(_, ty)| ChunkType::Part.can_be_child_of(ty))
+                .unwrap_or(true);
+
+            let is_scene = item.attribute("Type") == Some("Text")
+                && item_children(item).next().is_none()
+                && parent
+                    .as_ref()
+                    .is_some_and(|(_, ty)| *ty == ChunkType::Chapter);
+
+            let mut chunk: Box<dyn DocumentChunk> = if is_scene {
+                let mut scene = Scene::new();
+                if let Some(title) = title.as_ref() {
+                    scene.set_title(title.as_str());
+                }
+                scene.set_buffer(content.unwrap_or_default().into());
+                scene.set_synopsis(with_notes(synopsis, notes));
+                Box::new(scene)
+            } else if has_subfolders && can_be_part {
+                let mut part = Part::new();
+                if let Some(title) = title.as_ref() {
+                    part.set_title(title.as_str());
+                }
+                part.set_buffer(content.unwrap_or_default().into());
+                part.set_synopsis(with_notes(synopsis, notes));
+                Box::new(part)
+            } else {
+                let mut chapter = Chapter::new();
+                if let Some(title) = title.as_ref() {
+                    chapter.set_title(title.as_str());
+                }
+                let content = content.unwrap_or_default();
+                let length = content.chars().count() as i32;
+                chapter.set_buffer(content.into());
+                match notes {
+                    Some(notes) if length > 0 => {
+                        chapter.set_synopsis(synopsis);
+                        chapter.add_note(0, length, notes);
+                    }
+                    notes => chapter.set_synopsis(with_notes(synopsis, notes)),
+                }
+                Box::new(chapter)
+            };
+
+            let _ = chunk.set_include_in_compilation(include);
+            let _ = chunk.set_parent_id(parent.as_ref().map(|(id, _)| id.clone()));
+            chunk.set_priority(Some(priority as u64));
+
+            glib::g_debug!(
+                G_LOG_DOMAIN,
+                "Imported {} {}",
+                chunk.chunk_type(),
+                title.unwrap_or_default()
+            );
+
+            let id = chunk.id().to_string();
+            let ty = chunk.chunk_type();
+            document.add_boxed_chunk(chunk);
+            self.import_draft(item, document, Some((id, ty)));
+        }
+    }
+
+    /// Every text item in the characters folder becomes a character sheet,
+    /// with its content used as the character background
+    fn import_characters(&self, folder: &xml::XmlElement, document: &mut Document) {
+        let mut priority = 0;
+        let mut pending: Vec<&xml::XmlElement> = item_children(folder).collect();
+        pending.reverse();
+
+        while let Some(item) = pending.pop() {
+            let mut children: Vec<&xml::XmlElement> = item_children(item).collect();
+            children.reverse();
+            pending.append(&mut children);
+
+            if item.attribute("Type") != Some("Text") {
+                continue;
+            }
+
+            let mut sheet = CharacterSheet::default();
+            sheet.set_name(item.child_text("Title"));
+
+            let background = [self.synopsis(item), self.content(item), self.notes(item)]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join("\n\n");
+            sheet.set_background(background.as_bytes());
+            sheet.set_priority(Some(priority));
+            priority += 1;
+
+            document.add_chunk(sheet);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINDER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ScrivenerProject Version="2.0">
+  <Binder>
+    <BinderItem UUID="D1" Type="DraftFolder">
+      <Title>Draft</Title>
+      <Children>
+        <BinderItem UUID="P1" Type="Folder">
+          <Title>Book One</Title>
+          <Children>
+            <BinderItem UUID="C1" Type="Folder">
+              <Title>Arrival</Title>
+              <Children>
+                <BinderItem UUID="S1" Type="Text"><Title>Harbour</Title></BinderItem>
+                <BinderItem UUID="S2" Type="Text">
+                  <Title>Storm</Title>
+                  <MetaData><IncludeInCompile>No</IncludeInCompile></MetaData>
+                </BinderItem>
+              </Children>
+            </BinderItem>
+          </Children>
+        </BinderItem>
+        <BinderItem UUID="C2" Type="Text"><Title>Epilogue</Title></BinderItem>
+      </Children>
+    </BinderItem>
+    <BinderItem UUID="F1" Type="Folder">
+      <Title>Characters</Title>
+      <Children>
+        <BinderItem UUID="A1" Type="Text"><Title>Ada</Title></BinderItem>
+      </Children>
+    </BinderItem>
+    <BinderItem UUID="R1" Type="ResearchFolder"><Title>Research</Title></BinderItem>
+  </Binder>
+</ScrivenerProject>"#;
+
+    fn project(base: PathBuf) -> ScrivenerProject {
+        ScrivenerProject { base }
+    }
+
+    fn by_title<'a>(document: &'a Document, title: &str) -> &'a dyn DocumentChunk {
+        document
+            .chunks()
+            .into_iter()
+            .find(|c| c.title().as_deref() == Some(title))
+            .unwrap()
+    }
+
+    #[test]
+    fn binder_hierarchy() {
+        let document = project(PathBuf::from("/nonexistent"))
+            .import_binder(BINDER, Some("Voyage".into()))
+            .unwrap();
+        assert_eq!(document.title().map(String::as_str), Some("Voyage"));
+        assert_eq!(document.chunks().len(), 6);
+
+        let part = by_title(&document, "Book One");
+        let chapter = by_title(&document, "Arrival");
+        let storm = by_title(&document, "Storm");
+        assert_eq!(part.chunk_type(), ChunkType::Part);
+        assert_eq!(chapter.chunk_type(), ChunkType::Chapter);
+        assert_eq!(chapter.parent_id(), Some(part.id()));
+        assert_eq!(storm.chunk_type(), ChunkType::Scene);
+        assert_eq!(storm.parent_id(), Some(chapter.id()));
+        assert_eq!(storm.priority(), Some(1));
+        assert!(!storm.include_in_compilation());
+        assert!(by_title(&document, "Harbour").include_in_compilation());
+
+        let epilogue = by_title(&document, "Epilogue");
+        assert_eq!(epilogue.chunk_type(), ChunkType::Chapter);
+        assert_eq!(epilogue.parent_id(), None);
+        assert_eq!(epilogue.priority(), Some(1));
+
+        let sheets = document.chunks_by_type_ref(ChunkType::CharacterSheet);
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].title().as_deref(), Some("Ada"));
+    }
+
+    #[test]
+    fn missing_binder() {
+        let source = "<ScrivenerProject><Collections/></ScrivenerProject>";
+        assert!(project(PathBuf::new()).import_binder(source, None).is_err());
+    }
+
+    #[test]
+    fn document_notes() {
+        let base = std::env::temp_dir().join(format!("manuscript-scrivener-{}", ulid::Ulid::new()));
+        let write = |uuid: &str, name: &str, content: &str| {
+            let folder = base.join("Files").join("Data").join(uuid);
+            fs::create_dir_all(&folder).unwrap();
+            fs::write(folder.join(name), content).unwrap();
+        };
+        write("C2", "content.rtf", r"{\rtf1 The end.\par}");
+        write("C2", "notes.rtf", r"{\rtf1 Too abrupt?\par}");
+        write("S1", "synopsis.txt", "They land");
+        write("S1", "notes.rtf", r"{\rtf1 Check the tides\par}");
+
+        let document = project(base.clone()).import_binder(BINDER, None).unwrap();
+        fs::remove_dir_all(&base).unwrap();
+
+        let epilogue = by_title(&document, "Epilogue")
+            .as_any()
+            .downcast_ref::<Chapter>()
+            .unwrap();
+        assert_eq!(epilogue.buffer().as_ref(), b"The end.");
+        let note = &epilogue.notes()[0];
+        assert_eq!((note.offset_start(), note.offset_end()), (0, 8));
+        assert_eq!(note.buffer(), b"Too abrupt?");
+
+        let harbour = by_title(&document, "Harbour").as_buffer_chunk().unwrap();
+        assert_eq!(
+            harbour.synopsis().map(String::as_str),
+            Some("They land\n\nCheck the tides")
+        );
+    }
+}
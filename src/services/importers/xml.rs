@@ -0,0 +1,293 @@
+use crate::models::{ManuscriptError, ManuscriptResult};
+use quick_xml::{events::Event, Reader};
+
+/// A node inside a parsed XML tree. Text is kept in place so that
+/// mixed content (like XHTML paragraphs) can be walked in document order
+#[derive(Debug, Clone)]
+pub enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+/// A minimal, owned representation of an XML element. Names are stored without
+/// their namespace prefix, which is all the importers need
+#[derive(Debug, Clone, Default)]
+pub struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+impl XmlElement {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn nodes(&self) -> &Vec<XmlNode> {
+        self.children.as_ref()
+    }
+
+    /// Iterates over direct children elements, skipping text nodes
+    pub fn children(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|node| match node {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    /// Gets the first direct child named `name`
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children().find(|c| c.is(name))
+    }
+
+    /// Gets all the direct children named `name`
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children().filter(move |c| c.is(name))
+    }
+
+    /// Gets the first element named `name` found in this subtree, depth first
+    pub fn find(&self, name: &str) -> Option<&XmlElement> {
+        for child in self.children() {
+            if child.is(name) {
+                return Some(child);
+            }
+            if let Some(found) = child.find(name) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Concatenates all the text contained in this subtree
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for node in self.children.iter() {
+            match node {
+                XmlNode::Text(value) => text.push_str(value),
+                XmlNode::Element(element) => text.push_str(element.text().as_str()),
+            }
+        }
+        text
+    }
+
+    /// Gets the trimmed text of the direct child named `name`, if
+    /// that child exists and its text is not blank
+    pub fn child_text(&self, name: &str) -> Option<String> {
+        self.child(name)
+            .map(|c| c.text().trim().to_string())
+            .filter(|t| !t.is_empty())
+    }
+}
+
+fn resolve_entity(entity: &str) -> Option<&'static str> {
+    // XHTML documents often carry named entities that plain XML does not know about
+    match entity {
+        "nbsp" => Some("\u{a0}"),
+        "mdash" => Some("—"),
+        "ndash" => Some("–"),
+        "hellip" => Some("…"),
+        "lsquo" => Some("‘"),
+        "rsquo" => Some("’"),
+        "ldquo" => Some("“"),
+        "rdquo" => Some("”"),
+        "laquo" => Some("«"),
+        "raquo" => Some("»"),
+        "copy" => Some("©"),
+        "shy" => Some(""),
+        _ => None,
+    }
+}
+
+fn local_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_string()
+}
+
+/// Parses `source` into an owned tree and returns its root element
+pub fn parse(source: &str) -> ManuscriptResult<XmlElement> {
+    let mut reader = Reader::from_str(source);
+    reader.trim_text(false);
+
+    let parse_error =
+        |err: quick_xml::Error| ManuscriptError::Import(format!("Malformed XML: {err}"));
+    let mut stack: Vec<XmlElement> = vec![XmlElement::default()];
+
+    loop {
+        match reader.read_event().map_err(parse_error)? {
+            Event::Start(start) => {
+                let mut element = XmlElement {
+                    name: local_name(start.local_name().as_ref()),
+                    ..Default::default()
+                };
+                for attribute in start.attributes().flatten() {
+                    let key = local_name(attribute.key.local_name().as_ref());
+                    let value = attribute
+                        .unescape_value()
+                        .map(|v| v.to_string())
+                        .unwrap_or_default();
+                    element.attributes.push((key, value));
+                }
+                stack.push(element);
+            }
+            Event::Empty(start) => {
+                let mut element = XmlElement {
+                    name: local_name(start.local_name().as_ref()),
+                    ..Default::default()
+                };
+                for attribute in start.attributes().flatten() {
+                    let key = local_name(attribute.key.local_name().as_ref());
+                    let value = attribute
+                        .unescape_value()
+                        .map(|v| v.to_string())
+                        .unwrap_or_default();
+                    element.attributes.push((key, value));
+                }
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(XmlNode::Element(element));
+            }
+            Event::End(_) if stack.len() > 1 => {
+                let element = stack.pop().unwrap();
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(XmlNode::Element(element));
+            }
+            Event::Text(text) => {
+                let value = text
+                    .unescape_with(resolve_entity)
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(text.as_ref()).to_string());
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(XmlNode::Text(value));
+            }
+            Event::CData(data) => {
+                let value = String::from_utf8_lossy(data.into_inner().as_ref()).to_string();
+                stack
+                    .last_mut()
+                    .unwrap()
+                    .children
+                    .push(XmlNode::Text(value));
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    // Unclosed elements are tolerated and attached to their parents
+    while stack.len() > 1 {
+        let element = stack.pop().unwrap();
+        stack
+            .last_mut()
+            .unwrap()
+            .children
+            .push(XmlNode::Element(element));
+    }
+
+    stack
+        .pop()
+        .unwrap()
+        .children
+        .into_iter()
+        .find_map(|node| match node {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+        .ok_or(ManuscriptError::Import("Empty XML document".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINDER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ScrivenerProject xmlns:sc="http://example.com/scrivener" Version="2.0">
+  <Binder>
+    <BinderItem UUID="D1" Type="DraftFolder">
+      <Title>  Draft  </Title>
+      <Children>
+        <BinderItem UUID="C1" Type="Text">
+          <Title>Rock &amp; Roll &mdash; Part&nbsp;1</Title>
+          <sc:Synopsis><![CDATA[<b>Raw</b> text]]></sc:Synopsis>
+          <MetaData><IncludeInCompile/></MetaData>
+        </BinderItem>
+      </Children>
+    </BinderItem>
+    <BinderItem UUID="T1" Type="TrashFolder"><Title> </Title></BinderItem>
+  </Binder>
+</ScrivenerProject>"#;
+
+    #[test]
+    fn elements_and_attributes() {
+        let root = parse(BINDER).unwrap();
+        assert_eq!(root.name(), "ScrivenerProject");
+        assert_eq!(root.attribute("version"), Some("2.0"));
+
+        let binder = root.child("binder").unwrap();
+        let items: Vec<&str> = binder
+            .children_named("BinderItem")
+            .filter_map(|item| item.attribute("Type"))
+            .collect();
+        assert_eq!(items, vec!["DraftFolder", "TrashFolder"]);
+        assert!(binder.child("Collections").is_none());
+    }
+
+    #[test]
+    fn text_content() {
+        let root = parse(BINDER).unwrap();
+        let draft = root.find("BinderItem").unwrap();
+        assert_eq!(draft.child_text("Title").as_deref(), Some("Draft"));
+        // Blank children count as missing
+        let trash = root.child("Binder").unwrap().children().nth(1).unwrap();
+        assert_eq!(trash.child_text("Title"), None);
+
+        let chapter = draft.find("BinderItem").unwrap();
+        assert_eq!(chapter.attribute("UUID"), Some("C1"));
+        assert_eq!(
+            chapter.child_text("Title").as_deref(),
+            Some("Rock & Roll — Part\u{a0}1")
+        );
+        // Namespace prefixes are dropped, CDATA is kept verbatim
+        assert_eq!(
+            chapter.child_text("Synopsis").as_deref(),
+            Some("<b>Raw</b> text")
+        );
+        assert!(chapter.find("IncludeInCompile").is_some());
+    }
+
+    #[test]
+    fn mixed_content() {
+        let root = parse("<p>Some <em>emphasis</em> here</p>").unwrap();
+        assert_eq!(root.nodes().len(), 3);
+        assert!(matches!(&root.nodes()[1], XmlNode::Element(e) if e.is("em")));
+        assert_eq!(root.text(), "Some emphasis here");
+    }
+
+    #[test]
+    fn malformed_documents() {
+        // Unclosed elements are attached to their parents
+        let root = parse("<Binder><BinderItem><Title>Open").unwrap();
+        assert_eq!(
+            root.find("Title").map(|t| t.text()).as_deref(),
+            Some("Open")
+        );
+        assert!(parse("   ").is_err());
+        assert!(parse("<a></b>").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use super::{extension_of, paragraphs, xml, ChapterBuilder, DocumentImporter, SceneMetadata};
+use crate::{models::*, services::i18n::i18n};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{collections::HashMap, fs, path::Path};
+
+/// Imports yWriter 7 projects
+pub struct YWriterImporter;
+
+impl DocumentImporter for YWriterImporter {
+    fn accepts(&self, path: &Path) -> bool {
+        extension_of(path).as_deref() == Some("yw7")
+    }
+
+    fn import(&self, path: &Path) -> ManuscriptResult<Document> {
+        let source =
+            fs::read_to_string(path).or(Err(ManuscriptError::Open(path.display().to_string())))?;
+        let root = xml::parse(source.as_str())?;
+        let mut document = Document::default();
+
+        if let Some(project) = root.child("PROJECT") {
+            document.set_title(project.child_text("Title"));
+            if let Some(author) = project.child_text("AuthorName") {
+                document.manifest_mut().set_author(author);
+            }
+        }
+
+        let characters = import_characters(&root, &mut document);
+
+        let scenes: HashMap<String, &xml::XmlElement> = root
+            .child("SCENES")
+            .into_iter()
+            .flat_map(|scenes| scenes.children_named("SCENE"))
+            .filter_map(|scene| scene.child_text("ID").map(|id| (id, scene)))
+            .collect();
+
+        let chapters = root
+            .child("CHAPTERS")
+            .into_iter()
+            .flat_map(|chapters| chapters.children_named("CHAPTER"));
+
+        for (priority, chapter) in chapters.enumerate() {
+            let mut builder = ChapterBuilder::new(chapter.child_text("Title"));
+
+            let scene_ids = chapter
+                .child("Scenes")
+                .into_iter()
+                .flat_map(|s| s.children_named("ScID"));
+            for scene_id in scene_ids {
+                if let Some(scene) = scenes.get(scene_id.text().trim()) {
+                    let content = scene
+                        .child_text("SceneContent")
+                        .map(|c| to_markdown(c.as_str()))
+                        .unwrap_or_default();
+                    builder.push_scene(content.as_str(), scene_metadata(scene, &characters));
+                }
+            }
+
+            let is_unused = chapter.child_text("Unused").is_some();
+            // Chapter types other than 0 are notes and to-do chapters
+            let is_notes = chapter
+                .child_text("ChapterType")
+                .or(chapter.child_text("Type"))
+                .map(|t| t != "0")
+                .unwrap_or(false);
+
+            let chunk = builder.chapter_mut();
+            chunk.set_synopsis(chapter.child_text("Desc"));
+            chunk.set_priority(Some(priority as u64));
+            let _ = chunk.set_include_in_compilation(!(is_unused || is_notes));
+
+            builder.add_to(&mut document);
+        }
+
+        Ok(document)
+    }
+}
+
+/// Creates a character sheet for each character and returns
+/// a map between yWriter character ids and the ids of their sheets
+fn import_characters(root: &xml::XmlElement, document: &mut Document) -> HashMap<String, String> {
+    let mut sheets = HashMap::new();
+    let characters = root
+        .child("CHARACTERS")
+        .into_iter()
+        .flat_map(|c| c.children_named("CHARACTER"));
+
+    for (priority, character) in characters.enumerate() {
+        let name = character
+            .child_text("FullName")
+            .or(character.child_text("Title"));
+        let mut sheet = CharacterSheet::default();
+        if let Some(id) = character.child_text("ID") {
+            sheets.insert(id, sheet.id().to_string());
+        }
+        sheet.set_name(name);
+        sheet.set_role(Some(if character.child_text("Major").is_some() {
+            i18n("Major character")
+        } else {
+            i18n("Minor character")
+        }));
+        sheet.set_physical_traits(
+            paragraphs(character.child_text("Desc").unwrap_or_default().as_str()).as_bytes(),
+        );
+        sheet.set_psycological_traits(
+            paragraphs(character.child_text("Goals").unwrap_or_default().as_str()).as_bytes(),
+        );
+
+        let background = [character.child_text("Bio"), character.child_text("Notes")]
+            .into_iter()
+            .flatten()
+            .map(|text| paragraphs(text.as_str()))
+            .collect::<Vec<String>>()
+            .join("\n\n");
+        sheet.set_background(background.as_bytes());
+        sheet.set_priority(Some(priority as u64));
+
+        document.add_chunk(sheet);
+    }
+
+    sheets
+}
+
+fn scene_metadata(scene: &xml::XmlElement, characters: &HashMap<String, String>) -> SceneMetadata {
+    // yWriter has no explicit viewpoint, the first character of a scene is used instead
+    let point_of_view = scene
+        .child("Characters")
+        .and_then(|c| c.child_text("CharID"))
+        .and_then(|id| characters.get(&id).cloned());
+
+    let status = scene
+        .child_text("Status")
+        .and_then(|status| match status.as_str() {
+            "1" => Some(i18n("Outline")),
+            "2" => Some(i18n("Draft")),
+            "3" => Some(i18n("1st Edit")),
+            "4" => Some(i18n("2nd Edit")),
+            "5" => Some(i18n("Done")),
+            _ => None,
+        });
+
+    let description = [scene.child_text("Desc"), scene.child_text("Notes")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    SceneMetadata {
+        title: scene.child_text("Title"),
+        point_of_view,
+        status,
+        description: Some(description).filter(|d| !d.is_empty()),
+        unused: scene.child_text("Unused").is_some(),
+    }
+}
+
+lazy_static! {
+    static ref COMMENT: Regex = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    static ref MARKUP: Regex = Regex::new(r"\[/?([ibs])\]").unwrap();
+}
+
+/// Converts yWriter scene markup to markdown
+fn to_markdown(content: &str) -> String {
+    let content = COMMENT.replace_all(content, "");
+    let content = MARKUP.replace_all(&content, |captures: &regex::Captures| {
+        match &captures[1] {
+            "b" => "**",
+            "s" => "~~",
+            _ => "*",
+        }
+        .to_string()
+    });
+    paragraphs(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn characters() -> HashMap<String, String> {
+        HashMap::from([
+            ("1".to_string(), "01ADA".to_string()),
+            ("2".to_string(), "01BRAND".to_string()),
+        ])
+    }
+
+    fn metadata(scene: &str) -> SceneMetadata {
+        scene_metadata(&xml::parse(scene).unwrap(), &characters())
+    }
+
+    #[test]
+    fn point_of_view_is_the_first_character() {
+        let scene = metadata(
+            "<SCENE><Title>Harbour</Title>\
+             <Characters><CharID>2</CharID><CharID>1</CharID></Characters></SCENE>",
+        );
+        assert_eq!(scene.title.as_deref(), Some("Harbour"));
+        assert_eq!(scene.point_of_view.as_deref(), Some("01BRAND"));
+
+        let unknown = metadata("<SCENE><Characters><CharID>9</CharID></Characters></SCENE>");
+        assert_eq!(unknown.point_of_view, None);
+        assert_eq!(metadata("<SCENE/>").point_of_view, None);
+    }
+
+    #[test]
+    fn status() {
+        assert_eq!(
+            metadata("<SCENE><Status>2</Status></SCENE>")
+                .status
+                .as_deref(),
+            Some("Draft")
+        );
+        assert_eq!(
+            metadata("<SCENE><Status>5</Status></SCENE>")
+                .status
+                .as_deref(),
+            Some("Done")
+        );
+        assert_eq!(metadata("<SCENE><Status>7</Status></SCENE>").status, None);
+    }
+
+    #[test]
+    fn unused_scenes() {
+        let scene = metadata("<SCENE><Status>5</Status><Unused>-1</Unused></SCENE>");
+        assert_eq!(scene.status.as_deref(), Some("Done"));
+        assert!(scene.unused);
+        assert!(!metadata("<SCENE/>").unused);
+    }
+
+    #[test]
+    fn description() {
+        let scene = metadata("<SCENE><Desc>Arrival</Desc><Notes>Check dates</Notes></SCENE>");
+        assert_eq!(scene.description.as_deref(), Some("Arrival\n\nCheck dates"));
+        assert_eq!(metadata("<SCENE/>").description, None);
+    }
+
+    #[test]
+    fn markup() {
+        assert_eq!(
+            to_markdown("Some [b]bold[/b], [i]italic[/i] /* hidden */and [s]gone[/s]\nNext"),
+            "Some **bold**, *italic* and ~~gone~~\n\nNext"
+        );
+    }
+}
@@ -1,10 +1,11 @@
 pub mod analyst;
 mod document_manager;
 pub mod i18n;
+pub mod importers;
 pub mod prelude;
+mod rich_text_serde;
 mod settings;
-// mod rich_text_serde;
 
 pub use document_manager::*;
+pub use rich_text_serde::*;
 pub use settings::*;
-// pub use rich_text_serde::*;
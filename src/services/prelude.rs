@@ -49,3 +49,16 @@ pub fn bytes_from_text_buffer(source: &gtk::TextBuffer) -> Bytes {
     let end_iter = source.end_iter();
     Bytes::from(source.text(&start_iter, &end_iter, true).to_string())
 }
+
+/// Decodes an image stored as raw file bytes into a texture
+/// that can be shown by a `gtk::Picture`
+pub fn texture_from_bytes(source: &[u8]) -> Option<gtk::gdk::Texture> {
+    use gtk::gdk_pixbuf::{prelude::PixbufLoaderExt, PixbufLoader};
+
+    let loader = PixbufLoader::new();
+    loader.write(source).ok()?;
+    loader.close().ok()?;
+    loader
+        .pixbuf()
+        .map(|pixbuf| gtk::gdk::Texture::for_pixbuf(&pixbuf))
+}
@@ -1,80 +1,68 @@
-use bytes::{BufMut, Bytes, BytesMut};
-use gtk::prelude::{TextBufferExt, TextTagExt};
+use crate::{libs::consts::PERSISTENT_TAG_NAMES, models::TagMark, services::prelude::*};
+use bytes::Bytes;
+use gtk::prelude::TextBufferExt;
 
-struct RichTextSerde;
+pub struct RichTextSerde;
 
 impl RichTextSerde {
-    /// A function that serializes a source `gtk::TextBuffer` into raw bytes while carrying informations
-    /// about that buffer tags with it.
-    fn serialize_buffer_with_tags(source: &gtk::TextBuffer) -> Bytes {
-        let mut tags_stack = vec![];
-        let mut bytes = BytesMut::new();
-        let mut cursor = source.start_iter();
-        let mut counter: u64 = 0;
+    /// Serializes a source `gtk::TextBuffer` into its raw text, along with the marks of
+    /// the tags that are not derived from that text (see `PERSISTENT_TAG_NAMES`)
+    pub fn serialize_buffer_with_tags(source: &gtk::TextBuffer) -> (Bytes, Vec<TagMark>) {
+        let mut marks = vec![];
 
-        while !cursor.is_end() {
-            if cursor.starts_tag(None::<&gtk::TextTag>) {
-                let toggled_tags = cursor.toggled_tags(true);
-                for tag in toggled_tags {
-                    match tag.name() {
-                        Some(name) => {
-                            // Only consider non-anonymous tags...
-                            match source.tag_table().lookup(name.as_str()) {
-                                Some(existing_tag) => {
-                                    // ... and only if they exist in the buffer tag table
-                                    tags_stack.push(existing_tag);
-                                    bytes.put_slice(format!("<{}>", name).as_bytes());
-                                }
-                                None => (),
-                            }
-                        }
-                        None => (),
-                    }
+        for name in PERSISTENT_TAG_NAMES.iter() {
+            // Only consider tags that exist in the buffer tag table
+            let Some(tag) = source.tag_table().lookup(name) else {
+                continue;
+            };
+
+            let mut cursor = source.start_iter();
+            let mut opened_at = if cursor.has_tag(&tag) {
+                Some(cursor.offset())
+            } else {
+                None
+            };
+
+            while cursor.forward_to_tag_toggle(Some(&tag)) {
+                if cursor.starts_tag(Some(&tag)) {
+                    opened_at = Some(cursor.offset());
+                } else if let Some(start) = opened_at.take() {
+                    marks.push(TagMark::new(start, cursor.offset(), name));
                 }
             }
 
-            check_closing_tags(counter, &mut bytes, &mut cursor, &mut tags_stack);
-            put_char_into_buffer(cursor.char(), bytes);
-
-            cursor.forward_char();
-            counter += 1;
+            // A tag running up to the end of the buffer is never toggled off
+            if let Some(start) = opened_at {
+                marks.push(TagMark::new(start, source.end_iter().offset(), name));
+            }
         }
 
-        check_closing_tags(counter, &mut bytes, &mut cursor, &mut tags_stack);
-
-        bytes.freeze()
+        (bytes_from_text_buffer(source), marks)
     }
 
-    fn deserialize_buffer_with_tags(source: Bytes) -> gtk::TextBuffer {
-        todo!()
-    }
-}
+    /// Sets `source` as the text of `target`, applying `tags` back on it. Marks of tags
+    /// that do not exist in the `target` tag table are ignored
+    pub fn deserialize_buffer_with_tags(
+        target: &gtk::TextBuffer,
+        source: &Bytes,
+        tags: &[TagMark],
+    ) {
+        target.set_text(String::from_utf8_lossy(source).as_ref());
 
-fn check_closing_tags(
-    counter: u64,
-    bytes: &mut BytesMut,
-    cursor: &mut gtk::TextIter,
-    stack: &mut Vec<gtk::TextTag>,
-) {
-    if cursor.ends_tag(None::<&gtk::TextTag>) {
-        let mut closed_tags = cursor.toggled_tags(false);
-        while !closed_tags.is_empty() {
-            // Get the current closing tag from the list, remove it from the list...
-            closed_tags.pop();
+        let len = target.char_count();
+        for mark in tags.iter() {
+            let Some(tag) = target.tag_table().lookup(mark.name()) else {
+                glib::g_warning!("RichTextSerde", "Tag not supported: {}", mark.name());
+                continue;
+            };
 
-            if !stack.is_empty() {
-                // ... and pop it from the stack
-                let tag_to_close = stack.pop().unwrap();
-                bytes.put_slice(format!("</{}>", tag_to_close.name().unwrap()).as_bytes());
-            } else {
-                glib::g_warning!("check_closing_tags", "Be aware that the tag stack is null or zero sized when trying to pop an item. This is probably an error because this is called when an iter is closing some tag that should be present on the stack.");
-            }
+            let start = mark.start().clamp(0, len);
+            let end = mark.end().clamp(start, len);
+            target.apply_tag(
+                &tag,
+                &target.iter_at_offset(start),
+                &target.iter_at_offset(end),
+            );
         }
     }
 }
-
-fn put_char_into_buffer(ch: char, buf: &mut BytesMut) {
-    let mut b: Vec<u8> = Vec::with_capacity(ch.len_utf8());
-    ch.encode_utf8(&mut b);
-    buf.put_slice(b.as_slice());
-}
@@ -2,6 +2,9 @@ use crate::config::{APPLICATION_ID, G_LOG_DOMAIN};
 use gtk::gio::prelude::{SettingsExt, SettingsExtManual};
 use gtk::glib::IsA;
 
+/// How many documents are listed on the welcome screen
+const MAX_RECENT_DOCUMENTS: usize = 8;
+
 #[derive(Debug, Clone)]
 pub struct ManuscriptSettings {
     inner: gtk::gio::Settings,
@@ -56,6 +59,43 @@ impl ManuscriptSettings {
             .expect("Could not store last opened document");
     }
 
+    /// Absolute paths of the most recently opened documents, latest first
+    pub fn recent_documents(&self) -> Vec<String> {
+        self.inner
+            .strv("recent-documents")
+            .iter()
+            .map(|path| path.to_string())
+            .collect()
+    }
+
+    /// Moves `path` at the top of the recent documents, keeping at most
+    /// [`MAX_RECENT_DOCUMENTS`] of them
+    pub fn add_recent_document(&self, path: &str) {
+        let mut recent = self.recent_documents();
+        recent.retain(|p| p != path);
+        recent.insert(0, path.to_string());
+        recent.truncate(MAX_RECENT_DOCUMENTS);
+        let recent: Vec<&str> = recent.iter().map(String::as_str).collect();
+        self.inner
+            .set_strv("recent-documents", recent.as_slice())
+            .expect("Could not store recent documents");
+    }
+
+    /// Names of the columns shown by the outliner
+    pub fn outliner_columns(&self) -> Vec<String> {
+        self.inner
+            .strv("outliner-columns")
+            .iter()
+            .map(|column| column.to_string())
+            .collect()
+    }
+
+    pub fn set_outliner_columns(&self, value: &[&str]) {
+        self.inner
+            .set_strv("outliner-columns", value)
+            .expect("Could not store outliner columns");
+    }
+
     pub fn text_analysis_delay(&self) -> i32 {
         self.inner.int("text-analysis-delay")
     }
@@ -76,6 +116,10 @@ impl ManuscriptSettings {
             .expect("Could not store chunk-props-panel-visible");
     }
 
+    pub fn highlight_character_names(&self) -> bool {
+        self.inner.boolean("highlight-character-names")
+    }
+
     pub fn connect_changed<F>(&self, key: &str, f: F)
     where
         F: Fn(&gtk::gio::Settings, &str) + 'static,
@@ -1,16 +1,28 @@
-use crate::{models::*, services::i18n::i18n, widgets::ManuscriptProjectLayoutChunkContainer};
+use crate::{
+    models::*,
+    services::{
+        i18n::{i18n, ni18n},
+        prelude::texture_from_bytes,
+    },
+    widgets::ManuscriptProjectLayoutChunkContainer,
+};
 use adw::prelude::{ActionRowExt, PreferencesRowExt};
 use adw::subclass::prelude::*;
+use bytes::Bytes;
 use glib_macros::Properties;
-use gtk::{gio, glib, prelude::*};
+use gtk::{gdk, gio, glib, prelude::*};
 use std::cell::{Cell, RefCell};
 
 #[allow(unused)]
 const G_LOG_DOMAIN: &str = "ManuscriptChunkRow";
 
+/// Horizontal space, in pixels, added for each level of nesting
+const INDENT_WIDTH: u32 = 18;
+
 mod imp {
     use super::*;
-    use glib::ParamSpec;
+    use glib::{subclass::signal::Signal, ParamSpec};
+    use once_cell::sync::Lazy;
 
     /// A widget to display as a row entry in the project layout in place of a
     /// project chunk
@@ -21,6 +33,24 @@ mod imp {
         #[template_child]
         pub(super) lock_icon: TemplateChild<gtk::Image>,
 
+        #[template_child]
+        pub(super) indent_box: TemplateChild<gtk::Box>,
+
+        #[template_child]
+        pub(super) expander_button: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub(super) thumbnail: TemplateChild<gtk::Image>,
+
+        #[template_child]
+        pub(super) status_badge: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub(super) labels_badge: TemplateChild<gtk::Label>,
+
+        /// Image currently decoded into the thumbnail
+        pub(super) thumbnail_source: RefCell<Option<Bytes>>,
+
         #[property(name = "parent-container", get, set, nullable)]
         pub(super) parent_container: RefCell<Option<ManuscriptProjectLayoutChunkContainer>>,
 
@@ -41,6 +71,23 @@ mod imp {
         #[property(name = "accent", get, set)]
         pub(super) accent: RefCell<Option<Color>>,
 
+        /// Id of the chunk this row is nested under in the outline
+        #[property(name = "parent-chunk-id", get, set)]
+        pub(super) parent_chunk_id: RefCell<String>,
+
+        /// Position of this row in the depth first walk of the outline
+        #[property(name = "position", get, set)]
+        pub(super) position: Cell<u32>,
+
+        #[property(name = "depth", get, set = Self::set_depth)]
+        pub(super) depth: Cell<u32>,
+
+        #[property(name = "has-children", get, set)]
+        pub(super) has_children: Cell<bool>,
+
+        #[property(name = "expanded", get, set = Self::set_expanded)]
+        pub(super) expanded: Cell<bool>,
+
         pub(super) style_provider: gtk::CssProvider,
     }
 
@@ -52,6 +99,22 @@ mod imp {
 
             self.select_mode.set(value)
         }
+
+        pub fn set_depth(&self, value: u32) {
+            self.depth.set(value);
+            self.indent_box.set_visible(true);
+            self.indent_box
+                .set_margin_start((value * INDENT_WIDTH) as i32);
+        }
+
+        pub fn set_expanded(&self, value: bool) {
+            self.expanded.set(value);
+            self.expander_button.set_icon_name(if value {
+                "pan-down-symbolic"
+            } else {
+                "pan-end-symbolic"
+            });
+        }
     }
 
     #[glib::object_subclass]
@@ -71,6 +134,30 @@ mod imp {
     }
 
     impl ObjectImpl for ManuscriptChunkRow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_reordering();
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    // Asks to move the chunk up or down among its siblings by
+                    // a number of places
+                    Signal::builder("move-by")
+                        .param_types([i32::static_type()])
+                        .build(),
+                    // Asks to place the dragged chunk with the given id before
+                    // this one, or after it if set
+                    Signal::builder("chunk-dropped")
+                        .param_types([String::static_type(), bool::static_type()])
+                        .build(),
+                ]
+            });
+
+            SIGNALS.as_ref()
+        }
+
         fn properties() -> &'static [gtk::glib::ParamSpec] {
             Self::derived_properties()
         }
@@ -103,6 +190,7 @@ impl ManuscriptChunkRow {
         let obj: Self = glib::Object::builder()
             .property("parent-container", parent)
             .property("select-mode", false)
+            .property("expanded", true)
             .build();
         obj.init(chunk);
         obj
@@ -111,8 +199,8 @@ impl ManuscriptChunkRow {
     fn init(&self, chunk: Option<&dyn DocumentChunk>) {
         self.update_chunk(chunk);
         if let Some(chunk) = chunk {
-            if let Some(chapter) = chunk.as_any().downcast_ref::<Chapter>() {
-                self.update_chunk_reading_stats(chapter, chapter.words_count());
+            if let Some(buffer_chunk) = chunk.as_buffer_chunk() {
+                self.update_chunk_reading_stats(chunk, buffer_chunk.words_count());
             }
         }
     }
@@ -128,12 +216,30 @@ impl ManuscriptChunkRow {
 
                 if let Some(character_sheet) = chunk.as_any().downcast_ref::<CharacterSheet>() {
                     self.set_subtitle(character_sheet.role().unwrap_or(&i18n("No role")).as_str());
+                    self.set_thumbnail(character_sheet.portrait());
+                } else if let Some(location) = chunk.as_any().downcast_ref::<LocationSheet>() {
+                    self.set_subtitle(location.kind().map(String::as_str).unwrap_or_default());
+                } else if let Some(research) = chunk.as_any().downcast_ref::<Research>() {
+                    let count = research.attachments().len() as u32;
+                    self.set_subtitle(
+                        ni18n("{} attachment", "{} attachments", count)
+                            .replace("{}", count.to_string().as_str())
+                            .as_str(),
+                    );
+                } else if let Some(lore_entry) = chunk.as_any().downcast_ref::<LoreEntry>() {
+                    self.set_subtitle(
+                        lore_entry
+                            .category()
+                            .map(String::as_str)
+                            .unwrap_or_default(),
+                    );
                 }
             } else {
                 *borrow = "".into();
                 self.set_title("");
                 self.set_subtitle("");
                 self.lock_icon().set_visible(false);
+                self.set_thumbnail(None);
             }
         } else {
             glib::g_warning!(G_LOG_DOMAIN, "Could not borrow chunk_id cell");
@@ -157,12 +263,121 @@ impl ManuscriptChunkRow {
         }
     }
 
+    /// Shows a small version of `image` before the title, decoding it only
+    /// when it changed
+    fn set_thumbnail(&self, image: Option<&Bytes>) {
+        let imp = self.imp();
+        if imp.thumbnail_source.borrow().as_ref() == image {
+            return;
+        }
+
+        *imp.thumbnail_source.borrow_mut() = image.cloned();
+        let texture = image.and_then(|bytes| texture_from_bytes(bytes.as_ref()));
+        imp.thumbnail.set_paintable(texture.as_ref());
+        imp.thumbnail.set_visible(texture.is_some());
+    }
+
+    /// Shows the workflow status and the labels of `chunk`, looking their
+    /// names and colors up in `manifest`
+    pub fn update_badges(&self, manifest: &DocumentManifest, chunk: &dyn DocumentChunk) {
+        let imp = self.imp();
+        let buffer_chunk = chunk.as_buffer_chunk();
+
+        let status = buffer_chunk
+            .and_then(|c| c.status())
+            .and_then(|id| manifest.status(id));
+        imp.status_badge
+            .set_label(status.map(|s| s.name()).unwrap_or_default());
+        imp.status_badge.set_visible(status.is_some());
+
+        let labels: Vec<&ChunkLabel> = buffer_chunk
+            .map(|c| c.labels().as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|id| manifest.label(id))
+            .collect();
+        let markup: String = labels
+            .iter()
+            .map(|l| format!("<span foreground=\"{}\">●</span>", l.color().hex()))
+            .collect();
+        let names: Vec<&str> = labels.iter().map(|l| l.name()).collect();
+        imp.labels_badge.set_markup(markup.as_str());
+        imp.labels_badge
+            .set_tooltip_text(Some(names.join(", ").as_str()));
+        imp.labels_badge.set_visible(!labels.is_empty());
+    }
+
+    /// Dims the row when its chunk is left out of the compiled manuscript,
+    /// either by itself or through one of its parents
+    pub fn set_compiled(&self, value: bool) {
+        if value {
+            self.remove_css_class("dim-label");
+        } else {
+            self.add_css_class("dim-label");
+        }
+    }
+
     pub fn update_chunk_reading_stats(&self, chunk: &dyn DocumentChunk, words_count: u64) {
-        if chunk.as_any().downcast_ref::<Chapter>().is_some() {
+        // Rows with nested chunks show the rolled up count instead
+        if chunk.as_buffer_chunk().is_some() && !self.has_children() {
             self.set_subtitle(format!("{} {}", words_count, i18n("words")).as_str());
         }
     }
 
+    /// Shows the words count of this chunk and everything nested in it
+    pub fn update_rolled_up_reading_stats(&self, words_count: u64) {
+        self.set_subtitle(format!("{} {}", words_count, i18n("words")).as_str());
+    }
+
+    /// Lets the row be dragged onto other rows, or moved with Alt+Up and
+    /// Alt+Down while focused
+    fn setup_reordering(&self) {
+        let drag_source = gtk::DragSource::builder()
+            .actions(gdk::DragAction::MOVE)
+            .build();
+        drag_source.connect_prepare(
+            glib::clone!(@weak self as this => @default-return None, move |_, _, _| {
+                if this.select_mode() {
+                    return None;
+                }
+                Some(gdk::ContentProvider::for_value(&this.chunk_id().to_value()))
+            }),
+        );
+        drag_source.connect_drag_begin(glib::clone!(@weak self as this => move |source, _| {
+            source.set_icon(Some(&gtk::WidgetPaintable::new(Some(&this))), 0, 0);
+        }));
+        self.add_controller(drag_source);
+
+        let drop_target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+        drop_target.connect_drop(
+            glib::clone!(@weak self as this => @default-return false, move |_, value, _, y| {
+                let Ok(dragged) = value.get::<String>() else {
+                    return false;
+                };
+                if dragged == this.chunk_id() {
+                    return false;
+                }
+
+                let after = y > this.height() as f64 / 2.0;
+                this.emit_by_name::<()>("chunk-dropped", &[&dragged, &after]);
+                true
+            }),
+        );
+        self.add_controller(drop_target);
+
+        let shortcuts = gtk::ShortcutController::new();
+        for (trigger, offset) in [("<Alt>Up", -1), ("<Alt>Down", 1)] {
+            shortcuts.add_shortcut(gtk::Shortcut::new(
+                gtk::ShortcutTrigger::parse_string(trigger),
+                Some(gtk::CallbackAction::new(move |widget, _| {
+                    widget.emit_by_name::<()>("move-by", &[&offset]);
+                    true
+                })),
+            ));
+        }
+        self.add_controller(shortcuts);
+    }
+
     fn lock_icon(&self) -> gtk::Image {
         self.imp().lock_icon.get()
     }
@@ -0,0 +1,278 @@
+use crate::{
+    models::*,
+    services::{i18n::i18n, DocumentAction},
+};
+use adw::subclass::prelude::*;
+use gtk::{gdk, gio, glib, glib::Sender, prelude::*};
+use std::cell::RefCell;
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptCorkboard";
+
+const CARD_WIDTH: i32 = 220;
+const CARD_HEIGHT: i32 = 160;
+
+/// What a card shows of its chunk, telling whether it has to be built again
+#[derive(PartialEq)]
+struct CardContent {
+    id: String,
+    title: String,
+    status: Option<String>,
+    synopsis: Option<String>,
+    /// Style tinting the card with the accent of the chunk
+    style: Option<String>,
+}
+
+impl CardContent {
+    fn new(manifest: &DocumentManifest, chunk: &dyn DocumentChunk) -> Self {
+        let buffer_chunk = chunk.as_buffer_chunk();
+        Self {
+            id: chunk.id().to_string(),
+            title: chunk.safe_title(),
+            status: buffer_chunk
+                .and_then(|c| c.status())
+                .and_then(|id| manifest.status(id))
+                .map(|status| status.name().to_string()),
+            synopsis: buffer_chunk.and_then(|c| c.synopsis()).cloned(),
+            style: chunk.accent().map(|color| {
+                format!(
+                    ".corkboard-card {{ background-color: {}; color: {}; }}",
+                    color,
+                    color.contrast_color()
+                )
+            }),
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    /// A grid of index cards, one for each chapter and scene of the
+    /// manuscript, that can be dragged around to reorder them
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/corkboard.ui")]
+    pub struct ManuscriptCorkboard {
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub(super) flow_box: TemplateChild<gtk::FlowBox>,
+
+        pub(super) sender: RefCell<Option<Sender<DocumentAction>>>,
+
+        /// What the cards show, in the same order
+        pub(super) cards: RefCell<Vec<CardContent>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptCorkboard {
+        const NAME: &'static str = "ManuscriptCorkboard";
+        type Type = super::ManuscriptCorkboard;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptCorkboard {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_events();
+        }
+    }
+
+    impl WidgetImpl for ManuscriptCorkboard {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptCorkboard(ObjectSubclass<imp::ManuscriptCorkboard>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl Default for ManuscriptCorkboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManuscriptCorkboard {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn set_channel(&self, sender: Sender<DocumentAction>) {
+        *self.imp().sender.borrow_mut() = Some(sender);
+    }
+
+    fn connect_events(&self) {
+        self.imp().flow_box.connect_child_activated(
+            glib::clone!(@weak self as this => move |_, child| {
+                let id = this
+                    .imp()
+                    .cards
+                    .borrow()
+                    .get(child.index() as usize)
+                    .map(|card| card.id.clone());
+                if let Some(id) = id {
+                    this.send_action(DocumentAction::SelectChunk(id));
+                }
+            }),
+        );
+    }
+
+    /// Shows a card for each chapter and scene of `document`, in outline order
+    pub fn load_document(&self, document: Option<&Document>) {
+        let imp = self.imp();
+        let flow_box = imp.flow_box.get();
+        while let Some(child) = flow_box.first_child() {
+            flow_box.remove(&child);
+        }
+
+        let mut cards = vec![];
+        if let Some(document) = document {
+            for (chunk, _) in document
+                .outline()
+                .into_iter()
+                .filter(|(c, _)| matches!(c.chunk_type(), ChunkType::Chapter | ChunkType::Scene))
+            {
+                let content = CardContent::new(document.manifest(), chunk);
+                flow_box.insert(&self.card(&content), -1);
+                cards.push(content);
+            }
+        }
+
+        imp.stack
+            .set_visible_child_name(if cards.is_empty() { "empty" } else { "cards" });
+        *imp.cards.borrow_mut() = cards;
+    }
+
+    /// Builds the card of `chunk` again, if it has one and what it shows changed
+    pub fn update_chunk(&self, manifest: &DocumentManifest, chunk: &dyn DocumentChunk) {
+        let imp = self.imp();
+        let content = CardContent::new(manifest, chunk);
+        let Some(index) = imp.cards.borrow().iter().position(|c| c.id == content.id) else {
+            return;
+        };
+        if imp.cards.borrow()[index] == content {
+            return;
+        }
+
+        let flow_box = imp.flow_box.get();
+        if let Some(child) = flow_box.child_at_index(index as i32) {
+            flow_box.remove(&child);
+        }
+        flow_box.insert(&self.card(&content), index as i32);
+        imp.cards.borrow_mut()[index] = content;
+    }
+
+    fn card(&self, card_content: &CardContent) -> gtk::Widget {
+        let card = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .width_request(CARD_WIDTH)
+            .height_request(CARD_HEIGHT)
+            .css_classes(["card", "corkboard-card"])
+            .build();
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+        card.append(&content);
+
+        let title = gtk::Label::builder()
+            .label(card_content.title.as_str())
+            .xalign(0.0)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(["heading"])
+            .build();
+        content.append(&title);
+
+        if let Some(status) = card_content.status.as_ref() {
+            let status = gtk::Label::builder()
+                .label(status.as_str())
+                .xalign(0.0)
+                .css_classes(["caption", "dim-label"])
+                .build();
+            content.append(&status);
+        }
+
+        let synopsis = gtk::Label::builder()
+            .label(
+                card_content
+                    .synopsis
+                    .clone()
+                    .unwrap_or_else(|| i18n("No synopsis")),
+            )
+            .xalign(0.0)
+            .yalign(0.0)
+            .vexpand(true)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .lines(5)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .build();
+        if card_content.synopsis.is_none() {
+            synopsis.add_css_class("dim-label");
+        }
+        content.append(&synopsis);
+
+        if let Some(style) = card_content.style.as_ref() {
+            let provider = gtk::CssProvider::new();
+            provider.load_from_data(style.as_str());
+            card.style_context()
+                .add_provider(&provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+        }
+
+        self.setup_drag_and_drop(&card, card_content.id.as_str());
+        card.upcast()
+    }
+
+    /// Lets `card` be dragged onto other cards, placing its chunk before or
+    /// after the chunk of the card it is dropped on
+    fn setup_drag_and_drop(&self, card: &gtk::Box, id: &str) {
+        let drag_source = gtk::DragSource::builder()
+            .actions(gdk::DragAction::MOVE)
+            .content(&gdk::ContentProvider::for_value(&id.to_value()))
+            .build();
+        drag_source.connect_drag_begin(glib::clone!(@weak card => move |source, _| {
+            let icon = gtk::WidgetPaintable::new(Some(&card));
+            source.set_icon(Some(&icon), card.width() / 2, card.height() / 2);
+        }));
+        card.add_controller(drag_source);
+
+        let drop_target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+        let target = id.to_string();
+        drop_target.connect_drop(
+            glib::clone!(@weak self as this, @weak card => @default-return false, move |_, value, x, _| {
+                let Ok(dragged) = value.get::<String>() else {
+                    return false;
+                };
+                if dragged == target {
+                    return false;
+                }
+
+                let after = x > card.width() as f64 / 2.0;
+                this.send_action(DocumentAction::MoveChunkNextTo(dragged, target.clone(), after));
+                true
+            }),
+        );
+        card.add_controller(drop_target);
+    }
+
+    fn send_action(&self, action: DocumentAction) {
+        if let Some(sender) = self.imp().sender.borrow().as_ref() {
+            sender.send(action).expect("Could not send action");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{models::AppearanceIndex, services::i18n::i18n};
+use adw::subclass::prelude::*;
+use gtk::{gio, glib, prelude::*};
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptAppearancesDialog";
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/dialogs/appearances_dialog.ui")]
+    pub struct ManuscriptAppearancesDialog {
+        #[template_child]
+        pub(super) grid: TemplateChild<gtk::Grid>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptAppearancesDialog {
+        const NAME: &'static str = "ManuscriptAppearancesDialog";
+        type Type = super::ManuscriptAppearancesDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptAppearancesDialog {}
+    impl WidgetImpl for ManuscriptAppearancesDialog {}
+    impl WindowImpl for ManuscriptAppearancesDialog {}
+    impl AdwWindowImpl for ManuscriptAppearancesDialog {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptAppearancesDialog(ObjectSubclass<imp::ManuscriptAppearancesDialog>)
+        @extends adw::Window, gtk::Window, gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl ManuscriptAppearancesDialog {
+    /// Shows the appearances in `index` as a grid of characters by chapters,
+    /// along with the longest absence of each character
+    pub fn new(parent: &gtk::Window, index: &AppearanceIndex) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        obj.fill(index);
+        obj
+    }
+
+    fn fill(&self, index: &AppearanceIndex) {
+        let grid = self.imp().grid.get();
+        let absence_column = index.chapters().len() as i32 + 1;
+
+        for (column, chapter) in index.chapters().iter().enumerate() {
+            let button = gtk::Button::builder()
+                .label((column + 1).to_string())
+                .tooltip_text(chapter.title())
+                .action_name("project.select-chunk")
+                .action_target(&chapter.id().to_variant())
+                .css_classes(["flat", "caption"])
+                .build();
+            grid.attach(&button, column as i32 + 1, 0, 1, 1);
+        }
+        grid.attach(
+            &gtk::Label::builder()
+                .label(i18n("Longest absence"))
+                .margin_start(12)
+                .css_classes(["heading"])
+                .build(),
+            absence_column,
+            0,
+            1,
+            1,
+        );
+
+        for (row, appearance) in index.appearances().iter().enumerate() {
+            let row = row as i32 + 1;
+            let name = gtk::Button::builder()
+                .label(appearance.name())
+                .tooltip_text(
+                    i18n("{count} mentions")
+                        .replace("{count}", appearance.mentions().to_string().as_str()),
+                )
+                .action_name("project.select-chunk")
+                .action_target(&appearance.character_id().to_variant())
+                .halign(gtk::Align::Start)
+                .css_classes(["flat"])
+                .build();
+            grid.attach(&name, 0, row, 1, 1);
+
+            for (column, chapter) in index.chapters().iter().enumerate() {
+                let mentions = appearance.mentions_in(column);
+                let cell = gtk::Box::builder()
+                    .valign(gtk::Align::Center)
+                    .halign(gtk::Align::Center)
+                    .tooltip_text(
+                        i18n("{chapter}: {count} mentions")
+                            .replace("{chapter}", chapter.title())
+                            .replace("{count}", mentions.to_string().as_str()),
+                    )
+                    .css_classes(["appearance-cell"])
+                    .build();
+                if mentions > 0 {
+                    cell.add_css_class("present");
+                }
+                grid.attach(&cell, column as i32 + 1, row, 1, 1);
+            }
+
+            let (chapters, words) = index.longest_absence(appearance.character_id());
+            let absence = if appearance.first_chapter().is_none() {
+                i18n("Never mentioned")
+            } else if chapters == 0 {
+                i18n("None")
+            } else {
+                i18n("{chapters} chapters, {words} words")
+                    .replace("{chapters}", chapters.to_string().as_str())
+                    .replace("{words}", words.to_string().as_str())
+            };
+            grid.attach(
+                &gtk::Label::builder()
+                    .label(absence)
+                    .xalign(0.0)
+                    .margin_start(12)
+                    .css_classes(["dim-label"])
+                    .build(),
+                absence_column,
+                row,
+                1,
+                1,
+            );
+        }
+    }
+}
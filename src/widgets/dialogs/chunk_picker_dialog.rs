@@ -0,0 +1,141 @@
+use crate::models::DocumentChunk;
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gio, glib};
+use std::cell::{Cell, RefCell};
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/dialogs/chunk_picker_dialog.ui")]
+    pub struct ManuscriptChunkPickerDialog {
+        #[template_child]
+        pub(super) chunks_list: TemplateChild<gtk::ListBox>,
+
+        pub(super) checks: RefCell<Vec<(String, gtk::CheckButton)>>,
+
+        pub(super) single_choice: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptChunkPickerDialog {
+        const NAME: &'static str = "ManuscriptChunkPickerDialog";
+        type Type = super::ManuscriptChunkPickerDialog;
+        type ParentType = adw::MessageDialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptChunkPickerDialog {}
+    impl WidgetImpl for ManuscriptChunkPickerDialog {}
+    impl WindowImpl for ManuscriptChunkPickerDialog {}
+    impl MessageDialogImpl for ManuscriptChunkPickerDialog {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptChunkPickerDialog(ObjectSubclass<imp::ManuscriptChunkPickerDialog>)
+        @extends adw::MessageDialog, gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl ManuscriptChunkPickerDialog {
+    /// Creates a dialog listing `chunks`, grouped by category and sorted by priority
+    pub fn new(parent: &gtk::Window, heading: &str, chunks: Vec<&dyn DocumentChunk>) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("modal", true)
+            .property("transient-for", parent)
+            .property("heading", heading)
+            .build();
+
+        let mut chunks = chunks;
+        chunks.sort_by_key(|c| (c.category_name(), c.priority().unwrap_or(0)));
+        for chunk in chunks {
+            obj.append_chunk(chunk);
+        }
+
+        obj
+    }
+
+    /// Creates a dialog where only one of `chunks` can be chosen, listed in the
+    /// given order. An extra entry labelled `none_label` allows choosing nothing
+    pub fn new_single_choice(
+        parent: &gtk::Window,
+        heading: &str,
+        none_label: &str,
+        chunks: Vec<&dyn DocumentChunk>,
+        selected: Option<&str>,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("modal", true)
+            .property("transient-for", parent)
+            .property("heading", heading)
+            .build();
+        obj.imp().single_choice.set(true);
+
+        obj.append_row(String::new(), none_label, None, selected.is_none());
+        for chunk in chunks {
+            obj.append_row(
+                chunk.id().to_string(),
+                chunk.heading().as_str(),
+                Some(chunk.category_name().as_str()),
+                selected == Some(chunk.id()),
+            );
+        }
+
+        obj
+    }
+
+    fn append_chunk(&self, chunk: &dyn DocumentChunk) {
+        self.append_row(
+            chunk.id().to_string(),
+            chunk.heading().as_str(),
+            Some(chunk.category_name().as_str()),
+            true,
+        );
+    }
+
+    fn append_row(&self, id: String, title: &str, subtitle: Option<&str>, active: bool) {
+        let check = gtk::CheckButton::builder()
+            .active(active)
+            .valign(gtk::Align::Center)
+            .build();
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(title))
+            .activatable_widget(&check)
+            .build();
+        if let Some(subtitle) = subtitle {
+            row.set_subtitle(subtitle);
+        }
+        row.add_prefix(&check);
+
+        self.imp().chunks_list.append(&row);
+        let mut checks = self.imp().checks.borrow_mut();
+        if self.imp().single_choice.get() {
+            if let Some((_, first)) = checks.first() {
+                check.set_group(Some(first));
+            }
+        }
+        checks.push((id, check));
+    }
+
+    /// Ids of the chunks the user checked
+    pub fn selected_ids(&self) -> Vec<String> {
+        self.imp()
+            .checks
+            .borrow()
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Id of the chosen chunk, for dialogs created with `new_single_choice`
+    pub fn selected_id(&self) -> Option<String> {
+        self.selected_ids().into_iter().find(|id| !id.is_empty())
+    }
+}
@@ -1 +1,2 @@
+#[allow(dead_code)]
 pub trait CompilePanel {}
@@ -1,9 +1,18 @@
+mod appearances_dialog;
+mod chunk_picker_dialog;
 mod compile;
 mod compile_dialog;
 mod destroy_confirm_dialog;
 mod entry_input_dialog;
 mod pdf_panel;
+mod relationships_dialog;
+mod rename_dialog;
 
+pub use appearances_dialog::*;
+pub use chunk_picker_dialog::*;
+#[allow(unused_imports)]
 pub use compile_dialog::*;
 pub use destroy_confirm_dialog::*;
 pub use entry_input_dialog::*;
+pub use relationships_dialog::*;
+pub use rename_dialog::*;
@@ -0,0 +1,90 @@
+use crate::{
+    libs::files::with_svg_save_dialog, models::RelationshipGraph, services::i18n::i18n,
+    widgets::ManuscriptRelationshipGraphView,
+};
+use adw::subclass::prelude::*;
+use gtk::{gio, glib};
+use std::cell::RefCell;
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptRelationshipsDialog";
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/dialogs/relationships_dialog.ui")]
+    pub struct ManuscriptRelationshipsDialog {
+        #[template_child]
+        pub(super) graph_view: TemplateChild<ManuscriptRelationshipGraphView>,
+
+        /// The displayed graph, rendered as SVG for exporting
+        pub(super) svg: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptRelationshipsDialog {
+        const NAME: &'static str = "ManuscriptRelationshipsDialog";
+        type Type = super::ManuscriptRelationshipsDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptRelationshipsDialog {}
+    impl WidgetImpl for ManuscriptRelationshipsDialog {}
+    impl WindowImpl for ManuscriptRelationshipsDialog {}
+    impl AdwWindowImpl for ManuscriptRelationshipsDialog {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptRelationshipsDialog(ObjectSubclass<imp::ManuscriptRelationshipsDialog>)
+        @extends adw::Window, gtk::Window, gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl ManuscriptRelationshipsDialog {
+    pub fn new(parent: &gtk::Window, graph: RelationshipGraph) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        *obj.imp().svg.borrow_mut() = graph.to_svg();
+        obj.imp().graph_view.set_graph(graph);
+        obj
+    }
+
+    pub fn graph_view(&self) -> ManuscriptRelationshipGraphView {
+        self.imp().graph_view.get()
+    }
+}
+
+#[gtk::template_callbacks]
+impl ManuscriptRelationshipsDialog {
+    #[template_callback]
+    fn on_fit_clicked(&self, _button: &gtk::Button) {
+        self.imp().graph_view.reset_view();
+    }
+
+    #[template_callback]
+    fn on_export_clicked(&self, _button: &gtk::Button) {
+        let svg = self.imp().svg.borrow().clone();
+        with_svg_save_dialog(
+            i18n("Export Relationships").as_str(),
+            "relationships.svg",
+            move |path| {
+                if let Err(error) = std::fs::write(path.as_str(), svg.as_bytes()) {
+                    glib::g_warning!(
+                        G_LOG_DOMAIN,
+                        "Could not export relationships to {path} - {error}"
+                    );
+                }
+            },
+        );
+    }
+}
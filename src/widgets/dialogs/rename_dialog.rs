@@ -0,0 +1,158 @@
+use crate::{models::NameOccurrence, services::i18n::i18n};
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gio, glib};
+use std::cell::RefCell;
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptRenameDialog";
+
+mod imp {
+    use super::*;
+    use glib::subclass::signal::Signal;
+    use once_cell::sync::Lazy;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/dialogs/rename_dialog.ui")]
+    pub struct ManuscriptRenameDialog {
+        #[template_child]
+        pub(super) cancel_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub(super) rename_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub(super) summary_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub(super) occurrences_list: TemplateChild<gtk::ListBox>,
+
+        /// The occurrences listed, along with the check button choosing whether to replace each
+        pub(super) occurrences: RefCell<Vec<(NameOccurrence, gtk::CheckButton)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptRenameDialog {
+        const NAME: &'static str = "ManuscriptRenameDialog";
+        type Type = super::ManuscriptRenameDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptRenameDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                // The occurrences to replace were confirmed, see `selected_occurrences`
+                vec![Signal::builder("rename-requested").build()]
+            });
+            SIGNALS.as_ref()
+        }
+    }
+
+    impl WidgetImpl for ManuscriptRenameDialog {}
+    impl WindowImpl for ManuscriptRenameDialog {}
+    impl AdwWindowImpl for ManuscriptRenameDialog {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptRenameDialog(ObjectSubclass<imp::ManuscriptRenameDialog>)
+        @extends adw::Window, gtk::Window, gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl ManuscriptRenameDialog {
+    /// Lists `occurrences` with some context, to choose which of them become
+    /// `new_name`. Only the occurrences of `previous_name` are chosen at first,
+    /// as an alias may still fit the renamed character
+    pub fn new(
+        parent: &gtk::Window,
+        occurrences: Vec<NameOccurrence>,
+        previous_name: &str,
+        new_name: &str,
+    ) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        obj.fill(occurrences, previous_name, new_name);
+        obj.connect_events();
+        obj
+    }
+
+    /// The occurrences chosen to be replaced
+    pub fn selected_occurrences(&self) -> Vec<NameOccurrence> {
+        self.imp()
+            .occurrences
+            .borrow()
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(occurrence, _)| occurrence.clone())
+            .collect()
+    }
+
+    fn fill(&self, occurrences: Vec<NameOccurrence>, previous_name: &str, new_name: &str) {
+        let imp = self.imp();
+        imp.summary_label.set_label(
+            i18n("Choose which of these {count} occurrences to replace with “{name}”")
+                .replace("{count}", occurrences.len().to_string().as_str())
+                .replace("{name}", new_name)
+                .as_str(),
+        );
+
+        let mut rows = vec![];
+        for occurrence in occurrences.into_iter() {
+            let check = gtk::CheckButton::builder()
+                .active(occurrence.name() == previous_name.trim())
+                .valign(gtk::Align::Center)
+                .build();
+            check.connect_active_notify(glib::clone!(@weak self as this => move |_| {
+                this.update_rename_button();
+            }));
+
+            let context = format!(
+                "…{}<b>{}</b>{}…",
+                glib::markup_escape_text(occurrence.before()),
+                glib::markup_escape_text(occurrence.name()),
+                glib::markup_escape_text(occurrence.after()),
+            );
+            let row = adw::ActionRow::builder()
+                .title(context)
+                .subtitle(glib::markup_escape_text(occurrence.chunk_title()).as_str())
+                .activatable_widget(&check)
+                .build();
+            row.add_prefix(&check);
+            imp.occurrences_list.append(&row);
+            rows.push((occurrence, check));
+        }
+        *imp.occurrences.borrow_mut() = rows;
+        self.update_rename_button();
+    }
+
+    fn update_rename_button(&self) {
+        let imp = self.imp();
+        let count = imp
+            .occurrences
+            .borrow()
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .count();
+        imp.rename_button.set_sensitive(count > 0);
+    }
+
+    fn connect_events(&self) {
+        let imp = self.imp();
+        imp.cancel_button
+            .connect_clicked(glib::clone!(@weak self as this => move |_| {
+                this.close();
+            }));
+        imp.rename_button
+            .connect_clicked(glib::clone!(@weak self as this => move |_| {
+                this.emit_by_name::<()>("rename-requested", &[]);
+                this.close();
+            }));
+    }
+}
@@ -31,6 +31,9 @@ mod imp {
         #[template_child]
         pub(super) editor_tab_view: TemplateChild<adw::TabView>,
 
+        #[template_child]
+        pub(super) character_names_button: TemplateChild<gtk::ToggleButton>,
+
         pub(super) channel: RefCell<Option<Sender<DocumentAction>>>,
 
         pub(super) chunk_props_panel_visible: Cell<bool>,
@@ -132,6 +135,11 @@ impl ManuscriptEditorViewShell {
     fn setup_widgets(&self) {
         let settings = ManuscriptSettings::default();
         self.set_chunk_props_panel_visible(settings.chunk_props_panel_visible());
+        settings.bind(
+            "highlight-character-names",
+            &self.imp().character_names_button.get(),
+            "active",
+        );
 
         self.imp().editor_tab_view.connect_close_page(
             clone!(@weak self as this => @default-return false, move |_, _| {
@@ -194,15 +202,22 @@ impl ManuscriptEditorViewShell {
                     // editor.upcast::<gtk::Widget>()
                     Box::into_raw(Box::new(editor))
                 }
-                ChunkType::Chapter => {
+                ChunkType::Part | ChunkType::Chapter | ChunkType::Scene => {
                     let text_view = ManuscriptTextEditor::new(chunk, self.sender());
                     text_view.set_halign(gtk::Align::Fill);
                     text_view.set_valign(gtk::Align::Fill);
                     text_view.set_hexpand(true);
-                    if let Some(chapter) = chunk.as_any().downcast_ref::<Chapter>() {
-                        text_view.init(chunk.id().into(), Some(chapter.buffer().clone()));
+                    if let Some(buffer_chunk) = chunk.as_buffer_chunk() {
+                        text_view.init(
+                            chunk.id().into(),
+                            Some(buffer_chunk.buffer().clone()),
+                            buffer_chunk.tags_map(),
+                        );
                     } else {
-                        text_view.init(chunk.id().into(), None);
+                        text_view.init(chunk.id().into(), None, &[]);
+                    }
+                    if let Some(chapter) = chunk.as_any().downcast_ref::<Chapter>() {
+                        text_view.set_notes(chapter.notes());
                     }
                     // text_view.upcast::<gtk::Widget>()
                     Box::into_raw(Box::new(text_view))
@@ -216,6 +231,27 @@ impl ManuscriptEditorViewShell {
                     // editor.upcast::<gtk::Widget>()
                     Box::into_raw(Box::new(editor))
                 }
+                ChunkType::LocationSheet => {
+                    let editor = ManuscriptLocationSheetEditor::new(chunk, self.sender());
+                    editor.set_halign(gtk::Align::Fill);
+                    editor.set_valign(gtk::Align::Fill);
+                    editor.set_hexpand(true);
+                    Box::into_raw(Box::new(editor))
+                }
+                ChunkType::LoreEntry => {
+                    let editor = ManuscriptLoreEntryEditor::new(chunk, self.sender());
+                    editor.set_halign(gtk::Align::Fill);
+                    editor.set_valign(gtk::Align::Fill);
+                    editor.set_hexpand(true);
+                    Box::into_raw(Box::new(editor))
+                }
+                ChunkType::Research => {
+                    let editor = ManuscriptResearchEditor::new(chunk, self.sender());
+                    editor.set_halign(gtk::Align::Fill);
+                    editor.set_valign(gtk::Align::Fill);
+                    editor.set_hexpand(true);
+                    Box::into_raw(Box::new(editor))
+                }
             };
 
         let child_widget = unsafe { Box::from_raw(child_widget) };
@@ -325,6 +361,46 @@ impl ManuscriptEditorViewShell {
         }
     }
 
+    /// Loads the content of `chunk` again in its text editor, if open
+    pub fn reload_page(&self, chunk: &dyn DocumentChunk) {
+        let editor = self
+            .editor_for(chunk)
+            .and_then(|editor| editor.downcast::<ManuscriptTextEditor>().ok());
+        if let (Some(editor), Some(buffer_chunk)) = (editor, chunk.as_buffer_chunk()) {
+            editor.init(
+                chunk.id().into(),
+                Some(buffer_chunk.buffer().clone()),
+                buffer_chunk.tags_map(),
+            );
+            if let Some(chapter) = chunk.as_any().downcast_ref::<Chapter>() {
+                editor.set_notes(chapter.notes());
+            }
+        }
+    }
+
+    /// The side panel of the page showing `chunk`, if any
+    pub fn side_panel_for(
+        &self,
+        chunk: &(impl DocumentChunk + ?Sized),
+    ) -> Option<ManuscriptChunkSidePanel> {
+        self.editor_view_for(chunk)
+            .and_then(|view| view.side_panel())
+            .and_then(|panel| panel.downcast::<ManuscriptChunkSidePanel>().ok())
+    }
+
+    /// The editor widget of the page showing `chunk`, if any
+    pub fn editor_for(&self, chunk: &(impl DocumentChunk + ?Sized)) -> Option<gtk::Widget> {
+        self.editor_view_for(chunk).and_then(|view| view.child())
+    }
+
+    fn editor_view_for(
+        &self,
+        chunk: &(impl DocumentChunk + ?Sized),
+    ) -> Option<ManuscriptEditorView> {
+        self.page_for_chunk(chunk)
+            .and_then(|page| page.child().downcast::<ManuscriptEditorView>().ok())
+    }
+
     pub fn set_channel(&self, sender: Sender<DocumentAction>) {
         self.imp().channel.replace(Some(sender));
     }
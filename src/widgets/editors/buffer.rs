@@ -1,12 +1,20 @@
 use crate::{
     libs::{consts::*, text_metrics::get_font_size},
-    services::analyst::{TagApplyRules, TagLookup, TEXT_ANALYZER},
+    services::analyst::{MentionMatcher, TagApplyRules, TagLookup, TEXT_ANALYZER},
 };
 use gtk::{glib, glib::ToValue, pango, prelude::*, subclass::prelude::*};
 use std::cell::{Cell, RefCell};
 
 const G_LOG_DOMAIN: &str = "ManuscriptBuffer";
 
+/// A character whose names are tinted with its accent color
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterHighlight {
+    pub id: String,
+    pub names: Vec<String>,
+    pub color: gtk::gdk::RGBA,
+}
+
 mod imp {
     use super::*;
     use glib::{
@@ -20,6 +28,7 @@ mod imp {
         pub(super) autoformat: Cell<bool>,
         pub(super) accent_secondary_fg_color: Cell<Option<gtk::gdk::RGBA>>,
         pub(super) accent_primary_fg_color: Cell<Option<gtk::gdk::RGBA>>,
+        pub(super) characters: RefCell<Vec<(CharacterHighlight, MentionMatcher)>>,
     }
 
     impl Default for ManuscriptBuffer {
@@ -30,6 +39,7 @@ mod imp {
                 autoformat: Cell::default(),
                 accent_primary_fg_color: Cell::new(None),
                 accent_secondary_fg_color: Cell::new(None),
+                characters: RefCell::default(),
             }
         }
     }
@@ -180,7 +190,7 @@ impl ManuscriptBuffer {
         }
     }
 
-    pub fn parsed_tags(&self) -> std::cell::Ref<Vec<TagApplyRules>> {
+    pub fn parsed_tags(&self) -> std::cell::Ref<'_, Vec<TagApplyRules>> {
         self.imp().matched_rules.borrow()
     }
 
@@ -196,10 +206,18 @@ impl ManuscriptBuffer {
         }
     }
 
+    /// Removes every tag derived from the text, leaving `PERSISTENT_TAG_NAMES` alone
     fn clear_tags(&self) {
         let start_iter = self.start_iter();
         let end_iter = self.end_iter();
-        self.remove_all_tags(&start_iter, &end_iter);
+        self.tag_table().foreach(|tag| {
+            let persistent = tag
+                .name()
+                .is_some_and(|name| PERSISTENT_TAG_NAMES.contains(&name.as_str()));
+            if !persistent {
+                self.remove_tag(tag, &start_iter, &end_iter);
+            }
+        });
     }
 
     /// Applies `rules` to this buffer.
@@ -239,9 +257,62 @@ impl ManuscriptBuffer {
                 }
             }
         }
+        self.tint_characters();
         self.emit_by_name::<()>("parsed", &[]);
     }
 
+    /// Sets the characters whose names are tinted, and tints them right away
+    pub fn set_characters(&self, characters: Vec<CharacterHighlight>) {
+        let current: Vec<CharacterHighlight> = self
+            .imp()
+            .characters
+            .borrow()
+            .iter()
+            .map(|(character, _)| character.clone())
+            .collect();
+        if current == characters {
+            return;
+        }
+
+        *self.imp().characters.borrow_mut() = characters
+            .into_iter()
+            .map(|character| {
+                let matcher = MentionMatcher::new(&character.names);
+                (character, matcher)
+            })
+            .collect();
+        self.tint_characters();
+    }
+
+    fn tint_characters(&self) {
+        let (start, end) = (self.start_iter(), self.end_iter());
+        self.tag_table().foreach(|tag| {
+            if tag
+                .name()
+                .is_some_and(|name| name.starts_with(TAG_NAME_CHARACTER_PREFIX))
+            {
+                self.remove_tag(tag, &start, &end);
+            }
+        });
+
+        let text = self.text(&start, &end, true);
+        for (character, matcher) in self.imp().characters.borrow().iter() {
+            let tag_name = format!("{}{}", TAG_NAME_CHARACTER_PREFIX, character.id);
+            let tag = match self.tag_table().lookup(tag_name.as_str()) {
+                Some(tag) => tag,
+                None => match self.create_tag(Some(tag_name.as_str()), &[]) {
+                    Some(tag) => tag,
+                    None => continue,
+                },
+            };
+            tag.set_foreground_rgba(Some(&character.color));
+
+            for (from, to) in matcher.ranges(text.as_str()) {
+                self.apply_tag(&tag, &self.iter_at_offset(from), &self.iter_at_offset(to));
+            }
+        }
+    }
+
     ///  Populates `self`'s tag table with default tags. Note that this method makes use of
     /// `self.parent_view()` in order to calculate font sizes and extract informations from the underlying `gtk::StyleContext`.
     /// If that is `None` defaults will be assumed.
@@ -367,6 +438,25 @@ impl ManuscriptBuffer {
             ],
         );
 
+        let _ = buffer.create_tag(
+            Some(TAG_NAME_NOTE),
+            &[
+                (
+                    "background-rgba",
+                    &gtk::gdk::RGBA::new(0.96, 0.83, 0.18, 0.35),
+                ),
+                ("underline", &pango::Underline::Single),
+            ],
+        );
+
+        let _ = buffer.create_tag(
+            Some(TAG_NAME_HIGHLIGHT),
+            &[(
+                "background-rgba",
+                &gtk::gdk::RGBA::new(0.56, 0.94, 0.64, 0.35),
+            )],
+        );
+
         let code_bg_color = match self.parent_view() {
             Some(view) => view.style_context().lookup_color("code_bg_color"),
             None => None,
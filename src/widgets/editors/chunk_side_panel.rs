@@ -1,5 +1,11 @@
-use crate::{models::*, services::DocumentAction};
-use adw::subclass::prelude::*;
+use crate::{
+    models::*,
+    services::{i18n::i18n, DocumentAction},
+};
+use adw::{
+    prelude::{ActionRowExt, ComboRowExt},
+    subclass::prelude::*,
+};
 use glib_macros::Properties;
 use gtk::{gdk::RGBA, gio, glib::Sender, prelude::*};
 use std::cell::{Cell, RefCell};
@@ -23,6 +29,9 @@ mod imp {
         #[template_child]
         pub(super) priority_adjustment: TemplateChild<gtk::Adjustment>,
 
+        #[template_child]
+        pub(super) move_button: TemplateChild<gtk::Button>,
+
         #[property(get, set)]
         pub(super) chunk_id: RefCell<String>,
 
@@ -34,6 +43,72 @@ mod imp {
 
         #[property(get, set, nullable)]
         pub(super) accent: Cell<Option<Color>>,
+
+        /// Whether the chunk can be part of a compiled manuscript
+        #[property(get, set)]
+        pub(super) compilable: Cell<bool>,
+
+        /// Whether the chunk can be moved around the manuscript outline
+        #[property(get, set)]
+        pub(super) movable: Cell<bool>,
+
+        #[template_child]
+        pub(super) status_row: TemplateChild<adw::ComboRow>,
+
+        #[template_child]
+        pub(super) labels_button: TemplateChild<gtk::Button>,
+
+        /// Ids of the statuses listed by `status_row`, after the "None" item
+        pub(super) status_ids: RefCell<Vec<String>>,
+
+        /// Set while the workflow widgets are filled from the document, so
+        /// that no update is sent back
+        pub(super) updating_workflow: Cell<bool>,
+
+        /// Whether the chunk has text, and thus a workflow status and labels
+        #[property(get, set)]
+        pub(super) is_buffer: Cell<bool>,
+
+        #[property(get, set)]
+        pub(super) label_names: RefCell<String>,
+
+        #[template_child]
+        pub(super) point_of_view_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub(super) backlinks_list: TemplateChild<gtk::ListBox>,
+
+        /// Ids and titles of the chunks listed by `backlinks_list`
+        pub(super) backlinks: RefCell<Vec<(String, String)>>,
+
+        /// Whether the chunk is a scene, and thus shows scene details
+        #[property(get, set)]
+        pub(super) is_scene: Cell<bool>,
+
+        /// Whether the chunk is a chapter, which can be placed on the timeline
+        #[property(get, set)]
+        pub(super) is_chapter: Cell<bool>,
+
+        #[property(get, set)]
+        pub(super) point_of_view_name: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) synopsis: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) location: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) story_time: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) goal: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) conflict: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) outcome: RefCell<String>,
     }
 
     impl ManuscriptChunkSidePanel {
@@ -129,6 +204,34 @@ impl ManuscriptChunkSidePanel {
         obj.set_include_in_compilation(chunk.include_in_compilation());
         obj.imp().accent.set(chunk.accent());
         obj.set_locked(chunk.locked());
+        obj.set_compilable(chunk.chunk_type().is_compilable());
+        obj.set_movable(chunk.chunk_type().is_outline());
+        obj.imp()
+            .move_button
+            .set_action_target_value(Some(&chunk.id().to_variant()));
+        obj.imp()
+            .point_of_view_button
+            .set_action_target_value(Some(&chunk.id().to_variant()));
+        obj.imp()
+            .labels_button
+            .set_action_target_value(Some(&chunk.id().to_variant()));
+        let text = |value: Option<&String>| value.cloned().unwrap_or_default();
+        if let Some(buffer_chunk) = chunk.as_buffer_chunk() {
+            obj.set_is_buffer(true);
+            obj.set_synopsis(text(buffer_chunk.synopsis()));
+        }
+        if let Some(scene) = chunk.as_any().downcast_ref::<Scene>() {
+            obj.set_is_scene(true);
+            obj.set_location(text(scene.location()));
+            obj.set_story_time(text(scene.story_time()));
+            obj.set_goal(text(scene.goal()));
+            obj.set_conflict(text(scene.conflict()));
+            obj.set_outcome(text(scene.outcome()));
+        }
+        if let Some(chapter) = chunk.as_any().downcast_ref::<Chapter>() {
+            obj.set_is_chapter(true);
+            obj.set_story_time(text(chapter.story_time()));
+        }
         obj.notify("accent-rgba");
         obj.notify("has-accent");
         obj.connect_events();
@@ -156,6 +259,155 @@ impl ManuscriptChunkSidePanel {
                 chunk.set_accent(this.accent()).unwrap();
             })
         }));
+
+        self.connect_synopsis_notify(|widget| {
+            let value = non_empty(widget.synopsis());
+            widget.send_buffer_update(move |chunk| chunk.set_synopsis(value));
+        });
+
+        self.imp().status_row.connect_selected_notify(
+            glib::clone!(@weak self as this => move |row| {
+                let imp = this.imp();
+                if imp.updating_workflow.get() {
+                    return;
+                }
+
+                let status = (row.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| imp.status_ids.borrow().get(index).cloned());
+                this.send_buffer_update(move |chunk| chunk.set_status(status));
+            }),
+        );
+
+        self.connect_location_notify(|widget| {
+            let value = non_empty(widget.location());
+            widget.send_scene_update(move |scene| scene.set_location(value));
+        });
+
+        self.connect_story_time_notify(|widget| {
+            let value = non_empty(widget.story_time());
+            widget.send_update(move |chunk| {
+                if let Some(scene) = chunk.as_any_mut().downcast_mut::<Scene>() {
+                    scene.set_story_time(value);
+                } else if let Some(chapter) = chunk.as_any_mut().downcast_mut::<Chapter>() {
+                    chapter.set_story_time(value);
+                }
+            });
+        });
+
+        self.connect_goal_notify(|widget| {
+            let value = non_empty(widget.goal());
+            widget.send_scene_update(move |scene| scene.set_goal(value));
+        });
+
+        self.connect_conflict_notify(|widget| {
+            let value = non_empty(widget.conflict());
+            widget.send_scene_update(move |scene| scene.set_conflict(value));
+        });
+
+        self.connect_outcome_notify(|widget| {
+            let value = non_empty(widget.outcome());
+            widget.send_scene_update(move |scene| scene.set_outcome(value));
+        });
+    }
+
+    /// Shows the workflow status and labels of `chunk`, listing the
+    /// statuses defined by `manifest`
+    pub fn update_workflow(&self, manifest: &DocumentManifest, chunk: &dyn DocumentChunk) {
+        let Some(buffer_chunk) = chunk.as_buffer_chunk() else {
+            return;
+        };
+
+        let imp = self.imp();
+        imp.updating_workflow.set(true);
+
+        let ids: Vec<String> = manifest
+            .statuses()
+            .iter()
+            .map(|s| s.id().to_string())
+            .collect();
+        // Rebuilding the model resets the selection, so only do it when needed
+        if *imp.status_ids.borrow() != ids || imp.status_row.model().is_none() {
+            let mut names = vec![i18n("None")];
+            names.extend(manifest.statuses().iter().map(|s| s.name().to_string()));
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            imp.status_row
+                .set_model(Some(&gtk::StringList::new(names.as_slice())));
+            *imp.status_ids.borrow_mut() = ids;
+        }
+
+        let selected = buffer_chunk
+            .status()
+            .and_then(|id| imp.status_ids.borrow().iter().position(|s| s == id))
+            .map(|index| index as u32 + 1)
+            .unwrap_or(0);
+        if imp.status_row.selected() != selected {
+            imp.status_row.set_selected(selected);
+        }
+
+        let names: Vec<&str> = buffer_chunk
+            .labels()
+            .iter()
+            .filter_map(|id| manifest.label(id))
+            .map(|l| l.name())
+            .collect();
+        self.set_label_names(if names.is_empty() {
+            i18n("No labels")
+        } else {
+            names.join(", ")
+        });
+
+        imp.updating_workflow.set(false);
+    }
+
+    /// Lists the chunks linking to this one, each opening that chunk when activated
+    pub fn set_backlinks(&self, chunks: &[&dyn DocumentChunk]) {
+        let imp = self.imp();
+        let backlinks: Vec<(String, String)> = chunks
+            .iter()
+            .map(|c| (c.id().to_string(), c.safe_title()))
+            .collect();
+        if *imp.backlinks.borrow() == backlinks {
+            return;
+        }
+
+        while let Some(row) = imp.backlinks_list.row_at_index(0) {
+            imp.backlinks_list.remove(&row);
+        }
+        for chunk in chunks.iter() {
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(chunk.safe_title().as_str()))
+                .subtitle(chunk.category_name())
+                .activatable(true)
+                .action_name("project.select-chunk")
+                .action_target(&chunk.id().to_variant())
+                .build();
+            row.add_suffix(&gtk::Image::from_icon_name("go-next-symbolic"));
+            imp.backlinks_list.append(&row);
+        }
+        *imp.backlinks.borrow_mut() = backlinks;
+    }
+
+    fn send_buffer_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut dyn MutableBufferChunk) + 'static,
+    {
+        self.send_update(move |chunk| {
+            if let Some(buffer_chunk) = chunk.as_mutable_buffer_chunk() {
+                f(buffer_chunk);
+            }
+        });
+    }
+
+    fn send_scene_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Scene) + 'static,
+    {
+        self.send_update(move |chunk| {
+            if let Some(scene) = chunk.as_any_mut().downcast_mut::<Scene>() {
+                f(scene);
+            }
+        });
     }
 
     fn send_update<F>(&self, f: F)
@@ -173,6 +425,10 @@ impl ManuscriptChunkSidePanel {
     }
 }
 
+fn non_empty(value: String) -> Option<String> {
+    Some(value).filter(|v| !v.trim().is_empty())
+}
+
 #[gtk::template_callbacks]
 impl ManuscriptChunkSidePanel {
     #[template_callback]
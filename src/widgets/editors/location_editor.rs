@@ -0,0 +1,206 @@
+use super::{prelude::EditorWidgetProtocol, ManuscriptBuffer, ManuscriptChunkSidePanel};
+use crate::{
+    libs::files::with_image_open_dialog,
+    models::*,
+    services::{i18n::i18n, prelude::*, *},
+};
+use adw::{prelude::*, subclass::prelude::*};
+use bytes::Bytes;
+use glib_macros::Properties;
+use gtk::{gio, glib, glib::Sender};
+use std::cell::{Cell, RefCell};
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptLocationSheetEditor";
+
+mod imp {
+    use super::*;
+    use glib::ParamSpec;
+
+    #[derive(Default, Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::ManuscriptLocationSheetEditor)]
+    #[template(resource = "/io/sixpounder/Manuscript/editors/location_editor.ui")]
+    pub struct ManuscriptLocationSheetEditor {
+        #[template_child]
+        pub(super) location_name_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) location_kind_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) parent_location_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub(super) map_picture: TemplateChild<gtk::Picture>,
+
+        #[template_child]
+        pub(super) location_description_buffer: TemplateChild<ManuscriptBuffer>,
+
+        /// Name of the location containing this one, as resolved by the window
+        #[property(get, set)]
+        pub(super) parent_location_name: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) has_map: Cell<bool>,
+
+        pub(super) chunk_id: RefCell<String>,
+        pub(super) side_panel_widget: RefCell<Option<gtk::Widget>>,
+        pub(super) sender: RefCell<Option<Sender<DocumentAction>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptLocationSheetEditor {
+        const NAME: &'static str = "ManuscriptLocationSheetEditor";
+        type Type = super::ManuscriptLocationSheetEditor;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptLocationSheetEditor {
+        fn properties() -> &'static [gtk::glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+    }
+
+    impl WidgetImpl for ManuscriptLocationSheetEditor {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptLocationSheetEditor(ObjectSubclass<imp::ManuscriptLocationSheetEditor>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl EditorWidgetProtocol for ManuscriptLocationSheetEditor {
+    fn editor_widget(&self) -> Option<gtk::Widget> {
+        Some(self.upcast_ref::<gtk::Widget>().clone())
+    }
+
+    fn side_panel_widget(&self) -> Option<gtk::Widget> {
+        self.imp().side_panel_widget.borrow().clone()
+    }
+}
+
+impl ManuscriptLocationSheetEditor {
+    pub fn new(chunk: &dyn DocumentChunk, sender: Option<Sender<DocumentAction>>) -> Self {
+        let obj: Self = glib::Object::new();
+        *obj.imp().chunk_id.borrow_mut() = chunk.id().into();
+        *obj.imp().sender.borrow_mut() = sender.clone();
+        if let Some(source) = chunk.as_any().downcast_ref::<LocationSheet>() {
+            obj.setup_widgets(source);
+            obj.connect_events();
+        }
+
+        *obj.imp().side_panel_widget.borrow_mut() =
+            Some(ManuscriptChunkSidePanel::new(chunk, sender).upcast::<gtk::Widget>());
+        obj
+    }
+
+    fn setup_widgets(&self, source: &LocationSheet) {
+        let imp = self.imp();
+        imp.location_name_entry
+            .set_text(source.name().unwrap_or(&String::default()).as_str());
+        imp.location_kind_entry
+            .set_text(source.kind().unwrap_or(&String::default()).as_str());
+        imp.location_description_buffer.set_text(
+            String::from_utf8(source.description().to_vec())
+                .unwrap_or_default()
+                .as_str(),
+        );
+        imp.parent_location_button
+            .set_action_target_value(Some(&source.id().to_variant()));
+        self.set_map_texture(source.map().map(|map| map.as_ref()));
+    }
+
+    fn set_map_texture(&self, map: Option<&[u8]>) {
+        let texture = map.and_then(texture_from_bytes);
+        self.set_has_map(texture.is_some());
+        self.imp().map_picture.set_paintable(texture.as_ref());
+    }
+
+    fn send_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut LocationSheet) + 'static,
+    {
+        let imp = self.imp();
+        let chunk_id = imp.chunk_id.borrow();
+        let tx = imp.sender.borrow();
+        let tx = tx.as_ref().expect("No channel sender found");
+        tx.send(DocumentAction::UpdateChunkWith(
+            chunk_id.clone(),
+            Box::new(move |chunk| {
+                if let Some(location) = chunk.as_any_mut().downcast_mut::<LocationSheet>() {
+                    f(location);
+                }
+            }),
+        ))
+        .expect("Failed to send location sheet update");
+    }
+
+    fn connect_events(&self) {
+        let imp = self.imp();
+
+        imp.location_name_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value: String = entry.text().into();
+                this.send_update(move |location| location.set_name(Some(value)));
+            }));
+
+        imp.location_kind_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value: String = entry.text().into();
+                this.send_update(move |location| location.set_kind(Some(value)));
+            }));
+
+        imp.location_description_buffer.connect_changed(
+            glib::clone!(@weak self as this => move |buf| {
+                let value = bytes_from_text_buffer(buf.upcast_ref::<gtk::TextBuffer>());
+                this.send_update(move |location| location.set_description_bytes(value));
+            }),
+        );
+    }
+}
+
+#[gtk::template_callbacks]
+impl ManuscriptLocationSheetEditor {
+    #[template_callback]
+    fn on_choose_map_clicked(&self, _button: &gtk::Button) {
+        with_image_open_dialog(
+            i18n("Choose Map").as_str(),
+            glib::clone!(@weak self as this => move |path| {
+                match std::fs::read(path.as_str()) {
+                    Ok(content) => {
+                        this.set_map_texture(Some(content.as_slice()));
+                        let value = Bytes::from(content);
+                        this.send_update(move |location| location.set_map(Some(value)));
+                    }
+                    Err(error) => {
+                        glib::g_warning!(G_LOG_DOMAIN, "Could not read map {path} - {error}");
+                    }
+                }
+            }),
+        );
+    }
+
+    #[template_callback]
+    fn on_remove_map_clicked(&self, _button: &gtk::Button) {
+        self.set_map_texture(None);
+        self.send_update(|location| location.set_map(None));
+    }
+}
@@ -0,0 +1,143 @@
+use super::{prelude::EditorWidgetProtocol, ManuscriptBuffer, ManuscriptChunkSidePanel};
+use crate::{models::*, services::prelude::*, services::*};
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gio, glib, glib::Sender};
+use std::cell::RefCell;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/editors/lore_editor.ui")]
+    pub struct ManuscriptLoreEntryEditor {
+        #[template_child]
+        pub(super) lore_name_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) lore_category_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) lore_story_time_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) lore_body_buffer: TemplateChild<ManuscriptBuffer>,
+
+        pub(super) chunk_id: RefCell<String>,
+        pub(super) side_panel_widget: RefCell<Option<gtk::Widget>>,
+        pub(super) sender: RefCell<Option<Sender<DocumentAction>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptLoreEntryEditor {
+        const NAME: &'static str = "ManuscriptLoreEntryEditor";
+        type Type = super::ManuscriptLoreEntryEditor;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptLoreEntryEditor {}
+
+    impl WidgetImpl for ManuscriptLoreEntryEditor {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptLoreEntryEditor(ObjectSubclass<imp::ManuscriptLoreEntryEditor>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl EditorWidgetProtocol for ManuscriptLoreEntryEditor {
+    fn editor_widget(&self) -> Option<gtk::Widget> {
+        Some(self.upcast_ref::<gtk::Widget>().clone())
+    }
+
+    fn side_panel_widget(&self) -> Option<gtk::Widget> {
+        self.imp().side_panel_widget.borrow().clone()
+    }
+}
+
+impl ManuscriptLoreEntryEditor {
+    pub fn new(chunk: &dyn DocumentChunk, sender: Option<Sender<DocumentAction>>) -> Self {
+        let obj: Self = glib::Object::new();
+        *obj.imp().chunk_id.borrow_mut() = chunk.id().into();
+        *obj.imp().sender.borrow_mut() = sender.clone();
+        if let Some(source) = chunk.as_any().downcast_ref::<LoreEntry>() {
+            obj.setup_widgets(source);
+            obj.connect_events();
+        }
+
+        *obj.imp().side_panel_widget.borrow_mut() =
+            Some(ManuscriptChunkSidePanel::new(chunk, sender).upcast::<gtk::Widget>());
+        obj
+    }
+
+    fn setup_widgets(&self, source: &LoreEntry) {
+        let imp = self.imp();
+        imp.lore_name_entry
+            .set_text(source.name().unwrap_or(&String::default()).as_str());
+        imp.lore_category_entry
+            .set_text(source.category().unwrap_or(&String::default()).as_str());
+        imp.lore_story_time_entry
+            .set_text(source.story_time().unwrap_or(&String::default()).as_str());
+        imp.lore_body_buffer.set_text(
+            String::from_utf8(source.body().to_vec())
+                .unwrap_or_default()
+                .as_str(),
+        );
+    }
+
+    fn send_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut LoreEntry) + 'static,
+    {
+        let imp = self.imp();
+        let chunk_id = imp.chunk_id.borrow();
+        let tx = imp.sender.borrow();
+        let tx = tx.as_ref().expect("No channel sender found");
+        tx.send(DocumentAction::UpdateChunkWith(
+            chunk_id.clone(),
+            Box::new(move |chunk| {
+                if let Some(entry) = chunk.as_any_mut().downcast_mut::<LoreEntry>() {
+                    f(entry);
+                }
+            }),
+        ))
+        .expect("Failed to send lore entry update");
+    }
+
+    fn connect_events(&self) {
+        let imp = self.imp();
+
+        imp.lore_name_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value: String = entry.text().into();
+                this.send_update(move |lore| lore.set_name(Some(value)));
+            }));
+
+        imp.lore_category_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value: String = entry.text().into();
+                this.send_update(move |lore| lore.set_category(Some(value)));
+            }));
+
+        imp.lore_story_time_entry.connect_changed(
+            glib::clone!(@weak self as this => move |entry| {
+                let value = Some(entry.text().trim().to_string()).filter(|t| !t.is_empty());
+                this.send_update(move |lore| lore.set_story_time(value));
+            }),
+        );
+
+        imp.lore_body_buffer
+            .connect_changed(glib::clone!(@weak self as this => move |buf| {
+                let value = bytes_from_text_buffer(buf.upcast_ref::<gtk::TextBuffer>());
+                this.send_update(move |lore| lore.set_body_bytes(value));
+            }));
+    }
+}
@@ -1,14 +1,20 @@
 mod buffer;
 mod chunk_side_panel;
 mod editor_view;
+mod location_editor;
+mod lore_editor;
 pub mod prelude;
 mod project_settings_editor;
+mod research_editor;
 mod sheet_editor;
 mod text_editor;
 
 pub use buffer::*;
 pub use chunk_side_panel::*;
 pub use editor_view::*;
+pub use location_editor::*;
+pub use lore_editor::*;
 pub use project_settings_editor::*;
+pub use research_editor::*;
 pub use sheet_editor::*;
 pub use text_editor::*;
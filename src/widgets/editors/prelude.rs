@@ -1,4 +1,4 @@
-use crate::{models::DocumentChunk, services::DocumentAction};
+use crate::services::DocumentAction;
 use glib::Sender;
 use gtk::prelude::WidgetExt;
 
@@ -9,10 +9,6 @@ pub enum EditorWidgetFocusResult {
     NotFocused,
 }
 
-pub trait ConstructFromChunk {
-    fn new(chunk: &dyn DocumentChunk) -> Self;
-}
-
 pub trait EditorWidgetProtocol {
     fn document_action_sender(&self) -> Option<Sender<DocumentAction>> {
         None
@@ -1,11 +1,16 @@
 use super::prelude::EditorWidgetProtocol;
 use crate::{
-    models::{DocumentManifest, TextMetricSize},
-    services::{i18n::i18n, DocumentAction},
+    libs::files::with_image_open_dialog,
+    models::{
+        is_valid_isbn, parse_date, search_key, split_list, ChunkLabel, ChunkStatus, Color,
+        DocumentManifest, FieldDefinition, FieldKind, PublicationMetadata, TextMetricSize,
+    },
+    services::{i18n::i18n, prelude::texture_from_bytes, DocumentAction},
 };
-use adw::subclass::prelude::*;
+use adw::{prelude::*, subclass::prelude::*};
+use bytes::Bytes;
 use glib_macros::Properties;
-use gtk::{gio, glib::Sender, prelude::*};
+use gtk::{gio, glib, glib::Sender};
 use std::cell::{Cell, RefCell};
 
 #[allow(unused)]
@@ -33,6 +38,69 @@ mod imp {
         #[template_child]
         pub(super) line_height_entry: TemplateChild<adw::ComboRow>,
 
+        #[template_child]
+        pub(super) character_fields_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub(super) cover_picture: TemplateChild<gtk::Picture>,
+
+        #[template_child]
+        pub(super) statuses_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub(super) labels_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub(super) subtitle_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) series_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) series_number_adjustment: TemplateChild<gtk::Adjustment>,
+
+        #[template_child]
+        pub(super) language_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) keywords_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) publisher_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) isbns_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) publication_date_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) rights_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) publication_description_buffer: TemplateChild<gtk::TextBuffer>,
+
+        #[property(get, set)]
+        pub(super) has_cover: Cell<bool>,
+
+        /// Local copy of the custom character fields, kept in sync with the manifest
+        pub(super) character_fields: RefCell<Vec<FieldDefinition>>,
+
+        #[property(get, set)]
+        pub(super) has_character_fields: Cell<bool>,
+
+        /// Local copy of the workflow statuses, kept in sync with the manifest
+        pub(super) statuses: RefCell<Vec<ChunkStatus>>,
+
+        #[property(get, set)]
+        pub(super) has_statuses: Cell<bool>,
+
+        /// Local copy of the chunk labels, kept in sync with the manifest
+        pub(super) labels: RefCell<Vec<ChunkLabel>>,
+
+        #[property(get, set)]
+        pub(super) has_labels: Cell<bool>,
+
         #[property(get, set)]
         pub(super) heading: RefCell<String>,
 
@@ -60,6 +128,27 @@ mod imp {
                 author_entry: TemplateChild::default(),
                 paragraph_spacing_entry: TemplateChild::default(),
                 line_height_entry: TemplateChild::default(),
+                character_fields_list: TemplateChild::default(),
+                cover_picture: TemplateChild::default(),
+                statuses_list: TemplateChild::default(),
+                labels_list: TemplateChild::default(),
+                subtitle_entry: TemplateChild::default(),
+                series_entry: TemplateChild::default(),
+                series_number_adjustment: TemplateChild::default(),
+                language_entry: TemplateChild::default(),
+                keywords_entry: TemplateChild::default(),
+                publisher_entry: TemplateChild::default(),
+                isbns_entry: TemplateChild::default(),
+                publication_date_entry: TemplateChild::default(),
+                rights_entry: TemplateChild::default(),
+                publication_description_buffer: TemplateChild::default(),
+                has_cover: Cell::default(),
+                character_fields: RefCell::default(),
+                has_character_fields: Cell::default(),
+                statuses: RefCell::default(),
+                has_statuses: Cell::default(),
+                labels: RefCell::default(),
+                has_labels: Cell::default(),
                 sender: RefCell::default(),
                 heading: RefCell::new(i18n("Project Settings")),
                 title: RefCell::default(),
@@ -200,6 +289,7 @@ impl ManuscriptProjectSettingsEditor {
                 .unwrap_or(String::default()),
         );
         obj.set_author(manifest.author());
+        obj.set_cover_texture(manifest.cover().map(|cover| cover.as_ref()));
 
         // Set directly on impl to avoid triggering document updates, not needed here
         imp.paragraph_spacing
@@ -208,9 +298,499 @@ impl ManuscriptProjectSettingsEditor {
         obj.notify_paragraph_spacing_selected_index();
         obj.notify_line_height_selected_index();
 
+        *imp.character_fields.borrow_mut() = manifest.character_fields().clone();
+        obj.update_character_fields_list();
+
+        *imp.statuses.borrow_mut() = manifest.statuses().clone();
+        obj.update_statuses_list();
+        *imp.labels.borrow_mut() = manifest.labels().clone();
+        obj.update_labels_list();
+
+        obj.setup_publication(manifest.publication());
+
         obj
     }
 
+    fn set_cover_texture(&self, cover: Option<&[u8]>) {
+        let texture = cover.and_then(texture_from_bytes);
+        self.set_has_cover(texture.is_some());
+        self.imp().cover_picture.set_paintable(texture.as_ref());
+    }
+
+    fn setup_publication(&self, publication: &PublicationMetadata) {
+        let imp = self.imp();
+        let text = |value: Option<&String>| value.cloned().unwrap_or_default();
+        imp.subtitle_entry.set_text(&text(publication.subtitle()));
+        imp.series_entry.set_text(&text(publication.series()));
+        imp.series_number_adjustment
+            .set_value(publication.series_number().unwrap_or(0) as f64);
+        imp.language_entry.set_text(&text(publication.language()));
+        imp.keywords_entry
+            .set_text(&publication.keywords().join(", "));
+        imp.publisher_entry.set_text(&text(publication.publisher()));
+        imp.isbns_entry.set_text(&publication.isbns().join(", "));
+        imp.publication_date_entry.set_text(
+            &publication
+                .publication_date()
+                .map(|(year, month, day)| format!("{year:04}-{month:02}-{day:02}"))
+                .unwrap_or_default(),
+        );
+        imp.rights_entry.set_text(&text(publication.rights()));
+        imp.publication_description_buffer
+            .set_text(publication.description());
+
+        // Connected after filling the widgets so that opening the editor
+        // does not send updates
+        let optional_text = |entry: &adw::EntryRow| {
+            Some(entry.text().trim().to_string()).filter(|text| !text.is_empty())
+        };
+
+        imp.subtitle_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = optional_text(entry);
+                this.send_publication_update(move |p| p.set_subtitle(value));
+            }));
+
+        imp.series_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = optional_text(entry);
+                this.send_publication_update(move |p| p.set_series(value));
+            }));
+
+        imp.series_number_adjustment.connect_value_changed(
+            glib::clone!(@weak self as this => move |adjustment| {
+                let value = Some(adjustment.value() as u32).filter(|n| *n > 0);
+                this.send_publication_update(move |p| p.set_series_number(value));
+            }),
+        );
+
+        imp.language_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = optional_text(entry);
+                this.send_publication_update(move |p| p.set_language(value));
+            }));
+
+        imp.keywords_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = split_list(entry.text().as_str());
+                this.send_publication_update(move |p| p.set_keywords(value));
+            }));
+
+        imp.publisher_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = optional_text(entry);
+                this.send_publication_update(move |p| p.set_publisher(value));
+            }));
+
+        imp.isbns_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = split_list(entry.text().as_str());
+                if value.iter().all(|isbn| is_valid_isbn(isbn)) {
+                    entry.remove_css_class("error");
+                } else {
+                    entry.add_css_class("error");
+                }
+                this.send_publication_update(move |p| p.set_isbns(value));
+            }));
+
+        imp.publication_date_entry.connect_changed(
+            glib::clone!(@weak self as this => move |entry| {
+                let text = entry.text();
+                let value = parse_date(text.as_str());
+                if value.is_none() && !text.trim().is_empty() {
+                    entry.add_css_class("error");
+                    return;
+                }
+                entry.remove_css_class("error");
+                this.send_publication_update(move |p| p.set_publication_date(value));
+            }),
+        );
+
+        imp.rights_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value = optional_text(entry);
+                this.send_publication_update(move |p| p.set_rights(value));
+            }));
+
+        imp.publication_description_buffer.connect_changed(
+            glib::clone!(@weak self as this => move |buffer| {
+                let value = buffer
+                    .text(&buffer.start_iter(), &buffer.end_iter(), false)
+                    .to_string();
+                this.send_publication_update(move |p| p.set_description(value));
+            }),
+        );
+    }
+
+    fn send_publication_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut PublicationMetadata) + 'static,
+    {
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> =
+            Box::new(move |manifest| f(manifest.publication_mut()));
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
+    fn update_character_fields_list(&self) {
+        let list = self.imp().character_fields_list.get();
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        let fields = self.imp().character_fields.borrow();
+        for field in fields.iter() {
+            let subtitle = match field.kind() {
+                FieldKind::Choice(choices) => {
+                    format!("{} · {}", field.kind().label(), choices.join(", "))
+                }
+                kind => kind.label(),
+            };
+            let row = adw::ActionRow::builder()
+                .title(field.name())
+                .subtitle(subtitle.as_str())
+                .build();
+
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(i18n("Remove Field"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            let id = field.id().to_string();
+            remove_button.connect_clicked(glib::clone!(@weak self as this => move |_| {
+                this.remove_character_field(id.clone());
+            }));
+            row.add_suffix(&remove_button);
+            list.append(&row);
+        }
+        self.set_has_character_fields(!fields.is_empty());
+    }
+
+    fn add_character_field(&self, field: FieldDefinition) {
+        self.imp().character_fields.borrow_mut().push(field.clone());
+        self.update_character_fields_list();
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> = Box::new(move |manifest| {
+            manifest.add_character_field(field);
+        });
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
+    fn remove_character_field(&self, id: String) {
+        self.imp()
+            .character_fields
+            .borrow_mut()
+            .retain(|f| f.id() != id.as_str());
+        self.update_character_fields_list();
+        self.send_action(DocumentAction::RemoveCharacterField(id));
+    }
+
+    /// Asks for the name and kind of a new custom character field
+    fn show_add_character_field_dialog(&self) {
+        let name_entry = adw::EntryRow::builder().title(i18n("Name")).build();
+        let kind_entry = adw::ComboRow::builder()
+            .title(i18n("Type"))
+            .model(&gtk::StringList::new(&[
+                FieldKind::Text.label().as_str(),
+                FieldKind::Number.label().as_str(),
+                FieldKind::Date.label().as_str(),
+                FieldKind::Choice(vec![]).label().as_str(),
+                FieldKind::Link.label().as_str(),
+            ]))
+            .build();
+        let choices_entry = adw::EntryRow::builder()
+            .title(i18n("Choices, separated by commas"))
+            .visible(false)
+            .build();
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list.append(&name_entry);
+        list.append(&kind_entry);
+        list.append(&choices_entry);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(i18n("New Character Field"))
+            .extra_child(&list)
+            .modal(true)
+            .build();
+        if let Some(window) = self.root().and_then(|r| r.downcast::<gtk::Window>().ok()) {
+            dialog.set_transient_for(Some(&window));
+        }
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("add", &i18n("_Add"))]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("add"));
+        dialog.set_response_enabled("add", false);
+
+        kind_entry.connect_selected_notify(glib::clone!(@weak choices_entry => move |row| {
+            choices_entry.set_visible(row.selected() == 3);
+        }));
+
+        // Names are also search keys, so they must be unique
+        name_entry.connect_changed(
+            glib::clone!(@weak self as this, @weak dialog => move |entry| {
+                let candidate = FieldDefinition::new(entry.text().as_str(), FieldKind::Text);
+                let key = candidate.search_key();
+                let taken = this
+                    .imp()
+                    .character_fields
+                    .borrow()
+                    .iter()
+                    .any(|f| f.search_key() == key);
+                dialog.set_response_enabled("add", !key.is_empty() && !taken);
+            }),
+        );
+
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as this, @weak name_entry, @weak kind_entry, @weak choices_entry => move |_, response| {
+                if response != "add" {
+                    return;
+                }
+
+                let kind = match kind_entry.selected() {
+                    1 => FieldKind::Number,
+                    2 => FieldKind::Date,
+                    3 => FieldKind::Choice(
+                        choices_entry
+                            .text()
+                            .split(',')
+                            .map(|c| c.trim().to_string())
+                            .filter(|c| !c.is_empty())
+                            .collect(),
+                    ),
+                    4 => FieldKind::Link,
+                    _ => FieldKind::Text,
+                };
+                this.add_character_field(FieldDefinition::new(name_entry.text().trim(), kind));
+            }),
+        );
+        dialog.present();
+    }
+
+    fn update_statuses_list(&self) {
+        let list = self.imp().statuses_list.get();
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        let statuses = self.imp().statuses.borrow();
+        for status in statuses.iter() {
+            let row = adw::ActionRow::builder().title(status.name()).build();
+            let id = status.id().to_string();
+            row.add_suffix(&self.compile_switch(
+                status.compile(),
+                glib::clone!(@weak self as this, @strong id => move |value| {
+                    this.set_status_compile(id.clone(), value);
+                }),
+            ));
+            row.add_suffix(&self.remove_button(
+                i18n("Remove Status").as_str(),
+                glib::clone!(@weak self as this => move || {
+                    this.remove_status(id.clone());
+                }),
+            ));
+            list.append(&row);
+        }
+        self.set_has_statuses(!statuses.is_empty());
+    }
+
+    fn update_labels_list(&self) {
+        let list = self.imp().labels_list.get();
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        let labels = self.imp().labels.borrow();
+        for label in labels.iter() {
+            let row = adw::ActionRow::builder().title(label.name()).build();
+            let dot = gtk::Label::builder()
+                .label(format!(
+                    "<span foreground=\"{}\">●</span>",
+                    label.color().hex()
+                ))
+                .use_markup(true)
+                .build();
+            row.add_prefix(&dot);
+            let id = label.id().to_string();
+            row.add_suffix(&self.compile_switch(
+                label.compile(),
+                glib::clone!(@weak self as this, @strong id => move |value| {
+                    this.set_label_compile(id.clone(), value);
+                }),
+            ));
+            row.add_suffix(&self.remove_button(
+                i18n("Remove Label").as_str(),
+                glib::clone!(@weak self as this => move || {
+                    this.remove_label(id.clone());
+                }),
+            ));
+            list.append(&row);
+        }
+        self.set_has_labels(!labels.is_empty());
+    }
+
+    /// A switch telling whether chunks marked with a status or label are compiled
+    fn compile_switch<F>(&self, active: bool, f: F) -> gtk::Switch
+    where
+        F: Fn(bool) + 'static,
+    {
+        let switch = gtk::Switch::builder()
+            .active(active)
+            .valign(gtk::Align::Center)
+            .tooltip_text(i18n("Include in Compilation"))
+            .build();
+        switch.connect_active_notify(move |switch| f(switch.is_active()));
+        switch
+    }
+
+    fn remove_button<F>(&self, tooltip: &str, f: F) -> gtk::Button
+    where
+        F: Fn() + 'static,
+    {
+        let button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(tooltip)
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        button.connect_clicked(move |_| f());
+        button
+    }
+
+    fn add_status(&self, status: ChunkStatus) {
+        self.imp().statuses.borrow_mut().push(status.clone());
+        self.update_statuses_list();
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> = Box::new(move |manifest| {
+            manifest.add_status(status);
+        });
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
+    fn set_status_compile(&self, id: String, value: bool) {
+        if let Some(status) = self
+            .imp()
+            .statuses
+            .borrow_mut()
+            .iter_mut()
+            .find(|s| s.id() == id.as_str())
+        {
+            status.set_compile(value);
+        }
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> = Box::new(move |manifest| {
+            if let Some(status) = manifest.status_mut(id.as_str()) {
+                status.set_compile(value);
+            }
+        });
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
+    fn remove_status(&self, id: String) {
+        self.imp()
+            .statuses
+            .borrow_mut()
+            .retain(|s| s.id() != id.as_str());
+        self.update_statuses_list();
+        self.send_action(DocumentAction::RemoveStatus(id));
+    }
+
+    fn add_label(&self, label: ChunkLabel) {
+        self.imp().labels.borrow_mut().push(label.clone());
+        self.update_labels_list();
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> = Box::new(move |manifest| {
+            manifest.add_label(label);
+        });
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
+    fn set_label_compile(&self, id: String, value: bool) {
+        if let Some(label) = self
+            .imp()
+            .labels
+            .borrow_mut()
+            .iter_mut()
+            .find(|l| l.id() == id.as_str())
+        {
+            label.set_compile(value);
+        }
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> = Box::new(move |manifest| {
+            if let Some(label) = manifest.label_mut(id.as_str()) {
+                label.set_compile(value);
+            }
+        });
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
+    fn remove_label(&self, id: String) {
+        self.imp()
+            .labels
+            .borrow_mut()
+            .retain(|l| l.id() != id.as_str());
+        self.update_labels_list();
+        self.send_action(DocumentAction::RemoveLabel(id));
+    }
+
+    /// Asks for the name of a new status, or the name and color of a new
+    /// label if `with_color` is set
+    fn show_add_workflow_item_dialog<F>(&self, heading: &str, with_color: bool, on_add: F)
+    where
+        F: Fn(&str, Color) + 'static,
+    {
+        let name_entry = adw::EntryRow::builder().title(i18n("Name")).build();
+        let color_button = gtk::ColorButton::with_rgba(&gtk::gdk::RGBA::new(0.21, 0.52, 0.89, 1.0));
+        color_button.set_valign(gtk::Align::Center);
+        let color_row = adw::ActionRow::builder()
+            .title(i18n("Color"))
+            .visible(with_color)
+            .build();
+        color_row.add_suffix(&color_button);
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list.append(&name_entry);
+        list.append(&color_row);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .extra_child(&list)
+            .modal(true)
+            .build();
+        if let Some(window) = self.root().and_then(|r| r.downcast::<gtk::Window>().ok()) {
+            dialog.set_transient_for(Some(&window));
+        }
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("add", &i18n("_Add"))]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("add"));
+        dialog.set_response_enabled("add", false);
+
+        // Names are also search keys, so they must be unique
+        name_entry.connect_changed(
+            glib::clone!(@weak self as this, @weak dialog => move |entry| {
+                let key = search_key(entry.text().as_str());
+                let imp = this.imp();
+                let taken = if with_color {
+                    imp.labels.borrow().iter().any(|l| search_key(l.name()) == key)
+                } else {
+                    imp.statuses.borrow().iter().any(|s| search_key(s.name()) == key)
+                };
+                dialog.set_response_enabled("add", !key.is_empty() && !taken);
+            }),
+        );
+
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak name_entry, @weak color_button => move |_, response| {
+                if response == "add" {
+                    on_add(name_entry.text().trim(), Color::from(color_button.rgba()));
+                }
+            }),
+        );
+        dialog.present();
+    }
+
     fn send_action(&self, action: DocumentAction) {
         let maybe_channel = self.imp().sender.borrow();
         if let Some(channel) = maybe_channel.as_ref() {
@@ -221,6 +801,62 @@ impl ManuscriptProjectSettingsEditor {
 
 #[gtk::template_callbacks]
 impl ManuscriptProjectSettingsEditor {
+    #[template_callback]
+    fn on_add_character_field_clicked(&self, _button: &gtk::Button) {
+        self.show_add_character_field_dialog();
+    }
+
+    #[template_callback]
+    fn on_add_status_clicked(&self, _button: &gtk::Button) {
+        self.show_add_workflow_item_dialog(
+            i18n("New Status").as_str(),
+            false,
+            glib::clone!(@weak self as this => move |name, _| {
+                this.add_status(ChunkStatus::new(name));
+            }),
+        );
+    }
+
+    #[template_callback]
+    fn on_add_label_clicked(&self, _button: &gtk::Button) {
+        self.show_add_workflow_item_dialog(
+            i18n("New Label").as_str(),
+            true,
+            glib::clone!(@weak self as this => move |name, color| {
+                this.add_label(ChunkLabel::new(name, color));
+            }),
+        );
+    }
+
+    #[template_callback]
+    fn on_choose_cover_clicked(&self, _button: &gtk::Button) {
+        with_image_open_dialog(
+            i18n("Choose Cover").as_str(),
+            glib::clone!(@weak self as this => move |path| {
+                match std::fs::read(path.as_str()) {
+                    Ok(content) => {
+                        this.set_cover_texture(Some(content.as_slice()));
+                        let value = Bytes::from(content);
+                        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> =
+                            Box::new(move |manifest| manifest.set_cover(Some(value)));
+                        this.send_action(DocumentAction::UpdateManifestWith(func));
+                    }
+                    Err(error) => {
+                        glib::g_warning!(G_LOG_DOMAIN, "Could not read cover {path} - {error}");
+                    }
+                }
+            }),
+        );
+    }
+
+    #[template_callback]
+    fn on_remove_cover_clicked(&self, _button: &gtk::Button) {
+        self.set_cover_texture(None);
+        let func: Box<dyn FnOnce(&mut DocumentManifest) + 'static> =
+            Box::new(|manifest| manifest.set_cover(None));
+        self.send_action(DocumentAction::UpdateManifestWith(func));
+    }
+
     #[template_callback]
     fn on_project_title_entry_changed(&self, entry: adw::EntryRow) {
         let new_title = entry.text().to_string();
@@ -0,0 +1,292 @@
+use super::{prelude::EditorWidgetProtocol, ManuscriptBuffer, ManuscriptChunkSidePanel};
+use crate::{
+    libs::files::{open_with_system_viewer, with_attachment_open_dialog},
+    models::*,
+    services::{i18n::i18n, prelude::*, *},
+};
+use adw::{prelude::*, subclass::prelude::*};
+use bytes::Bytes;
+use glib_macros::Properties;
+use gtk::{gio, glib, glib::Sender};
+use std::cell::{Cell, RefCell};
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptResearchEditor";
+
+mod imp {
+    use super::*;
+    use glib::ParamSpec;
+
+    #[derive(Default, Properties, gtk::CompositeTemplate)]
+    #[properties(wrapper_type = super::ManuscriptResearchEditor)]
+    #[template(resource = "/io/sixpounder/Manuscript/editors/research_editor.ui")]
+    pub struct ManuscriptResearchEditor {
+        #[template_child]
+        pub(super) research_title_entry: TemplateChild<adw::EntryRow>,
+
+        #[template_child]
+        pub(super) research_notes_buffer: TemplateChild<ManuscriptBuffer>,
+
+        #[template_child]
+        pub(super) attachments_list: TemplateChild<gtk::ListBox>,
+
+        #[template_child]
+        pub(super) preview_picture: TemplateChild<gtk::Picture>,
+
+        /// Whether an image attachment is being previewed
+        #[property(get, set)]
+        pub(super) has_preview: Cell<bool>,
+
+        #[property(get, set)]
+        pub(super) has_attachments: Cell<bool>,
+
+        /// Local copy of the attachments, kept in sync with the chunk
+        pub(super) attachments: RefCell<Vec<Attachment>>,
+
+        pub(super) chunk_id: RefCell<String>,
+        pub(super) side_panel_widget: RefCell<Option<gtk::Widget>>,
+        pub(super) sender: RefCell<Option<Sender<DocumentAction>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptResearchEditor {
+        const NAME: &'static str = "ManuscriptResearchEditor";
+        type Type = super::ManuscriptResearchEditor;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptResearchEditor {
+        fn properties() -> &'static [gtk::glib::ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+    }
+
+    impl WidgetImpl for ManuscriptResearchEditor {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptResearchEditor(ObjectSubclass<imp::ManuscriptResearchEditor>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl EditorWidgetProtocol for ManuscriptResearchEditor {
+    fn editor_widget(&self) -> Option<gtk::Widget> {
+        Some(self.upcast_ref::<gtk::Widget>().clone())
+    }
+
+    fn side_panel_widget(&self) -> Option<gtk::Widget> {
+        self.imp().side_panel_widget.borrow().clone()
+    }
+}
+
+impl ManuscriptResearchEditor {
+    pub fn new(chunk: &dyn DocumentChunk, sender: Option<Sender<DocumentAction>>) -> Self {
+        let obj: Self = glib::Object::new();
+        *obj.imp().chunk_id.borrow_mut() = chunk.id().into();
+        *obj.imp().sender.borrow_mut() = sender.clone();
+        if let Some(source) = chunk.as_any().downcast_ref::<Research>() {
+            obj.setup_widgets(source);
+            obj.connect_events();
+        }
+
+        *obj.imp().side_panel_widget.borrow_mut() =
+            Some(ManuscriptChunkSidePanel::new(chunk, sender).upcast::<gtk::Widget>());
+        obj
+    }
+
+    fn setup_widgets(&self, source: &Research) {
+        let imp = self.imp();
+        imp.research_title_entry
+            .set_text(source.title().unwrap_or_default().as_str());
+        imp.research_notes_buffer.set_text(
+            String::from_utf8(source.notes().to_vec())
+                .unwrap_or_default()
+                .as_str(),
+        );
+        *imp.attachments.borrow_mut() = source.attachments().clone();
+        self.update_attachments_list();
+    }
+
+    /// Rebuilds the attachment rows from the local copy of the attachments
+    fn update_attachments_list(&self) {
+        let list = self.imp().attachments_list.get();
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        let attachments = self.imp().attachments.borrow();
+        for attachment in attachments.iter() {
+            list.append(&self.attachment_row(attachment));
+        }
+        self.set_has_attachments(!attachments.is_empty());
+    }
+
+    fn attachment_row(&self, attachment: &Attachment) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(attachment.name())
+            .subtitle(gio::content_type_get_description(attachment.content_type()).as_str())
+            .activatable(attachment.is_image())
+            .build();
+
+        let id = attachment.id().to_string();
+        row.connect_activated(glib::clone!(@weak self as this, @strong id => move |_| {
+            this.preview_attachment(id.as_str());
+        }));
+
+        let open_button = gtk::Button::builder()
+            .icon_name("external-link-symbolic")
+            .tooltip_text(i18n("Open With System Viewer"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        open_button.connect_clicked(glib::clone!(@weak self as this, @strong id => move |_| {
+            this.open_attachment(id.as_str());
+        }));
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(i18n("Remove Attachment"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        remove_button.connect_clicked(glib::clone!(@weak self as this, @strong id => move |_| {
+            this.remove_attachment(id.clone());
+        }));
+
+        row.add_suffix(&open_button);
+        row.add_suffix(&remove_button);
+        row
+    }
+
+    fn preview_attachment(&self, id: &str) {
+        let texture = self
+            .imp()
+            .attachments
+            .borrow()
+            .iter()
+            .find(|a| a.id() == id)
+            .and_then(|a| texture_from_bytes(a.content().as_ref()));
+        self.set_has_preview(texture.is_some());
+        self.imp().preview_picture.set_paintable(texture.as_ref());
+    }
+
+    fn open_attachment(&self, id: &str) {
+        let attachments = self.imp().attachments.borrow();
+        if let Some(attachment) = attachments.iter().find(|a| a.id() == id) {
+            let file_name = format!("{}-{}", attachment.id(), attachment.name());
+            if let Err(error) =
+                open_with_system_viewer(file_name.as_str(), attachment.content().as_ref())
+            {
+                glib::g_warning!(
+                    G_LOG_DOMAIN,
+                    "Could not open attachment {} - {error}",
+                    attachment.name()
+                );
+            }
+        }
+    }
+
+    fn add_attachment(&self, path: String) {
+        let content = match std::fs::read(path.as_str()) {
+            Ok(content) => content,
+            Err(error) => {
+                glib::g_warning!(G_LOG_DOMAIN, "Could not read attachment {path} - {error}");
+                return;
+            }
+        };
+
+        let (content_type, _) = gio::content_type_guess(Some(path.as_str()), &content);
+        let name = std::path::Path::new(path.as_str())
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        let attachment = Attachment::new(name, content_type.as_str(), Bytes::from(content));
+
+        self.imp().attachments.borrow_mut().push(attachment.clone());
+        self.update_attachments_list();
+        self.send_update(move |research| research.add_attachment(attachment));
+    }
+
+    fn remove_attachment(&self, id: String) {
+        self.imp()
+            .attachments
+            .borrow_mut()
+            .retain(|a| a.id() != id.as_str());
+        self.update_attachments_list();
+        self.set_has_preview(false);
+        self.send_update(move |research| {
+            research.remove_attachment(id.as_str());
+        });
+    }
+
+    fn send_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Research) + 'static,
+    {
+        let imp = self.imp();
+        let chunk_id = imp.chunk_id.borrow();
+        let tx = imp.sender.borrow();
+        let tx = tx.as_ref().expect("No channel sender found");
+        tx.send(DocumentAction::UpdateChunkWith(
+            chunk_id.clone(),
+            Box::new(move |chunk| {
+                if let Some(research) = chunk.as_any_mut().downcast_mut::<Research>() {
+                    f(research);
+                }
+            }),
+        ))
+        .expect("Failed to send research update");
+    }
+
+    fn connect_events(&self) {
+        let imp = self.imp();
+
+        imp.research_title_entry
+            .connect_changed(glib::clone!(@weak self as this => move |entry| {
+                let value: String = entry.text().into();
+                this.send_update(move |research| research.set_title(Some(value)));
+            }));
+
+        imp.research_notes_buffer
+            .connect_changed(glib::clone!(@weak self as this => move |buf| {
+                let value = bytes_from_text_buffer(buf.upcast_ref::<gtk::TextBuffer>());
+                this.send_update(move |research| research.set_notes_bytes(value));
+            }));
+    }
+}
+
+#[gtk::template_callbacks]
+impl ManuscriptResearchEditor {
+    #[template_callback]
+    fn on_add_attachment_clicked(&self, _button: &gtk::Button) {
+        with_attachment_open_dialog(glib::clone!(@weak self as this => move |path| {
+            this.add_attachment(path);
+        }));
+    }
+
+    #[template_callback]
+    fn on_close_preview_clicked(&self, _button: &gtk::Button) {
+        self.set_has_preview(false);
+        self.imp()
+            .preview_picture
+            .set_paintable(None::<&gtk::gdk::Paintable>);
+    }
+}
@@ -1,9 +1,16 @@
 use super::{prelude::EditorWidgetProtocol, ManuscriptBuffer, ManuscriptChunkSidePanel};
-use crate::{models::*, services::prelude::*, services::*};
+use crate::{
+    libs::files::with_image_open_dialog,
+    models::*,
+    services::{i18n::i18n, prelude::*, *},
+};
 use adw::{prelude::*, subclass::prelude::*};
 use bytes::Bytes;
 use gtk::{gio, glib, glib::Sender};
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::HashMap};
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptCharacterSheetEditor";
 
 mod imp {
     use super::*;
@@ -13,9 +20,27 @@ mod imp {
     #[derive(Default, gtk::CompositeTemplate)]
     #[template(resource = "/io/sixpounder/Manuscript/editors/sheet_editor.ui")]
     pub struct ManuscriptCharacterSheetEditor {
+        #[template_child]
+        pub(super) portrait_picture: TemplateChild<gtk::Picture>,
+
+        #[template_child]
+        pub(super) choose_portrait_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub(super) remove_portrait_button: TemplateChild<gtk::Button>,
+
         #[template_child]
         pub(super) character_name_entry: TemplateChild<adw::EntryRow>,
 
+        #[template_child]
+        pub(super) rename_everywhere_button: TemplateChild<gtk::Button>,
+
+        /// The name the manuscript knows the character by, until it is renamed everywhere
+        pub(super) previous_name: RefCell<String>,
+
+        #[template_child]
+        pub(super) character_aliases_entry: TemplateChild<adw::EntryRow>,
+
         #[template_child]
         pub(super) character_role_entry: TemplateChild<adw::EntryRow>,
 
@@ -43,6 +68,36 @@ mod imp {
         #[template_child]
         pub(super) character_psycological_traits_buffer: TemplateChild<ManuscriptBuffer>,
 
+        #[template_child]
+        pub(super) custom_fields_group: TemplateChild<adw::PreferencesGroup>,
+
+        #[template_child]
+        pub(super) custom_fields_list: TemplateChild<gtk::ListBox>,
+
+        /// Field definitions the custom field rows were built from
+        pub(super) custom_field_definitions: RefCell<Vec<FieldDefinition>>,
+
+        /// Rows of link fields, by field id, showing the name of the linked chunk
+        pub(super) custom_link_rows: RefCell<HashMap<String, adw::ActionRow>>,
+
+        #[template_child]
+        pub(super) add_relationship_button: TemplateChild<gtk::Button>,
+
+        #[template_child]
+        pub(super) relationships_list: TemplateChild<gtk::ListBox>,
+
+        /// Identifiers and titles of the relationship rows currently shown
+        pub(super) relationship_titles: RefCell<Vec<(String, String)>>,
+
+        #[template_child]
+        pub(super) first_appearance_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub(super) appearance_chapters_row: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        pub(super) mentions_row: TemplateChild<adw::ActionRow>,
+
         pub(super) chunk_id: RefCell<String>,
         pub(super) side_panel_widget: RefCell<Option<gtk::Widget>>,
         pub(super) sender: RefCell<Option<Sender<DocumentAction>>>,
@@ -161,8 +216,19 @@ impl ManuscriptCharacterSheetEditor {
     }
 
     fn setup_widgets(&self, source: &CharacterSheet) {
+        self.imp()
+            .add_relationship_button
+            .set_action_target_value(Some(&source.id().to_variant()));
+
+        self.set_portrait_texture(source.portrait().map(|portrait| portrait.as_ref()));
+
         self.character_name_entry()
             .set_text(source.name().unwrap_or(&String::default()).as_str());
+        self.forget_previous_name();
+
+        self.imp()
+            .character_aliases_entry
+            .set_text(source.aliases().join(", ").as_str());
 
         self.character_role_entry()
             .set_text(source.role().unwrap_or(&String::default()).as_str());
@@ -192,6 +258,243 @@ impl ManuscriptCharacterSheetEditor {
         );
     }
 
+    fn set_portrait_texture(&self, portrait: Option<&[u8]>) {
+        let imp = self.imp();
+        let texture = portrait.and_then(texture_from_bytes);
+        imp.portrait_picture.set_paintable(texture.as_ref());
+        imp.portrait_picture.set_visible(texture.is_some());
+        imp.remove_portrait_button.set_visible(texture.is_some());
+    }
+
+    fn set_character_portrait(&self, value: Option<Bytes>) {
+        self.send_update(move |chunk| {
+            let obj = chunk
+                .as_any_mut()
+                .downcast_mut::<CharacterSheet>()
+                .expect("How?");
+            obj.set_portrait(value);
+        });
+    }
+
+    /// Rebuilds the custom field rows if the project field definitions
+    /// changed, and refreshes the names of the chunks linked by the sheet
+    pub fn update_custom_fields(&self, document: &Document, sheet: &CharacterSheet) {
+        let imp = self.imp();
+        let definitions = document.manifest().character_fields();
+        if *imp.custom_field_definitions.borrow() != *definitions {
+            *imp.custom_field_definitions.borrow_mut() = definitions.clone();
+            imp.custom_link_rows.borrow_mut().clear();
+
+            let list = imp.custom_fields_list.get();
+            while let Some(row) = list.first_child() {
+                list.remove(&row);
+            }
+            for field in definitions.iter() {
+                list.append(&self.custom_field_row(
+                    document,
+                    field,
+                    sheet.custom_field(field.id()),
+                ));
+            }
+            imp.custom_fields_group.set_visible(!definitions.is_empty());
+        }
+
+        for (field_id, row) in imp.custom_link_rows.borrow().iter() {
+            let linked_name = sheet
+                .custom_field(field_id)
+                .map(|value| value.display(document))
+                .filter(|name| !name.is_empty());
+            row.set_subtitle(linked_name.unwrap_or_else(|| i18n("Nothing")).as_str());
+        }
+    }
+
+    /// Rebuilds the relationship rows when relationships were added or
+    /// removed, or the characters they point to were renamed
+    pub fn update_relationships(&self, document: &Document, sheet: &CharacterSheet) {
+        let imp = self.imp();
+        let titles: Vec<(String, String)> = sheet
+            .relationships()
+            .iter()
+            .map(|relationship| {
+                let target = document
+                    .get_chunk_ref(relationship.target())
+                    .map(|c| c.safe_title())
+                    .unwrap_or_default();
+                let title = i18n("{kind} of {name}")
+                    .replace("{kind}", relationship.kind().label().as_str())
+                    .replace("{name}", target.as_str());
+                (relationship.id().to_string(), title)
+            })
+            .collect();
+        if *imp.relationship_titles.borrow() == titles {
+            return;
+        }
+
+        let list = imp.relationships_list.get();
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+        for (relationship, (id, title)) in sheet.relationships().iter().zip(titles.iter()) {
+            let row = adw::EntryRow::builder().title(title.as_str()).build();
+            row.set_text(relationship.note().cloned().unwrap_or_default().as_str());
+            row.set_tooltip_text(Some(i18n("Note").as_str()));
+
+            let remove_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(i18n("Remove Relationship"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+            remove_button.connect_clicked(
+                glib::clone!(@weak self as this, @strong id => move |_| {
+                    let id = id.clone();
+                    this.send_update(move |chunk| {
+                        if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                            sheet.remove_relationship(id.as_str());
+                        }
+                    });
+                }),
+            );
+            row.add_suffix(&remove_button);
+
+            row.connect_changed(glib::clone!(@weak self as this, @strong id => move |row| {
+                let id = id.clone();
+                let note: String = row.text().into();
+                this.send_update(move |chunk| {
+                    if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                        sheet.set_relationship_note(id.as_str(), Some(note).filter(|n| !n.is_empty()));
+                    }
+                });
+            }));
+            list.append(&row);
+        }
+        list.set_visible(!titles.is_empty());
+        *imp.relationship_titles.borrow_mut() = titles;
+    }
+
+    /// Shows where the chapters of `index` mention the character
+    pub fn update_appearances(&self, index: &AppearanceIndex) {
+        let imp = self.imp();
+        let appearance = index.appearance(imp.chunk_id.borrow().as_str());
+        let first = appearance
+            .and_then(|a| a.first_chapter())
+            .and_then(|chapter| index.chapters().get(chapter));
+
+        imp.first_appearance_row.set_subtitle(
+            first
+                .map(|c| glib::markup_escape_text(c.title()).to_string())
+                .unwrap_or_else(|| i18n("Not mentioned yet"))
+                .as_str(),
+        );
+        imp.first_appearance_row.set_activatable(first.is_some());
+        imp.first_appearance_row
+            .set_action_target_value(first.map(|c| c.id().to_variant()).as_ref());
+
+        let chapters = appearance.map_or(0, |a| a.chapters().len());
+        imp.appearance_chapters_row.set_subtitle(
+            i18n("{count} of {total}")
+                .replace("{count}", chapters.to_string().as_str())
+                .replace("{total}", index.chapters().len().to_string().as_str())
+                .as_str(),
+        );
+        imp.mentions_row
+            .set_subtitle(appearance.map_or(0, |a| a.mentions()).to_string().as_str());
+    }
+
+    fn custom_field_row(
+        &self,
+        document: &Document,
+        field: &FieldDefinition,
+        value: Option<&FieldValue>,
+    ) -> gtk::Widget {
+        let field_id = field.id().to_string();
+        match field.kind() {
+            FieldKind::Choice(choices) => {
+                let mut labels = vec![i18n("None")];
+                labels.extend(choices.iter().cloned());
+                let labels: Vec<&str> = labels.iter().map(|l| l.as_str()).collect();
+                let selected = match value {
+                    Some(FieldValue::Choice(choice)) => choices
+                        .iter()
+                        .position(|c| c == choice)
+                        .map(|p| p + 1)
+                        .unwrap_or(0),
+                    _ => 0,
+                };
+                let row = adw::ComboRow::builder()
+                    .title(field.name())
+                    .model(&gtk::StringList::new(labels.as_slice()))
+                    .selected(selected as u32)
+                    .build();
+
+                let choices = choices.clone();
+                row.connect_selected_notify(
+                    glib::clone!(@weak self as this, @strong field_id => move |row| {
+                        let value = (row.selected() as usize)
+                            .checked_sub(1)
+                            .and_then(|idx| choices.get(idx))
+                            .cloned()
+                            .map(FieldValue::Choice);
+                        this.set_custom_field(field_id.clone(), value);
+                    }),
+                );
+                row.upcast()
+            }
+            FieldKind::Link => {
+                let row = adw::ActionRow::builder().title(field.name()).build();
+                let button = gtk::Button::builder()
+                    .label(i18n("Choose…"))
+                    .valign(gtk::Align::Center)
+                    .action_name("project.choose-field-link")
+                    .build();
+                let chunk_id = self.imp().chunk_id.borrow().clone();
+                button.set_action_target_value(Some(&(chunk_id, field_id.clone()).to_variant()));
+                row.add_suffix(&button);
+                self.imp()
+                    .custom_link_rows
+                    .borrow_mut()
+                    .insert(field_id, row.clone());
+                row.upcast()
+            }
+            kind => {
+                let row = adw::EntryRow::builder().title(field.name()).build();
+                match kind {
+                    FieldKind::Number => row.set_input_purpose(gtk::InputPurpose::Number),
+                    FieldKind::Date => row.set_tooltip_text(Some(
+                        i18n("Year, month and day, like 1984-04-04").as_str(),
+                    )),
+                    _ => (),
+                }
+                if let Some(value) = value {
+                    row.set_text(value.display(document).as_str());
+                }
+
+                let field = field.clone();
+                row.connect_changed(glib::clone!(@weak self as this => move |row| {
+                    let text = row.text();
+                    let value = field.parse_value(text.as_str());
+                    if value.is_none() && !text.trim().is_empty() {
+                        row.add_css_class("error");
+                        return;
+                    }
+                    row.remove_css_class("error");
+                    this.set_custom_field(field.id().to_string(), value);
+                }));
+                row.upcast()
+            }
+        }
+    }
+
+    fn set_custom_field(&self, field_id: String, value: Option<FieldValue>) {
+        self.send_update(move |chunk| {
+            let obj = chunk
+                .as_any_mut()
+                .downcast_mut::<CharacterSheet>()
+                .expect("How?");
+            obj.set_custom_field(field_id.as_str(), value);
+        });
+    }
+
     fn set_chunk_id(&self, value: String) {
         *self.imp().chunk_id.borrow_mut() = value;
     }
@@ -214,6 +517,33 @@ impl ManuscriptCharacterSheetEditor {
         });
     }
 
+    /// Takes the current name as the one the manuscript uses, once the text
+    /// was renamed or the character is first loaded
+    pub fn forget_previous_name(&self) {
+        let imp = self.imp();
+        *imp.previous_name.borrow_mut() = imp.character_name_entry.text().trim().to_string();
+        imp.rename_everywhere_button.set_action_target_value(Some(
+            &(
+                imp.chunk_id.borrow().as_str(),
+                imp.previous_name.borrow().as_str(),
+            )
+                .to_variant(),
+        ));
+        self.update_rename_everywhere_button();
+    }
+
+    /// Offers to rename the character in the text once its name changed
+    fn update_rename_everywhere_button(&self) {
+        let imp = self.imp();
+        let name = imp.character_name_entry.text();
+        let previous_name = imp.previous_name.borrow();
+        imp.rename_everywhere_button.set_visible(
+            !previous_name.is_empty()
+                && !name.trim().is_empty()
+                && name.trim() != previous_name.as_str(),
+        );
+    }
+
     pub fn character_gender(&self) -> Gender {
         let idx = self.character_gender_entry().selected();
         Gender::from(idx)
@@ -368,6 +698,33 @@ impl ManuscriptCharacterSheetEditor {
     fn connect_events(&self) {
         let imp = self.imp();
 
+        imp.choose_portrait_button
+            .connect_clicked(glib::clone!(@weak self as this => move |_| {
+                with_image_open_dialog(
+                    i18n("Choose Portrait").as_str(),
+                    glib::clone!(@weak this => move |path| {
+                        match std::fs::read(path.as_str()) {
+                            Ok(content) => {
+                                this.set_portrait_texture(Some(content.as_slice()));
+                                this.set_character_portrait(Some(Bytes::from(content)));
+                            }
+                            Err(error) => {
+                                glib::g_warning!(
+                                    G_LOG_DOMAIN,
+                                    "Could not read portrait {path} - {error}"
+                                );
+                            }
+                        }
+                    }),
+                );
+            }));
+
+        imp.remove_portrait_button
+            .connect_clicked(glib::clone!(@weak self as this => move |_| {
+                this.set_portrait_texture(None);
+                this.set_character_portrait(None);
+            }));
+
         imp.character_age_adjustment.connect_value_changed(
             glib::clone!(@weak self as this => move |adjustment| {
                 this.set_character_age(adjustment.value() as u32);
@@ -377,9 +734,21 @@ impl ManuscriptCharacterSheetEditor {
         imp.character_name_entry
             .connect_changed(glib::clone!(@weak self as this => move |_| {
                 let imp = this.imp();
-                this.set_character_name(Some(imp.character_name_entry.text().into()))
+                this.set_character_name(Some(imp.character_name_entry.text().into()));
+                this.update_rename_everywhere_button();
             }));
 
+        imp.character_aliases_entry.connect_changed(
+            glib::clone!(@weak self as this => move |entry| {
+                let aliases = split_list(entry.text().as_str());
+                this.send_update(move |chunk| {
+                    if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                        sheet.set_aliases(aliases);
+                    }
+                });
+            }),
+        );
+
         imp.character_role_entry
             .connect_changed(glib::clone!(@weak self as this => move |_| {
                 let imp = this.imp();
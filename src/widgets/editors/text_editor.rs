@@ -1,9 +1,10 @@
-use super::{prelude::EditorWidgetProtocol, ManuscriptBuffer};
+use super::{prelude::EditorWidgetProtocol, CharacterHighlight, ManuscriptBuffer};
 use crate::{
+    libs::consts::{TAG_NAME_CHARACTER_PREFIX, TAG_NAME_HIGHLIGHT, TAG_NAME_NOTE},
     models::*,
     services::{
         i18n::i18n, prelude::bytes_from_text_buffer, BufferStats, DocumentAction,
-        ManuscriptSettings,
+        ManuscriptSettings, RichTextSerde,
     },
     widgets::{editors::ManuscriptChunkSidePanel, ManuscriptProgressIndicator},
 };
@@ -16,6 +17,22 @@ use gtk::{
 };
 use std::cell::{Cell, RefCell};
 
+/// Vertical space, in pixels, kept between the balloons of notes
+const NOTE_BALLOON_SPACING: i32 = 32;
+
+/// How many characters of a character sheet are shown when hovering their name
+const CHARACTER_SNIPPET_LENGTH: usize = 160;
+
+/// A note of the chapter, anchored to the text it is about by a pair of
+/// marks so that it follows that text while it is edited
+struct NoteAnchor {
+    id: String,
+    start: gtk::TextMark,
+    end: gtk::TextMark,
+    resolved: bool,
+    balloon: gtk::MenuButton,
+}
+
 mod imp {
     use super::*;
     use glib::{ParamSpecBoolean, ParamSpecInt, ParamSpecObject, ParamSpecString};
@@ -51,6 +68,13 @@ mod imp {
         pub(super) reading_time: Cell<(u64, u64)>,
         pub(super) settings: ManuscriptSettings,
         pub(super) style_manager: adw::StyleManager,
+        pub(super) notes: RefCell<Vec<NoteAnchor>>,
+        /// Note ranges as last sent to the document
+        pub(super) sent_note_ranges: RefCell<Vec<(String, i32, i32)>>,
+        /// Marks of persistent tags as last sent to the document
+        pub(super) sent_tags: RefCell<Vec<TagMark>>,
+        /// Characters of the project, shown when hovering their names
+        pub(super) characters: RefCell<Vec<CharacterSheet>>,
     }
 
     impl Default for ManuscriptTextEditor {
@@ -73,6 +97,10 @@ mod imp {
                 reading_time: Cell::new((0, 0)),
                 settings: ManuscriptSettings::default(),
                 style_manager: adw::StyleManager::default(),
+                notes: RefCell::default(),
+                sent_note_ranges: RefCell::default(),
+                sent_tags: RefCell::default(),
+                characters: RefCell::default(),
             }
         }
     }
@@ -86,6 +114,22 @@ mod imp {
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
             klass.set_layout_manager_type::<gtk::BinLayout>();
+
+            klass.install_action("editor.split-chapter", None, move |editor, _, _| {
+                editor.split_chapter();
+            });
+
+            klass.install_action("editor.merge-with-next", None, move |editor, _, _| {
+                editor.merge_with_next();
+            });
+
+            klass.install_action("editor.add-note", None, move |editor, _, _| {
+                editor.add_note();
+            });
+
+            klass.install_action("editor.highlight", None, move |editor, _, _| {
+                editor.toggle_highlight();
+            });
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -215,14 +259,19 @@ impl ManuscriptTextEditor {
         obj.imp().sender.replace(sender.clone());
         *obj.imp().side_panel_widget.borrow_mut() =
             Some(ManuscriptChunkSidePanel::new(chunk, sender).upcast::<gtk::Widget>());
+
+        let is_chapter = chunk.chunk_type() == ChunkType::Chapter;
+        obj.action_set_enabled("editor.split-chapter", is_chapter);
+        obj.action_set_enabled("editor.merge-with-next", is_chapter);
+        obj.action_set_enabled("editor.add-note", is_chapter);
         obj
     }
 
-    pub fn init(&self, chunk_id: String, buffer: Option<Bytes>) {
+    pub fn init(&self, chunk_id: String, buffer: Option<Bytes>, tags: &[TagMark]) {
         let imp = self.imp();
 
         imp.chunk_id.replace(Some(chunk_id));
-        self.set_buffer_irreversible(buffer);
+        self.set_buffer_irreversible(buffer, tags);
     }
 
     pub fn chunk_id(&self) -> Option<String> {
@@ -233,6 +282,23 @@ impl ManuscriptTextEditor {
     fn setup_widgets(&self) {
         let imp = self.imp();
 
+        let chapter_menu = gio::Menu::new();
+        chapter_menu.append(
+            Some(i18n("Split Chapter at Cursor").as_str()),
+            Some("editor.split-chapter"),
+        );
+        chapter_menu.append(
+            Some(i18n("Merge with Next Chapter").as_str()),
+            Some("editor.merge-with-next"),
+        );
+        let notes_menu = gio::Menu::new();
+        notes_menu.append(Some(i18n("Add Note").as_str()), Some("editor.add-note"));
+        notes_menu.append(Some(i18n("Highlight").as_str()), Some("editor.highlight"));
+        let extra_menu = gio::Menu::new();
+        extra_menu.append_section(None, &notes_menu);
+        extra_menu.append_section(None, &chapter_menu);
+        imp.text_view.set_extra_menu(Some(&extra_menu));
+
         imp.style_manager
             .connect_dark_notify(glib::clone!(@weak self as this => move |_| {
                 this.setup_themes();
@@ -249,6 +315,46 @@ impl ManuscriptTextEditor {
                 this.notify("overflowing");
             })
         );
+
+        // Ctrl+click follows [[Name]] links
+        let link_click = gtk::GestureClick::builder()
+            .propagation_phase(gtk::PropagationPhase::Capture)
+            .build();
+        link_click.connect_pressed(glib::clone!(@weak self as this => move |gesture, _, x, y| {
+            if !gesture
+                .current_event_state()
+                .contains(gtk::gdk::ModifierType::CONTROL_MASK)
+            {
+                return;
+            }
+
+            if let Some(name) = this.link_at(x, y) {
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                let _ = WidgetExt::activate_action(&this, "project.follow-link", Some(&name.to_variant()));
+            }
+        }));
+        imp.text_view.add_controller(link_click);
+
+        imp.text_view.set_has_tooltip(true);
+        imp.text_view.connect_query_tooltip(
+            glib::clone!(@weak self as this => @default-return false, move |_, x, y, keyboard_mode, tooltip| {
+                !keyboard_mode && this.query_character_tooltip(x, y, tooltip)
+            }),
+        );
+
+        imp.settings.connect_changed(
+            "highlight-character-names",
+            glib::clone!(@weak self as this => move |_, _| {
+                this.tint_characters();
+            }),
+        );
+
+        // Text flows again when the editor is resized
+        imp.scroll_container.vadjustment().connect_changed(
+            glib::clone!(@weak self as this => move |_| {
+                this.place_note_balloons();
+            }),
+        );
     }
 
     fn setup_themes(&self) {
@@ -257,46 +363,41 @@ impl ManuscriptTextEditor {
         }
     }
 
-    fn set_buffer_irreversible(&self, value: Option<Bytes>) {
-        self.set_buffer(value, true);
+    fn set_buffer_irreversible(&self, value: Option<Bytes>, tags: &[TagMark]) {
+        self.set_buffer(value, tags, true);
         self.debounce_analyze();
     }
 
-    fn set_buffer(&self, value: Option<Bytes>, irreversible: bool) {
+    fn set_buffer(&self, value: Option<Bytes>, tags: &[TagMark], irreversible: bool) {
+        self.clear_notes();
         let text_buffer = ManuscriptBuffer::new(None, Some(self.text_view()));
-        let bytes = value.unwrap_or(Bytes::new());
+        let bytes = value.unwrap_or_default();
         let imp = self.imp();
         imp.words_count.set(bytes.words_count());
         imp.reading_time.set(bytes.estimate_reading_time());
 
         if irreversible {
             text_buffer.begin_irreversible_action();
-            text_buffer.set_text(
-                String::from_utf8(bytes.slice(..).to_vec())
-                    .unwrap()
-                    .as_str(),
-            );
+            RichTextSerde::deserialize_buffer_with_tags(text_buffer.upcast_ref(), &bytes, tags);
             text_buffer.end_irreversible_action();
         } else {
-            text_buffer.set_text(
-                String::from_utf8(bytes.slice(..).to_vec())
-                    .unwrap()
-                    .as_str(),
-            );
+            RichTextSerde::deserialize_buffer_with_tags(text_buffer.upcast_ref(), &bytes, tags);
         }
+        *imp.sent_tags.borrow_mut() = tags.to_vec();
         imp.text_view.set_buffer(Some(&text_buffer));
         imp.text_buffer.replace(Some(text_buffer));
+        self.tint_characters();
         self.connect_text_buffer();
         self.setup_themes();
         self.idle_format();
         self.notify("overflowing");
     }
 
-    pub fn text_buffer(&self) -> std::cell::Ref<Option<ManuscriptBuffer>> {
+    pub fn text_buffer(&self) -> std::cell::Ref<'_, Option<ManuscriptBuffer>> {
         self.imp().text_buffer.borrow()
     }
 
-    pub fn text_buffer_mut(&self) -> std::cell::RefMut<Option<ManuscriptBuffer>> {
+    pub fn text_buffer_mut(&self) -> std::cell::RefMut<'_, Option<ManuscriptBuffer>> {
         self.imp().text_buffer.borrow_mut()
     }
 
@@ -314,6 +415,18 @@ impl ManuscriptTextEditor {
                 this.on_buffer_changed(buf);
             }));
 
+            // Formatting drops every tag, notes highlights included
+            buffer.connect_closure(
+                "parsed",
+                false,
+                closure_local!(@weak-allow-none self as this => move |_buf: ManuscriptBuffer| {
+                    if let Some(this) = this {
+                        this.highlight_notes();
+                        this.place_note_balloons();
+                    }
+                }),
+            );
+
             buffer.connect_closure(
                 "parse-first-header",
                 false,
@@ -383,8 +496,480 @@ impl ManuscriptTextEditor {
 
     fn on_parse_first_header(&self, value: String) {
         self.send_update(move |chunk| {
-            if let Some(chapter) = chunk.as_any_mut().downcast_mut::<Chapter>() {
+            let any = chunk.as_any_mut();
+            if let Some(chapter) = any.downcast_mut::<Chapter>() {
                 chapter.set_title(value.as_str());
+            } else if let Some(part) = any.downcast_mut::<Part>() {
+                part.set_title(value.as_str());
+            } else if let Some(scene) = any.downcast_mut::<Scene>() {
+                scene.set_title(value.as_str());
+            }
+        });
+    }
+
+    /// Moves the text after the cursor into a new chapter
+    fn split_chapter(&self) {
+        let Some(chunk_id) = self.chunk_id() else {
+            return;
+        };
+        self.flush_edits();
+        let offset = match self.text_buffer().as_ref() {
+            Some(buffer) => buffer.iter_at_mark(&buffer.get_insert()).offset(),
+            None => return,
+        };
+        self.sender()
+            .send(DocumentAction::SplitChapter(chunk_id, offset))
+            .expect("Failed to send chapter split");
+    }
+
+    fn merge_with_next(&self) {
+        self.flush_edits();
+        if let Some(chunk_id) = self.chunk_id() {
+            self.sender()
+                .send(DocumentAction::MergeChapterWithNext(chunk_id))
+                .expect("Failed to send chapter merge");
+        }
+    }
+
+    /// Sets the characters of the project, whose names are tinted with their
+    /// accent color unless disabled in settings
+    pub fn set_characters(&self, characters: Vec<CharacterSheet>) {
+        *self.imp().characters.borrow_mut() = characters;
+        self.tint_characters();
+    }
+
+    fn tint_characters(&self) {
+        let highlights = if self.settings().highlight_character_names() {
+            self.imp()
+                .characters
+                .borrow()
+                .iter()
+                .filter_map(|sheet| {
+                    Some(CharacterHighlight {
+                        id: sheet.id().to_string(),
+                        names: sheet.mention_names(),
+                        color: sheet.accent()?.into(),
+                    })
+                })
+                .filter(|highlight| !highlight.names.is_empty())
+                .collect()
+        } else {
+            vec![]
+        };
+
+        if let Some(buffer) = self.text_buffer().as_ref() {
+            buffer.set_characters(highlights);
+        }
+    }
+
+    /// Shows the details of the character whose name is under the widget
+    /// coordinates `x`, `y`, if any
+    fn query_character_tooltip(&self, x: i32, y: i32, tooltip: &gtk::Tooltip) -> bool {
+        let text_view = self.text_view();
+        let (buffer_x, buffer_y) =
+            text_view.window_to_buffer_coords(gtk::TextWindowType::Widget, x, y);
+        let Some(iter) = text_view.iter_at_location(buffer_x, buffer_y) else {
+            return false;
+        };
+        let Some((tag, id)) = iter.tags().into_iter().find_map(|tag| {
+            let id = tag
+                .name()?
+                .strip_prefix(TAG_NAME_CHARACTER_PREFIX)?
+                .to_string();
+            Some((tag, id))
+        }) else {
+            return false;
+        };
+        let characters = self.imp().characters.borrow();
+        let Some(sheet) = characters.iter().find(|sheet| sheet.id() == id) else {
+            return false;
+        };
+
+        let mut start = iter;
+        if !start.starts_tag(Some(&tag)) {
+            start.backward_to_tag_toggle(Some(&tag));
+        }
+        let mut end = iter;
+        end.forward_to_tag_toggle(Some(&tag));
+        let (start_location, end_location) = (
+            text_view.iter_location(&start),
+            text_view.iter_location(&end),
+        );
+        let (area_x, area_y) = text_view.buffer_to_window_coords(
+            gtk::TextWindowType::Widget,
+            start_location.x(),
+            start_location.y(),
+        );
+        tooltip.set_tip_area(&gtk::gdk::Rectangle::new(
+            area_x,
+            area_y,
+            (end_location.x() - start_location.x()).max(1),
+            start_location.height(),
+        ));
+        tooltip.set_custom(Some(&character_card(sheet)));
+        true
+    }
+
+    /// Name of the `[[Name]]` link under the widget coordinates `x`, `y`
+    fn link_at(&self, x: f64, y: f64) -> Option<String> {
+        let text_view = self.text_view();
+        let (x, y) =
+            text_view.window_to_buffer_coords(gtk::TextWindowType::Widget, x as i32, y as i32);
+        let iter = text_view.iter_at_location(x, y)?;
+        let mut line_start = iter;
+        line_start.set_line_offset(0);
+        let mut line_end = iter;
+        if !line_end.ends_line() {
+            line_end.forward_to_line_end();
+        }
+
+        let offset = iter.line_offset();
+        wiki_links(line_start.text(&line_end).as_str())
+            .into_iter()
+            .find(|link| link.start() <= offset && offset < link.end())
+            .map(|link| link.name().to_string())
+    }
+
+    /// Saves where notes and persistent tags moved to, before the chapter is reshaped
+    pub fn flush_edits(&self) {
+        self.flush_note_ranges();
+        let marks = match self.text_buffer().as_ref() {
+            Some(buffer) => RichTextSerde::serialize_buffer_with_tags(buffer.upcast_ref()).1,
+            None => return,
+        };
+        self.flush_tags(marks);
+    }
+
+    /// Highlights the selected text, or clears it if it is all highlighted already
+    fn toggle_highlight(&self) {
+        let marks = {
+            let buffer = self.text_buffer();
+            let Some(buffer) = buffer.as_ref() else {
+                return;
+            };
+            let Some((start, end)) = buffer.selection_bounds() else {
+                return;
+            };
+            let Some(tag) = buffer.tag_table().lookup(TAG_NAME_HIGHLIGHT) else {
+                return;
+            };
+
+            let mut cursor = start;
+            let highlighted = cursor.has_tag(&tag)
+                && (!cursor.forward_to_tag_toggle(Some(&tag)) || cursor >= end);
+            if highlighted {
+                buffer.remove_tag(&tag, &start, &end);
+            } else {
+                buffer.apply_tag(&tag, &start, &end);
+            }
+            RichTextSerde::serialize_buffer_with_tags(buffer.upcast_ref()).1
+        };
+        self.flush_tags(marks);
+    }
+
+    /// Saves the marks of persistent tags, if they changed since they were last sent
+    fn flush_tags(&self, marks: Vec<TagMark>) {
+        if *self.imp().sent_tags.borrow() == marks {
+            return;
+        }
+
+        *self.imp().sent_tags.borrow_mut() = marks.clone();
+        self.send_update(move |chunk| {
+            if let Some(chunk) = chunk.as_mutable_buffer_chunk() {
+                chunk.set_tags_map(marks);
+            }
+        });
+    }
+
+    /// Shows `notes` over the text they are anchored to, with a balloon in
+    /// the margin to read and manage each of them
+    pub fn set_notes(&self, notes: &[Note]) {
+        self.clear_notes();
+        for note in notes.iter() {
+            self.add_note_anchor(note);
+        }
+        *self.imp().sent_note_ranges.borrow_mut() = self.note_ranges();
+        self.highlight_notes();
+        self.place_note_balloons();
+    }
+
+    /// Adds an empty note over the selected text, or at the cursor
+    fn add_note(&self) {
+        let (start, end) = match self.text_buffer().as_ref() {
+            Some(buffer) => buffer.selection_bounds().unwrap_or_else(|| {
+                let cursor = buffer.iter_at_mark(&buffer.get_insert());
+                (cursor, cursor)
+            }),
+            None => return,
+        };
+
+        let note = Note::new(start.offset(), end.offset() - start.offset(), String::new());
+        let anchored = note.clone();
+        self.send_note_update(move |chapter| chapter.push_note(anchored));
+        let balloon = self.add_note_anchor(&note);
+        self.imp().sent_note_ranges.borrow_mut().push((
+            note.id().to_string(),
+            note.offset_start(),
+            note.offset_end(),
+        ));
+        self.highlight_notes();
+        self.place_note_balloons();
+        balloon.popup();
+    }
+
+    fn add_note_anchor(&self, note: &Note) -> gtk::MenuButton {
+        let balloon = self.note_balloon(note);
+        let text_view = self.text_view();
+        let buffer = text_view.buffer();
+        let start = buffer.create_mark(None, &buffer.iter_at_offset(note.offset_start()), true);
+        let end = buffer.create_mark(None, &buffer.iter_at_offset(note.offset_end()), false);
+        text_view.add_overlay(&balloon, 0, 0);
+        self.imp().notes.borrow_mut().push(NoteAnchor {
+            id: note.id().to_string(),
+            start,
+            end,
+            resolved: note.resolved(),
+            balloon: balloon.clone(),
+        });
+        balloon
+    }
+
+    /// A button showing the note in a popover, where it can be edited,
+    /// resolved or deleted
+    fn note_balloon(&self, note: &Note) -> gtk::MenuButton {
+        let id = note.id().to_string();
+        let content = String::from_utf8_lossy(note.buffer()).to_string();
+
+        let text_view = gtk::TextView::builder()
+            .wrap_mode(gtk::WrapMode::WordChar)
+            .top_margin(6)
+            .bottom_margin(6)
+            .left_margin(6)
+            .right_margin(6)
+            .build();
+        text_view.buffer().set_text(content.as_str());
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .width_request(260)
+            .height_request(120)
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .child(&text_view)
+            .css_classes(["card"])
+            .build();
+
+        let resolve_button = gtk::Button::with_label(
+            if note.resolved() {
+                i18n("Reopen")
+            } else {
+                i18n("Resolve")
+            }
+            .as_str(),
+        );
+        let delete_button = gtk::Button::builder()
+            .label(i18n("Delete"))
+            .css_classes(["destructive-action"])
+            .build();
+        let buttons = gtk::Box::builder()
+            .spacing(6)
+            .halign(gtk::Align::End)
+            .build();
+        buttons.append(&resolve_button);
+        buttons.append(&delete_button);
+
+        let content_box = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        content_box.append(&scrolled_window);
+        content_box.append(&buttons);
+        let popover = gtk::Popover::builder().child(&content_box).build();
+
+        let balloon = gtk::MenuButton::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text(content.as_str())
+            .popover(&popover)
+            .css_classes(["circular", "flat"])
+            .build();
+        if note.resolved() {
+            balloon.add_css_class("dim-label");
+        }
+
+        popover.connect_closed(
+            glib::clone!(@weak self as this, @weak balloon, @weak text_view, @strong id, @strong content => move |_| {
+                let buffer = text_view.buffer();
+                let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string();
+                if text == balloon.tooltip_text().map(|t| t.to_string()).unwrap_or_default() {
+                    return;
+                }
+
+                balloon.set_tooltip_text(Some(text.as_str()));
+                let id = id.clone();
+                this.send_note_update(move |chapter| {
+                    if let Some(note) = chapter.note_mut(id.as_str()) {
+                        note.set_buffer(Bytes::from(text));
+                    }
+                });
+            }),
+        );
+
+        resolve_button.connect_clicked(
+            glib::clone!(@weak self as this, @weak balloon, @weak popover, @strong id => move |button| {
+                let resolved = {
+                    let mut notes = this.imp().notes.borrow_mut();
+                    let Some(anchor) = notes.iter_mut().find(|a| a.id == id) else {
+                        return;
+                    };
+                    anchor.resolved = !anchor.resolved;
+                    anchor.resolved
+                };
+
+                button.set_label(if resolved { i18n("Reopen") } else { i18n("Resolve") }.as_str());
+                if resolved {
+                    balloon.add_css_class("dim-label");
+                } else {
+                    balloon.remove_css_class("dim-label");
+                }
+                popover.popdown();
+                this.highlight_notes();
+
+                let id = id.clone();
+                this.send_note_update(move |chapter| {
+                    if let Some(note) = chapter.note_mut(id.as_str()) {
+                        note.set_resolved(resolved);
+                    }
+                });
+            }),
+        );
+
+        delete_button.connect_clicked(
+            glib::clone!(@weak self as this, @weak popover, @strong id => move |_| {
+                popover.popdown();
+                this.remove_note_anchor(id.as_str());
+                let id = id.clone();
+                this.send_note_update(move |chapter| {
+                    chapter.remove_note(id.as_str());
+                });
+            }),
+        );
+
+        balloon
+    }
+
+    fn remove_note_anchor(&self, id: &str) {
+        let removed = {
+            let mut notes = self.imp().notes.borrow_mut();
+            notes
+                .iter()
+                .position(|a| a.id == id)
+                .map(|index| notes.remove(index))
+        };
+        if let Some(anchor) = removed {
+            self.remove_anchor_widgets(&anchor);
+        }
+        self.imp()
+            .sent_note_ranges
+            .borrow_mut()
+            .retain(|(note_id, _, _)| note_id != id);
+        self.highlight_notes();
+        self.place_note_balloons();
+    }
+
+    fn clear_notes(&self) {
+        let notes: Vec<NoteAnchor> = self.imp().notes.borrow_mut().drain(..).collect();
+        for anchor in notes.iter() {
+            self.remove_anchor_widgets(anchor);
+        }
+        self.imp().sent_note_ranges.borrow_mut().clear();
+    }
+
+    fn remove_anchor_widgets(&self, anchor: &NoteAnchor) {
+        self.text_view().remove(&anchor.balloon);
+        for mark in [&anchor.start, &anchor.end] {
+            if let Some(buffer) = mark.buffer() {
+                buffer.delete_mark(mark);
+            }
+        }
+    }
+
+    /// Highlights the text of the notes left to resolve
+    fn highlight_notes(&self) {
+        let Some(buffer) = self.text_buffer().clone() else {
+            return;
+        };
+        buffer.remove_tag_by_name(TAG_NAME_NOTE, &buffer.start_iter(), &buffer.end_iter());
+        for anchor in self.imp().notes.borrow().iter().filter(|a| !a.resolved) {
+            buffer.apply_tag_by_name(
+                TAG_NAME_NOTE,
+                &buffer.iter_at_mark(&anchor.start),
+                &buffer.iter_at_mark(&anchor.end),
+            );
+        }
+    }
+
+    /// Lines the balloons of notes up in the right margin, next to the
+    /// text they are anchored to
+    fn place_note_balloons(&self) {
+        let text_view = self.text_view();
+        let buffer = text_view.buffer();
+        let visible = text_view.visible_rect();
+        let x = visible.x() + visible.width() - text_view.right_margin() + 24;
+
+        let mut balloons: Vec<(i32, gtk::MenuButton)> = self
+            .imp()
+            .notes
+            .borrow()
+            .iter()
+            .map(|anchor| {
+                let location = text_view.iter_location(&buffer.iter_at_mark(&anchor.start));
+                (location.y(), anchor.balloon.clone())
+            })
+            .collect();
+        balloons.sort_by_key(|(y, _)| *y);
+
+        let mut last_y = i32::MIN / 2;
+        for (y, balloon) in balloons {
+            let y = y.max(last_y + NOTE_BALLOON_SPACING);
+            text_view.move_overlay(&balloon, x, y);
+            last_y = y;
+        }
+    }
+
+    fn note_ranges(&self) -> Vec<(String, i32, i32)> {
+        let buffer = self.text_view().buffer();
+        self.imp()
+            .notes
+            .borrow()
+            .iter()
+            .map(|anchor| {
+                (
+                    anchor.id.clone(),
+                    buffer.iter_at_mark(&anchor.start).offset(),
+                    buffer.iter_at_mark(&anchor.end).offset(),
+                )
+            })
+            .collect()
+    }
+
+    /// Saves where the notes moved to after edits, if they did
+    fn flush_note_ranges(&self) {
+        let ranges = self.note_ranges();
+        if *self.imp().sent_note_ranges.borrow() == ranges {
+            return;
+        }
+
+        *self.imp().sent_note_ranges.borrow_mut() = ranges.clone();
+        self.send_note_update(move |chapter| {
+            for (id, start, end) in ranges.iter() {
+                if let Some(note) = chapter.note_mut(id) {
+                    note.set_range(*start, end - start);
+                }
+            }
+        });
+    }
+
+    fn send_note_update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Chapter) + 'static,
+    {
+        self.send_update(move |chunk| {
+            if let Some(chapter) = chunk.as_any_mut().downcast_mut::<Chapter>() {
+                f(chapter);
             }
         });
     }
@@ -406,12 +991,14 @@ impl ManuscriptTextEditor {
             glib::clone!(@weak self as this => @default-return glib::ControlFlow::Break, move || {
                 let imp = this.imp();
                 if let Some(buf) = this.text_buffer().as_ref() {
-                    let bytes = bytes_from_text_buffer(buf.upcast_ref::<gtk::TextBuffer>());
+                    let (bytes, marks) = RichTextSerde::serialize_buffer_with_tags(buf.upcast_ref());
                     let words_count = bytes.words_count();
                     let (reading_time_minutes, reading_time_seconds) = bytes.estimate_reading_time();
                     this.set_words_count(words_count);
                     this.set_reading_time((reading_time_minutes, reading_time_seconds));
 
+                    this.flush_note_ranges();
+                    this.flush_tags(marks);
                     let chunk_id = imp.chunk_id.borrow();
                         if let Some(chunk_id) = chunk_id.as_ref() {
                         let tx = imp.sender.borrow();
@@ -467,3 +1054,65 @@ impl ManuscriptTextEditor {
         tx.as_ref().expect("No channel sender found").clone()
     }
 }
+
+/// A short summary of a character: their name, role, age and the
+/// beginning of their sheet
+fn character_card(sheet: &CharacterSheet) -> gtk::Widget {
+    let card = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(4)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(6)
+        .margin_end(6)
+        .build();
+    card.append(
+        &gtk::Label::builder()
+            .label(sheet.safe_title())
+            .xalign(0.0)
+            .css_classes(["heading"])
+            .build(),
+    );
+
+    let mut details = vec![];
+    if let Some(role) = sheet.role().filter(|r| !r.trim().is_empty()) {
+        details.push(role.to_string());
+    }
+    if let Some(age) = sheet.age() {
+        details.push(i18n("{age} years old").replace("{age}", age.to_string().as_str()));
+    }
+    if !details.is_empty() {
+        card.append(
+            &gtk::Label::builder()
+                .label(details.join(" · "))
+                .xalign(0.0)
+                .css_classes(["dim-label"])
+                .build(),
+        );
+    }
+
+    let snippet = [
+        sheet.background(),
+        sheet.physical_traits(),
+        sheet.psycological_traits(),
+    ]
+    .into_iter()
+    .map(|text| String::from_utf8_lossy(text).trim().to_string())
+    .find(|text| !text.is_empty());
+    if let Some(snippet) = snippet {
+        let mut shortened: String = snippet.chars().take(CHARACTER_SNIPPET_LENGTH).collect();
+        if shortened.len() < snippet.len() {
+            shortened.push('…');
+        }
+        card.append(
+            &gtk::Label::builder()
+                .label(shortened)
+                .xalign(0.0)
+                .wrap(true)
+                .max_width_chars(40)
+                .build(),
+        );
+    }
+
+    card.upcast()
+}
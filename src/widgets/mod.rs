@@ -1,21 +1,30 @@
 mod chunk_row;
 mod command_palette;
+mod corkboard;
 pub mod dialogs;
 mod editor_view_shell;
 pub mod editors;
+mod outliner;
 mod primary_menu_button;
 mod progress_indicator;
 mod project_layout;
 mod project_layout_chunk_container;
+mod relationship_graph_view;
 mod theme_switcher;
+mod timeline;
 mod welcome_view;
 
 pub use chunk_row::*;
+#[allow(unused_imports)]
 pub use command_palette::*;
+pub use corkboard::*;
 pub use editor_view_shell::*;
+pub use outliner::*;
 pub use primary_menu_button::*;
 pub use progress_indicator::*;
 pub use project_layout::*;
 pub use project_layout_chunk_container::*;
+pub use relationship_graph_view::*;
 pub use theme_switcher::*;
+pub use timeline::*;
 pub use welcome_view::*;
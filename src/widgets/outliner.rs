@@ -0,0 +1,706 @@
+use crate::{
+    models::*,
+    services::{i18n::i18n, DocumentAction, DocumentManager, ManuscriptSettings},
+};
+use adw::subclass::prelude::*;
+use glib_macros::Properties;
+use gtk::{gdk, gio, glib, glib::closure_local, prelude::*};
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+};
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptOutliner";
+
+/// Horizontal space, in pixels, added to titles for each level of nesting
+const INDENT_WIDTH: i32 = 18;
+
+mod item_imp {
+    use super::*;
+    use glib::ParamSpec;
+
+    /// A chapter or scene listed by the outliner
+    #[derive(Default, Properties)]
+    #[properties(wrapper_type = super::ManuscriptOutlineItem)]
+    pub struct ManuscriptOutlineItem {
+        #[property(get, set)]
+        pub(super) chunk_id: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) depth: Cell<u32>,
+
+        #[property(get, set)]
+        pub(super) is_scene: Cell<bool>,
+
+        #[property(get, set)]
+        pub(super) title: RefCell<String>,
+
+        /// Id of the workflow status, empty if there is none
+        #[property(get, set)]
+        pub(super) status_id: RefCell<String>,
+
+        /// Id of the point of view character, empty if there is none
+        #[property(get, set)]
+        pub(super) point_of_view_id: RefCell<String>,
+
+        #[property(get, set)]
+        pub(super) words_count: Cell<u64>,
+
+        /// Words target, zero if there is none
+        #[property(get, set)]
+        pub(super) words_target: Cell<u64>,
+
+        /// Last edit time in seconds since the Unix epoch, zero if unknown
+        #[property(get, set)]
+        pub(super) modified: Cell<i64>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptOutlineItem {
+        const NAME: &'static str = "ManuscriptOutlineItem";
+        type Type = super::ManuscriptOutlineItem;
+    }
+
+    impl ObjectImpl for ManuscriptOutlineItem {
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> glib::Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn set_property(&self, id: usize, value: &glib::Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec)
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct ManuscriptOutlineItem(ObjectSubclass<item_imp::ManuscriptOutlineItem>);
+}
+
+impl ManuscriptOutlineItem {
+    fn new(chunk: &dyn DocumentChunk, depth: u32) -> Self {
+        let obj: Self = glib::Object::builder()
+            .property("chunk-id", chunk.id())
+            .property("depth", depth)
+            .build();
+        obj.update(chunk);
+        obj
+    }
+
+    /// Copies the outlined details of `chunk`
+    fn update(&self, chunk: &dyn DocumentChunk) {
+        let text = |value: Option<&String>| value.cloned().unwrap_or_default();
+        let scene = chunk.as_any().downcast_ref::<Scene>();
+        self.set_title(chunk.safe_title());
+        self.set_is_scene(scene.is_some());
+        self.set_point_of_view_id(text(scene.and_then(|s| s.point_of_view())));
+        if let Some(buffer_chunk) = chunk.as_buffer_chunk() {
+            self.set_status_id(text(buffer_chunk.status()));
+            self.set_words_count(buffer_chunk.words_count());
+            self.set_words_target(buffer_chunk.words_target().unwrap_or(0));
+            self.set_modified(buffer_chunk.modified().unwrap_or(0));
+        }
+    }
+}
+
+mod imp {
+    use super::*;
+
+    /// A table of the chapters and scenes of the manuscript, with sortable
+    /// and editable columns for their details
+    #[derive(gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/outliner.ui")]
+    pub struct ManuscriptOutliner {
+        #[template_child]
+        pub(super) column_view: TemplateChild<gtk::ColumnView>,
+
+        #[template_child]
+        pub(super) columns_box: TemplateChild<gtk::Box>,
+
+        pub(super) store: gio::ListStore,
+
+        /// Names of the workflow statuses after a "None" item
+        pub(super) status_model: gtk::StringList,
+
+        pub(super) status_ids: RefCell<Vec<String>>,
+
+        /// Names of the characters after a "Nobody" item
+        pub(super) character_model: gtk::StringList,
+
+        pub(super) character_ids: RefCell<Vec<String>>,
+
+        pub(super) document_manager: RefCell<Option<DocumentManager>>,
+
+        pub(super) settings: ManuscriptSettings,
+
+        /// Set while cells are filled from the items, so that no update is sent back
+        pub(super) binding: Cell<bool>,
+    }
+
+    impl Default for ManuscriptOutliner {
+        fn default() -> Self {
+            Self {
+                column_view: TemplateChild::default(),
+                columns_box: TemplateChild::default(),
+                store: gio::ListStore::new::<ManuscriptOutlineItem>(),
+                status_model: gtk::StringList::new(&[]),
+                status_ids: RefCell::default(),
+                character_model: gtk::StringList::new(&[]),
+                character_ids: RefCell::default(),
+                document_manager: RefCell::default(),
+                settings: ManuscriptSettings::default(),
+                binding: Cell::new(false),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptOutliner {
+        const NAME: &'static str = "ManuscriptOutliner";
+        type Type = super::ManuscriptOutliner;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptOutliner {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_columns();
+        }
+    }
+
+    impl WidgetImpl for ManuscriptOutliner {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptOutliner(ObjectSubclass<imp::ManuscriptOutliner>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl Default for ManuscriptOutliner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManuscriptOutliner {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Lists the chunks of the document managed by `document_manager`,
+    /// keeping them up to date as it changes
+    pub fn set_document_manager(&self, document_manager: &DocumentManager) {
+        *self.imp().document_manager.borrow_mut() = Some(document_manager.clone());
+
+        for signal in ["document-loaded", "document-unloaded", "manifest-updated"] {
+            document_manager.connect_closure(
+                signal,
+                false,
+                closure_local!(@weak-allow-none self as this => move |_: DocumentManager| {
+                    if let Some(this) = this {
+                        this.reload();
+                    }
+                }),
+            );
+        }
+
+        for signal in [
+            "chunk-added",
+            "chunk-imported",
+            "chunk-removed",
+            "chunk-moved",
+        ] {
+            document_manager.connect_closure(
+                signal,
+                false,
+                closure_local!(@weak-allow-none self as this => move |_: DocumentManager, _id: String| {
+                    if let Some(this) = this {
+                        this.reload();
+                    }
+                }),
+            );
+        }
+
+        for signal in ["chunk-updated", "chunk-reloaded"] {
+            document_manager.connect_closure(
+                signal,
+                false,
+                closure_local!(@weak-allow-none self as this => move |_: DocumentManager, id: String| {
+                    if let Some(this) = this {
+                        this.update_item(id.as_str());
+                    }
+                }),
+            );
+        }
+
+        document_manager.connect_closure(
+            "chunk-stats-updated",
+            false,
+            closure_local!(@weak-allow-none self as this => move |_: DocumentManager, id: String, words_count: u64, _minutes: u64, _seconds: u64| {
+                if let Some(this) = this {
+                    if let Some((position, item)) = this.item(id.as_str()) {
+                        item.set_words_count(words_count);
+                        this.imp().store.items_changed(position, 1, 1);
+                    }
+                }
+            }),
+        );
+
+        self.reload();
+    }
+
+    /// Rebuilds the whole table from the document
+    pub fn reload(&self) {
+        let imp = self.imp();
+        let Some(document_manager) = imp.document_manager.borrow().clone() else {
+            return;
+        };
+        let Ok(lock) = document_manager.document_ref() else {
+            return;
+        };
+
+        imp.binding.set(true);
+        let mut status_names = vec![i18n("None")];
+        let mut status_ids = vec![];
+        let mut character_names = vec![i18n("Nobody")];
+        let mut character_ids = vec![];
+        let mut items = vec![];
+        if let Some(document) = lock.as_ref() {
+            for status in document.manifest().statuses().iter() {
+                status_names.push(status.name().to_string());
+                status_ids.push(status.id().to_string());
+            }
+
+            let mut characters = document.chunks_by_type_ref(ChunkType::CharacterSheet);
+            characters.sort_by_key(|c| c.safe_title());
+            for character in characters.iter() {
+                character_names.push(character.safe_title());
+                character_ids.push(character.id().to_string());
+            }
+
+            items = document
+                .outline()
+                .into_iter()
+                .filter(|(c, _)| matches!(c.chunk_type(), ChunkType::Chapter | ChunkType::Scene))
+                .map(|(chunk, depth)| ManuscriptOutlineItem::new(chunk, depth))
+                .collect();
+        }
+
+        let status_names: Vec<&str> = status_names.iter().map(String::as_str).collect();
+        imp.status_model
+            .splice(0, imp.status_model.n_items(), status_names.as_slice());
+        *imp.status_ids.borrow_mut() = status_ids;
+        let character_names: Vec<&str> = character_names.iter().map(String::as_str).collect();
+        imp.character_model
+            .splice(0, imp.character_model.n_items(), character_names.as_slice());
+        *imp.character_ids.borrow_mut() = character_ids;
+        imp.store.splice(0, imp.store.n_items(), items.as_slice());
+        imp.binding.set(false);
+    }
+
+    /// Refreshes the row of the chunk identified by `id`
+    fn update_item(&self, id: &str) {
+        let Some((position, item)) = self.item(id) else {
+            return;
+        };
+        let Some(document_manager) = self.imp().document_manager.borrow().clone() else {
+            return;
+        };
+        let Ok(lock) = document_manager.document_ref() else {
+            return;
+        };
+        if let Some(chunk) = lock.as_ref().and_then(|d| d.get_chunk_ref(id)) {
+            item.update(chunk);
+            self.imp().store.items_changed(position, 1, 1);
+        }
+    }
+
+    fn item(&self, id: &str) -> Option<(u32, ManuscriptOutlineItem)> {
+        let store = &self.imp().store;
+        (0..store.n_items()).find_map(|position| {
+            store
+                .item(position)
+                .and_downcast::<ManuscriptOutlineItem>()
+                .filter(|item| item.chunk_id() == id)
+                .map(|item| (position, item))
+        })
+    }
+
+    fn setup_columns(&self) {
+        let imp = self.imp();
+        let column_view = imp.column_view.get();
+        let sorted = gtk::SortListModel::new(Some(imp.store.clone()), column_view.sorter());
+        column_view.set_model(Some(&gtk::NoSelection::new(Some(sorted))));
+        column_view.connect_activate(glib::clone!(@weak self as this => move |view, position| {
+            let item = view
+                .model()
+                .and_then(|model| model.item(position))
+                .and_downcast::<ManuscriptOutlineItem>();
+            if let Some(item) = item {
+                this.send_action(DocumentAction::SelectChunk(item.chunk_id()));
+            }
+        }));
+
+        let title_column = self.append_column(
+            i18n("Title").as_str(),
+            None,
+            glib::clone!(@weak self as this => @default-panic, move |list_item| this.title_cell(list_item)),
+            |cell, item| {
+                cell.set_margin_start(item.depth() as i32 * INDENT_WIDTH);
+                if let Some(label) = cell.first_child().and_downcast::<gtk::EditableLabel>() {
+                    label.set_text(item.title().as_str());
+                }
+            },
+            |a, b| a.title().to_lowercase().cmp(&b.title().to_lowercase()),
+        );
+        title_column.set_expand(true);
+
+        self.append_column(
+            i18n("Status").as_str(),
+            Some("status"),
+            glib::clone!(@weak self as this => @default-panic, move |list_item| this.status_cell(list_item)),
+            glib::clone!(@weak self as this => move |cell, item| {
+                let index = this.imp().status_ids.borrow().iter().position(|id| *id == item.status_id());
+                if let Some(drop_down) = cell.downcast_ref::<gtk::DropDown>() {
+                    drop_down.set_selected(index.map(|i| i as u32 + 1).unwrap_or(0));
+                }
+            }),
+            glib::clone!(@weak self as this => @default-return Ordering::Equal, move |a, b| {
+                let ids = this.imp().status_ids.borrow();
+                let index = |item: &ManuscriptOutlineItem| {
+                    ids.iter().position(|id| *id == item.status_id())
+                };
+                index(a).cmp(&index(b))
+            }),
+        );
+
+        self.append_column(
+            i18n("Point of View").as_str(),
+            Some("point-of-view"),
+            glib::clone!(@weak self as this => @default-panic, move |list_item| this.point_of_view_cell(list_item)),
+            glib::clone!(@weak self as this => move |cell, item| {
+                let index = this
+                    .imp()
+                    .character_ids
+                    .borrow()
+                    .iter()
+                    .position(|id| *id == item.point_of_view_id());
+                cell.set_visible(item.is_scene());
+                if let Some(drop_down) = cell.downcast_ref::<gtk::DropDown>() {
+                    drop_down.set_selected(index.map(|i| i as u32 + 1).unwrap_or(0));
+                }
+            }),
+            glib::clone!(@weak self as this => @default-return Ordering::Equal, move |a, b| {
+                let name = |item: &ManuscriptOutlineItem| {
+                    let index = this
+                        .imp()
+                        .character_ids
+                        .borrow()
+                        .iter()
+                        .position(|id| *id == item.point_of_view_id());
+                    index.and_then(|i| this.imp().character_model.string(i as u32 + 1))
+                };
+                name(a).cmp(&name(b))
+            }),
+        );
+
+        self.append_column(
+            i18n("Words").as_str(),
+            Some("words"),
+            |_| {
+                gtk::Label::builder()
+                    .xalign(1.0)
+                    .css_classes(["numeric"])
+                    .build()
+                    .upcast()
+            },
+            |cell, item| {
+                if let Some(label) = cell.downcast_ref::<gtk::Label>() {
+                    label.set_label(item.words_count().to_string().as_str());
+                }
+            },
+            |a, b| a.words_count().cmp(&b.words_count()),
+        );
+
+        self.append_column(
+            i18n("Target").as_str(),
+            Some("target"),
+            glib::clone!(@weak self as this => @default-panic, move |list_item| this.target_cell(list_item)),
+            |cell, item| {
+                if let Some(spin_button) = cell.downcast_ref::<gtk::SpinButton>() {
+                    spin_button.set_value(item.words_target() as f64);
+                }
+            },
+            |a, b| a.words_target().cmp(&b.words_target()),
+        );
+
+        self.append_column(
+            i18n("Last Modified").as_str(),
+            Some("modified"),
+            |_| gtk::Label::builder().xalign(0.0).build().upcast(),
+            |cell, item| {
+                let modified = Some(item.modified())
+                    .filter(|m| *m > 0)
+                    .and_then(|m| glib::DateTime::from_unix_local(m).ok())
+                    .and_then(|d| d.format("%x %H:%M").ok());
+                if let Some(label) = cell.downcast_ref::<gtk::Label>() {
+                    label.set_label(modified.as_deref().unwrap_or("—"));
+                }
+            },
+            |a, b| a.modified().cmp(&b.modified()),
+        );
+    }
+
+    /// Adds a column filled by `setup` and `bind` and sorted by `compare`.
+    /// Columns with a `name` can be hidden, which is remembered in the settings
+    fn append_column<S, B, C>(
+        &self,
+        title: &str,
+        name: Option<&'static str>,
+        setup: S,
+        bind: B,
+        compare: C,
+    ) -> gtk::ColumnViewColumn
+    where
+        S: Fn(&gtk::ListItem) -> gtk::Widget + 'static,
+        B: Fn(&gtk::Widget, &ManuscriptOutlineItem) + 'static,
+        C: Fn(&ManuscriptOutlineItem, &ManuscriptOutlineItem) -> Ordering + 'static,
+    {
+        let factory = gtk::SignalListItemFactory::new();
+        factory.connect_setup(move |_, list_item| {
+            if let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() {
+                list_item.set_child(Some(&setup(list_item)));
+            }
+        });
+        factory.connect_bind(glib::clone!(@weak self as this => move |_, list_item| {
+            let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                return;
+            };
+            if let (Some(cell), Some(item)) = (
+                list_item.child(),
+                list_item.item().and_downcast::<ManuscriptOutlineItem>(),
+            ) {
+                this.imp().binding.set(true);
+                bind(&cell, &item);
+                this.imp().binding.set(false);
+            }
+        }));
+
+        let sorter = gtk::CustomSorter::new(move |a, b| {
+            match (
+                a.downcast_ref::<ManuscriptOutlineItem>(),
+                b.downcast_ref::<ManuscriptOutlineItem>(),
+            ) {
+                (Some(a), Some(b)) => compare(a, b).into(),
+                _ => gtk::Ordering::Equal,
+            }
+        });
+
+        let column = gtk::ColumnViewColumn::new(Some(title), Some(factory));
+        column.set_resizable(true);
+        column.set_sorter(Some(&sorter));
+        self.imp().column_view.append_column(&column);
+
+        if let Some(name) = name {
+            let visible = self
+                .imp()
+                .settings
+                .outliner_columns()
+                .iter()
+                .any(|c| c == name);
+            column.set_visible(visible);
+
+            let check = gtk::CheckButton::builder()
+                .label(title)
+                .active(visible)
+                .build();
+            check.connect_toggled(
+                glib::clone!(@weak self as this, @weak column => move |check| {
+                    column.set_visible(check.is_active());
+                    let mut columns = this.imp().settings.outliner_columns();
+                    columns.retain(|c| c != name);
+                    if check.is_active() {
+                        columns.push(name.to_string());
+                    }
+                    let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+                    this.imp().settings.set_outliner_columns(columns.as_slice());
+                }),
+            );
+            self.imp().columns_box.append(&check);
+        }
+
+        column
+    }
+
+    /// An editable title that can be dragged onto other rows to reorder them
+    fn title_cell(&self, list_item: &gtk::ListItem) -> gtk::Widget {
+        let cell = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        let label = gtk::EditableLabel::new("");
+        label.set_hexpand(true);
+        cell.append(&label);
+
+        label.connect_editing_notify(
+            glib::clone!(@weak self as this, @weak list_item => move |label| {
+                let Some(item) = list_item.item().and_downcast::<ManuscriptOutlineItem>() else {
+                    return;
+                };
+                let title = label.text().trim().to_string();
+                if label.is_editing() || title.is_empty() || title == item.title() {
+                    return;
+                }
+
+                this.send_update(item.chunk_id(), move |chunk| {
+                    if let Some(chapter) = chunk.as_any_mut().downcast_mut::<Chapter>() {
+                        chapter.set_title(title.as_str());
+                    } else if let Some(scene) = chunk.as_any_mut().downcast_mut::<Scene>() {
+                        scene.set_title(title.as_str());
+                    }
+                });
+            }),
+        );
+
+        let drag_source = gtk::DragSource::builder()
+            .actions(gdk::DragAction::MOVE)
+            .build();
+        drag_source.connect_prepare(
+            glib::clone!(@weak list_item => @default-return None, move |_, _, _| {
+                list_item
+                    .item()
+                    .and_downcast::<ManuscriptOutlineItem>()
+                    .map(|item| gdk::ContentProvider::for_value(&item.chunk_id().to_value()))
+            }),
+        );
+        cell.add_controller(drag_source);
+
+        let drop_target = gtk::DropTarget::new(String::static_type(), gdk::DragAction::MOVE);
+        drop_target.connect_drop(
+            glib::clone!(@weak self as this, @weak list_item, @weak cell => @default-return false, move |_, value, _, y| {
+                let (Ok(dragged), Some(item)) = (
+                    value.get::<String>(),
+                    list_item.item().and_downcast::<ManuscriptOutlineItem>(),
+                ) else {
+                    return false;
+                };
+                if dragged == item.chunk_id() {
+                    return false;
+                }
+
+                let after = y > cell.height() as f64 / 2.0;
+                this.send_action(DocumentAction::MoveChunkNextTo(dragged, item.chunk_id(), after));
+                true
+            }),
+        );
+        cell.add_controller(drop_target);
+
+        cell.upcast()
+    }
+
+    fn status_cell(&self, list_item: &gtk::ListItem) -> gtk::Widget {
+        let drop_down =
+            gtk::DropDown::new(Some(self.imp().status_model.clone()), gtk::Expression::NONE);
+        drop_down.connect_selected_notify(
+            glib::clone!(@weak self as this, @weak list_item => move |drop_down| {
+                let Some(item) = list_item.item().and_downcast::<ManuscriptOutlineItem>() else {
+                    return;
+                };
+                if this.imp().binding.get() {
+                    return;
+                }
+
+                let status = (drop_down.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| this.imp().status_ids.borrow().get(index).cloned());
+                if status.clone().unwrap_or_default() == item.status_id() {
+                    return;
+                }
+                this.send_update(item.chunk_id(), move |chunk| {
+                    if let Some(buffer_chunk) = chunk.as_mutable_buffer_chunk() {
+                        buffer_chunk.set_status(status);
+                    }
+                });
+            }),
+        );
+        drop_down.upcast()
+    }
+
+    fn point_of_view_cell(&self, list_item: &gtk::ListItem) -> gtk::Widget {
+        let drop_down = gtk::DropDown::new(
+            Some(self.imp().character_model.clone()),
+            gtk::Expression::NONE,
+        );
+        drop_down.connect_selected_notify(
+            glib::clone!(@weak self as this, @weak list_item => move |drop_down| {
+                let Some(item) = list_item.item().and_downcast::<ManuscriptOutlineItem>() else {
+                    return;
+                };
+                if this.imp().binding.get() {
+                    return;
+                }
+
+                let point_of_view = (drop_down.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| this.imp().character_ids.borrow().get(index).cloned());
+                if point_of_view.clone().unwrap_or_default() == item.point_of_view_id() {
+                    return;
+                }
+                this.send_update(item.chunk_id(), move |chunk| {
+                    if let Some(scene) = chunk.as_any_mut().downcast_mut::<Scene>() {
+                        scene.set_point_of_view(point_of_view);
+                    }
+                });
+            }),
+        );
+        drop_down.upcast()
+    }
+
+    fn target_cell(&self, list_item: &gtk::ListItem) -> gtk::Widget {
+        let spin_button = gtk::SpinButton::with_range(0.0, 1_000_000.0, 100.0);
+        spin_button.connect_value_changed(
+            glib::clone!(@weak self as this, @weak list_item => move |spin_button| {
+                let Some(item) = list_item.item().and_downcast::<ManuscriptOutlineItem>() else {
+                    return;
+                };
+                let target = spin_button.value() as u64;
+                if this.imp().binding.get() || target == item.words_target() {
+                    return;
+                }
+
+                this.send_update(item.chunk_id(), move |chunk| {
+                    if let Some(buffer_chunk) = chunk.as_mutable_buffer_chunk() {
+                        buffer_chunk.set_words_target(Some(target).filter(|t| *t > 0));
+                    }
+                });
+            }),
+        );
+        spin_button.upcast()
+    }
+
+    fn send_update<F>(&self, id: String, f: F)
+    where
+        F: FnOnce(&mut dyn DocumentChunk) + 'static,
+    {
+        self.send_action(DocumentAction::UpdateChunkWith(id, Box::new(f)));
+    }
+
+    fn send_action(&self, action: DocumentAction) {
+        if let Some(document_manager) = self.imp().document_manager.borrow().as_ref() {
+            document_manager
+                .action_sender()
+                .send(action)
+                .expect("Could not send action");
+        }
+    }
+}
@@ -31,17 +31,29 @@ mod imp {
         pub(super) primary_menu_button: TemplateChild<ManuscriptPrimaryMenuButton>,
 
         #[template_child]
-        pub(super) chapters_container: TemplateChild<ManuscriptProjectLayoutChunkContainer>,
+        pub(super) manuscript_container: TemplateChild<ManuscriptProjectLayoutChunkContainer>,
 
         #[template_child]
         pub(super) character_sheets_container: TemplateChild<ManuscriptProjectLayoutChunkContainer>,
 
+        #[template_child]
+        pub(super) locations_container: TemplateChild<ManuscriptProjectLayoutChunkContainer>,
+
+        #[template_child]
+        pub(super) lore_container: TemplateChild<ManuscriptProjectLayoutChunkContainer>,
+
+        #[template_child]
+        pub(super) research_container: TemplateChild<ManuscriptProjectLayoutChunkContainer>,
+
         #[template_child]
         pub(super) searchbar: TemplateChild<gtk::SearchBar>,
 
         #[template_child]
         pub(super) searchentry: TemplateChild<gtk::SearchEntry>,
 
+        #[template_child]
+        pub(super) search_summary_label: TemplateChild<gtk::Label>,
+
         #[template_child]
         pub(super) project_actionbar: TemplateChild<gtk::ActionBar>,
 
@@ -84,6 +96,9 @@ mod imp {
                     Signal::builder("remove-selected-activated")
                         .param_types([Vec::<String>::static_type()])
                         .build(),
+                    Signal::builder("export-selected-activated")
+                        .param_types([Vec::<String>::static_type()])
+                        .build(),
                     Signal::builder("settings-activated").build(),
                 ]
             });
@@ -218,6 +233,72 @@ impl ManuscriptProjectLayout {
             document.chunks().iter().for_each(|chunk| {
                 self.add_chunk(*chunk);
             });
+
+            // 3. Arrange the manuscript outline
+            self.update_hierarchy(document);
+        }
+    }
+
+    /// Arranges manuscript rows following the outline of `document`,
+    /// nesting each row under its parent
+    pub fn update_hierarchy(&self, document: &Document) {
+        let container = self.imp().manuscript_container.get();
+        for (position, (chunk, depth)) in document.outline().iter().enumerate() {
+            if let Some(row) = container.chunk_row(*chunk) {
+                row.set_position(position as u32);
+                row.set_depth(*depth);
+                row.set_parent_chunk_id(chunk.parent_id().unwrap_or_default());
+                row.set_priority(chunk.priority().unwrap_or(0));
+
+                let has_children = !document.children_of(Some(chunk.id())).is_empty();
+                row.set_has_children(has_children);
+                if has_children {
+                    row.update_rolled_up_reading_stats(document.words_count_of(chunk.id()));
+                } else if let Some(buffer_chunk) = chunk.as_buffer_chunk() {
+                    row.update_chunk_reading_stats(*chunk, buffer_chunk.words_count());
+                }
+            }
+        }
+        // Other rows are listed by priority alone
+        for chunk in document
+            .chunks()
+            .iter()
+            .filter(|c| !c.chunk_type().is_outline())
+        {
+            if let Some(row) = self.container_for(*chunk).chunk_row(*chunk) {
+                row.set_priority(chunk.priority().unwrap_or(0));
+            }
+        }
+        self.update_badges(document);
+        self.containers_apply(|c| c.invalidate());
+        self.apply_search(document);
+    }
+
+    /// Refreshes the workflow status and labels shown by manuscript rows,
+    /// dimming the rows left out of the compiled manuscript
+    pub fn update_badges(&self, document: &Document) {
+        let container = self.imp().manuscript_container.get();
+        let compiled: Vec<&str> = document.compile_order().iter().map(|c| c.id()).collect();
+        for (chunk, _) in document.outline().iter() {
+            if let Some(row) = container.chunk_row(*chunk) {
+                row.update_badges(document.manifest(), *chunk);
+                row.set_compiled(compiled.contains(&chunk.id()));
+            }
+        }
+    }
+
+    /// Refreshes the rolled up words count of the chunks containing `id`
+    pub fn update_rolled_up_reading_stats(&self, document: &Document, id: &str) {
+        let container = self.imp().manuscript_container.get();
+        let mut ids = vec![id.to_string()];
+        ids.append(&mut document.ancestors_of(id));
+        for id in ids {
+            if let Some(row) = container
+                .chunk_row_by_id(id.as_str())
+                .filter(|row| row.has_children())
+            {
+                row.update_rolled_up_reading_stats(document.words_count_of(id.as_str()));
+            }
         }
     }
 
@@ -228,8 +309,11 @@ impl ManuscriptProjectLayout {
     fn containers(&self) -> Vec<ManuscriptProjectLayoutChunkContainer> {
         let imp = self.imp();
         vec![
-            imp.chapters_container.get(),
+            imp.manuscript_container.get(),
             imp.character_sheets_container.get(),
+            imp.locations_container.get(),
+            imp.lore_container.get(),
+            imp.research_container.get(),
         ]
     }
 
@@ -242,10 +326,16 @@ impl ManuscriptProjectLayout {
 
     fn container_for(&self, chunk: &dyn DocumentChunk) -> ManuscriptProjectLayoutChunkContainer {
         let imp = self.imp();
-        if chunk.as_any().downcast_ref::<Chapter>().is_some() {
-            imp.chapters_container.get()
+        if chunk.chunk_type().is_outline() {
+            imp.manuscript_container.get()
         } else if chunk.as_any().downcast_ref::<CharacterSheet>().is_some() {
             imp.character_sheets_container.get()
+        } else if chunk.as_any().downcast_ref::<LocationSheet>().is_some() {
+            imp.locations_container.get()
+        } else if chunk.as_any().downcast_ref::<LoreEntry>().is_some() {
+            imp.lore_container.get()
+        } else if chunk.as_any().downcast_ref::<Research>().is_some() {
+            imp.research_container.get()
         } else {
             unimplemented!("Not a known chunk type");
         }
@@ -270,6 +360,26 @@ impl ManuscriptProjectLayout {
             }
         }));
 
+        row.connect_closure(
+            "move-by",
+            false,
+            glib::closure_local!(@weak-allow-none self as this => move |row: ManuscriptChunkRow, offset: i32| {
+                if let Some(this) = this {
+                    this.send_action(DocumentAction::ReorderChunk(row.chunk_id(), offset));
+                }
+            }),
+        );
+
+        row.connect_closure(
+            "chunk-dropped",
+            false,
+            glib::closure_local!(@weak-allow-none self as this => move |row: ManuscriptChunkRow, dragged: String, after: bool| {
+                if let Some(this) = this {
+                    this.send_action(DocumentAction::MoveChunkNextTo(dragged, row.chunk_id(), after));
+                }
+            }),
+        );
+
         row.connect_notify_local(
             Some("selected"),
             glib::clone!(@weak self as this => move |row, _| {
@@ -315,6 +425,69 @@ impl ManuscriptProjectLayout {
         self.imp().searchbar.get()
     }
 
+    pub fn searchentry(&self) -> gtk::SearchEntry {
+        self.imp().searchentry.get()
+    }
+
+    /// Shows only the chunks of `document` matching the search entry text,
+    /// along with the parts and chapters containing them
+    pub fn apply_search(&self, document: &Document) {
+        let imp = self.imp();
+        let query = ChunkQuery::parse(imp.searchentry.text().as_str());
+        self.sort_character_sheets(document, query.sort_by().map(|k| k.as_str()));
+        if query.is_empty() {
+            self.containers_apply(|c| c.set_visible_ids(None));
+            imp.search_summary_label.set_visible(false);
+            return;
+        }
+
+        let found = document.search(&query);
+        let mut visible_ids: Vec<String> = vec![];
+        for chunk in found.iter() {
+            visible_ids.push(chunk.id().to_string());
+            visible_ids.append(&mut document.ancestors_of(chunk.id()));
+        }
+        self.containers_apply(|c| c.set_visible_ids(Some(visible_ids.clone())));
+
+        let scenes_count = found
+            .iter()
+            .filter(|c| c.chunk_type() == ChunkType::Scene)
+            .count() as u32;
+        let words_count: u64 = found
+            .iter()
+            .filter_map(|c| c.as_buffer_chunk())
+            .map(|c| c.words_count())
+            .sum();
+        let summary = format!(
+            "{} · {}",
+            i18n::ni18n("{} scene", "{} scenes", scenes_count)
+                .replace("{}", scenes_count.to_string().as_str()),
+            i18n::ni18n("{} word", "{} words", words_count as u32)
+                .replace("{}", words_count.to_string().as_str())
+        );
+        imp.search_summary_label.set_label(summary.as_str());
+        imp.search_summary_label.set_visible(true);
+    }
+
+    /// Orders character sheets by the custom field named `key`, or by
+    /// their position in the project if `None` or no such field exists
+    fn sort_character_sheets(&self, document: &Document, key: Option<&str>) {
+        let container = self.imp().character_sheets_container.get();
+        let field_id = key
+            .and_then(|k| document.manifest().character_field_by_key(k))
+            .map(|f| f.id());
+        for (position, sheet) in document
+            .character_sheets_sorted_by(field_id)
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(row) = container.chunk_row(sheet) {
+                row.set_position(position as u32);
+            }
+        }
+        container.invalidate();
+    }
+
     pub fn set_search(&self, value: bool) {
         self.searchbar().set_search_mode(value);
     }
@@ -401,6 +574,12 @@ impl ManuscriptProjectLayout {
         self.emit_by_name::<()>("remove-selected-activated", &[&ids]);
     }
 
+    #[template_callback]
+    fn on_export_items_clicked(&self, _button: &gtk::Button) {
+        let ids = self.imp().selected_ids.borrow().clone();
+        self.emit_by_name::<()>("export-selected-activated", &[&ids]);
+    }
+
     #[template_callback]
     fn on_select_all_button_clicked(&self, _button: &gtk::Button) {
         self.select_all_rows();
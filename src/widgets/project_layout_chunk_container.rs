@@ -26,6 +26,8 @@ mod imp {
         pub(super) title: RefCell<String>,
 
         pub(super) children_map: RefCell<HashMap<String, ManuscriptChunkRow>>,
+
+        pub(super) visible_ids: RefCell<Option<Vec<String>>>,
     }
 
     impl ManuscriptProjectLayoutChunkContainer {}
@@ -53,8 +55,24 @@ mod imp {
                 let chunk_row1 = row1.downcast_ref::<ManuscriptChunkRow>().expect("How?");
                 let chunk_row2 = row2.downcast_ref::<ManuscriptChunkRow>().expect("How?");
 
-                chunk_row1.priority().cmp(&chunk_row2.priority()).into()
-            })
+                (chunk_row1.position(), chunk_row1.priority())
+                    .cmp(&(chunk_row2.position(), chunk_row2.priority()))
+                    .into()
+            });
+
+            // Rows nested under a collapsed row are hidden, as well as rows
+            // not matching an ongoing search
+            let obj = self.obj().downgrade();
+            self.listbox.set_filter_func(move |row| {
+                let chunk_row = row.downcast_ref::<ManuscriptChunkRow>().expect("How?");
+                match obj.upgrade() {
+                    Some(container) => match container.imp().visible_ids.borrow().as_ref() {
+                        Some(ids) => ids.contains(&chunk_row.chunk_id()),
+                        None => !container.has_collapsed_ancestor(chunk_row),
+                    },
+                    None => true,
+                }
+            });
         }
 
         fn properties() -> &'static [gtk::glib::ParamSpec] {
@@ -75,7 +93,7 @@ mod imp {
 
         fn property(&self, id: usize, pspec: &ParamSpec) -> glib::Value {
             match pspec.name() {
-                "has-items" => (self.children_map.borrow().len() > 0).to_value(),
+                "has-items" => (!self.children_map.borrow().is_empty()).to_value(),
                 _ => self.derived_property(id, pspec),
             }
         }
@@ -138,6 +156,10 @@ impl ManuscriptProjectLayoutChunkContainer {
             self.imp().listbox.append(&row);
         }
 
+        row.connect_expanded_notify(glib::clone!(@weak self as this => move |_| {
+            this.imp().listbox.invalidate_filter();
+        }));
+
         self.notify("has-items");
         row
     }
@@ -175,7 +197,41 @@ impl ManuscriptProjectLayoutChunkContainer {
     }
 
     pub fn chunk_row(&self, chunk: &dyn DocumentChunk) -> Option<ManuscriptChunkRow> {
+        self.chunk_row_by_id(chunk.id())
+    }
+
+    pub fn chunk_row_by_id(&self, id: &str) -> Option<ManuscriptChunkRow> {
         let widget_ref = self.imp().children_map.borrow();
-        widget_ref.get(&chunk.id().to_string()).cloned()
+        widget_ref.get(id).cloned()
+    }
+
+    /// Sorts and filters rows again, after their position in the outline changed
+    pub fn invalidate(&self) {
+        let listbox = self.imp().listbox.get();
+        listbox.invalidate_sort();
+        listbox.invalidate_filter();
+    }
+
+    /// Restricts the visible rows to those in `ids`, or shows all of them if `None`
+    pub fn set_visible_ids(&self, ids: Option<Vec<String>>) {
+        self.imp().visible_ids.replace(ids);
+        self.imp().listbox.invalidate_filter();
+    }
+
+    fn has_collapsed_ancestor(&self, row: &ManuscriptChunkRow) -> bool {
+        let map = self.imp().children_map.borrow();
+        let mut visited = vec![row.chunk_id()];
+        let mut parent_id = row.parent_chunk_id();
+        while let Some(parent) = map.get(&parent_id) {
+            if !parent.expanded() {
+                return true;
+            }
+            if visited.contains(&parent_id) {
+                break;
+            }
+            visited.push(parent_id);
+            parent_id = parent.parent_chunk_id();
+        }
+        false
     }
 }
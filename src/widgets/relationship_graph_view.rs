@@ -0,0 +1,258 @@
+use crate::models::{RelationshipGraph, NODE_RADIUS};
+use adw::subclass::prelude::*;
+use gtk::{gio, glib, prelude::*};
+use std::cell::{Cell, RefCell};
+
+/// Space left around the graph when fitting it in the widget
+const FIT_MARGIN: f64 = 48.0;
+
+/// Drags shorter than this are handled as clicks
+const CLICK_THRESHOLD: f64 = 4.0;
+
+mod imp {
+    use super::*;
+    use glib::subclass::Signal;
+    use gtk::gdk::RGBA;
+    use once_cell::sync::Lazy;
+
+    #[derive(gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/relationship_graph_view.ui")]
+    pub struct ManuscriptRelationshipGraphView {
+        pub(super) graph: RefCell<RelationshipGraph>,
+        pub(super) offset: Cell<(f64, f64)>,
+        pub(super) zoom: Cell<f64>,
+        pub(super) gesture_start_offset: Cell<(f64, f64)>,
+        pub(super) gesture_start_zoom: Cell<f64>,
+    }
+
+    impl Default for ManuscriptRelationshipGraphView {
+        fn default() -> Self {
+            Self {
+                graph: RefCell::default(),
+                offset: Cell::new((0.0, 0.0)),
+                zoom: Cell::new(1.0),
+                gesture_start_offset: Cell::new((0.0, 0.0)),
+                gesture_start_zoom: Cell::new(1.0),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptRelationshipGraphView {
+        const NAME: &'static str = "ManuscriptRelationshipGraphView";
+        type Type = super::ManuscriptRelationshipGraphView;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptRelationshipGraphView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_gestures();
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("node-activated")
+                    .param_types([String::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+    }
+
+    impl WidgetImpl for ManuscriptRelationshipGraphView {
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let obj = self.obj();
+            let bounds =
+                gtk::graphene::Rect::new(0.0, 0.0, obj.width() as f32, obj.height() as f32);
+            let graph = self.graph.borrow();
+            let (x, y, scale) = obj.transform();
+
+            let fg: RGBA = obj.style_context().color();
+            let accent = obj
+                .style_context()
+                .lookup_color("accent_bg_color")
+                .unwrap_or(fg);
+
+            let cr = snapshot.append_cairo(&bounds);
+            cr.translate(x, y);
+            cr.scale(scale, scale);
+            cr.select_font_face(
+                "Sans",
+                gtk::cairo::FontSlant::Normal,
+                gtk::cairo::FontWeight::Normal,
+            );
+            cr.set_font_size(12.0);
+            cr.set_line_width(1.5);
+
+            for edge in graph.edges().iter() {
+                let ((x1, y1), (x2, y2)) = graph.edge_line(edge);
+                cr.set_source_rgba(fg.red().into(), fg.green().into(), fg.blue().into(), 0.6);
+                cr.move_to(x1, y1);
+                cr.line_to(x2, y2);
+
+                // Arrow head at the target end
+                let angle = (y2 - y1).atan2(x2 - x1);
+                for side in [-0.4, 0.4] {
+                    cr.move_to(x2, y2);
+                    cr.line_to(
+                        x2 - 10.0 * (angle + side).cos(),
+                        y2 - 10.0 * (angle + side).sin(),
+                    );
+                }
+                let _ = cr.stroke();
+
+                if let Ok(extents) = cr.text_extents(edge.label.as_str()) {
+                    cr.move_to(
+                        (x1 + x2) / 2.0 - extents.width() / 2.0,
+                        (y1 + y2) / 2.0 - 4.0,
+                    );
+                    let _ = cr.show_text(edge.label.as_str());
+                }
+            }
+
+            for node in graph.nodes().iter() {
+                cr.arc(node.x, node.y, NODE_RADIUS, 0.0, std::f64::consts::TAU);
+                cr.set_source_rgba(
+                    accent.red().into(),
+                    accent.green().into(),
+                    accent.blue().into(),
+                    0.25,
+                );
+                let _ = cr.fill_preserve();
+                cr.set_source_rgba(
+                    accent.red().into(),
+                    accent.green().into(),
+                    accent.blue().into(),
+                    1.0,
+                );
+                let _ = cr.stroke();
+
+                cr.set_source_rgba(
+                    fg.red().into(),
+                    fg.green().into(),
+                    fg.blue().into(),
+                    fg.alpha().into(),
+                );
+                if let Ok(extents) = cr.text_extents(node.name.as_str()) {
+                    cr.move_to(
+                        node.x - extents.width() / 2.0 - extents.x_bearing(),
+                        node.y - extents.height() / 2.0 - extents.y_bearing(),
+                    );
+                    let _ = cr.show_text(node.name.as_str());
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct ManuscriptRelationshipGraphView(ObjectSubclass<imp::ManuscriptRelationshipGraphView>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl Default for ManuscriptRelationshipGraphView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManuscriptRelationshipGraphView {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn set_graph(&self, graph: RelationshipGraph) {
+        self.imp().graph.replace(graph);
+        self.reset_view();
+    }
+
+    /// Fits the whole graph in the widget again, undoing pan and zoom
+    pub fn reset_view(&self) {
+        let imp = self.imp();
+        imp.offset.set((0.0, 0.0));
+        imp.zoom.set(1.0);
+        self.queue_draw();
+    }
+
+    /// Translation and scale mapping graph coordinates to widget coordinates
+    fn transform(&self) -> (f64, f64, f64) {
+        let imp = self.imp();
+        let (width, height) = (self.width() as f64, self.height() as f64);
+        let (bx, by, bw, bh) = imp.graph.borrow().bounds();
+        let fit = (width / (bw + FIT_MARGIN * 2.0))
+            .min(height / (bh + FIT_MARGIN * 2.0))
+            .min(1.0);
+        let scale = fit * imp.zoom.get();
+        let (ox, oy) = imp.offset.get();
+        (
+            width / 2.0 + ox - (bx + bw / 2.0) * scale,
+            height / 2.0 + oy - (by + bh / 2.0) * scale,
+            scale,
+        )
+    }
+
+    fn set_zoom(&self, value: f64) {
+        self.imp().zoom.set(value.clamp(0.2, 5.0));
+        self.queue_draw();
+    }
+
+    fn setup_gestures(&self) {
+        // Dragging pans the view, while a click on a character activates it
+        let drag = gtk::GestureDrag::new();
+        drag.connect_drag_begin(glib::clone!(@weak self as this => move |_, _, _| {
+            let imp = this.imp();
+            imp.gesture_start_offset.set(imp.offset.get());
+        }));
+        drag.connect_drag_update(glib::clone!(@weak self as this => move |_, dx, dy| {
+            let imp = this.imp();
+            let (ox, oy) = imp.gesture_start_offset.get();
+            imp.offset.set((ox + dx, oy + dy));
+            this.queue_draw();
+        }));
+        drag.connect_drag_end(glib::clone!(@weak self as this => move |gesture, dx, dy| {
+            if dx.hypot(dy) > CLICK_THRESHOLD {
+                return;
+            }
+            if let Some((sx, sy)) = gesture.start_point() {
+                let (x, y, scale) = this.transform();
+                let id = this
+                    .imp()
+                    .graph
+                    .borrow()
+                    .node_at((sx - x) / scale, (sy - y) / scale)
+                    .map(|node| node.id.clone());
+                if let Some(id) = id {
+                    this.emit_by_name::<()>("node-activated", &[&id]);
+                }
+            }
+        }));
+        self.add_controller(drag);
+
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        scroll.connect_scroll(glib::clone!(@weak self as this => @default-return glib::Propagation::Proceed, move |_, _, dy| {
+            this.set_zoom(this.imp().zoom.get() * 1.1_f64.powf(-dy));
+            glib::Propagation::Stop
+        }));
+        self.add_controller(scroll);
+
+        let pinch = gtk::GestureZoom::new();
+        pinch.connect_begin(glib::clone!(@weak self as this => move |_, _| {
+            let imp = this.imp();
+            imp.gesture_start_zoom.set(imp.zoom.get());
+        }));
+        pinch.connect_scale_changed(glib::clone!(@weak self as this => move |_, scale| {
+            this.set_zoom(this.imp().gesture_start_zoom.get() * scale);
+        }));
+        self.add_controller(pinch);
+    }
+}
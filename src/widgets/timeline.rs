@@ -0,0 +1,309 @@
+use crate::{
+    models::*,
+    services::{i18n::i18n, DocumentManager},
+};
+use adw::subclass::prelude::*;
+use gtk::{gio, glib, glib::closure_local, prelude::*};
+use std::{cell::RefCell, collections::HashMap};
+
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptTimeline";
+
+const ITEM_WIDTH: i32 = 160;
+
+mod imp {
+    use super::*;
+
+    /// The dated chapters, scenes and lore entries of the manuscript laid out
+    /// chronologically, with one lane per viewpoint character or plot thread
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/sixpounder/Manuscript/timeline.ui")]
+    pub struct ManuscriptTimeline {
+        #[template_child]
+        pub(super) stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub(super) grid: TemplateChild<gtk::Grid>,
+
+        #[template_child]
+        pub(super) undated_label: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        pub(super) lanes_dropdown: TemplateChild<gtk::DropDown>,
+
+        pub(super) document_manager: RefCell<Option<DocumentManager>>,
+
+        /// The timeline currently shown, if it is up to date
+        pub(super) timeline: RefCell<Option<Timeline>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ManuscriptTimeline {
+        const NAME: &'static str = "ManuscriptTimeline";
+        type Type = super::ManuscriptTimeline;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.set_layout_manager_type::<gtk::BinLayout>();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ManuscriptTimeline {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().connect_events();
+        }
+    }
+
+    impl WidgetImpl for ManuscriptTimeline {}
+}
+
+glib::wrapper! {
+    pub struct ManuscriptTimeline(ObjectSubclass<imp::ManuscriptTimeline>)
+        @extends gtk::Widget, @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl Default for ManuscriptTimeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManuscriptTimeline {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Lays out the document managed by `document_manager`, keeping the
+    /// timeline up to date as it changes
+    pub fn set_document_manager(&self, document_manager: &DocumentManager) {
+        *self.imp().document_manager.borrow_mut() = Some(document_manager.clone());
+
+        for signal in ["document-loaded", "document-unloaded", "manifest-updated"] {
+            document_manager.connect_closure(
+                signal,
+                false,
+                closure_local!(@weak-allow-none self as this => move |_: DocumentManager| {
+                    if let Some(this) = this {
+                        this.reload();
+                    }
+                }),
+            );
+        }
+
+        for signal in [
+            "chunk-added",
+            "chunk-imported",
+            "chunk-removed",
+            "chunk-moved",
+            "chunk-updated",
+            "chunk-reloaded",
+        ] {
+            document_manager.connect_closure(
+                signal,
+                false,
+                closure_local!(@weak-allow-none self as this => move |_: DocumentManager, _id: String| {
+                    if let Some(this) = this {
+                        this.reload();
+                    }
+                }),
+            );
+        }
+
+        self.reload();
+    }
+
+    fn connect_events(&self) {
+        let imp = self.imp();
+        // Changes are only laid out while the timeline shows
+        self.connect_map(|this| this.reload());
+        imp.lanes_dropdown
+            .connect_selected_notify(glib::clone!(@weak self as this => move |_| {
+                this.imp().timeline.replace(None);
+                this.reload();
+            }));
+    }
+
+    fn lanes(&self) -> TimelineLanes {
+        match self.imp().lanes_dropdown.selected() {
+            1 => TimelineLanes::PlotThread,
+            _ => TimelineLanes::PointOfView,
+        }
+    }
+
+    /// Lays the timeline out again, if it changed since it was last shown
+    pub fn reload(&self) {
+        let imp = self.imp();
+        if !self.is_mapped() {
+            imp.timeline.replace(None);
+            return;
+        }
+        let Some(document_manager) = imp.document_manager.borrow().clone() else {
+            return;
+        };
+        let timeline = match document_manager.document_ref() {
+            Ok(lock) => lock
+                .as_ref()
+                .map(|document| Timeline::new(document, self.lanes()))
+                .unwrap_or_default(),
+            Err(_) => return,
+        };
+        if imp.timeline.borrow().as_ref() == Some(&timeline) {
+            return;
+        }
+
+        self.fill(&timeline);
+        imp.timeline.replace(Some(timeline));
+    }
+
+    fn fill(&self, timeline: &Timeline) {
+        let imp = self.imp();
+        let grid = imp.grid.get();
+        while let Some(child) = grid.first_child() {
+            grid.remove(&child);
+        }
+
+        imp.undated_label.set_label(
+            match timeline.undated() {
+                0 => String::new(),
+                count => i18n("{count} chapters or scenes have no story time")
+                    .replace("{count}", count.to_string().as_str()),
+            }
+            .as_str(),
+        );
+        imp.stack
+            .set_visible_child_name(if timeline.items().is_empty() {
+                "empty"
+            } else {
+                "timeline"
+            });
+
+        for (column, story_time) in timeline.columns().iter().enumerate() {
+            let header = gtk::Label::builder()
+                .label(story_time)
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .width_request(ITEM_WIDTH)
+                .max_width_chars(1)
+                .tooltip_text(story_time)
+                .css_classes(["caption-heading", "dim-label"])
+                .build();
+            grid.attach(&header, column as i32 + 1, 0, 1, 1);
+        }
+
+        for (row, lane) in timeline.lanes().iter().enumerate() {
+            let row = row as i32 + 1;
+            grid.attach(&self.lane_header(lane), 0, row, 1, 1);
+
+            // Items of the lane happening at the same time are stacked
+            let mut cells: HashMap<usize, gtk::Box> = HashMap::new();
+            for item in lane.items().iter().filter_map(|i| timeline.items().get(*i)) {
+                let cell = cells.entry(item.column()).or_insert_with(|| {
+                    let cell = gtk::Box::builder()
+                        .orientation(gtk::Orientation::Vertical)
+                        .spacing(6)
+                        .valign(gtk::Align::Center)
+                        .build();
+                    grid.attach(&cell, item.column() as i32 + 1, row, 1, 1);
+                    cell
+                });
+                cell.append(&self.item_button(item));
+            }
+        }
+    }
+
+    fn lane_header(&self, lane: &TimelineLane) -> gtk::Widget {
+        let header = gtk::Box::builder()
+            .spacing(6)
+            .valign(gtk::Align::Center)
+            .css_classes(["timeline-lane"])
+            .build();
+        let dot = gtk::Box::builder()
+            .valign(gtk::Align::Center)
+            .css_classes(["timeline-lane-color"])
+            .build();
+        if let Some(color) = lane.color() {
+            let provider = gtk::CssProvider::new();
+            provider.load_from_data(
+                format!(".timeline-lane-color {{ background-color: {color}; }}").as_str(),
+            );
+            dot.style_context()
+                .add_provider(&provider, gtk::STYLE_PROVIDER_PRIORITY_USER);
+        }
+        header.append(&dot);
+        header.append(
+            &gtk::Label::builder()
+                .label(lane.title())
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(18)
+                .css_classes(["heading"])
+                .build(),
+        );
+        header.upcast()
+    }
+
+    fn item_button(&self, item: &TimelineItem) -> gtk::Widget {
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(2)
+            .build();
+        let title_row = gtk::Box::builder().spacing(6).build();
+        title_row.append(
+            &gtk::Label::builder()
+                .label(item.title())
+                .xalign(0.0)
+                .hexpand(true)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(1)
+                .build(),
+        );
+        if let Some(position) = item.reading_position() {
+            title_row.append(
+                &gtk::Label::builder()
+                    .label(format!("#{}", position + 1))
+                    .css_classes(["caption", "dim-label", "numeric"])
+                    .build(),
+            );
+        }
+        content.append(&title_row);
+        content.append(
+            &gtk::Label::builder()
+                .label(item.story_time())
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(1)
+                .css_classes(["caption", "dim-label"])
+                .build(),
+        );
+
+        let tooltip = match item.reading_position() {
+            None => i18n("Event"),
+            Some(_) if item.is_flashback() => {
+                i18n("Flashback: happens before the part read right before it")
+            }
+            Some(position) => i18n("Read in position {position}")
+                .replace("{position}", (position + 1).to_string().as_str()),
+        };
+        let button = gtk::Button::builder()
+            .child(&content)
+            .width_request(ITEM_WIDTH)
+            .tooltip_text(format!("{}\n{}", item.title(), tooltip))
+            .action_name("project.select-chunk")
+            .action_target(&item.chunk_id().to_variant())
+            .css_classes(["card", "timeline-item"])
+            .build();
+        if item.is_event() {
+            button.add_css_class("event");
+        }
+        if item.is_flashback() {
+            button.add_css_class("flashback");
+        }
+        button.upcast()
+    }
+}
@@ -1,12 +1,25 @@
-use adw::subclass::prelude::*;
+use crate::{
+    models::{decode_manifest, DocumentChunk},
+    services::prelude::texture_from_bytes,
+};
+use adw::{prelude::*, subclass::prelude::*};
 use gtk::{gio, glib};
 
+#[allow(unused)]
+const G_LOG_DOMAIN: &str = "ManuscriptWelcomeView";
+
 mod imp {
     use super::*;
 
     #[derive(Default, gtk::CompositeTemplate)]
     #[template(resource = "/io/sixpounder/Manuscript/welcome_view.ui")]
-    pub struct ManuscriptWelcomeView;
+    pub struct ManuscriptWelcomeView {
+        #[template_child]
+        pub(super) recent_group: TemplateChild<adw::PreferencesGroup>,
+
+        #[template_child]
+        pub(super) recent_list: TemplateChild<gtk::ListBox>,
+    }
 
     #[glib::object_subclass]
     impl ObjectSubclass for ManuscriptWelcomeView {
@@ -43,4 +56,78 @@ impl ManuscriptWelcomeView {
     pub fn new() -> Self {
         glib::Object::new()
     }
+
+    /// Lists the documents at `paths`, filling in their title and cover image
+    /// as their manifests are read in the background and dropping the ones
+    /// that can no longer be read
+    pub fn set_recent_documents(&self, paths: &[String]) {
+        let imp = self.imp();
+        let list = imp.recent_list.get();
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        for path in paths.iter() {
+            let file_name = std::path::Path::new(path.as_str())
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone());
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(file_name.as_str()))
+                .subtitle(glib::markup_escape_text(path.as_str()))
+                .activatable(true)
+                .action_name("win.open-recent-project")
+                .action_target(&path.to_variant())
+                .build();
+            let cover = gtk::Image::builder()
+                .pixel_size(48)
+                .icon_name("library-symbolic")
+                .build();
+            row.add_prefix(&cover);
+            list.append(&row);
+
+            let path = path.clone();
+            glib::MainContext::default().spawn_local(
+                glib::clone!(@weak self as this, @weak row, @weak cover => async move {
+                    let read_path = path.clone();
+                    let manifest = gio::spawn_blocking(move || {
+                        let manifest = std::fs::read(read_path.as_str())
+                            .ok()
+                            .and_then(|bytes| decode_manifest(bytes.as_slice()).ok())?;
+                        let texture = manifest
+                            .cover()
+                            .and_then(|cover| texture_from_bytes(cover.as_ref()));
+                        Some((manifest.safe_title(), texture))
+                    })
+                    .await
+                    .ok()
+                    .flatten();
+
+                    match manifest {
+                        Some((title, texture)) => {
+                            row.set_title(glib::markup_escape_text(title.as_str()).as_str());
+                            if let Some(texture) = texture {
+                                cover.set_paintable(Some(&texture));
+                            }
+                        }
+                        None => {
+                            glib::g_debug!(G_LOG_DOMAIN, "Skipping unreadable recent document {path}");
+                            this.remove_recent_row(&row);
+                        }
+                    }
+                }),
+            );
+        }
+
+        imp.recent_group.set_visible(!paths.is_empty());
+    }
+
+    fn remove_recent_row(&self, row: &adw::ActionRow) {
+        let imp = self.imp();
+        let list = imp.recent_list.get();
+        if row.parent().as_ref() == Some(list.upcast_ref::<gtk::Widget>()) {
+            list.remove(row);
+        }
+        imp.recent_group.set_visible(list.first_child().is_some());
+    }
 }
@@ -1,19 +1,72 @@
 use crate::{
-    libs::files::{with_file_open_dialog, with_file_save_dialog},
+    libs::files::{with_file_import_dialog, with_file_open_dialog, with_file_save_dialog},
     models::*,
-    services::{i18n::i18n, DocumentManager, ManuscriptSettings},
+    services::{
+        i18n::{i18n, ni18n},
+        importers, DocumentAction, DocumentManager, ManuscriptSettings,
+    },
     widgets::{
-        dialogs::ManuscriptDestroyConfirmDialog, ManuscriptEditorViewShell,
-        ManuscriptPrimaryMenuButton, ManuscriptProjectLayout, ManuscriptWelcomeView,
+        dialogs::{
+            ManuscriptAppearancesDialog, ManuscriptChunkPickerDialog,
+            ManuscriptDestroyConfirmDialog, ManuscriptRelationshipsDialog, ManuscriptRenameDialog,
+        },
+        editors::{
+            ManuscriptCharacterSheetEditor, ManuscriptLocationSheetEditor, ManuscriptTextEditor,
+        },
+        ManuscriptCorkboard, ManuscriptEditorViewShell, ManuscriptOutliner,
+        ManuscriptPrimaryMenuButton, ManuscriptProjectLayout, ManuscriptRelationshipGraphView,
+        ManuscriptTimeline, ManuscriptWelcomeView,
     },
 };
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::{gio, glib::closure_local};
-use std::{cell::Cell, ops::Deref};
+use once_cell::unsync::OnceCell;
+use std::{
+    cell::{Cell, RefCell},
+    ops::Deref,
+};
 
 const G_LOG_DOMAIN: &str = "ManuscriptWindow";
 const PROJECT_VIEW_NAME: &str = "project-view";
 const WELCOME_VIEW_NAME: &str = "welcome-view";
+const EDITOR_VIEW_NAME: &str = "editor-view";
+const CORKBOARD_VIEW_NAME: &str = "corkboard-view";
+const OUTLINER_VIEW_NAME: &str = "outliner-view";
+const TIMELINE_VIEW_NAME: &str = "timeline-view";
+
+/// Indexes over the whole manuscript used to refresh editors after a change,
+/// each built at most once however many editors are refreshed. Links are
+/// kept indexed by the window as chunks change
+struct ManuscriptIndexes<'a> {
+    document: &'a Document,
+    links: &'a LinkIndex,
+    appearances: OnceCell<AppearanceIndex>,
+    /// Whether the change may have moved where characters appear
+    refresh_appearances: bool,
+}
+
+impl<'a> ManuscriptIndexes<'a> {
+    fn new(document: &'a Document, links: &'a LinkIndex, refresh_appearances: bool) -> Self {
+        Self {
+            document,
+            links,
+            appearances: OnceCell::new(),
+            refresh_appearances,
+        }
+    }
+
+    fn links(&self) -> &LinkIndex {
+        self.links
+    }
+
+    /// Where characters appear, unless it can not have changed
+    fn appearances(&self) -> Option<&AppearanceIndex> {
+        self.refresh_appearances.then(|| {
+            self.appearances
+                .get_or_init(|| AppearanceIndex::new(self.document))
+        })
+    }
+}
 
 mod imp {
     use super::*;
@@ -41,6 +94,18 @@ mod imp {
         #[template_child]
         pub(super) editor_view: TemplateChild<ManuscriptEditorViewShell>,
 
+        #[template_child]
+        pub(super) content_stack: TemplateChild<gtk::Stack>,
+
+        #[template_child]
+        pub(super) corkboard: TemplateChild<ManuscriptCorkboard>,
+
+        #[template_child]
+        pub(super) outliner: TemplateChild<ManuscriptOutliner>,
+
+        #[template_child]
+        pub(super) timeline: TemplateChild<ManuscriptTimeline>,
+
         #[template_child]
         pub(super) split_view: TemplateChild<adw::NavigationSplitView>,
 
@@ -54,10 +119,18 @@ mod imp {
 
         pub(super) document_manager: DocumentManager,
 
+        pub(super) links: RefCell<LinkIndex>,
+
         pub(super) search_mode: Cell<bool>,
 
         pub(super) select_mode: Cell<bool>,
 
+        pub(super) corkboard_mode: Cell<bool>,
+
+        pub(super) outliner_mode: Cell<bool>,
+
+        pub(super) timeline_mode: Cell<bool>,
+
         pub(super) close_anyway: Cell<bool>,
     }
 
@@ -75,13 +148,21 @@ mod imp {
                 welcome_view: TemplateChild::default(),
                 command_palette_overlay: TemplateChild::default(),
                 editor_view: TemplateChild::default(),
+                content_stack: TemplateChild::default(),
+                corkboard: TemplateChild::default(),
+                outliner: TemplateChild::default(),
+                timeline: TemplateChild::default(),
                 split_view: TemplateChild::default(),
                 editor_view_shell_page: TemplateChild::default(),
                 project_layout: TemplateChild::default(),
                 settings: ManuscriptSettings::default(),
                 document_manager: DocumentManager::default(),
+                links: RefCell::default(),
                 search_mode: Cell::default(),
                 select_mode: Cell::default(),
+                corkboard_mode: Cell::default(),
+                outliner_mode: Cell::default(),
+                timeline_mode: Cell::default(),
                 close_anyway: Cell::new(false),
             }
         }
@@ -96,7 +177,21 @@ mod imp {
             });
 
             klass.install_action("win.open-project", None, move |win, _, _| {
-                win.open_project(false);
+                win.open_project(None, false);
+            });
+
+            klass.install_action(
+                "win.open-recent-project",
+                Some("s"),
+                move |win, _, param| {
+                    if let Some(path) = param.and_then(|p| p.get::<String>()) {
+                        win.open_project(Some(path), false);
+                    }
+                },
+            );
+
+            klass.install_action("win.import-project", None, move |win, _, _| {
+                win.import_project(false);
             });
 
             klass.install_action("project.save", None, move |win, _, _| {
@@ -107,6 +202,10 @@ mod imp {
                 win.save_project_as();
             });
 
+            klass.install_action("project.import-from-project", None, move |win, _, _| {
+                win.import_from_project();
+            });
+
             klass.install_action("project.close", None, move |win, _, _| {
                 win.close_project(false);
             });
@@ -115,16 +214,133 @@ mod imp {
                 win.toggle_command_palette();
             });
 
+            klass.install_action("project.add-part", None, move |win, _, _| {
+                win.add_part();
+            });
+
             klass.install_action("project.add-chapter", None, move |win, _, _| {
                 win.add_chapter();
             });
 
+            klass.install_action("project.add-scene", None, move |win, _, _| {
+                win.add_scene();
+            });
+
             klass.install_action("project.add-character-sheet", None, move |win, _, _| {
                 win.add_character_sheet();
             });
 
+            klass.install_action("project.add-location-sheet", None, move |win, _, _| {
+                win.add_location_sheet();
+            });
+
+            klass.install_action("project.add-lore-entry", None, move |win, _, _| {
+                win.add_lore_entry();
+            });
+
+            klass.install_action("project.add-research", None, move |win, _, _| {
+                win.add_research();
+            });
+
+            klass.install_action("project.move-chunk", Some("s"), move |win, _, param| {
+                if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                    win.move_chunk(id);
+                }
+            });
+
+            klass.install_action(
+                "project.choose-point-of-view",
+                Some("s"),
+                move |win, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        win.choose_point_of_view(id);
+                    }
+                },
+            );
+
+            klass.install_action(
+                "project.choose-parent-location",
+                Some("s"),
+                move |win, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        win.choose_parent_location(id);
+                    }
+                },
+            );
+
+            klass.install_action(
+                "project.choose-field-link",
+                Some("(ss)"),
+                move |win, _, param| {
+                    if let Some((id, field_id)) = param.and_then(|p| p.get::<(String, String)>()) {
+                        win.choose_field_link(id, field_id);
+                    }
+                },
+            );
+
+            klass.install_action("project.choose-labels", Some("s"), move |win, _, param| {
+                if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                    win.choose_labels(id);
+                }
+            });
+
+            klass.install_action(
+                "project.add-relationship",
+                Some("s"),
+                move |win, _, param| {
+                    if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                        win.add_relationship(id);
+                    }
+                },
+            );
+
+            klass.install_action(
+                "project.rename-character",
+                Some("(ss)"),
+                move |win, _, param| {
+                    if let Some((id, previous_name)) =
+                        param.and_then(|p| p.get::<(String, String)>())
+                    {
+                        win.rename_character(id, previous_name);
+                    }
+                },
+            );
+
+            klass.install_action("project.select-chunk", Some("s"), move |win, _, param| {
+                if let Some(id) = param.and_then(|p| p.get::<String>()) {
+                    win.document_manager()
+                        .action_sender()
+                        .send(DocumentAction::SelectChunk(id))
+                        .expect("Could not send action");
+                }
+            });
+
+            klass.install_action("project.follow-link", Some("s"), move |win, _, param| {
+                if let Some(name) = param.and_then(|p| p.get::<String>()) {
+                    win.follow_link(name);
+                }
+            });
+
+            klass.install_action("project.show-appearances", None, move |win, _, _| {
+                win.show_appearances();
+            });
+
+            klass.install_action("project.show-relationships", None, move |win, _, _| {
+                win.show_relationships();
+            });
+
+            klass.install_action("project.undo-edit", None, move |win, _, _| {
+                win.document_manager()
+                    .action_sender()
+                    .send(DocumentAction::UndoEdit)
+                    .expect("Could not send action");
+            });
+
             klass.install_property_action("project.search", "project-search");
             klass.install_property_action("project.select", "project-select");
+            klass.install_property_action("project.corkboard", "project-corkboard");
+            klass.install_property_action("project.outliner", "project-outliner");
+            klass.install_property_action("project.timeline", "project-timeline");
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -152,6 +368,18 @@ mod imp {
                         .default_value(false)
                         .readwrite()
                         .build(),
+                    ParamSpecBoolean::builder("project-corkboard")
+                        .default_value(false)
+                        .readwrite()
+                        .build(),
+                    ParamSpecBoolean::builder("project-outliner")
+                        .default_value(false)
+                        .readwrite()
+                        .build(),
+                    ParamSpecBoolean::builder("project-timeline")
+                        .default_value(false)
+                        .readwrite()
+                        .build(),
                 ]
             });
             PROPERTIES.as_ref()
@@ -162,6 +390,9 @@ mod imp {
             match pspec.name() {
                 "project-search" => obj.search_mode().to_value(),
                 "project-select" => obj.select().to_value(),
+                "project-corkboard" => obj.corkboard_mode().to_value(),
+                "project-outliner" => obj.outliner_mode().to_value(),
+                "project-timeline" => obj.timeline_mode().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -171,6 +402,9 @@ mod imp {
             match pspec.name() {
                 "project-search" => obj.set_search_mode(value.get::<bool>().unwrap()),
                 "project-select" => obj.set_select(value.get::<bool>().unwrap()),
+                "project-corkboard" => obj.set_corkboard_mode(value.get::<bool>().unwrap()),
+                "project-outliner" => obj.set_outliner_mode(value.get::<bool>().unwrap()),
+                "project-timeline" => obj.set_timeline_mode(value.get::<bool>().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -258,6 +492,14 @@ impl ManuscriptWindow {
             }),
         );
 
+        dm.connect_closure(
+            "chunk-imported",
+            false,
+            closure_local!(@strong self as this => move |_obj: DocumentManager, id: String| {
+                this.on_chunk_imported(id);
+            }),
+        );
+
         dm.connect_closure(
             "chunk-removed",
             false,
@@ -266,6 +508,30 @@ impl ManuscriptWindow {
             }),
         );
 
+        dm.connect_closure(
+            "chunk-moved",
+            false,
+            closure_local!(@strong self as this => move |_obj: DocumentManager, id: String| {
+                this.on_chunk_moved(id);
+            }),
+        );
+
+        dm.connect_closure(
+            "chunk-reloaded",
+            false,
+            closure_local!(@strong self as this => move |_obj: DocumentManager, id: String| {
+                this.on_chunk_reloaded(id);
+            }),
+        );
+
+        dm.connect_closure(
+            "edit-applied",
+            false,
+            closure_local!(@strong self as this => move |_obj: DocumentManager, message: String| {
+                this.on_edit_applied(message);
+            }),
+        );
+
         dm.connect_closure(
             "chunk-selected",
             false,
@@ -305,6 +571,13 @@ impl ManuscriptWindow {
             }),
         );
 
+        self.imp()
+            .project_layout
+            .searchentry()
+            .connect_search_changed(glib::clone!(@weak self as win => move |_| {
+                win.update_project_layout_search();
+            }));
+
         self.connect_close_request(move |window| {
             glib::g_debug!(G_LOG_DOMAIN, "Saving window state");
             let width = window.default_size().0;
@@ -323,6 +596,22 @@ impl ManuscriptWindow {
         let editor_view = self.editor_view();
         editor_view.set_channel(self.document_manager().action_sender());
 
+        self.imp()
+            .corkboard
+            .set_channel(self.document_manager().action_sender());
+
+        self.imp()
+            .outliner
+            .set_document_manager(self.document_manager());
+
+        self.imp()
+            .timeline
+            .set_document_manager(self.document_manager());
+
+        let imp = self.imp();
+        imp.welcome_view
+            .set_recent_documents(imp.settings.recent_documents().as_slice());
+
         self.update_actions();
     }
 
@@ -340,7 +629,8 @@ impl ManuscriptWindow {
             .set_visible_child_name(PROJECT_VIEW_NAME);
     }
 
-    fn open_project(&self, pass: bool) {
+    /// Opens the document at `path`, or asks for one when `path` is `None`
+    fn open_project(&self, path: Option<String>, pass: bool) {
         let dm = self.document_manager();
         if !(dm.is_sync() || pass) {
             let dialog = ManuscriptDestroyConfirmDialog::new(self.upcast_ref::<gtk::Window>());
@@ -349,47 +639,748 @@ impl ManuscriptWindow {
                 glib::clone!(@strong self as this => move |_dialog, res| {
                     if res == "save" {
                         this.save_project();
-                        this.open_project(true);
+                        this.open_project(path.clone(), true);
                     } else if res == "discard" {
-                        this.open_project(true);
+                        this.open_project(path.clone(), true);
                     }
                 }),
             );
             dialog.show();
+        } else if let Some(path) = path {
+            self.load_project_file(path);
         } else {
             with_file_open_dialog(glib::clone!(@strong self as win => move |path| {
-                let imp = win.imp();
-                let dm = win.document_manager();
+                win.load_project_file(path);
+            }));
+        }
+    }
 
-                if dm.has_document() && dm.unload_document().is_ok() {
-                    win.editor_view().clear();
-                    win.project_layout().clear();
+    fn load_project_file(&self, path: String) {
+        let imp = self.imp();
+        let dm = self.document_manager();
+
+        if dm.has_document() && dm.unload_document().is_ok() {
+            self.editor_view().clear();
+            self.project_layout().clear();
+        }
+        match dm.load_document(Some(path)) {
+            Ok(_) => {
+                imp.main_stack.set_visible_child_name(PROJECT_VIEW_NAME);
+
+                // Update last opened document
+                let settings = &imp.settings;
+                if dm.has_backend() {
+                    let backend_path = dm.backend_file();
+                    let backend_path = backend_path.as_ref().unwrap();
+                    let backend_path = backend_path.path().unwrap();
+                    let backend_path = backend_path.as_path().to_str().unwrap();
+                    let backend_path = backend_path.to_string();
+
+                    settings.set_last_opened_document(&backend_path);
+                    settings.add_recent_document(&backend_path);
+                    glib::g_debug!(
+                        G_LOG_DOMAIN,
+                        "Updated last opened document with {backend_path}"
+                    );
+                }
+            }
+            Err(error) => {
+                if let ManuscriptError::Open(path) = error {
+                    self.add_toast(format!("Unreadable file: {}", path));
                 }
-                match dm.load_document(Some(path)) {
-                    Ok(_) => {
-                        imp.main_stack.set_visible_child_name(PROJECT_VIEW_NAME);
-
-                        // Update last opened document
-                        let settings = &imp.settings;
-                        if dm.has_backend() {
-                            let backend_path = dm.backend_file();
-                            let backend_path = backend_path.as_ref().unwrap();
-                            let backend_path = backend_path.path().unwrap();
-                            let backend_path = backend_path.as_path().to_str().unwrap();
-                            let backend_path = backend_path.to_string();
-
-                            settings.set_last_opened_document(&backend_path);
-                            glib::g_debug!(G_LOG_DOMAIN, "Updated last opened document with {backend_path}");
+            }
+        }
+    }
+
+    /// Shows the welcome screen, listing the recently opened documents
+    fn show_welcome_view(&self) {
+        let imp = self.imp();
+        imp.welcome_view
+            .set_recent_documents(imp.settings.recent_documents().as_slice());
+        imp.main_stack.set_visible_child_name(WELCOME_VIEW_NAME);
+    }
+
+    fn import_project(&self, pass: bool) {
+        let dm = self.document_manager();
+        if !(dm.is_sync() || pass) {
+            let dialog = ManuscriptDestroyConfirmDialog::new(self.upcast_ref::<gtk::Window>());
+            dialog.connect_response(
+                None,
+                glib::clone!(@strong self as this => move |_dialog, res| {
+                    if res == "save" {
+                        this.save_project();
+                        this.import_project(true);
+                    } else if res == "discard" {
+                        this.import_project(true);
+                    }
+                }),
+            );
+            dialog.show();
+        } else {
+            let patterns = importers::supported_patterns();
+            with_file_import_dialog(
+                patterns.as_slice(),
+                glib::clone!(@strong self as win => move |path| {
+                    let document = match importers::import_document(path.as_str()) {
+                        Ok(document) => document,
+                        Err(error) => {
+                            glib::g_warning!(G_LOG_DOMAIN, "Could not import {path} - {:?}", error);
+                            win.add_toast(i18n("Could not import project"));
+                            return;
                         }
-                    },
-                    Err(error) => {
-                        if let ManuscriptError::Open(path) = error {
-                            win.add_toast(format!("Unreadable file: {}", path));
+                    };
+
+                    let dm = win.document_manager();
+                    if dm.has_document() && dm.unload_document().is_ok() {
+                        win.editor_view().clear();
+                        win.project_layout().clear();
+                    }
+                    match dm.import_document(document) {
+                        Ok(_) => {
+                            win.imp().main_stack.set_visible_child_name(PROJECT_VIEW_NAME);
+                        }
+                        Err(error) => {
+                            glib::g_warning!(G_LOG_DOMAIN, "Could not load imported project - {:?}", error);
                         }
                     }
+                }),
+            );
+        }
+    }
+
+    fn import_from_project(&self) {
+        with_file_open_dialog(glib::clone!(@strong self as win => move |path| {
+            let source = match std::fs::read(path.as_str()) {
+                Ok(bytes) => Document::try_from(bytes.as_slice()),
+                Err(_) => Err(ManuscriptError::Open(path.clone())),
+            };
+
+            let source = match source {
+                Ok(source) => source,
+                Err(error) => {
+                    glib::g_warning!(G_LOG_DOMAIN, "Could not read {path} - {:?}", error);
+                    win.add_toast(format!("Unreadable file: {}", path));
+                    return;
                 }
-            }));
+            };
+
+            let dialog = ManuscriptChunkPickerDialog::new(
+                win.upcast_ref::<gtk::Window>(),
+                i18n("Import from project").as_str(),
+                source.chunks(),
+            );
+            dialog.set_response_label("confirm", i18n("_Import").as_str());
+            dialog.connect_response(
+                None,
+                glib::clone!(@strong win => move |dialog, res| {
+                    if res != "confirm" {
+                        return;
+                    }
+
+                    let dialog = dialog.downcast_ref::<ManuscriptChunkPickerDialog>().unwrap();
+                    let ids = dialog.selected_ids();
+                    match win.document_manager().merge_document(&source, ids.as_slice()) {
+                        Ok(count) => {
+                            win.add_toast(ni18n("Imported {} item", "Imported {} items", count as u32)
+                                .replace("{}", count.to_string().as_str()));
+                        }
+                        Err(error) => {
+                            glib::g_warning!(G_LOG_DOMAIN, "Could not merge project - {:?}", error);
+                        }
+                    }
+                }),
+            );
+            dialog.show();
+        }));
+    }
+
+    /// Lets the user choose where the chunk identified by `id`
+    /// sits in the manuscript outline
+    fn move_chunk(&self, id: String) {
+        let dialog = self.document_manager().with_document(|document| {
+            let chunk = document
+                .get_chunk_ref(id.as_str())
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            let descendants_of_chunk = |candidate: &dyn DocumentChunk| {
+                candidate.id() == id.as_str()
+                    || document
+                        .ancestors_of(candidate.id())
+                        .iter()
+                        .any(|a| a == id.as_str())
+            };
+            let candidates = document
+                .outline()
+                .into_iter()
+                .map(|(candidate, _)| candidate)
+                .filter(|c| chunk.chunk_type().can_be_child_of(&c.chunk_type()))
+                .filter(|c| !descendants_of_chunk(*c))
+                .collect::<Vec<&dyn DocumentChunk>>();
+
+            Ok(ManuscriptChunkPickerDialog::new_single_choice(
+                self.upcast_ref::<gtk::Window>(),
+                i18n("Move To").as_str(),
+                i18n("Top level").as_str(),
+                candidates,
+                chunk.parent_id(),
+            ))
+        });
+
+        let dialog = match dialog {
+            Ok(dialog) => dialog,
+            Err(error) => {
+                glib::g_warning!(G_LOG_DOMAIN, "Could not move chunk {id} - {:?}", error);
+                return;
+            }
+        };
+
+        dialog.set_response_label("confirm", i18n("_Move").as_str());
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as win => move |dialog, res| {
+                if res != "confirm" {
+                    return;
+                }
+
+                let dialog = dialog.downcast_ref::<ManuscriptChunkPickerDialog>().unwrap();
+                let parent = dialog.selected_id();
+                if let Err(error) = win.document_manager().set_chunk_parent(id.as_str(), parent.as_deref()) {
+                    glib::g_warning!(G_LOG_DOMAIN, "Could not move chunk {id} - {:?}", error);
+                    win.add_toast(i18n("Could not move this item there"));
+                }
+            }),
+        );
+        dialog.show();
+    }
+
+    /// Lets the user pick the character whose viewpoint the scene
+    /// identified by `id` is told from
+    fn choose_point_of_view(&self, id: String) {
+        let dialog = self.document_manager().with_document(|document| {
+            let scene = document
+                .get_chunk_ref(id.as_str())
+                .and_then(|c| c.as_any().downcast_ref::<Scene>())
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            let mut characters = document
+                .chunks()
+                .into_iter()
+                .filter(|c| c.chunk_type() == ChunkType::CharacterSheet)
+                .collect::<Vec<&dyn DocumentChunk>>();
+            characters.sort_by_key(|c| c.safe_title());
+
+            Ok(ManuscriptChunkPickerDialog::new_single_choice(
+                self.upcast_ref::<gtk::Window>(),
+                i18n("Point of View").as_str(),
+                i18n("Nobody").as_str(),
+                characters,
+                scene.point_of_view().map(|pov| pov.as_str()),
+            ))
+        });
+
+        let dialog = match dialog {
+            Ok(dialog) => dialog,
+            Err(error) => {
+                glib::g_warning!(
+                    G_LOG_DOMAIN,
+                    "Could not choose point of view for {id} - {:?}",
+                    error
+                );
+                return;
+            }
+        };
+
+        dialog.set_response_label("confirm", i18n("_Choose").as_str());
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as win => move |dialog, res| {
+                if res != "confirm" {
+                    return;
+                }
+
+                let dialog = dialog.downcast_ref::<ManuscriptChunkPickerDialog>().unwrap();
+                let point_of_view = dialog.selected_id();
+                let _ = win.document_manager().action_sender().send(DocumentAction::UpdateChunkWith(
+                    id.clone(),
+                    Box::new(move |chunk| {
+                        if let Some(scene) = chunk.as_any_mut().downcast_mut::<Scene>() {
+                            scene.set_point_of_view(point_of_view);
+                        }
+                    }),
+                ));
+            }),
+        );
+        dialog.show();
+    }
+
+    /// Lets the user pick the location containing the
+    /// location identified by `id`
+    fn choose_parent_location(&self, id: String) {
+        let dialog = self.document_manager().with_document(|document| {
+            let location = document
+                .get_chunk_ref(id.as_str())
+                .and_then(|c| c.as_any().downcast_ref::<LocationSheet>())
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            let mut candidates = document
+                .chunks_by_type_ref(ChunkType::LocationSheet)
+                .into_iter()
+                .filter(|c| {
+                    c.id() != id.as_str()
+                        && !document
+                            .location_ancestors_of(c.id())
+                            .iter()
+                            .any(|a| a == id.as_str())
+                })
+                .collect::<Vec<&dyn DocumentChunk>>();
+            candidates.sort_by_key(|c| c.safe_title());
+
+            Ok(ManuscriptChunkPickerDialog::new_single_choice(
+                self.upcast_ref::<gtk::Window>(),
+                i18n("Located In").as_str(),
+                i18n("Nowhere").as_str(),
+                candidates,
+                location.parent_location().map(|p| p.as_str()),
+            ))
+        });
+
+        let dialog = match dialog {
+            Ok(dialog) => dialog,
+            Err(error) => {
+                glib::g_warning!(
+                    G_LOG_DOMAIN,
+                    "Could not choose parent location for {id} - {:?}",
+                    error
+                );
+                return;
+            }
+        };
+
+        dialog.set_response_label("confirm", i18n("_Choose").as_str());
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as win => move |dialog, res| {
+                if res != "confirm" {
+                    return;
+                }
+
+                let dialog = dialog.downcast_ref::<ManuscriptChunkPickerDialog>().unwrap();
+                let parent = dialog.selected_id();
+                if let Err(error) = win.document_manager().set_parent_location(id.as_str(), parent.as_deref()) {
+                    glib::g_warning!(G_LOG_DOMAIN, "Could not set parent location of {id} - {:?}", error);
+                    win.add_toast(i18n("Could not place this location there"));
+                }
+            }),
+        );
+        dialog.show();
+    }
+
+    /// Lets the user pick the chunk linked by the custom field `field_id`
+    /// of the character sheet identified by `id`
+    fn choose_field_link(&self, id: String, field_id: String) {
+        let dialog = self.document_manager().with_document(|document| {
+            let sheet = document
+                .get_chunk_ref(id.as_str())
+                .and_then(|c| c.as_any().downcast_ref::<CharacterSheet>())
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            let field = document
+                .manifest()
+                .character_field(field_id.as_str())
+                .ok_or(ManuscriptError::Reason("Unknown custom field"))?;
+            let mut candidates = document
+                .chunks()
+                .into_iter()
+                .filter(|c| c.id() != id.as_str())
+                .collect::<Vec<&dyn DocumentChunk>>();
+            candidates.sort_by_key(|c| (c.category_name(), c.safe_title()));
+            let linked = match sheet.custom_field(field_id.as_str()) {
+                Some(FieldValue::Link(link)) => Some(link.as_str()),
+                _ => None,
+            };
+
+            Ok(ManuscriptChunkPickerDialog::new_single_choice(
+                self.upcast_ref::<gtk::Window>(),
+                field.name(),
+                i18n("Nothing").as_str(),
+                candidates,
+                linked,
+            ))
+        });
+
+        let dialog = match dialog {
+            Ok(dialog) => dialog,
+            Err(error) => {
+                glib::g_warning!(
+                    G_LOG_DOMAIN,
+                    "Could not choose a link for field {field_id} of {id} - {:?}",
+                    error
+                );
+                return;
+            }
+        };
+
+        dialog.set_response_label("confirm", i18n("_Choose").as_str());
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as win => move |dialog, res| {
+                if res != "confirm" {
+                    return;
+                }
+
+                let dialog = dialog.downcast_ref::<ManuscriptChunkPickerDialog>().unwrap();
+                let value = dialog.selected_id().map(FieldValue::Link);
+                let field_id = field_id.clone();
+                let _ = win.document_manager().action_sender().send(DocumentAction::UpdateChunkWith(
+                    id.clone(),
+                    Box::new(move |chunk| {
+                        if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                            sheet.set_custom_field(field_id.as_str(), value);
+                        }
+                    }),
+                ));
+            }),
+        );
+        dialog.show();
+    }
+
+    /// Lets the user pick the labels of the buffer chunk identified by `id`
+    fn choose_labels(&self, id: String) {
+        let labels = self.document_manager().with_document(|document| {
+            let chunk = document
+                .get_chunk_ref(id.as_str())
+                .and_then(|c| c.as_buffer_chunk())
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            Ok(document
+                .manifest()
+                .labels()
+                .iter()
+                .map(|l| {
+                    let active = chunk.labels().iter().any(|id| id == l.id());
+                    (l.id().to_string(), l.name().to_string(), l.color(), active)
+                })
+                .collect::<Vec<(String, String, Color, bool)>>())
+        });
+        let labels = match labels {
+            Ok(labels) if !labels.is_empty() => labels,
+            Ok(_) => {
+                self.add_toast(i18n("Add labels in the project settings first"));
+                return;
+            }
+            Err(error) => {
+                glib::g_warning!(
+                    G_LOG_DOMAIN,
+                    "Could not choose labels for {id} - {:?}",
+                    error
+                );
+                return;
+            }
+        };
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        let mut checks = vec![];
+        for (label_id, name, color, active) in labels.into_iter() {
+            let check = gtk::CheckButton::builder()
+                .active(active)
+                .valign(gtk::Align::Center)
+                .build();
+            let dot = gtk::Label::builder()
+                .label(format!("<span foreground=\"{}\">●</span>", color.hex()))
+                .use_markup(true)
+                .build();
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(name.as_str()))
+                .activatable_widget(&check)
+                .build();
+            row.add_prefix(&check);
+            row.add_suffix(&dot);
+            list.append(&row);
+            checks.push((label_id, check));
         }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(i18n("Labels"))
+            .extra_child(&list)
+            .modal(true)
+            .transient_for(self)
+            .build();
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("confirm", &i18n("_Apply"))]);
+        dialog.set_response_appearance("confirm", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("confirm"));
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as win => move |_, response| {
+                if response != "confirm" {
+                    return;
+                }
+
+                let labels: Vec<String> = checks
+                    .iter()
+                    .filter(|(_, check)| check.is_active())
+                    .map(|(label_id, _)| label_id.clone())
+                    .collect();
+                let _ = win.document_manager().action_sender().send(DocumentAction::UpdateChunkWith(
+                    id.clone(),
+                    Box::new(move |chunk| {
+                        if let Some(buffer_chunk) = chunk.as_mutable_buffer_chunk() {
+                            buffer_chunk.set_labels(labels);
+                        }
+                    }),
+                ));
+            }),
+        );
+        dialog.present();
+    }
+
+    /// Asks for the kind, target and note of a new relationship from
+    /// the character identified by `id` to another one
+    fn add_relationship(&self, id: String) {
+        let targets = self.document_manager().with_document(|document| {
+            let mut targets: Vec<(String, String)> = document
+                .chunks_by_type_ref(ChunkType::CharacterSheet)
+                .into_iter()
+                .filter(|c| c.id() != id.as_str())
+                .map(|c| (c.id().to_string(), c.safe_title()))
+                .collect();
+            targets.sort_by(|a, b| a.1.cmp(&b.1));
+            Ok(targets)
+        });
+        let targets = match targets {
+            Ok(targets) if !targets.is_empty() => targets,
+            _ => {
+                self.add_toast(i18n("Add another character first"));
+                return;
+            }
+        };
+
+        let target_entry = adw::ComboRow::builder()
+            .title(i18n("Character"))
+            .model(&gtk::StringList::new(
+                targets
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<&str>>()
+                    .as_slice(),
+            ))
+            .build();
+        let kinds = [
+            RelationshipKind::Sibling.label(),
+            RelationshipKind::Rival.label(),
+            RelationshipKind::Mentor.label(),
+            RelationshipKind::Spouse.label(),
+            i18n("Custom"),
+        ];
+        let kind_entry = adw::ComboRow::builder()
+            .title(i18n("Relationship"))
+            .model(&gtk::StringList::new(
+                kinds
+                    .iter()
+                    .map(|k| k.as_str())
+                    .collect::<Vec<&str>>()
+                    .as_slice(),
+            ))
+            .build();
+        let custom_kind_entry = adw::EntryRow::builder()
+            .title(i18n("Custom relationship"))
+            .visible(false)
+            .build();
+        let note_entry = adw::EntryRow::builder().title(i18n("Note")).build();
+
+        let list = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list.append(&target_entry);
+        list.append(&kind_entry);
+        list.append(&custom_kind_entry);
+        list.append(&note_entry);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(i18n("New Relationship"))
+            .body(i18n("Choose what this character is to another one"))
+            .extra_child(&list)
+            .modal(true)
+            .transient_for(self)
+            .build();
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("add", &i18n("_Add"))]);
+        dialog.set_response_appearance("add", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("add"));
+
+        // Custom relationships need a name
+        let update_add_enabled = glib::clone!(@weak dialog, @weak kind_entry, @weak custom_kind_entry => move || {
+            let is_custom = kind_entry.selected() == 4;
+            custom_kind_entry.set_visible(is_custom);
+            dialog.set_response_enabled(
+                "add",
+                !is_custom || !custom_kind_entry.text().trim().is_empty(),
+            );
+        });
+        let update = update_add_enabled.clone();
+        kind_entry.connect_selected_notify(move |_| update());
+        custom_kind_entry.connect_changed(move |_| update_add_enabled());
+
+        dialog.connect_response(
+            None,
+            glib::clone!(@weak self as win, @weak target_entry, @weak kind_entry, @weak custom_kind_entry, @weak note_entry => move |_, response| {
+                if response != "add" {
+                    return;
+                }
+
+                let Some((target, _)) = targets.get(target_entry.selected() as usize) else {
+                    return;
+                };
+                let kind = match kind_entry.selected() {
+                    0 => RelationshipKind::Sibling,
+                    1 => RelationshipKind::Rival,
+                    2 => RelationshipKind::Mentor,
+                    3 => RelationshipKind::Spouse,
+                    _ => RelationshipKind::Custom(custom_kind_entry.text().trim().to_string()),
+                };
+                let note: String = note_entry.text().trim().into();
+                let relationship =
+                    Relationship::new(target.as_str(), kind, Some(note).filter(|n| !n.is_empty()));
+                let _ = win.document_manager().action_sender().send(DocumentAction::UpdateChunkWith(
+                    id.clone(),
+                    Box::new(move |chunk| {
+                        if let Some(sheet) = chunk.as_any_mut().downcast_mut::<CharacterSheet>() {
+                            sheet.add_relationship(relationship);
+                        }
+                    }),
+                ));
+            }),
+        );
+        dialog.present();
+    }
+
+    /// Lets the user choose which occurrences of the previous name and the aliases
+    /// of the character `id` to replace with its current name
+    fn rename_character(&self, id: String, previous_name: String) {
+        let found = self.document_manager().with_document(|document| {
+            let sheet = document
+                .get_chunk_ref(id.as_str())
+                .and_then(|c| c.as_any().downcast_ref::<CharacterSheet>())
+                .ok_or(ManuscriptError::ChunkUnavailable)?;
+            let name = sheet
+                .name()
+                .map(|n| n.trim().to_string())
+                .unwrap_or_default();
+            // Looking for the new name too keeps the text already using it out of
+            // the matches of a shorter alias, like "Ada" in "Ada Byron"
+            let mut names = vec![previous_name.clone(), name.clone()];
+            names.extend(sheet.aliases().iter().cloned());
+            let occurrences: Vec<NameOccurrence> = name_occurrences(document, &names)
+                .into_iter()
+                .filter(|o| o.name() != name.as_str())
+                .collect();
+            Ok((name, occurrences))
+        });
+        let (name, occurrences) = match found {
+            Ok((name, occurrences)) if !occurrences.is_empty() => (name, occurrences),
+            _ => {
+                self.add_toast(
+                    i18n("“{}” does not appear in the manuscript")
+                        .replace("{}", previous_name.as_str()),
+                );
+                return;
+            }
+        };
+
+        let dialog = ManuscriptRenameDialog::new(
+            self.upcast_ref::<gtk::Window>(),
+            occurrences,
+            previous_name.as_str(),
+            name.as_str(),
+        );
+        dialog.connect_closure(
+            "rename-requested",
+            false,
+            closure_local!(@weak-allow-none self as win => move |dialog: ManuscriptRenameDialog| {
+                let Some(win) = win else {
+                    return;
+                };
+                if let Ok(lock) = win.document_manager().document_ref() {
+                    if let Some(document) = lock.as_ref() {
+                        // Notes and highlights of open editors must be up to date
+                        // before the text around them changes
+                        document
+                            .chunks()
+                            .into_iter()
+                            .filter(|chunk| chunk.as_buffer_chunk().is_some())
+                            .filter_map(|chunk| win.editor_view().editor_for(chunk))
+                            .filter_map(|editor| editor.downcast::<ManuscriptTextEditor>().ok())
+                            .for_each(|editor| editor.flush_edits());
+                        if let Some(editor) = document
+                            .get_chunk_ref(id.as_str())
+                            .and_then(|chunk| win.editor_view().editor_for(chunk))
+                            .and_then(|e| e.downcast::<ManuscriptCharacterSheetEditor>().ok())
+                        {
+                            editor.forget_previous_name();
+                        }
+                    }
+                }
+                let _ = win.document_manager().action_sender().send(
+                    DocumentAction::RenameOccurrences(dialog.selected_occurrences(), name.clone()),
+                );
+            }),
+        );
+        dialog.present();
+    }
+
+    /// Opens the chunk a `[[Name]]` link points to
+    fn follow_link(&self, name: String) {
+        let id = self.document_manager().with_document(|document| {
+            Ok(document.chunk_by_title(&name).map(|c| c.id().to_string()))
+        });
+        match id {
+            Ok(Some(id)) => {
+                let _ = self
+                    .document_manager()
+                    .action_sender()
+                    .send(DocumentAction::SelectChunk(id));
+            }
+            _ => self.add_toast(i18n("Nothing is named “{}”").replace("{}", name.as_str())),
+        }
+    }
+
+    /// Shows which chapters mention each character of the project
+    fn show_appearances(&self) {
+        let index = self
+            .document_manager()
+            .with_document(|document| Ok(AppearanceIndex::new(document)));
+        let index = match index {
+            Ok(index) if !index.appearances().is_empty() => index,
+            _ => {
+                self.add_toast(i18n("There are no characters in this project yet"));
+                return;
+            }
+        };
+
+        ManuscriptAppearancesDialog::new(self.upcast_ref::<gtk::Window>(), &index).present();
+    }
+
+    /// Shows the characters of the project and their relationships as a graph
+    fn show_relationships(&self) {
+        let graph = self
+            .document_manager()
+            .with_document(|document| Ok(RelationshipGraph::new(document)));
+        let graph = match graph {
+            Ok(graph) if !graph.nodes().is_empty() => graph,
+            _ => {
+                self.add_toast(i18n("There are no characters in this project yet"));
+                return;
+            }
+        };
+
+        let dialog = ManuscriptRelationshipsDialog::new(self.upcast_ref::<gtk::Window>(), graph);
+        dialog.graph_view().connect_closure(
+            "node-activated",
+            false,
+            closure_local!(@weak-allow-none self as win => move |_view: ManuscriptRelationshipGraphView, id: String| {
+                if let Some(win) = win {
+                    let _ = win.document_manager().action_sender().send(DocumentAction::SelectChunk(id));
+                }
+            }),
+        );
+        dialog.present();
     }
 
     fn save_project(&self) {
@@ -427,6 +1418,7 @@ impl ManuscriptWindow {
             with_file_save_dialog(glib::clone!(@strong self as win => move |path| {
                 let settings = &win.imp().settings;
                 settings.set_last_opened_document(&path);
+                settings.add_recent_document(&path);
                 win.document_manager().set_backend_path(path);
                 if let Err(_error) = win.document_manager().sync() {
                     win.add_toast("Could not save file".into());
@@ -455,9 +1447,7 @@ impl ManuscriptWindow {
             } else if dm.unload_document().is_ok() {
                 self.editor_view().clear();
                 self.project_layout().clear();
-                self.imp()
-                    .main_stack
-                    .set_visible_child_name(WELCOME_VIEW_NAME);
+                self.show_welcome_view();
             }
         }
     }
@@ -472,6 +1462,10 @@ impl ManuscriptWindow {
                     .document_ref()
                     .expect("Could not lock document");
                 let new_document = new_document.deref();
+                *imp.links.borrow_mut() = new_document
+                    .as_ref()
+                    .map(LinkIndex::new)
+                    .unwrap_or_default();
 
                 // Update project layout
                 imp.project_layout.load_document(new_document.as_ref());
@@ -482,6 +1476,11 @@ impl ManuscriptWindow {
 
     fn on_document_unloaded(&self) {
         self.update_actions();
+        self.set_corkboard_mode(false);
+        self.set_outliner_mode(false);
+        self.set_timeline_mode(false);
+        self.imp().corkboard.load_document(None);
+        *self.imp().links.borrow_mut() = LinkIndex::default();
     }
 
     fn on_manifest_updated(&self) {
@@ -489,8 +1488,22 @@ impl ManuscriptWindow {
             if let Some(document) = lock.as_ref() {
                 self.project_layout()
                     .set_document_title_label_text(document.manifest().title());
+
+                // Custom character fields, statuses and labels may have changed
+                let links = self.imp().links.borrow();
+                let indexes = ManuscriptIndexes::new(document, &links, false);
+                document
+                    .chunks()
+                    .into_iter()
+                    .filter(|c| {
+                        c.chunk_type() == ChunkType::CharacterSheet || c.as_buffer_chunk().is_some()
+                    })
+                    .for_each(|chunk| self.refresh_chunk_links(document, chunk, &indexes));
+                self.project_layout().update_badges(document);
             }
         }
+        self.update_project_layout_search();
+        self.update_corkboard();
     }
 
     fn on_title_set(&self) {
@@ -507,17 +1520,50 @@ impl ManuscriptWindow {
             if let Some(document) = lock.as_ref() {
                 let imp = self.imp();
                 let added_chunk = document.get_chunk_ref(id.as_str()).unwrap();
+                imp.links.borrow_mut().update(document, id.as_str());
                 imp.project_layout.add_chunk(added_chunk);
+                imp.project_layout.update_hierarchy(document);
                 imp.editor_view.add_chunk_page(added_chunk);
+                self.update_chunk_links(document, added_chunk, true);
                 self.show_chunk_page(added_chunk);
             }
         }
     }
 
+    fn on_chunk_imported(&self, id: String) {
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(document) = lock.as_ref() {
+                if let Some(imported_chunk) = document.get_chunk_ref(id.as_str()) {
+                    self.imp().project_layout.add_chunk(imported_chunk);
+                    self.imp().project_layout.update_hierarchy(document);
+                }
+            }
+        }
+        self.update_backlinks(&id, true);
+        self.update_corkboard();
+    }
+
     fn on_chunk_removed(&self, id: String) {
+        self.update_backlinks(&id, true);
         let imp = self.imp();
         imp.project_layout.remove_chunk(id.clone());
         imp.editor_view.close_page_by_id(id);
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(document) = lock.as_ref() {
+                imp.project_layout.update_hierarchy(document);
+                self.update_character_highlights(document);
+            }
+        }
+        self.update_corkboard();
+    }
+
+    fn on_chunk_moved(&self, _id: String) {
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(document) = lock.as_ref() {
+                self.imp().project_layout.update_hierarchy(document);
+            }
+        }
+        self.update_corkboard();
     }
 
     fn on_chunk_selected(&self, id: String) {
@@ -525,18 +1571,113 @@ impl ManuscriptWindow {
             if let Some(document) = &*lock {
                 let selected_chunk = document.get_chunk_ref(id.as_str()).unwrap();
                 self.show_chunk_page(selected_chunk);
+                self.update_chunk_links(document, selected_chunk, true);
+            }
+        }
+    }
+
+    /// Shows the new content of the chunk `id` in its editor, if open
+    fn on_chunk_reloaded(&self, id: String) {
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(chunk) = lock.as_ref().and_then(|d| d.get_chunk_ref(id.as_str())) {
+                self.editor_view().reload_page(chunk);
             }
         }
+        self.update_backlinks(&id, true);
+        self.on_chunk_updated(id);
+    }
+
+    fn on_edit_applied(&self, message: String) {
+        let toast = adw::Toast::builder()
+            .title(message)
+            .button_label(i18n("Undo"))
+            .action_name("project.undo-edit")
+            .build();
+        self.imp().toast_overlay.add_toast(toast);
     }
 
     fn on_chunk_updated(&self, id: String) {
+        // The text is indexed once it settles, see `on_chunk_stats_updated`.
+        // Most other updates, like an accent or a note, leave the title alone
+        // too, and with it how the chunk shows in other chunks
+        let renamed = self.update_backlinks(&id, false);
         self.update_layout_chunk_row(id.clone());
-        self.update_editor_view_shell(id);
+        self.update_project_layout_search();
+        self.update_corkboard_card(&id);
+        if renamed {
+            self.update_appearances(&id);
+        }
+        self.update_editor_view_shell(id, renamed);
         // TODO: maybe tick for autosave here?
     }
 
     fn on_chunk_stats_updated(&self, id: String, words_count: u64, reading_time: (u64, u64)) {
-        self.update_layout_chunk_row_reading_stats(id, words_count, reading_time);
+        self.update_layout_chunk_row_reading_stats(id.clone(), words_count, reading_time);
+        // Links and mentions in the text may have changed, now that it settled
+        self.update_backlinks(&id, true);
+        self.update_appearances(&id);
+    }
+
+    /// Shows where each open character sheet is mentioned, after the text of
+    /// the chunk `id` changed. Only the text of chapters and of the scenes
+    /// nested in them counts as appearances
+    fn update_appearances(&self, id: &str) {
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(document) = lock.as_ref() {
+                let counts = document.get_chunk_ref(id).is_some_and(|chunk| {
+                    matches!(chunk.chunk_type(), ChunkType::Chapter | ChunkType::Scene)
+                });
+                if !counts {
+                    return;
+                }
+
+                let editors: Vec<ManuscriptCharacterSheetEditor> = document
+                    .chunks_by_type_ref(ChunkType::CharacterSheet)
+                    .into_iter()
+                    .filter_map(|sheet| self.editor_view().editor_for(sheet))
+                    .filter_map(|editor| editor.downcast::<ManuscriptCharacterSheetEditor>().ok())
+                    .collect();
+                if editors.is_empty() {
+                    return;
+                }
+
+                let index = AppearanceIndex::new(document);
+                editors
+                    .iter()
+                    .for_each(|editor| editor.update_appearances(&index));
+            }
+        }
+    }
+
+    /// Indexes the links of the chunk `id` again, only its title unless `text`
+    /// is set, and if they changed lists the chunks linking to each open chunk
+    /// in its side panel. Returns whether they changed
+    fn update_backlinks(&self, id: &str, text: bool) -> bool {
+        let Ok(lock) = self.document_manager().document_ref() else {
+            return false;
+        };
+        let Some(document) = lock.as_ref() else {
+            return false;
+        };
+
+        let mut links = self.imp().links.borrow_mut();
+        let changed = if text {
+            links.update(document, id)
+        } else {
+            links.rename(document, id)
+        };
+        drop(links);
+        if !changed {
+            return false;
+        }
+
+        let links = self.imp().links.borrow();
+        for chunk in document.chunks() {
+            if let Some(panel) = self.editor_view().side_panel_for(chunk) {
+                panel.set_backlinks(&links.backlinks(document, chunk.id()));
+            }
+        }
+        true
     }
 
     fn search_mode(&self) -> bool {
@@ -563,16 +1704,146 @@ impl ManuscriptWindow {
         }
     }
 
+    fn corkboard_mode(&self) -> bool {
+        self.imp().corkboard_mode.get()
+    }
+
+    /// Shows the chapters and scenes of the project as index cards in
+    /// place of the editors
+    fn set_corkboard_mode(&self, value: bool) {
+        let imp = self.imp();
+        if value != imp.corkboard_mode.replace(value) {
+            if value {
+                self.set_outliner_mode(false);
+                self.set_timeline_mode(false);
+                self.update_corkboard();
+            }
+            self.update_content_view();
+            self.notify("project-corkboard");
+        }
+    }
+
+    fn outliner_mode(&self) -> bool {
+        self.imp().outliner_mode.get()
+    }
+
+    /// Shows the chapters and scenes of the project as a table in place
+    /// of the editors
+    fn set_outliner_mode(&self, value: bool) {
+        let imp = self.imp();
+        if value != imp.outliner_mode.replace(value) {
+            if value {
+                self.set_corkboard_mode(false);
+                self.set_timeline_mode(false);
+            }
+            self.update_content_view();
+            self.notify("project-outliner");
+        }
+    }
+
+    fn timeline_mode(&self) -> bool {
+        self.imp().timeline_mode.get()
+    }
+
+    /// Shows the dated chapters, scenes and events of the project in
+    /// chronological order in place of the editors
+    fn set_timeline_mode(&self, value: bool) {
+        let imp = self.imp();
+        if value != imp.timeline_mode.replace(value) {
+            if value {
+                self.set_corkboard_mode(false);
+                self.set_outliner_mode(false);
+            }
+            self.update_content_view();
+            self.notify("project-timeline");
+        }
+    }
+
+    fn update_content_view(&self) {
+        let name = if self.corkboard_mode() {
+            CORKBOARD_VIEW_NAME
+        } else if self.outliner_mode() {
+            OUTLINER_VIEW_NAME
+        } else if self.timeline_mode() {
+            TIMELINE_VIEW_NAME
+        } else {
+            EDITOR_VIEW_NAME
+        };
+        self.imp().content_stack.set_visible_child_name(name);
+    }
+
+    /// Rebuilds the corkboard cards, if the corkboard is showing
+    fn update_corkboard(&self) {
+        if !self.corkboard_mode() {
+            return;
+        }
+
+        if let Ok(lock) = self.document_manager().document_ref() {
+            self.imp().corkboard.load_document(lock.as_ref());
+        }
+    }
+
+    /// Rebuilds the card of the chunk `id` if it shows something else now,
+    /// and the corkboard is showing
+    fn update_corkboard_card(&self, id: &str) {
+        if !self.corkboard_mode() {
+            return;
+        }
+
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(document) = lock.as_ref() {
+                if let Some(chunk) = document.get_chunk_ref(id) {
+                    self.imp()
+                        .corkboard
+                        .update_chunk(document.manifest(), chunk);
+                }
+            }
+        }
+    }
+
+    fn add_part(&self) {
+        glib::g_debug!(G_LOG_DOMAIN, "Adding empty part to the project");
+        self.document_manager().add_chunk(Part::new());
+    }
+
     fn add_chapter(&self) {
         glib::g_debug!(G_LOG_DOMAIN, "Adding empty chapter to the project");
         self.document_manager().add_chunk(Chapter::default());
     }
 
+    fn add_scene(&self) {
+        glib::g_debug!(G_LOG_DOMAIN, "Adding empty scene to the project");
+        self.document_manager().add_chunk(Scene::new());
+    }
+
     fn add_character_sheet(&self) {
         glib::g_debug!(G_LOG_DOMAIN, "Adding empty character sheet to the project");
         self.document_manager().add_chunk(CharacterSheet::default());
     }
 
+    fn add_location_sheet(&self) {
+        glib::g_debug!(G_LOG_DOMAIN, "Adding empty location sheet to the project");
+        self.document_manager().add_chunk(LocationSheet::default());
+    }
+
+    fn add_lore_entry(&self) {
+        glib::g_debug!(G_LOG_DOMAIN, "Adding empty lore entry to the project");
+        self.document_manager().add_chunk(LoreEntry::default());
+    }
+
+    fn add_research(&self) {
+        glib::g_debug!(G_LOG_DOMAIN, "Adding empty research to the project");
+        self.document_manager().add_chunk(Research::default());
+    }
+
+    fn update_project_layout_search(&self) {
+        if let Ok(lock) = self.document_manager().document_ref() {
+            if let Some(document) = lock.as_ref() {
+                self.project_layout().apply_search(document);
+            }
+        }
+    }
+
     fn update_layout_chunk_row(&self, id: String) {
         if let Ok(lock) = self.document_manager().document_ref() {
             if let Some(document) = &*lock {
@@ -580,6 +1851,11 @@ impl ManuscriptWindow {
                     if let Some(row) = self.project_layout().chunk_row(chunk) {
                         let the_chunk = Some(chunk);
                         row.update_chunk(the_chunk);
+                        row.update_badges(document.manifest(), chunk);
+                        // Compilation settings roll down to nested rows too
+                        if chunk.chunk_type().is_outline() {
+                            self.project_layout().update_badges(document);
+                        }
                     } else {
                         glib::g_warning!(
                             G_LOG_DOMAIN,
@@ -614,6 +1890,8 @@ impl ManuscriptWindow {
                 if let Some(chunk) = document.get_chunk_ref(&id) {
                     if let Some(row) = self.project_layout().chunk_row(chunk) {
                         row.update_chunk_reading_stats(chunk, words_count);
+                        self.project_layout()
+                            .update_rolled_up_reading_stats(document, &id);
                     } else {
                         glib::g_warning!(
                             G_LOG_DOMAIN,
@@ -637,11 +1915,12 @@ impl ManuscriptWindow {
         }
     }
 
-    fn update_editor_view_shell(&self, id: String) {
+    fn update_editor_view_shell(&self, id: String, renamed: bool) {
         if let Ok(lock) = self.document_manager().document_ref() {
             if let Some(document) = &*lock {
                 if let Some(chunk) = document.get_chunk_ref(&id) {
                     self.editor_view().update_page(chunk);
+                    self.update_chunk_links(document, chunk, renamed);
                 } else {
                     glib::g_warning!(
                         G_LOG_DOMAIN,
@@ -659,6 +1938,121 @@ impl ManuscriptWindow {
         }
     }
 
+    /// Refreshes editor details that depend on other chunks, like the name
+    /// of a scene's viewpoint character or of the location containing another.
+    /// Chunks referring to `chunk` are refreshed as well, along with where
+    /// characters appear if `appearances` is set
+    fn update_chunk_links(
+        &self,
+        document: &Document,
+        chunk: &dyn DocumentChunk,
+        appearances: bool,
+    ) {
+        let links = self.imp().links.borrow();
+        let indexes = ManuscriptIndexes::new(document, &links, appearances);
+        self.refresh_chunk_links(document, chunk, &indexes);
+        if chunk.chunk_type() == ChunkType::CharacterSheet {
+            self.update_character_highlights(document);
+        }
+
+        let refers_to_chunk = |candidate: &&dyn DocumentChunk| {
+            let any = candidate.as_any();
+            let links_chunk = any
+                .downcast_ref::<CharacterSheet>()
+                .is_some_and(|sheet| sheet.links_to(chunk.id()));
+            links_chunk
+                || any
+                    .downcast_ref::<Scene>()
+                    .and_then(|s| s.point_of_view())
+                    .or_else(|| {
+                        any.downcast_ref::<LocationSheet>()
+                            .and_then(|l| l.parent_location())
+                    })
+                    .is_some_and(|id| id == chunk.id())
+        };
+        document
+            .chunks()
+            .into_iter()
+            .filter(refers_to_chunk)
+            .for_each(|other| self.refresh_chunk_links(document, other, &indexes));
+    }
+
+    /// Tints the names of characters in every open text editor
+    fn update_character_highlights(&self, document: &Document) {
+        let sheets: Vec<CharacterSheet> = document
+            .character_sheets_sorted_by(None)
+            .into_iter()
+            .cloned()
+            .collect();
+        document
+            .chunks()
+            .into_iter()
+            .filter(|chunk| chunk.as_buffer_chunk().is_some())
+            .filter_map(|chunk| self.editor_view().editor_for(chunk))
+            .filter_map(|editor| editor.downcast::<ManuscriptTextEditor>().ok())
+            .for_each(|editor| editor.set_characters(sheets.clone()));
+    }
+
+    fn refresh_chunk_links(
+        &self,
+        document: &Document,
+        chunk: &dyn DocumentChunk,
+        indexes: &ManuscriptIndexes,
+    ) {
+        if let Some(editor) = self
+            .editor_view()
+            .editor_for(chunk)
+            .and_then(|e| e.downcast::<ManuscriptTextEditor>().ok())
+        {
+            editor.set_characters(
+                document
+                    .character_sheets_sorted_by(None)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+            );
+        }
+
+        if let Some(panel) = self.editor_view().side_panel_for(chunk) {
+            if chunk.as_buffer_chunk().is_some() {
+                panel.update_workflow(document.manifest(), chunk);
+            }
+            panel.set_backlinks(&indexes.links().backlinks(document, chunk.id()));
+        }
+
+        if let Some(scene) = chunk.as_any().downcast_ref::<Scene>() {
+            if let Some(panel) = self.editor_view().side_panel_for(chunk) {
+                panel.set_point_of_view_name(
+                    point_of_view_name(document, scene).unwrap_or_else(|| i18n("Nobody")),
+                );
+            }
+        } else if let Some(location) = chunk.as_any().downcast_ref::<LocationSheet>() {
+            if let Some(editor) = self
+                .editor_view()
+                .editor_for(chunk)
+                .and_then(|e| e.downcast::<ManuscriptLocationSheetEditor>().ok())
+            {
+                let parent_name = location
+                    .parent_location()
+                    .and_then(|id| document.get_chunk_ref(id))
+                    .map(|parent| parent.safe_title());
+                editor.set_parent_location_name(parent_name.unwrap_or_else(|| i18n("Nowhere")));
+            }
+        } else if let Some(sheet) = chunk.as_any().downcast_ref::<CharacterSheet>() {
+            if let Some(editor) = self
+                .editor_view()
+                .editor_for(chunk)
+                .and_then(|e| e.downcast::<ManuscriptCharacterSheetEditor>().ok())
+            {
+                editor.update_custom_fields(document, sheet);
+                editor.update_relationships(document, sheet);
+                if let Some(appearances) = indexes.appearances() {
+                    editor.update_appearances(appearances);
+                }
+            }
+        }
+    }
+
     fn toggle_command_palette(&self) {
         const COMMAND_PALETTE_CLASS: &str = "command-palette";
         let main_stack_style_context = self.imp().main_stack.style_context();
@@ -675,9 +2069,15 @@ impl ManuscriptWindow {
         self.action_set_enabled("project.save", dm.has_document());
         self.action_set_enabled("project.save-as", dm.has_document());
         self.action_set_enabled("project.close", dm.has_document());
+        self.action_set_enabled("project.import-from-project", dm.has_document());
+        self.action_set_enabled("project.show-relationships", dm.has_document());
+        self.action_set_enabled("project.show-appearances", dm.has_document());
     }
 
     pub fn show_chunk_page(&self, chunk: &dyn DocumentChunk) {
+        self.set_corkboard_mode(false);
+        self.set_outliner_mode(false);
+        self.set_timeline_mode(false);
         self.editor_view().select_chunk_page(chunk);
         self.split_view().set_show_content(true);
     }
@@ -731,6 +2131,32 @@ impl ManuscriptWindow {
         }
     }
 
+    #[template_callback]
+    fn on_export_selected_activated(&self, ids: Vec<String>) {
+        if ids.is_empty() {
+            return;
+        }
+
+        let serialized = self
+            .document_manager()
+            .with_document(|document| document.subset(ids.as_slice()).serialize());
+
+        match serialized {
+            Ok(serialized) => {
+                with_file_save_dialog(glib::clone!(@strong self as win => move |path| {
+                    if std::fs::write(path.as_str(), serialized.as_slice()).is_ok() {
+                        win.add_toast(i18n("Selection exported as a new project"));
+                    } else {
+                        win.add_toast(i18n("Could not save file"));
+                    }
+                }));
+            }
+            Err(error) => {
+                glib::g_warning!(G_LOG_DOMAIN, "Could not export selection - {:?}", error);
+            }
+        }
+    }
+
     #[template_callback]
     fn on_close_request(&self) -> bool {
         if self.can_close() {